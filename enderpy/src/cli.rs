@@ -1,6 +1,11 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use enderpy_python_type_checker::{
+    settings::{DocstringStyle, PythonPlatform, PythonVersion},
+    symbol_query::SymbolKind,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 /// Enderpy CLI
 #[derive(Parser)]
@@ -23,14 +28,138 @@ pub enum Commands {
         file: PathBuf,
     },
     /// Type check
-    Check { path: PathBuf },
+    Check(CheckArgs),
     ///  Symbol table
-    Symbols { path: PathBuf },
+    Symbols {
+        path: PathBuf,
+        /// Only show symbols of this scope, e.g. `global` or `Class.method`
+        #[arg(long)]
+        scope: Option<String>,
+        /// Only show symbols of this kind, e.g. function, class or variable
+        #[arg(long)]
+        kind: Option<SymbolKind>,
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Insert inferred return and variable annotations
+    Annotate {
+        path: PathBuf,
+        /// Print the changes as a diff instead of writing them
+        #[arg(long)]
+        diff: bool,
+    },
+    /// Report functions, classes and variables that are never used
+    Unused { path: PathBuf },
+    /// Print the inheritance tree of a class
+    Hierarchy {
+        /// Name of the class
+        class_name: String,
+        /// File or directory to build
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Print complexity metrics of functions
+    Metrics {
+        path: PathBuf,
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Explain an error code
+    Explain {
+        /// Error code, e.g. E203
+        code: String,
+    },
 
     /// Watch changes to type check
     Watch,
 }
 
+/// Options of `enderpy check`
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Files or directories to check
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+    /// Apply safe fixes to the source files
+    #[arg(long)]
+    pub fix: bool,
+    /// Print the safe fixes as a unified diff instead of writing them
+    #[arg(long, requires = "fix")]
+    pub diff: bool,
+    /// Only check files that are modified or untracked in git
+    #[arg(long)]
+    pub changed_only: bool,
+    /// Report `from module import *` statements
+    #[arg(long)]
+    pub warn_star_imports: bool,
+    /// Report imports that are not sorted and grouped into sections
+    #[arg(long)]
+    pub sort_imports: bool,
+    /// Report missing docstrings and docstring parameters that do not
+    /// match the signature: google or numpy. Replaces `docstring-style`
+    /// of `[tool.enderpy]` in pyproject.toml
+    #[arg(long)]
+    pub docstring_style: Option<DocstringStyle>,
+    /// Treat module level UPPER_CASE names as constants
+    #[arg(long)]
+    pub uppercase_constants: bool,
+    /// Report attribute access and calls on values that may be None
+    #[arg(long)]
+    pub strict_optional: bool,
+    /// Infer the element type of containers with elements of different
+    /// types as their common base class instead of their union
+    #[arg(long)]
+    pub join_container_types: bool,
+    /// Check the arguments of format strings and the format specs of
+    /// f-strings
+    #[arg(long)]
+    pub check_format_strings: bool,
+    /// Print the modules of the build with their size, parse time and
+    /// number of symbols, diagnostics and resolved imports
+    #[arg(long)]
+    pub explain_cache: bool,
+    /// Only report these codes or code prefixes, e.g. `E2,E001`.
+    /// Replaces `select` of `[tool.enderpy]` in pyproject.toml
+    #[arg(long, value_delimiter = ',')]
+    pub select: Vec<String>,
+    /// Do not report these codes or code prefixes. Replaces `ignore` of
+    /// `[tool.enderpy]` in pyproject.toml
+    #[arg(long, value_delimiter = ',')]
+    pub ignore: Vec<String>,
+    #[command(flatten)]
+    pub target: Target,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+/// Version and platform that `sys.version_info` and `sys.platform` checks
+/// are evaluated for
+#[derive(Args)]
+pub struct Target {
+    /// Python version, e.g. 3.10
+    #[arg(long, default_value = "3.12")]
+    pub python_version: PythonVersion,
+    /// Value of `sys.platform`: darwin, linux or win32. Defaults to the
+    /// platform enderpy runs on.
+    #[arg(long, default_value = PythonPlatform::default().sys_platform())]
+    pub python_platform: PythonPlatform,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    /// GitHub Actions workflow commands
+    Github,
+    /// SARIF 2.1.0 JSON
+    Sarif,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
 #[test]
 fn verify_cli() {
     use clap::CommandFactory;
@@ -0,0 +1,179 @@
+use std::path::Path;
+
+/// Number of unchanged lines shown around the changes of a hunk
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// A line of the diff with the index of the line in the old source and the
+/// index in the new source. Deleted lines have the index the line would have
+/// in the new source and inserted lines the other way around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DiffLine {
+    op: Op,
+    old: usize,
+    new: usize,
+}
+
+/// Renders the changes from `old` to `new` as a unified diff that can be
+/// applied with `patch` or `git apply`. Returns an empty string if the sources
+/// are equal.
+pub fn unified_diff(path: &Path, old: &str, new: &str) -> String {
+    let old_lines = old.split_inclusive('\n').collect::<Vec<_>>();
+    let new_lines = new.split_inclusive('\n').collect::<Vec<_>>();
+    let lines = diff_lines(&old_lines, &new_lines);
+    let changes = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.op != Op::Equal)
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let path = path.display();
+    let mut out = format!("--- {}\n+++ {}\n", path, path);
+    // Changes that are close enough to share their context lines are in the
+    // same hunk
+    let mut groups: Vec<(usize, usize)> = vec![];
+    for &change in &changes {
+        match groups.last_mut() {
+            Some((_, last)) if change - *last <= 2 * CONTEXT => *last = change,
+            _ => groups.push((change, change)),
+        }
+    }
+    for (first, last) in groups {
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(lines.len());
+        let hunk = &lines[start..end];
+        let old_count = hunk.iter().filter(|l| l.op != Op::Insert).count();
+        let new_count = hunk.iter().filter(|l| l.op != Op::Delete).count();
+        // Empty ranges start at the line before them
+        let old_start = hunk[0].old + usize::from(old_count > 0);
+        let new_start = hunk[0].new + usize::from(new_count > 0);
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+        for line in hunk {
+            let (prefix, text) = match line.op {
+                Op::Equal => (' ', old_lines[line.old]),
+                Op::Delete => ('-', old_lines[line.old]),
+                Op::Insert => ('+', new_lines[line.new]),
+            };
+            out.push(prefix);
+            out.push_str(text);
+            if !text.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+/// Longest common subsequence of the lines. Fixes change few lines, so the
+/// common prefix and suffix are skipped to keep the table small.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // lcs[i][j] is the length of the common subsequence of old_middle[i..]
+    // and new_middle[j..]
+    let width = new_middle.len() + 1;
+    let mut lcs = vec![0u32; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lcs[i * width + j] = if old_middle[i] == new_middle[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines = (0..prefix)
+        .map(|i| DiffLine {
+            op: Op::Equal,
+            old: i,
+            new: i,
+        })
+        .collect::<Vec<_>>();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        let op = if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            Op::Equal
+        } else if j == new_middle.len()
+            || (i < old_middle.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            Op::Delete
+        } else {
+            Op::Insert
+        };
+        lines.push(DiffLine {
+            op,
+            old: prefix + i,
+            new: prefix + j,
+        });
+        if op != Op::Insert {
+            i += 1;
+        }
+        if op != Op::Delete {
+            j += 1;
+        }
+    }
+    lines.extend((0..suffix).map(|k| DiffLine {
+        op: Op::Equal,
+        old: old.len() - suffix + k,
+        new: new.len() - suffix + k,
+    }));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_sources() {
+        assert_eq!(unified_diff(Path::new("a.py"), "x = 1\n", "x = 1\n"), "");
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nl\nm\n";
+        assert_eq!(
+            unified_diff(Path::new("a.py"), old, new),
+            "--- a.py\n+++ a.py\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -8,5 +8,5 @@\n h\n i\n j\n-k\n l\n+m\n"
+        );
+    }
+
+    #[test]
+    fn test_inserted_lines() {
+        assert_eq!(
+            unified_diff(Path::new("a.py"), "", "x = 1\n"),
+            "--- a.py\n+++ a.py\n@@ -0,0 +1,1 @@\n+x = 1\n"
+        );
+        assert_eq!(
+            unified_diff(Path::new("a.py"), "x = (1", "x = (1)"),
+            "--- a.py\n+++ a.py\n@@ -1,1 +1,1 @@\n\
+             -x = (1\n\\ No newline at end of file\n\
+             +x = (1)\n\\ No newline at end of file\n"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Enderpy is a type checker for Python.
+//!
+//! This crate is the API for tools that use enderpy as a library. The items
+//! exported here follow semantic versioning, the modules of the parser and
+//! type checker crates they come from can change between releases.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+pub use enderpy_python_parser::{
+    Edit, Lexer, Parser, ast,
+    error::ParsingError,
+    token::{Kind, Token},
+};
+pub use enderpy_python_type_checker::{
+    build::BuildManager,
+    build_source::BuildSource,
+    cancellation::CancellationToken,
+    diagnostic::{Applicability, Diagnostic, Fix, Position, Range, TextEdit},
+    error_code::ErrorCode,
+    file_system::{FileSystem, OsFileSystem, OverlayFileSystem},
+    settings::{FollowImports, ImportDiscovery, PythonPlatform, PythonVersion, Settings},
+};
+
+/// Parses the source of a module, the syntax errors are returned with the
+/// part of the module that was parsed before the first error
+pub fn parse(source: &str) -> (ast::Module, Vec<ParsingError>) {
+    let mut parser = Parser::new(source.to_string(), String::new());
+    let module = parser.parse();
+    (module, parser.errors)
+}
+
+/// Returns the tokens of the source without the end of file token
+pub fn tokenize(source: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(source);
+    enderpy_python_parser::utils::lex(&mut lexer)
+}
+
+/// Type checks the sources and returns the diagnostics of each source by its
+/// path. Modules found by following imports are checked but not reported.
+pub fn check(sources: Vec<BuildSource>, settings: Settings) -> BTreeMap<PathBuf, Vec<Diagnostic>> {
+    let paths = sources
+        .iter()
+        .map(|source| source.path.clone())
+        .collect::<Vec<PathBuf>>();
+    let mut manager = BuildManager::new(sources, settings);
+    manager.type_check();
+    paths
+        .into_iter()
+        .map(|path| {
+            let diagnostics = manager
+                .get_state(path.clone())
+                .map(|state| state.diagnostics.clone())
+                .unwrap_or_default();
+            (path, diagnostics)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_tokenize() {
+        let (module, errors) = parse("a = 1\n");
+        assert_eq!(module.body.len(), 1);
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse("a = (\n");
+        assert_eq!(errors.len(), 1);
+
+        let kinds = tokenize("a = 1")
+            .iter()
+            .map(|token| token.kind)
+            .collect::<Vec<Kind>>();
+        assert_eq!(kinds, vec![Kind::Identifier, Kind::Assign, Kind::Integer]);
+    }
+
+    #[test]
+    fn test_check() {
+        let path = PathBuf::from("test.py");
+        let diagnostics = check(
+            vec![BuildSource {
+                path: path.clone(),
+                module: String::from("test"),
+                source: String::from("x = 1\nx()\n"),
+                followed: false,
+            }],
+            Settings::default(),
+        );
+        let bodies = diagnostics[&path]
+            .iter()
+            .map(|diagnostic| diagnostic.body.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(bodies, vec!["x is not callable"]);
+    }
+}
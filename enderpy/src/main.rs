@@ -1,54 +1,252 @@
 use std::{
+    collections::{HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
 };
 
 use clap::Parser as ClapParser;
-use cli::{Cli, Commands};
-use enderpy_python_parser::{Lexer, Parser};
+use cli::{CheckArgs, Cli, Commands, OutputFormat, ReportFormat};
+use enderpy_python_parser::{Lexer, Parser, TextSize};
 use enderpy_python_type_checker::{
     build::BuildManager,
     build_source::BuildSource,
-    project::find_project_root,
+    diagnostic::{Applicability, Fix, LineIndex, Position, apply_fixes},
+    error_code::{ErrorCode, RuleSelection},
+    metrics::FileMetrics,
+    module_info::ModuleInfo,
+    notebook::Notebook,
+    project::{find_project_root, read_docstring_style, read_rule_selection},
     settings::{ImportDiscovery, Settings},
+    symbol_query::{SymbolKind, SymbolQuery},
 };
-use miette::{bail, IntoDiagnostic, Result};
+use miette::{IntoDiagnostic, Result, bail, miette};
 
 mod cli;
+mod diff;
+mod output;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     match &cli.command {
         Commands::Tokenize { file } => tokenize(file),
         Commands::Parse { file } => parse(file),
-        Commands::Check { path } => check(path),
+        Commands::Check(args) => check(args),
         Commands::Watch => watch(),
-        Commands::Symbols { path } => symbols(path),
+        Commands::Symbols {
+            path,
+            scope,
+            kind,
+            format,
+        } => symbols(path, scope.clone(), *kind, *format),
+        Commands::Annotate { path, diff } => annotate(path, *diff),
+        Commands::Unused { path } => unused(path),
+        Commands::Hierarchy { class_name, path } => hierarchy(class_name, path),
+        Commands::Metrics { path, format } => metrics(path, *format),
+        Commands::Explain { code } => explain(code),
     }
 }
 
-fn symbols(path: &Path) -> Result<()> {
-    let initial_source = BuildSource::from_path(path.to_path_buf(), false).unwrap();
-    let dir_of_path = path.parent().unwrap();
-    let python_executable = Some(get_python_executable()?);
-    let typeshed_path = Some(get_typeshed_path()?);
+fn annotate(path: &Path, diff: bool) -> Result<()> {
+    let initial_source = read_build_source(path)?;
+    let source = initial_source.source.clone();
+    let settings = settings_for(find_project_root(path))?;
+    let mut manager = BuildManager::new(vec![initial_source], settings);
+    manager.build();
+
+    let edits = manager.annotations(path.to_path_buf());
+    let fix = Fix {
+        message: "Insert inferred annotations".to_string(),
+        edits,
+        applicability: Applicability::Unsafe,
+    };
+    let annotated = apply_fixes(&source, &[&fix]).source;
+    if diff {
+        print!("{}", diff::unified_diff(path, &source, &annotated));
+    } else if annotated != source {
+        write_atomically(path, &annotated).map_err(|e| file_error(path, e))?;
+        println!("{:#?}: inserted {} annotations", path, fix.edits.len());
+    }
+    Ok(())
+}
+
+/// Replaces the contents of the file by renaming a temporary file next to it,
+/// so that an interrupted write does not leave a truncated source behind
+fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "not a file"))?;
+    let temp_path = path.with_file_name(format!(".{}.enderpy-tmp", file_name.to_string_lossy()));
+    fs::write(&temp_path, contents)?;
+    let result = fs::metadata(path)
+        .and_then(|metadata| fs::set_permissions(&temp_path, metadata.permissions()))
+        .and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn unused(path: &Path) -> Result<()> {
+    let mut sources = vec![];
+    for file in python_files(path).map_err(|e| file_error(path, e))? {
+        sources.push(read_build_source(&file)?);
+    }
+    let settings = settings_for(find_project_root(path))?;
+    let mut manager = BuildManager::new(sources, settings);
+    manager.build();
+
+    for symbol in manager.unused_symbols() {
+        println!(
+            "{}:{}:{}: unused {} '{}'",
+            symbol.path.display(),
+            symbol.position.line + 1,
+            symbol.position.character + 1,
+            symbol.kind,
+            symbol.name
+        );
+    }
+    Ok(())
+}
+
+fn hierarchy(class_name: &str, path: &Path) -> Result<()> {
+    let mut sources = vec![];
+    for file in python_files(path).map_err(|e| file_error(path, e))? {
+        sources.push(read_build_source(&file)?);
+    }
+    let settings = settings_for(find_project_root(path))?;
+    let mut manager = BuildManager::new(sources, settings);
+    manager.build();
+
+    let hierarchies = manager.class_hierarchy(class_name);
+    if hierarchies.is_empty() {
+        bail!("class {} not found", class_name);
+    }
+    for hierarchy in hierarchies {
+        print!("{}", hierarchy);
+    }
+    Ok(())
+}
+
+fn metrics(path: &Path, format: ReportFormat) -> Result<()> {
+    let mut all_metrics = vec![];
+    for file in python_files(path).map_err(|e| file_error(path, e))? {
+        let (source, _) = read_source(&file)?;
+        all_metrics.push(FileMetrics::from_source(file, &source));
+    }
+    match format {
+        ReportFormat::Json => {
+            let json = serde_json::to_string_pretty(&all_metrics).into_diagnostic()?;
+            println!("{}", json);
+        }
+        ReportFormat::Text => {
+            for file_metrics in all_metrics {
+                for function in file_metrics.functions {
+                    println!(
+                        "{}:{}: {} complexity={} statements={} max_nesting_depth={}",
+                        file_metrics.path.display(),
+                        function.line,
+                        function.name,
+                        function.cyclomatic_complexity,
+                        function.statements,
+                        function.max_nesting_depth
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Python files and notebooks in the directory. Other paths are returned as
+/// they are, so that scripts without an extension are checked when they are
+/// named explicitly.
+fn python_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = vec![];
+    let mut entries = fs::read_dir(path)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.path());
+    for entry in entries {
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            files.extend(python_files(&entry_path)?);
+        } else if entry_path
+            .extension()
+            .is_some_and(|ext| ext == "py" || ext == "ipynb")
+        {
+            files.push(entry_path);
+        }
+    }
+    Ok(files)
+}
+
+fn explain(code: &str) -> Result<()> {
+    let code = match code.parse::<ErrorCode>() {
+        Ok(code) => code,
+        Err(e) => bail!(e),
+    };
+    println!("{} ({})", code, code.name());
+    println!();
+    println!("{}", code.explanation());
+    Ok(())
+}
+
+fn symbols(
+    path: &Path,
+    scope: Option<String>,
+    kind: Option<SymbolKind>,
+    format: ReportFormat,
+) -> Result<()> {
+    let initial_source = read_build_source(path)?;
+    let dir_of_path = path.parent().unwrap_or(Path::new(""));
     let settings = Settings {
         debug: true,
-        root: dir_of_path.to_path_buf(),
-        import_discovery: ImportDiscovery {
-            python_executable,
-            typeshed_path,
-        },
         follow_imports: enderpy_python_type_checker::settings::FollowImports::All,
+        ..settings_for(dir_of_path)?
     };
 
     let mut manager = BuildManager::new(vec![initial_source], settings);
     manager.build();
 
-    let module = manager.get_state(path.to_path_buf()).unwrap();
-    println!("{}", module.file.module_name());
-    println!("{}", module.get_symbol_table());
+    if scope.is_none() && kind.is_none() {
+        let Some(module) = manager.get_state(path.to_path_buf()) else {
+            bail!("{} was not analyzed", path.display());
+        };
+        match format {
+            ReportFormat::Json => {
+                let json = module.get_symbol_table().to_json();
+                println!("{}", serde_json::to_string_pretty(&json).into_diagnostic()?);
+            }
+            ReportFormat::Text => {
+                println!("{}", module.file.module_name());
+                println!("{}", module.get_symbol_table());
+            }
+        }
+        return Ok(());
+    }
 
+    let query = SymbolQuery { scope, kind };
+    let symbols = manager.symbols(path.to_path_buf(), &query);
+    match format {
+        ReportFormat::Json => {
+            let json = serde_json::to_string_pretty(&symbols).into_diagnostic()?;
+            println!("{}", json);
+        }
+        ReportFormat::Text => {
+            for symbol in symbols {
+                println!(
+                    "{}:{}:{}: {} {} in {}",
+                    path.display(),
+                    symbol.line + 1,
+                    symbol.character + 1,
+                    symbol.kind,
+                    symbol.name,
+                    symbol.scope
+                );
+            }
+        }
+    }
     Ok(())
 }
 
@@ -68,8 +266,22 @@ fn get_typeshed_path() -> Result<PathBuf> {
     Ok(path.join("typeshed"))
 }
 
+/// Settings of a build of the project at `root` with the python executable
+/// and typeshed of the environment, commands set the other settings from
+/// their arguments
+fn settings_for(root: &Path) -> Result<Settings> {
+    Ok(Settings {
+        root: root.to_path_buf(),
+        import_discovery: ImportDiscovery {
+            python_executable: Some(get_python_executable()?),
+            typeshed_path: Some(get_typeshed_path()?),
+        },
+        ..Default::default()
+    })
+}
+
 fn tokenize(file: &PathBuf) -> Result<()> {
-    let source = fs::read_to_string(file).into_diagnostic()?;
+    let source = fs::read_to_string(file).map_err(|e| file_error(file, e))?;
     let mut lexer = Lexer::new(&source);
     let tokens = enderpy_python_parser::utils::lex(&mut lexer);
     for token in tokens {
@@ -78,50 +290,344 @@ fn tokenize(file: &PathBuf) -> Result<()> {
     Ok(())
 }
 
-fn parse(file: &PathBuf) -> Result<()> {
-    let source = fs::read_to_string(file).into_diagnostic()?;
+fn parse(file: &Path) -> Result<()> {
+    let (source, notebook) = read_source(file)?;
     let file_path = file.to_str().unwrap_or("");
-    let mut parser = Parser::new(source, file_path.into());
+    let mut parser = Parser::new(source.clone(), file_path.into());
     let ast = parser.parse();
+    let line_index = LineIndex::new(&source);
     for err in parser.errors {
+        if notebook.is_some() {
+            let position = line_index.position(&source, err.span().0 as TextSize);
+            println!("{}:", location(notebook.as_ref(), &position));
+        }
         println!("{:#?}", err);
     }
     println!("{:#?}", ast);
     Ok(())
 }
 
-fn check(path: &Path) -> Result<()> {
-    if path.is_dir() {
-        bail!("Path must be a file");
+/// Reads a python file or the code cells of a notebook
+fn read_source(path: &Path) -> Result<(String, Option<Notebook>)> {
+    let source = fs::read_to_string(path).map_err(|e| file_error(path, e))?;
+    if !Notebook::is_notebook(path) {
+        return Ok((source, None));
+    }
+    let notebook = Notebook::from_json(&source)
+        .map_err(|e| miette!("{} is not a valid notebook: {}", path.display(), e))?;
+    Ok((notebook.source(), Some(notebook)))
+}
+
+/// Reads a file that is passed on the command line
+fn read_build_source(path: &Path) -> Result<BuildSource> {
+    BuildSource::from_path(path.to_path_buf(), false).map_err(|e| file_error(path, e))
+}
+
+fn file_error(path: &Path, error: std::io::Error) -> miette::Report {
+    miette!(
+        help = "check that the path exists and is readable",
+        "cannot read {}: {}",
+        path.display(),
+        error
+    )
+}
+
+/// Formats a position of a source as one based line and column, lines of
+/// notebooks are shown relative to their cell. Cells are numbered from one
+/// and only code cells are counted.
+fn location(notebook: Option<&Notebook>, position: &Position) -> String {
+    match notebook.and_then(|n| n.cell_position(position.line)) {
+        Some((cell, cell_line)) => format!(
+            "cell {}: line {}, column {}",
+            cell.number,
+            cell_line + 1,
+            position.character + 1
+        ),
+        None => format!(
+            "line {}, column {}",
+            position.line + 1,
+            position.character + 1
+        ),
+    }
+}
+
+fn check(args: &CheckArgs) -> Result<()> {
+    let &CheckArgs {
+        ref paths,
+        fix,
+        diff,
+        changed_only,
+        warn_star_imports,
+        sort_imports,
+        docstring_style,
+        uppercase_constants,
+        strict_optional,
+        join_container_types,
+        check_format_strings,
+        explain_cache,
+        ref select,
+        ref ignore,
+        ref target,
+        format,
+    } = args;
+    let rule_selection = RuleSelection {
+        select: select.clone(),
+        ignore: ignore.clone(),
+    };
+    let mut files = vec![];
+    for path in paths {
+        files.extend(python_files(path).map_err(|e| file_error(path, e))?);
+    }
+    let root = find_project_root(paths.first().map_or(Path::new("."), |p| p.as_path()));
+    let rule_selection = read_rule_selection(root)
+        .map_err(|e| miette!("invalid [tool.enderpy] in pyproject.toml: {}", e))?
+        .merge(rule_selection);
+    let docstring_style = match docstring_style {
+        Some(style) => Some(style),
+        None => read_docstring_style(root)
+            .map_err(|e| miette!("invalid [tool.enderpy] in pyproject.toml: {}", e))?,
+    };
+    let unknown_selectors = rule_selection.unknown_selectors();
+    if !unknown_selectors.is_empty() {
+        bail!("unknown error codes: {}", unknown_selectors.join(", "));
+    }
+    if changed_only {
+        let changed = changed_files(root)?;
+        files.retain(|file| {
+            file.canonicalize()
+                .is_ok_and(|file| changed.contains(&file))
+        });
+    }
+    files.sort();
+    files.dedup();
+
+    let mut notebooks = HashMap::new();
+    let mut sources = vec![];
+    for file in &files {
+        if let Some(notebook) = read_source(file)?.1 {
+            if fix {
+                bail!("Fixes cannot be applied to notebooks");
+            }
+            notebooks.insert(file.clone(), notebook);
+        }
+        sources.push(read_build_source(file)?);
     }
-    let initial_source = BuildSource::from_path(path.to_path_buf(), false).unwrap();
-    let root = find_project_root(path);
-    let python_executable = Some(get_python_executable()?);
-    let typeshed_path = Some(get_typeshed_path()?);
     let settings = Settings {
-        debug: true,
-        root: PathBuf::from(root),
-        import_discovery: ImportDiscovery {
-            python_executable,
-            typeshed_path,
-        },
-        follow_imports: enderpy_python_type_checker::settings::FollowImports::Skip,
+        warn_star_imports,
+        sort_imports,
+        docstring_style,
+        uppercase_constants,
+        strict_optional,
+        join_container_types,
+        check_format_strings,
+        python_version: target.python_version,
+        python_platform: target.python_platform,
+        ..settings_for(root)?
     };
-    let mut build_manager = BuildManager::new(vec![initial_source], settings);
+    let mut build_manager = BuildManager::new(sources, settings);
     build_manager.type_check();
+    if explain_cache {
+        print_module_infos(&build_manager.module_infos());
+    }
 
-    for file_result in build_manager.get_result() {
-        for err in file_result.diagnostics {
-            println!(
-                "{:#?}: line {}: {}",
-                file_result.file.path(),
-                err.range.start.line,
-                err.body
-            );
+    let mut results = build_manager
+        .get_result()
+        .into_iter()
+        .filter(|state| files.contains(&state.file.path()))
+        .collect::<Vec<_>>();
+    results.sort_by_key(|state| state.file.path());
+    // Passes report diagnostics in the order they run, they are shown in the
+    // order of their positions
+    for file_result in &mut results {
+        file_result
+            .diagnostics
+            .retain(|diagnostic| rule_selection.is_reported(diagnostic.code));
+        file_result
+            .diagnostics
+            .sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    }
+
+    let mut error_count = 0;
+    let mut files_with_errors = 0;
+    let mut fixable_count = 0;
+    for file_result in &results {
+        let path = file_result.file.path();
+        let notebook = notebooks.get(&path);
+        // The diff is the only output on stdout so that it can be piped to
+        // `patch`
+        for err in file_result.diagnostics.iter().filter(|_| !diff) {
+            match format {
+                OutputFormat::Text => {
+                    println!(
+                        "{:#?}: {}: enderpy[{}] {}",
+                        path,
+                        location(notebook, &err.range.start),
+                        err.code,
+                        err.body
+                    );
+                    for (range, message) in &err.labels {
+                        println!(
+                            "    {:#?}: {}: note: {}",
+                            path,
+                            location(notebook, &range.start),
+                            message
+                        );
+                    }
+                    if let Some(help) = err.suggestion.as_ref().filter(|s| !s.is_empty()) {
+                        println!("    help: {}", help);
+                    }
+                }
+                OutputFormat::Github => {
+                    println!("{}", output::github_annotation(&path, notebook, err))
+                }
+                OutputFormat::Sarif => (),
+            }
+        }
+        if !file_result.diagnostics.is_empty() {
+            error_count += file_result.diagnostics.len();
+            files_with_errors += 1;
+        }
+        if fix {
+            let fixes = file_result
+                .diagnostics
+                .iter()
+                .filter_map(|d| d.fix.as_ref())
+                .filter(|f| f.applicability == Applicability::Safe)
+                .collect::<Vec<_>>();
+            if fixes.is_empty() {
+                continue;
+            }
+            let source = file_result.file.source();
+            let fixed = apply_fixes(&source, &fixes);
+            for conflict in &fixed.conflicts {
+                eprintln!(
+                    "{:#?}: skipped fix \"{}\" because it overlaps another fix",
+                    path, conflict.message
+                );
+            }
+            fixable_count += fixed.applied;
+            if diff {
+                print!("{}", diff::unified_diff(&path, &source, &fixed.source));
+            } else {
+                write_atomically(&path, &fixed.source).map_err(|e| file_error(&path, e))?;
+                eprintln!("{:#?}: applied {} fixes", path, fixed.applied);
+            }
         }
     }
 
-    Ok(())
+    if diff {
+        let fixes = if fixable_count == 1 { "fix" } else { "fixes" };
+        eprintln!("Would apply {} {}", fixable_count, fixes);
+        if fixable_count > 0 {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if format == OutputFormat::Sarif {
+        let paths = results
+            .iter()
+            .map(|file_result| file_result.file.path())
+            .collect::<Vec<_>>();
+        let diagnostics = results
+            .iter()
+            .zip(&paths)
+            .flat_map(|(file_result, path)| {
+                let notebook = notebooks.get(path);
+                file_result
+                    .diagnostics
+                    .iter()
+                    .map(move |d| (path.as_path(), notebook, d))
+            })
+            .collect::<Vec<_>>();
+        let log = serde_json::to_string_pretty(&output::sarif(&diagnostics)).into_diagnostic()?;
+        println!("{}", log);
+    }
+
+    if format != OutputFormat::Text {
+        if error_count > 0 {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if error_count == 0 {
+        println!("Success: no errors in {}", plural(files.len(), "file"));
+        return Ok(());
+    }
+    println!(
+        "Found {} in {} (checked {})",
+        plural(error_count, "error"),
+        plural(files_with_errors, "file"),
+        plural(files.len(), "file")
+    );
+    std::process::exit(1);
+}
+
+/// Prints a table of the modules of the build to stderr, so that it does not
+/// mix with the diagnostics in the machine readable formats
+fn print_module_infos(module_infos: &[ModuleInfo]) {
+    let paths = module_infos
+        .iter()
+        .map(|info| info.path.display().to_string())
+        .collect::<Vec<_>>();
+    let width = paths
+        .iter()
+        .map(|path| path.len())
+        .max()
+        .unwrap_or(0)
+        .max("path".len());
+    eprintln!(
+        "{:<width$} {:>7} {:>10} {:>8} {:>12} {:>8} {:>4}",
+        "path", "lines", "parse (ms)", "symbols", "diagnostics", "imports", "ast"
+    );
+    for (info, path) in module_infos.iter().zip(&paths) {
+        eprintln!(
+            "{:<width$} {:>7} {:>10.2} {:>8} {:>12} {:>8} {:>4}",
+            path,
+            info.lines,
+            info.parse_time.as_secs_f64() * 1000.0,
+            info.symbols,
+            info.diagnostics,
+            info.resolved_imports,
+            if info.ast_retained { "yes" } else { "no" }
+        );
+    }
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, word)
+    } else {
+        format!("{} {}s", count, word)
+    }
+}
+
+/// Returns the canonical paths of files that are modified or untracked in the
+/// git repository containing `root`
+fn changed_files(root: &Path) -> Result<HashSet<PathBuf>> {
+    let git = |args: &[&str]| -> Result<String> {
+        let output = std::process::Command::new("git")
+            .current_dir(root)
+            .args(args)
+            .output()
+            .into_diagnostic()?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        String::from_utf8(output.stdout).into_diagnostic()
+    };
+    let toplevel = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?.trim());
+    let modified = git(&["diff", "--name-only", "HEAD"])?;
+    let untracked = git(&["ls-files", "--others", "--exclude-standard", "--full-name"])?;
+    Ok(modified
+        .lines()
+        .chain(untracked.lines())
+        .filter_map(|file| toplevel.join(file).canonicalize().ok())
+        .collect())
 }
 
 fn watch() -> Result<()> {
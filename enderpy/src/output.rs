@@ -0,0 +1,226 @@
+use std::path::Path;
+
+use enderpy_python_type_checker::{
+    diagnostic::{Diagnostic, Range},
+    error_code::ErrorCode,
+    notebook::Notebook,
+};
+use serde_json::{Value, json};
+
+/// Formats a diagnostic as a GitHub Actions workflow command.
+/// GitHub shows these as annotations on the lines of the pull request.
+/// Lines of notebooks are relative to their cell and the cell is in the title.
+pub fn github_annotation(
+    path: &Path,
+    notebook: Option<&Notebook>,
+    diagnostic: &Diagnostic,
+) -> String {
+    let (range, title) = match notebook.and_then(|n| n.cell_range(&diagnostic.range)) {
+        Some((cell, range)) => (
+            range,
+            format!("enderpy {} (cell {})", diagnostic.code, cell.number),
+        ),
+        None => (
+            diagnostic.range.clone(),
+            format!("enderpy {}", diagnostic.code),
+        ),
+    };
+    format!(
+        "::error file={},line={},col={},endLine={},endColumn={},title={}::{}",
+        escape_property(&path.display().to_string()),
+        range.start.line + 1,
+        range.start.character + 1,
+        range.end.line + 1,
+        range.end.character + 1,
+        escape_property(&title),
+        escape_data(&diagnostic.body)
+    )
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Builds a SARIF 2.1.0 log with one run containing all diagnostics.
+/// Regions in notebooks are relative to their cell, the 1-based number of the
+/// code cell is in the `cell` property of the location.
+pub fn sarif(diagnostics: &[(&Path, Option<&Notebook>, &Diagnostic)]) -> Value {
+    let rules = ErrorCode::ALL
+        .iter()
+        .map(|code| {
+            json!({
+                "id": code.as_str(),
+                "name": code.name(),
+                "fullDescription": { "text": code.explanation() },
+                "helpUri": code.url(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let results = diagnostics
+        .iter()
+        .map(|&(path, notebook, diagnostic)| {
+            let related_locations = diagnostic
+                .labels
+                .iter()
+                .enumerate()
+                .map(|(id, (range, message))| {
+                    json!({
+                        "id": id,
+                        "message": { "text": message },
+                        "physicalLocation": physical_location(path, notebook, range),
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "ruleId": diagnostic.code.as_str(),
+                "level": "error",
+                "message": { "text": diagnostic.body },
+                "locations": [{
+                    "physicalLocation": physical_location(path, notebook, &diagnostic.range),
+                }],
+                "relatedLocations": related_locations,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "enderpy",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+fn physical_location(path: &Path, notebook: Option<&Notebook>, range: &Range) -> Value {
+    let cell_range = notebook.and_then(|n| n.cell_range(range));
+    let region_range = cell_range.as_ref().map_or(range, |(_, range)| range);
+    let mut location = json!({
+        "artifactLocation": { "uri": path.display().to_string() },
+        "region": {
+            "startLine": region_range.start.line + 1,
+            "startColumn": region_range.start.character + 1,
+            "endLine": region_range.end.line + 1,
+            "endColumn": region_range.end.character + 1,
+        }
+    });
+    if let Some((cell, _)) = cell_range {
+        location["properties"] = json!({ "cell": cell.number });
+    }
+    location
+}
+
+#[test]
+fn test_github_annotation_escaping() {
+    use enderpy_python_type_checker::diagnostic::Position;
+
+    let diagnostic = Diagnostic {
+        body: "100% wrong\nvalue".to_string(),
+        suggestion: None,
+        range: Range {
+            start: Position {
+                line: 0,
+                character: 4,
+            },
+            end: Position {
+                line: 0,
+                character: 7,
+            },
+        },
+        code: ErrorCode::NotCallable,
+        fix: None,
+        labels: vec![],
+    };
+    assert_eq!(
+        github_annotation(Path::new("a,b.py"), None, &diagnostic),
+        "::error file=a%2Cb.py,line=1,col=5,endLine=1,endColumn=8,title=enderpy E201::100%25 wrong%0Avalue"
+    );
+}
+
+#[test]
+fn test_github_annotation_notebook() {
+    use enderpy_python_type_checker::diagnostic::Position;
+
+    let notebook = Notebook::from_json(
+        r##"{"cells": [
+            {"cell_type": "markdown", "source": "# Title"},
+            {"cell_type": "code", "source": "x = 1"},
+            {"cell_type": "code", "source": "y = 2\nx()"}
+        ]}"##,
+    )
+    .unwrap();
+    let diagnostic = Diagnostic {
+        body: "x is not callable".to_string(),
+        suggestion: None,
+        range: Range {
+            start: Position {
+                line: 2,
+                character: 0,
+            },
+            end: Position {
+                line: 2,
+                character: 3,
+            },
+        },
+        code: ErrorCode::NotCallable,
+        fix: None,
+        labels: vec![],
+    };
+    assert_eq!(
+        github_annotation(Path::new("a.ipynb"), Some(&notebook), &diagnostic),
+        "::error file=a.ipynb,line=2,col=1,endLine=2,endColumn=4,title=enderpy E201 (cell 2)::x is not callable"
+    );
+}
+
+#[test]
+fn test_sarif_notebook() {
+    use enderpy_python_type_checker::diagnostic::Position;
+
+    let notebook = Notebook::from_json(
+        r##"{"cells": [
+            {"cell_type": "markdown", "source": "# Title"},
+            {"cell_type": "code", "source": "x = 1"},
+            {"cell_type": "code", "source": "y = 2\nx()"}
+        ]}"##,
+    )
+    .unwrap();
+    let diagnostic = Diagnostic {
+        body: "x is not callable".to_string(),
+        suggestion: None,
+        range: Range {
+            start: Position {
+                line: 2,
+                character: 0,
+            },
+            end: Position {
+                line: 2,
+                character: 3,
+            },
+        },
+        code: ErrorCode::NotCallable,
+        fix: None,
+        labels: vec![],
+    };
+    let log = sarif(&[(Path::new("a.ipynb"), Some(&notebook), &diagnostic)]);
+    assert_eq!(
+        log["runs"][0]["results"][0]["locations"][0]["physicalLocation"],
+        json!({
+            "artifactLocation": { "uri": "a.ipynb" },
+            "region": { "startLine": 2, "startColumn": 1, "endLine": 2, "endColumn": 4 },
+            "properties": { "cell": 2 },
+        })
+    );
+}
@@ -0,0 +1,164 @@
+// Runs the enderpy binary on files in temporary directories. Inputs that
+// cannot be read must exit with an error that names the path instead of
+// panicking.
+
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Output},
+};
+
+fn enderpy(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_enderpy"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn assert_fails_with(output: &Output, message: &str) {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "stderr: {}", stderr);
+    assert!(!stderr.contains("panicked"), "stderr: {}", stderr);
+    assert!(
+        stderr.contains(message),
+        "expected {:?} in stderr: {}",
+        message,
+        stderr
+    );
+}
+
+#[test]
+fn test_missing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.py");
+    let missing = missing.to_str().unwrap();
+    let message = format!("cannot read {}", missing);
+
+    for command in [
+        "check", "symbols", "annotate", "parse", "tokenize", "metrics", "unused",
+    ] {
+        assert_fails_with(&enderpy(&[command, missing]), &message);
+    }
+    assert_fails_with(&enderpy(&["hierarchy", "A", "--path", missing]), &message);
+}
+
+#[test]
+fn test_unreadable_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let directory = dir.path().join("package.py");
+    fs::create_dir(&directory).unwrap();
+    let notebook = dir.path().join("notebook.ipynb");
+    fs::write(&notebook, "not json").unwrap();
+    let path = |path: &Path| path.to_str().unwrap().to_string();
+
+    assert_fails_with(
+        &enderpy(&["tokenize", &path(&directory)]),
+        &format!("cannot read {}", path(&directory)),
+    );
+    assert_fails_with(
+        &enderpy(&["parse", &path(&notebook)]),
+        &format!("{} is not a valid notebook", path(&notebook)),
+    );
+    assert_fails_with(
+        &enderpy(&["check", &path(&notebook)]),
+        &format!("{} is not a valid notebook", path(&notebook)),
+    );
+}
+
+#[test]
+fn test_select_and_ignore() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("main.py");
+    fs::write(&source, "x = 1\nx()\ny = 1 + \"a\"\n").unwrap();
+    let source = source.to_str().unwrap();
+    let codes = |args: &[&str]| {
+        let output = enderpy(&[&["check", source], args].concat());
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| Some(line.split("enderpy[").nth(1)?[..4].to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(codes(&[]), vec!["E201", "E203"]);
+    assert_eq!(codes(&["--select", "E203"]), vec!["E203"]);
+    assert_eq!(codes(&["--select", "E2", "--ignore", "E201"]), vec!["E203"]);
+    assert_eq!(codes(&["--ignore", "E201,E203"]), Vec::<String>::new());
+    assert_fails_with(
+        &enderpy(&["check", source, "--select", "W9"]),
+        "unknown error codes: W9",
+    );
+
+    // The command line replaces the selectors of the config file
+    fs::write(
+        dir.path().join("pyproject.toml"),
+        "[tool.enderpy]\nignore = [\"E201\"]\n",
+    )
+    .unwrap();
+    assert_eq!(codes(&[]), vec!["E203"]);
+    assert_eq!(codes(&["--ignore", "E203"]), vec!["E201"]);
+}
+
+#[test]
+fn test_diagnostics_sorted_by_position() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("main.py");
+    fs::write(&source, "x = 1\nx()\nif x\n    pass\n").unwrap();
+
+    // Syntax errors are found before type errors but shown in source order
+    let output = enderpy(&["check", source.to_str().unwrap()]);
+    let lines = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| Some(line.split(": line ").nth(1)?[..1].to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec!["2", "3"]);
+}
+
+#[test]
+fn test_fix_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("main.py");
+    fs::write(&source, "if x\n    pass\n").unwrap();
+    let path = source.to_str().unwrap();
+
+    let output = enderpy(&["check", path, "--fix", "--diff"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "--- {}\n+++ {}\n@@ -1,2 +1,2 @@\n-if x\n+if x:\n     pass\n",
+            path, path
+        )
+    );
+    assert_eq!(fs::read_to_string(&source).unwrap(), "if x\n    pass\n");
+
+    enderpy(&["check", path, "--fix"]);
+    assert_eq!(fs::read_to_string(&source).unwrap(), "if x:\n    pass\n");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    let output = enderpy(&["check", path, "--fix", "--diff"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_extensionless_script() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("script");
+    fs::write(
+        &script,
+        "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\nx = 1\nx()\n",
+    )
+    .unwrap();
+    let stdout = |args: &[&str]| String::from_utf8_lossy(&enderpy(args).stdout).to_string();
+
+    // Scripts are checked when they are given explicitly but not when they
+    // are found in a directory
+    let output = stdout(&["check", script.to_str().unwrap()]);
+    assert!(
+        output.contains("line 4, column 1: enderpy[E201]"),
+        "{}",
+        output
+    );
+    let output = stdout(&["check", dir.path().to_str().unwrap()]);
+    assert!(output.contains("no errors in 0 files"), "{}", output);
+}
@@ -0,0 +1,31 @@
+#ifndef ENDERPY_H
+#define ENDERPY_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* UTF-8 JSON owned by the caller, release it with enderpy_buffer_free.
+ * data is NULL when the input was NULL or not valid UTF-8.
+ * Internal errors are returned as {"error": "<message>"}. */
+typedef struct EnderpyBuffer {
+  uint8_t *data;
+  size_t len;
+} EnderpyBuffer;
+
+/* Parses python source and returns {"ast": ..., "errors": [...]} */
+EnderpyBuffer enderpy_parse(const uint8_t *source, size_t len);
+
+/* Type checks python source and returns a JSON array of diagnostics */
+EnderpyBuffer enderpy_diagnostics(const uint8_t *source, size_t len);
+
+void enderpy_buffer_free(EnderpyBuffer buffer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ENDERPY_H */
@@ -0,0 +1,193 @@
+// C ABI for hosts that embed enderpy in process.
+// All results are returned as UTF-8 JSON in an `EnderpyBuffer` that is owned
+// by the caller and must be released with `enderpy_buffer_free`.
+// Panics must not unwind into the host, they are caught and returned as
+// `{"error": "<message>"}`.
+// The declarations for C are in `include/enderpy.h`.
+
+use std::{
+    panic::{self, UnwindSafe},
+    path::PathBuf,
+    ptr, slice,
+};
+
+use enderpy_python_parser::{Parser, error::ParsingError};
+use enderpy_python_type_checker::{
+    build::BuildManager, build_source::BuildSource, diagnostic::Diagnostic, error_code::ErrorCode,
+    settings::Settings,
+};
+use serde_json::{Value, json};
+
+const FILE_NAME: &str = "main.py";
+
+#[repr(C)]
+pub struct EnderpyBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl EnderpyBuffer {
+    fn empty() -> Self {
+        EnderpyBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn from_json(value: Value) -> Self {
+        let bytes = value.to_string().into_bytes().into_boxed_slice();
+        let len = bytes.len();
+        EnderpyBuffer {
+            data: Box::into_raw(bytes) as *mut u8,
+            len,
+        }
+    }
+}
+
+/// # Safety
+/// `source` must point to `len` bytes of valid memory or be null.
+unsafe fn read_source<'a>(source: *const u8, len: usize) -> Option<&'a str> {
+    if source.is_null() {
+        return None;
+    }
+    std::str::from_utf8(slice::from_raw_parts(source, len)).ok()
+}
+
+/// Parses the source and returns `{"ast": ..., "errors": [...]}`.
+/// Returns an empty buffer if the source is null or not valid UTF-8 and
+/// `{"error": ...}` if parsing panics.
+///
+/// # Safety
+/// `source` must point to `len` bytes of valid memory or be null.
+#[no_mangle]
+pub unsafe extern "C" fn enderpy_parse(source: *const u8, len: usize) -> EnderpyBuffer {
+    let Some(source) = read_source(source, len) else {
+        return EnderpyBuffer::empty();
+    };
+    EnderpyBuffer::from_json(catch_panic(|| parse(source)))
+}
+
+/// Type checks the source and returns the diagnostics as a JSON array.
+/// Returns an empty buffer if the source is null or not valid UTF-8 and
+/// `{"error": ...}` if type checking panics.
+///
+/// # Safety
+/// `source` must point to `len` bytes of valid memory or be null.
+#[no_mangle]
+pub unsafe extern "C" fn enderpy_diagnostics(source: *const u8, len: usize) -> EnderpyBuffer {
+    let Some(source) = read_source(source, len) else {
+        return EnderpyBuffer::empty();
+    };
+    EnderpyBuffer::from_json(catch_panic(|| diagnostics(source)))
+}
+
+/// Releases a buffer returned by enderpy.
+///
+/// # Safety
+/// The buffer must come from enderpy and must not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn enderpy_buffer_free(buffer: EnderpyBuffer) {
+    if buffer.data.is_null() {
+        return;
+    }
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+        buffer.data,
+        buffer.len,
+    )));
+}
+
+/// Returns the result of `f` or `{"error": ...}` with the panic message if it
+/// panics
+fn catch_panic(f: impl FnOnce() -> Value + UnwindSafe) -> Value {
+    panic::catch_unwind(f).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        json!({ "error": message })
+    })
+}
+
+fn parse(source: &str) -> Value {
+    let mut parser = Parser::new(source.to_string(), FILE_NAME.to_string());
+    let ast = parser.parse();
+    let errors = parser
+        .errors
+        .iter()
+        .map(parsing_error_to_json)
+        .collect::<Vec<_>>();
+    json!({
+        "ast": ast,
+        "errors": errors,
+    })
+}
+
+fn diagnostics(source: &str) -> Value {
+    let build_source = BuildSource {
+        path: PathBuf::from(FILE_NAME),
+        module: String::from("main"),
+        source: source.to_string(),
+        followed: false,
+    };
+    let mut manager = BuildManager::new(vec![build_source], Settings::default());
+    manager.type_check();
+    let diagnostics = manager
+        .get_result()
+        .iter()
+        .flat_map(|state| state.diagnostics.iter().map(Diagnostic::to_json))
+        .collect::<Vec<_>>();
+    Value::Array(diagnostics)
+}
+
+fn parsing_error_to_json(error: &ParsingError) -> Value {
+    let (start, end) = error.span();
+    json!({
+        "code": ErrorCode::from(error).as_str(),
+        "message": error.message(),
+        "start": start,
+        "end": end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(f: unsafe extern "C" fn(*const u8, usize) -> EnderpyBuffer, source: &str) -> Value {
+        unsafe {
+            let buffer = f(source.as_ptr(), source.len());
+            let json = slice::from_raw_parts(buffer.data, buffer.len).to_vec();
+            enderpy_buffer_free(buffer);
+            serde_json::from_slice(&json).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let result = call(enderpy_parse, "def f(:\n    pass\n");
+        assert!(result["ast"]["body"].is_array());
+        assert_eq!(result["errors"][0]["code"], "E002");
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let result = call(enderpy_diagnostics, "x = 1\nx()\n");
+        assert_eq!(result[0]["code"], "E201");
+        assert_eq!(result[0]["start"], json!({ "line": 1, "character": 0 }));
+    }
+
+    #[test]
+    fn test_panic() {
+        // Chained assignments are not supported by the semantic analyzer yet
+        let result = call(enderpy_diagnostics, "a = b = 1\n");
+        assert!(result["error"].is_string());
+    }
+
+    #[test]
+    fn test_null_source() {
+        let buffer = unsafe { enderpy_parse(ptr::null(), 0) };
+        assert!(buffer.data.is_null());
+        unsafe { enderpy_buffer_free(buffer) };
+    }
+}
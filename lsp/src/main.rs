@@ -1,56 +1,91 @@
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
+use dashmap::DashMap;
 use enderpy_python_type_checker::{
     build::BuildManager,
     build_source::BuildSource,
+    cancellation::CancellationToken,
+    file_system::{OsFileSystem, OverlayFileSystem},
     project::find_project_root,
     settings::{ImportDiscovery, Settings},
 };
 use env_logger::Builder;
-use log::{error, info, LevelFilter};
-use tower_lsp::{jsonrpc::Result, lsp_types::*, Client, LanguageServer, LspService, Server};
+use log::{LevelFilter, error, info};
+use tower_lsp::{
+    Client, LanguageServer, LspService, Server,
+    jsonrpc::{Error, Result},
+    lsp_types::*,
+};
 
 #[derive(Debug)]
 struct Backend {
     client: Client,
+    // Tokens of the running checks, a new check of a document cancels the
+    // previous check of the same document
+    cancellation: DashMap<Url, CancellationToken>,
+    // Content of the open documents, which can differ from the files on disk
+    documents: Mutex<OverlayFileSystem>,
 }
 
 impl Backend {
-    async fn check_file(&self, path: PathBuf) -> Vec<Diagnostic> {
+    // Returns None when a newer check cancelled this one
+    async fn check_file(&self, path: PathBuf) -> Option<Vec<Diagnostic>> {
         let root = PathBuf::from(find_project_root(path.as_path()));
         let python_executable = None;
-        let path = std::env::current_dir().unwrap();
-        let typeshed_path = Some(path.join("typeshed"));
+        let cwd = std::env::current_dir().unwrap();
+        let typeshed_path = Some(cwd.join("typeshed"));
         let settings = Settings {
-            debug: false,
             root,
             import_discovery: ImportDiscovery {
                 typeshed_path,
                 python_executable,
             },
-            follow_imports: enderpy_python_type_checker::settings::FollowImports::Skip,
+            ..Default::default()
         };
 
-        let source = match BuildSource::from_path(path.clone(), false) {
+        let file_system = Arc::new(self.documents.lock().unwrap().clone());
+        let source = BuildSource::from_file_system(file_system.as_ref(), path.clone(), false);
+        let source = match source {
             Ok(source) => source,
             Err(err) => {
                 error!("error: {:?}", err);
-                return Vec::new();
+                return Some(Vec::new());
             }
         };
 
+        let uri = Url::from_file_path(&path).ok();
+        let token = CancellationToken::new();
+        if let Some(uri) = uri.clone() {
+            if let Some(previous) = self.cancellation.insert(uri, token.clone()) {
+                previous.cancel();
+            }
+        }
         let mut manager = BuildManager::new(vec![source], settings);
-        manager.type_check();
+        manager.set_cancellation_token(token);
+        manager.set_file_system(file_system);
+        let manager = tokio::task::spawn_blocking(move || {
+            manager.type_check();
+            manager
+        })
+        .await
+        .ok()?;
+        if manager.is_cancelled() {
+            info!("check of {path:?} was cancelled");
+            return None;
+        }
         let mut diagnostics = Vec::new();
         info!("path: {path:?}");
 
         if let Some(state) = manager.get_state(path) {
             for err in state.diagnostics.iter() {
-                diagnostics.push(from(err.clone()));
+                diagnostics.push(from(err.clone(), uri.as_ref()));
             }
         }
 
-        diagnostics
+        Some(diagnostics)
     }
 }
 
@@ -91,10 +126,11 @@ impl LanguageServer for Backend {
         let uri = params.text_document.uri;
         let path = uri.to_file_path();
         if let Ok(path) = path {
-            let diagnostics = self.check_file(path).await;
-            self.client
-                .publish_diagnostics(uri, diagnostics, None)
-                .await;
+            if let Some(diagnostics) = self.check_file(path).await {
+                self.client
+                    .publish_diagnostics(uri, diagnostics, None)
+                    .await;
+            }
         }
     }
 
@@ -105,10 +141,15 @@ impl LanguageServer for Backend {
         let uri = params.text_document.uri;
         let path = uri.to_file_path();
         if let Ok(path) = path {
-            let diagnostics = self.check_file(path).await;
-            self.client
-                .publish_diagnostics(uri, diagnostics, None)
-                .await;
+            self.documents
+                .lock()
+                .unwrap()
+                .set_file(path.clone(), params.text_document.text);
+            if let Some(diagnostics) = self.check_file(path).await {
+                self.client
+                    .publish_diagnostics(uri, diagnostics, None)
+                    .await;
+            }
         }
     }
 
@@ -119,10 +160,27 @@ impl LanguageServer for Backend {
         let uri = params.text_document.uri;
         let path = uri.to_file_path();
         if let Ok(path) = path {
-            let diagnostics = self.check_file(path).await;
-            self.client
-                .publish_diagnostics(uri, diagnostics, None)
-                .await;
+            // The whole document is sent on every change
+            if let Some(change) = params.content_changes.into_iter().last() {
+                self.documents
+                    .lock()
+                    .unwrap()
+                    .set_file(path.clone(), change.text);
+            }
+            if let Some(diagnostics) = self.check_file(path).await {
+                self.client
+                    .publish_diagnostics(uri, diagnostics, None)
+                    .await;
+            }
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        if let Some((_, token)) = self.cancellation.remove(&params.text_document.uri) {
+            token.cancel();
+        }
+        if let Ok(path) = params.text_document.uri.to_file_path() {
+            self.documents.lock().unwrap().remove_file(&path);
         }
     }
 
@@ -139,7 +197,9 @@ impl LanguageServer for Backend {
         info!("diagnostic: {:?}", path);
         match path {
             Ok(path) => {
-                let diagnostics = self.check_file(path).await;
+                let Some(diagnostics) = self.check_file(path).await else {
+                    return Err(Error::request_cancelled());
+                };
                 info!("diagnostics: {:?}", diagnostics);
                 Ok(DocumentDiagnosticReportResult::Report(
                     DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
@@ -167,29 +227,52 @@ impl LanguageServer for Backend {
     }
 }
 
-fn from(diagnostic: enderpy_python_type_checker::diagnostic::Diagnostic) -> Diagnostic {
+fn from(
+    diagnostic: enderpy_python_type_checker::diagnostic::Diagnostic,
+    uri: Option<&Url>,
+) -> Diagnostic {
+    // Labels are locations in the same file as the diagnostic
+    let related_information = uri.filter(|_| !diagnostic.labels.is_empty()).map(|uri| {
+        diagnostic
+            .labels
+            .iter()
+            .map(|(range, message)| DiagnosticRelatedInformation {
+                location: Location {
+                    uri: uri.clone(),
+                    range: to_lsp_range(range),
+                },
+                message: message.clone(),
+            })
+            .collect()
+    });
     Diagnostic {
-        range: Range {
-            start: Position {
-                line: diagnostic.range.start.line,
-                character: diagnostic.range.start.character,
-            },
-            end: Position {
-                line: diagnostic.range.end.line,
-                character: diagnostic.range.end.character,
-            },
-        },
+        range: to_lsp_range(&diagnostic.range),
         severity: Some(DiagnosticSeverity::ERROR),
-        code: None,
-        code_description: None,
+        code: Some(NumberOrString::String(diagnostic.code.to_string())),
+        code_description: Url::parse(&diagnostic.code.url())
+            .ok()
+            .map(|href| CodeDescription { href }),
         source: Some("Enderpy".to_string()),
         message: diagnostic.body,
-        related_information: None,
+        related_information,
         tags: None,
         data: None,
     }
 }
 
+fn to_lsp_range(range: &enderpy_python_type_checker::diagnostic::Range) -> Range {
+    Range {
+        start: Position {
+            line: range.start.line,
+            character: range.start.character,
+        },
+        end: Position {
+            line: range.end.line,
+            character: range.end.character,
+        },
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let mut builder = Builder::from_default_env();
@@ -200,6 +283,10 @@ async fn main() {
 
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
-    let (service, socket) = LspService::new(|client| Backend { client });
+    let (service, socket) = LspService::new(|client| Backend {
+        client,
+        cancellation: DashMap::new(),
+        documents: Mutex::new(OverlayFileSystem::new(Arc::new(OsFileSystem))),
+    });
     Server::new(stdin, stdout, socket).serve(service).await;
 }
@@ -1,6 +1,8 @@
 use miette::Diagnostic;
 use thiserror::Error;
 
+use crate::token::Kind;
+
 #[derive(Error, Diagnostic, Debug, Clone)]
 pub enum ParsingError {
     #[error("Invalid syntax")]
@@ -14,9 +16,199 @@ pub enum ParsingError {
         #[label("span")]
         span: (usize, usize),
     },
+    #[error("expected {}, found `{found}`", expected_kinds(expected))]
+    #[diagnostic(code(parser::expected_token))]
+    ExpectedToken {
+        expected: Vec<Kind>,
+        found: Kind,
+        #[source_code]
+        input: String,
+        #[help]
+        advice: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    #[error("String not terminated")]
+    #[diagnostic(code(parser::unterminated_string))]
+    UnterminatedString {
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    #[error("Unindent does not match any outer indentation level")]
+    #[diagnostic(code(parser::invalid_indentation))]
+    InvalidIndentation {
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    #[error("{msg}")]
+    #[diagnostic(code(parser::invalid_number))]
+    InvalidNumber {
+        msg: Box<str>,
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    #[error("Too deeply nested")]
+    #[diagnostic(code(parser::too_deeply_nested))]
+    TooDeeplyNested {
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    /// The source is longer than `MAX_SOURCE_LEN`, offsets in it don't fit
+    /// in `TextSize`
+    #[error("Source is larger than 4 GiB")]
+    #[diagnostic(code(parser::source_too_large))]
+    SourceTooLarge {
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    /// A keyword argument that is passed twice, e.g. `f(a=1, a=2)`
+    #[error("keyword argument repeated: {name}")]
+    #[diagnostic(code(parser::repeated_keyword_argument))]
+    RepeatedKeywordArgument {
+        name: String,
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    /// A positional argument after keyword arguments, e.g. `f(a=1, b)`, or
+    /// after `**` unpacking when `unpacking` is set, e.g. `f(**a, b)`
+    #[error(
+        "positional argument follows keyword argument{}",
+        if *unpacking { " unpacking" } else { "" }
+    )]
+    #[diagnostic(code(parser::positional_argument_after_keyword))]
+    PositionalArgumentAfterKeyword {
+        unpacking: bool,
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    /// `*` unpacking after `**` unpacking, e.g. `f(**a, *b)`
+    #[error("iterable argument unpacking follows keyword argument unpacking")]
+    #[diagnostic(code(parser::unpacking_after_keyword_unpacking))]
+    UnpackingAfterKeywordUnpacking {
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    /// Assignment to a name that cannot be assigned, e.g. `__debug__ = 1` or
+    /// `f(None=1)`
+    #[error("cannot assign to {name}")]
+    #[diagnostic(code(parser::cannot_assign))]
+    CannotAssign {
+        name: String,
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
+    /// Interactive input that more lines can complete, see `Mode::Interactive`
+    #[error("Incomplete input")]
+    #[diagnostic(code(parser::incomplete_input))]
+    IncompleteInput {
+        #[source_code]
+        input: String,
+        #[label("span")]
+        span: (usize, usize),
+    },
 }
 
-#[derive(Error, Debug)]
+impl ParsingError {
+    /// The span of the error in the source code
+    pub fn span(&self) -> (usize, usize) {
+        match self {
+            ParsingError::InvalidSyntax { span, .. }
+            | ParsingError::ExpectedToken { span, .. }
+            | ParsingError::UnterminatedString { span, .. }
+            | ParsingError::InvalidIndentation { span, .. }
+            | ParsingError::InvalidNumber { span, .. }
+            | ParsingError::TooDeeplyNested { span, .. }
+            | ParsingError::SourceTooLarge { span, .. }
+            | ParsingError::RepeatedKeywordArgument { span, .. }
+            | ParsingError::PositionalArgumentAfterKeyword { span, .. }
+            | ParsingError::UnpackingAfterKeywordUnpacking { span, .. }
+            | ParsingError::CannotAssign { span, .. }
+            | ParsingError::IncompleteInput { span, .. } => *span,
+        }
+    }
+
+    /// The source line the error was reported on
+    pub fn input(&self) -> &str {
+        match self {
+            ParsingError::InvalidSyntax { input, .. }
+            | ParsingError::ExpectedToken { input, .. }
+            | ParsingError::UnterminatedString { input, .. }
+            | ParsingError::InvalidIndentation { input, .. }
+            | ParsingError::InvalidNumber { input, .. }
+            | ParsingError::TooDeeplyNested { input, .. }
+            | ParsingError::SourceTooLarge { input, .. }
+            | ParsingError::RepeatedKeywordArgument { input, .. }
+            | ParsingError::PositionalArgumentAfterKeyword { input, .. }
+            | ParsingError::UnpackingAfterKeywordUnpacking { input, .. }
+            | ParsingError::CannotAssign { input, .. }
+            | ParsingError::IncompleteInput { input, .. } => input,
+        }
+    }
+
+    /// Human readable message describing the error
+    pub fn message(&self) -> String {
+        match self {
+            ParsingError::InvalidSyntax { msg, .. } => msg.to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Suggestion on how to fix the error, if any
+    pub fn advice(&self) -> Option<&str> {
+        match self {
+            ParsingError::InvalidSyntax { advice, .. }
+            | ParsingError::ExpectedToken { advice, .. } => Some(advice),
+            ParsingError::RepeatedKeywordArgument { .. } => {
+                Some("remove one of the repeated arguments")
+            }
+            ParsingError::PositionalArgumentAfterKeyword {
+                unpacking: false, ..
+            } => Some("move positional arguments before keyword arguments"),
+            ParsingError::PositionalArgumentAfterKeyword {
+                unpacking: true, ..
+            } => Some("move positional arguments before `**` arguments"),
+            ParsingError::UnpackingAfterKeywordUnpacking { .. } => {
+                Some("move `*` arguments before `**` arguments")
+            }
+            ParsingError::CannotAssign { .. } => Some("use another name"),
+            _ => None,
+        }
+    }
+}
+
+/// Lexemes of the expected tokens, e.g. "one of `)`, `,`"
+fn expected_kinds(expected: &[Kind]) -> String {
+    let kinds = expected
+        .iter()
+        .map(|kind| format!("`{}`", kind))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if expected.len() == 1 {
+        kinds
+    } else {
+        format!("one of {}", kinds)
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum LexError {
     #[error("String not terminated")]
     StringNotTerminated,
@@ -24,12 +216,12 @@ pub enum LexError {
     InvalidDigitInBinaryLiteral(char),
     #[error("Invalid char {0} in octal literal. Must be between 0 and 7")]
     InvalidDigitInOctalLiteral(char),
-    #[error(
-        "Invalid char {0} in hexadecimal literal. Must be between 0 and 9, or between A and F"
-    )]
+    #[error("Invalid char {0} in hexadecimal literal. Must be between 0 and 9, or between A and F")]
     InvalidDigitInHexadecimalLiteral(char),
     #[error("Invalid digit in decimal literal")]
     InvalidDigitInDecimalLiteral,
     #[error("Unindent does not match any outer indentation level")]
     UnindentDoesNotMatchAnyOuterIndentationLevel,
+    #[error("Source is larger than 4 GiB")]
+    SourceTooLarge,
 }
@@ -2,7 +2,8 @@ use unicode_id_start::{is_id_continue, is_id_start};
 
 use crate::{
     error::LexError,
-    token::{Kind, Token, TokenValue},
+    text_size::{MAX_SOURCE_LEN, TextSize, text_size},
+    token::{Kind, StringKind, Token, TokenValue},
 };
 
 #[derive(Debug)]
@@ -11,51 +12,105 @@ pub struct Lexer {
     source: String,
     /// The current position in the source code
     current: usize,
-    current_line: u16,
+    current_line: u32,
     /// Keeps track of whether the lexer is at the start of a line
     start_of_line: bool,
     /// keeps track of the indentation level
     /// the first element is always 0
     /// because the first line is always at indentation level 0
     indent_stack: Vec<usize>,
-    nesting: i8,
+    nesting: i32,
     // This stack means we are in a fstring that started with
     // character at the top of the stack
     fstring_stack: Vec<String>,
-    // This is a counter used to keep track of how many
-    // brackets we are inside
-    // Each time we see a left bracket we increment this
-    // Each time we see a right bracket we decrement this
-    // This is used to match brackets in fstrings
-    inside_fstring_bracket: i8,
+    /// The replacement fields of fstrings that we are inside, the last one
+    /// is the innermost. Used to match the brackets of the fields and to
+    /// find where their format specs start.
+    fstring_fields: Vec<FStringField>,
 
     // TODO: Hacky way to handle emitting multiple de indents
     next_token_is_dedent: u8,
+    /// The error that produced the last `Kind::Error` token
+    last_error: Option<LexError>,
+    /// Whether the source is longer than `MAX_SOURCE_LEN` and the first
+    /// token is still to be lexed
+    source_too_large: bool,
+}
+
+/// A replacement field of an fstring, the part between `{` and `}`
+#[derive(Debug, Clone, Copy)]
+struct FStringField {
+    /// Nesting of the brackets where the field starts. A `:` or `}` at this
+    /// nesting starts the format spec or ends the field, inside other
+    /// brackets they belong to the expression of the field.
+    nesting: i32,
+    /// Whether the lexer is in the format spec after the `:` of the field
+    in_format_spec: bool,
 }
 
 impl Lexer {
+    /// A source longer than `MAX_SOURCE_LEN` is lexed as an empty source
+    /// that starts with a `SourceTooLarge` error
     pub fn new(source: &str) -> Self {
+        let source_too_large = source.len() > MAX_SOURCE_LEN;
+        let source = if source_too_large { "" } else { source };
         Self {
             source: source.to_string(),
-            current: 0,
+            current: bom_length(source),
             current_line: 1,
             start_of_line: true,
             indent_stack: vec![0],
             nesting: 0,
             fstring_stack: vec![],
-            inside_fstring_bracket: 0,
+            fstring_fields: vec![],
             next_token_is_dedent: 0,
+            last_error: None,
+            source_too_large,
         }
     }
 
+    /// Moves the lexer to the offset, which must be at the start of a line
+    /// that is not inside brackets or an indented block
+    pub fn seek(&mut self, offset: TextSize) {
+        let offset = offset as usize;
+        let line = self.source[..offset].matches('\n').count() + 1;
+        self.current = offset.max(bom_length(&self.source));
+        self.current_line = text_size(line);
+        self.start_of_line = true;
+        self.indent_stack = vec![0];
+        self.nesting = 0;
+        self.fstring_stack.clear();
+        self.fstring_fields.clear();
+        self.next_token_is_dedent = 0;
+        self.last_error = None;
+    }
+
+    /// Returns the error of the last token if it was `Kind::Error`
+    pub fn last_error(&self) -> Option<&LexError> {
+        self.last_error.as_ref()
+    }
+
     pub fn next_token(&mut self) -> Token {
+        self.last_error = None;
+        if self.source_too_large {
+            self.source_too_large = false;
+            let error = LexError::SourceTooLarge;
+            let token = Token {
+                kind: Kind::Error,
+                value: TokenValue::Str(error.to_string()),
+                start: 0,
+                end: 0,
+            };
+            self.last_error = Some(error);
+            return token;
+        }
         if self.next_token_is_dedent > 0 {
             self.next_token_is_dedent -= 1;
             return Token {
                 kind: Kind::Dedent,
                 value: TokenValue::None,
-                start: self.current,
-                end: self.current,
+                start: text_size(self.current),
+                end: text_size(self.current),
             };
         }
 
@@ -63,19 +118,21 @@ impl Lexer {
         let kind = match self.next_kind() {
             Ok(kind) => kind,
             Err(e) => {
-                return Token {
+                let token = Token {
                     kind: Kind::Error,
                     value: TokenValue::Str(e.to_string()),
-                    start,
-                    end: match e {
+                    start: text_size(start),
+                    end: text_size(match e {
                         //  If the string is not terminated it means that we consumed all the
                         // characters  in the source code and we are at the
                         // end of the file  so we return the position of
                         // character before the end of the file
                         LexError::StringNotTerminated => self.current - 1,
                         _ => self.current,
-                    },
+                    }),
                 };
+                self.last_error = Some(e);
+                return token;
             }
         };
 
@@ -90,37 +147,66 @@ impl Lexer {
         Token {
             kind,
             value,
-            start,
-            end,
+            start: text_size(start),
+            end: text_size(end),
         }
     }
 
     // peek_token is a side-effect free version of next_token
     pub fn peek_token(&mut self) -> Token {
+        self.lookahead(|lexer| lexer.next_token())
+    }
+
+    /// Runs `f` and moves the lexer back to where it was, used to look at
+    /// more than one token ahead
+    pub fn lookahead<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
         let current = self.current;
         let current_line = self.current_line;
         let nesting = self.nesting;
         let fstring_stack = self.fstring_stack.clone();
         let start_of_line = self.start_of_line;
-        let inside_fstring_bracket = self.inside_fstring_bracket;
+        let indent_stack = self.indent_stack.clone();
+        let fstring_fields = self.fstring_fields.clone();
         let next_token_is_dedent = self.next_token_is_dedent;
-        let token = self.next_token();
+        let last_error = self.last_error.take();
+        let source_too_large = self.source_too_large;
+        let result = f(self);
         self.current = current;
         self.current_line = current_line;
         self.nesting = nesting;
         self.fstring_stack = fstring_stack;
         self.start_of_line = start_of_line;
-        self.inside_fstring_bracket = inside_fstring_bracket;
+        self.indent_stack = indent_stack;
+        self.fstring_fields = fstring_fields;
         self.next_token_is_dedent = next_token_is_dedent;
-        token
+        self.last_error = last_error;
+        self.source_too_large = source_too_large;
+        result
     }
 
+    #[allow(clippy::collapsible_match)]
     pub fn next_fstring_token(&mut self) -> Option<Kind> {
-        if self.inside_fstring_bracket > 0 {
-            if self.peek() == Some('}') && self.double_peek() != Some('}') {
-                self.next();
-                self.inside_fstring_bracket -= 1;
-                return Some(Kind::RightBracket);
+        if let Some(field) = self.fstring_fields.last().copied() {
+            if field.in_format_spec {
+                return self.next_format_spec_token();
+            }
+            // Brackets of the expression in the field are lexed as usual
+            if field.nesting == self.nesting {
+                match self.peek() {
+                    Some('}') => {
+                        self.next();
+                        self.fstring_fields.pop();
+                        return Some(Kind::RBrace);
+                    }
+                    Some(':') => {
+                        self.next();
+                        if let Some(field) = self.fstring_fields.last_mut() {
+                            field.in_format_spec = true;
+                        }
+                        return Some(Kind::Colon);
+                    }
+                    _ => {}
+                }
             }
             // if we are inside a bracket return none
             // and let the other tokens be matched
@@ -137,8 +223,8 @@ impl Lexer {
                     continue;
                 }
 
-                self.inside_fstring_bracket += 1;
-                return Some(Kind::LeftBracket);
+                self.push_fstring_field();
+                return Some(Kind::LBrace);
             }
             match str_finisher.len() {
                 1 => {
@@ -189,6 +275,37 @@ impl Lexer {
         None
     }
 
+    /// Lexes the format spec of a replacement field. The spec is text that
+    /// can contain nested replacement fields, e.g. `>{width}` in
+    /// `f"{value:>{width}}"`.
+    fn next_format_spec_token(&mut self) -> Option<Kind> {
+        match self.peek()? {
+            '{' => {
+                self.next();
+                self.push_fstring_field();
+                Some(Kind::LBrace)
+            }
+            '}' => {
+                self.next();
+                self.fstring_fields.pop();
+                Some(Kind::RBrace)
+            }
+            _ => {
+                while self.peek().is_some_and(|c| c != '{' && c != '}') {
+                    self.next();
+                }
+                Some(Kind::FStringMiddle)
+            }
+        }
+    }
+
+    fn push_fstring_field(&mut self) {
+        self.fstring_fields.push(FStringField {
+            nesting: self.nesting,
+            in_format_spec: false,
+        });
+    }
+
     fn next_kind(&mut self) -> Result<Kind, LexError> {
         if self.start_of_line {
             if let Some(indent_kind) = self.match_indentation()? {
@@ -311,36 +428,37 @@ impl Lexer {
                     }
                     _ => return Ok(Kind::Colon),
                 },
-                '!' => {
-                    if let Some('=') = self.peek() {
+                '!' => match self.peek() {
+                    Some('=') => {
                         self.next();
                         return Ok(Kind::NotEq);
                     }
-                }
+                    _ => return Ok(Kind::Exclamation),
+                },
                 // Delimiters
                 '(' => {
                     self.nesting += 1;
-                    return Ok(Kind::LeftParen);
+                    return Ok(Kind::LParen);
                 }
                 ')' => {
                     self.nesting -= 1;
-                    return Ok(Kind::RightParen);
+                    return Ok(Kind::RParen);
                 }
                 '[' => {
                     self.nesting += 1;
-                    return Ok(Kind::LeftBrace);
+                    return Ok(Kind::LBracket);
                 }
                 ']' => {
                     self.nesting -= 1;
-                    return Ok(Kind::RightBrace);
+                    return Ok(Kind::RBracket);
                 }
                 '{' => {
                     self.nesting += 1;
-                    return Ok(Kind::LeftBracket);
+                    return Ok(Kind::LBrace);
                 }
                 '}' => {
                     self.nesting -= 1;
-                    return Ok(Kind::RightBracket);
+                    return Ok(Kind::RBrace);
                 }
                 ',' => return Ok(Kind::Comma),
                 '.' => {
@@ -810,21 +928,24 @@ impl Lexer {
                 }
             }
         }
-        if spaces_count == 0 {
-            // When there are no spaces and only a new line
-            // like the following
-            // if True:
-            //
-            //   print("Hello")
-            //
-            // We should not consider that new line as dedent
-            //
-            // But also if this part is the last part of the file
-            // we should not consider it as dedent
-            // Thanks python
-            if self.peek() == Some('\n') && self.double_peek().is_some() {
-                return Ok(None);
-            }
+        // Blank lines and lines with only a comment do not change the
+        // indentation, like the following
+        // if True:
+        //
+        //   # comment
+        //   print("Hello")
+        //
+        // The same goes for a shebang or an indented comment on the first
+        // line. A blank line at the end of the file is not skipped, it
+        // closes the open blocks.
+        // Thanks python
+        let is_blank_line = match self.peek() {
+            Some('#') => true,
+            Some('\n' | '\r') => self.double_peek().is_some(),
+            _ => false,
+        };
+        if is_blank_line {
+            return Ok((spaces_count > 0).then_some(Kind::WhiteSpace));
         }
         if let Some(top) = self.indent_stack.last() {
             match spaces_count.cmp(top) {
@@ -871,13 +992,17 @@ impl Lexer {
             Kind::Identifier => TokenValue::Str(kind_value),
             Kind::StringLiteral
             | Kind::FStringStart
-            | Kind::FStringMiddle
-            | Kind::FStringEnd
             | Kind::RawBytes
             | Kind::RawFStringStart
             | Kind::Bytes
-            | Kind::Unicode
-            | Kind::Comment => TokenValue::Str(kind_value),
+            | Kind::Unicode => {
+                let (kind, value) = StringKind::from_literal(&kind_value);
+                TokenValue::String {
+                    value: value.to_string(),
+                    kind,
+                }
+            }
+            Kind::FStringMiddle | Kind::FStringEnd | Kind::Comment => TokenValue::Str(kind_value),
             Kind::Dedent => {
                 let mut spaces_count = 0;
                 for c in kind_value.chars() {
@@ -933,6 +1058,16 @@ impl Lexer {
     }
 }
 
+/// Length of the UTF-8 byte order mark at the start of the source, which is
+/// not part of the first token
+fn bom_length(source: &str) -> usize {
+    if source.starts_with('\u{feff}') {
+        '\u{feff}'.len_utf8()
+    } else {
+        0
+    }
+}
+
 fn match_whitespace(c: char) -> bool {
     c.is_whitespace() && c != '\n' && c != '\r'
 }
@@ -945,6 +1080,7 @@ mod tests {
 
     use super::{Kind, Lexer};
     use crate::error::LexError;
+    use crate::token::{KEYWORDS, PUNCTUATION};
 
     fn snapshot_test_lexer(snap_name: &str, inputs: &[&str]) -> Result<(), LexError> {
         for (i, test_input) in inputs.iter().enumerate() {
@@ -1208,11 +1344,39 @@ def",
 
     # Path: test_local.py	
 ",
+                "if a:
+    pass
+  # comment between blocks
+b = 1
+",
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_shebang() {
+        snapshot_test_lexer(
+            "shebang",
+            &[
+                "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\nx = 1\n",
+                "\u{feff}#!/usr/bin/env python\n# coding=latin-1\nif x:\n    pass\n",
+                "#!/usr/bin/env python\r\n# vim: set fileencoding=utf-8 :\r\nx = 1\r\n",
+                "  #!/usr/bin/env python\nx = 1\n",
+                "#!/usr/bin/env python",
             ],
         )
         .unwrap();
     }
 
+    #[test]
+    fn test_line_count_past_u16() {
+        let source = "\n".repeat(70_000) + "x";
+        let mut lexer = Lexer::new(&source);
+        while lexer.next_token().kind != Kind::Eof {}
+        assert_eq!(lexer.current_line, 70_001);
+    }
+
     #[test]
     fn test_fstring() {
         // F-strings
@@ -1229,6 +1393,8 @@ def",
                 "f\"{{hey}}\"",
                 "f\"oh_{{hey}}\"",
                 "f'a' 'c'",
+                "f'{a!r:>{width}}'",
+                "f'{a[1:2]}'",
                 // unsupported
                 // "f'hello_{f'''{a}'''}'",
             ],
@@ -1273,6 +1439,29 @@ def",
         .unwrap();
     }
 
+    #[test]
+    fn test_token_tables() {
+        for (lexeme, kind) in KEYWORDS.iter().chain(PUNCTUATION) {
+            if *kind == Kind::BackSlash {
+                continue;
+            }
+            let mut lexer = Lexer::new(lexeme);
+            assert_eq!(lexer.next_token().kind, *kind, "{}", lexeme);
+            assert_eq!(lexer.next_token().kind, Kind::Eof, "{}", lexeme);
+            assert_eq!(kind.to_string(), *lexeme);
+        }
+        assert!(Kind::And.is_keyword());
+        assert!(!Kind::Identifier.is_keyword());
+        assert!(Kind::Walrus.is_operator());
+        assert!(!Kind::AddAssign.is_operator());
+        assert_eq!(Kind::Identifier.to_string(), "Identifier");
+        #[allow(deprecated)]
+        {
+            assert_eq!(Kind::LeftBrace, Kind::LBracket);
+            assert_eq!(Kind::LeftBracket, Kind::LBrace);
+        }
+    }
+
     fn snapshot_test_lexer_and_errors(test_case: &str) {
         let mut lexer = Lexer::new(test_case);
         let mut tokens = vec![];
@@ -3,8 +3,14 @@ mod parser;
 
 pub use crate::{
     lexer::Lexer,
-    parser::{ast, parser::Parser},
+    parser::{
+        ast,
+        incremental::Edit,
+        parser::{DEFAULT_MAX_NESTING_DEPTH, Mode, Parser},
+    },
+    text_size::{MAX_SOURCE_LEN, TextSize},
 };
 pub mod error;
+pub mod text_size;
 pub mod token;
 pub mod utils;
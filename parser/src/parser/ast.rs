@@ -1,25 +1,33 @@
 use std::fmt;
 
 use miette::{SourceOffset, SourceSpan};
+use serde::Serialize;
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)] // #[serde(tag = "type")]
+use crate::{text_size::TextSize, token::StringKind};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Node {
     /// Start offset in source
-    pub start: usize,
+    pub start: TextSize,
 
     /// End offset in source
-    pub end: usize,
+    pub end: TextSize,
 }
 
 impl Node {
-    pub fn new(start: usize, end: usize) -> Self {
+    pub fn new(start: TextSize, end: TextSize) -> Self {
         Self { start, end }
     }
 
-    pub fn len(&self) -> usize {
+    pub fn len(&self) -> TextSize {
         self.end - self.start
     }
 
+    /// The range of the node for slicing the source
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start as usize..self.end as usize
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
@@ -31,20 +39,23 @@ pub trait GetNode {
 
 impl From<Node> for SourceSpan {
     fn from(val: Node) -> Self {
-        Self::new(SourceOffset::from(val.start), SourceOffset::from(val.len()))
+        Self::new(
+            SourceOffset::from(val.start as usize),
+            SourceOffset::from(val.len() as usize),
+        )
     }
 }
 
 // The following structs are used to represent the AST
 // https://docs.python.org/3/library/ast.html#abstract-grammar
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Module {
     pub node: Node,
     pub body: Vec<Statement>,
 }
 
 // Use box to reduce the enum size
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum Statement {
     AssignStatement(Assign),
     AnnAssignStatement(AnnAssign),
@@ -111,14 +122,14 @@ impl GetNode for Statement {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Assign {
     pub node: Node,
     pub targets: Vec<Expression>,
     pub value: Expression,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AnnAssign {
     pub node: Node,
     pub target: Expression,
@@ -127,7 +138,7 @@ pub struct AnnAssign {
     pub simple: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AugAssign {
     pub node: Node,
     pub target: Expression,
@@ -135,7 +146,7 @@ pub struct AugAssign {
     pub value: Expression,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum AugAssignOp {
     Add,
     Sub,
@@ -152,32 +163,32 @@ pub enum AugAssignOp {
     FloorDiv,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Assert {
     pub node: Node,
     pub test: Expression,
     pub msg: Option<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Pass {
     pub node: Node,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Delete {
     pub node: Node,
     pub targets: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Return {
     pub node: Node,
     pub value: Option<Expression>,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Raise
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Raise {
     pub node: Node,
     pub exc: Option<Expression>,
@@ -185,26 +196,26 @@ pub struct Raise {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Break
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Break {
     pub node: Node,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Continue
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Continue {
     pub node: Node,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Import
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Import {
     pub node: Node,
     pub names: Vec<Alias>,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.alias
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Alias {
     pub node: Node,
     pub name: String,
@@ -222,7 +233,7 @@ impl Alias {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.ImportFrom
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ImportFrom {
     pub node: Node,
     pub module: String,
@@ -231,20 +242,20 @@ pub struct ImportFrom {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Global
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Global {
     pub node: Node,
     pub names: Vec<String>,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Nonlocal
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Nonlocal {
     pub node: Node,
     pub names: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum Expression {
     Constant(Box<Constant>),
     List(Box<List>),
@@ -310,19 +321,22 @@ impl GetNode for Expression {
 }
 
 // https://docs.python.org/3/reference/expressions.html#atom-identifiers
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Name {
     pub node: Node,
     pub id: String,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Constant {
     pub node: Node,
     pub value: ConstantValue,
+    /// The prefix and quotes of a string or bytes literal, `None` for other
+    /// constants. Concatenated literals have the kind of the first one.
+    pub kind: Option<StringKind>,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize)]
 pub enum ConstantValue {
     None,
     Ellipsis,
@@ -341,19 +355,7 @@ impl fmt::Debug for ConstantValue {
             ConstantValue::None => write!(f, "None"),
             ConstantValue::Ellipsis => write!(f, "..."),
             ConstantValue::Bool(b) => write!(f, "{}", b),
-            ConstantValue::Str(s) => {
-                if s.starts_with("r\"") || s.starts_with("R\"") {
-                    let mut s = s.chars().skip(1).collect::<String>();
-                    s = s.chars().take(s.len() - 1).collect::<String>();
-
-                    // drop first another char from beginning
-                    s = s.chars().skip(1).collect::<String>();
-
-                    return write!(f, "{:?}", s);
-                }
-
-                write!(f, "\"{}\"", s)
-            }
+            ConstantValue::Str(s) => write!(f, "\"{}\"", s),
             ConstantValue::Bytes(b) => write!(f, "{:?}", b),
             ConstantValue::Tuple(t) => write!(f, "{:?}", t),
             ConstantValue::Int(i) => write!(f, "{}", i),
@@ -362,54 +364,54 @@ impl fmt::Debug for ConstantValue {
         }
     }
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct List {
     pub node: Node,
     pub elements: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Tuple {
     pub node: Node,
     pub elements: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Dict {
     pub node: Node,
     pub keys: Vec<Expression>,
     pub values: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Set {
     pub node: Node,
     pub elements: Vec<Expression>,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.BoolOp
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BoolOperation {
     pub node: Node,
     pub op: BooleanOperator,
     pub values: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum BooleanOperator {
     And,
     Or,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.UnaryOp
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct UnaryOperation {
     pub node: Node,
     pub op: UnaryOperator,
     pub operand: Box<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum UnaryOperator {
     Not,
     Invert,
@@ -418,7 +420,7 @@ pub enum UnaryOperator {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.BinOp
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BinOp {
     pub node: Node,
     pub op: BinaryOperator,
@@ -426,7 +428,7 @@ pub struct BinOp {
     pub right: Box<Expression>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum BinaryOperator {
     Add,
     Sub,
@@ -466,7 +468,7 @@ impl std::fmt::Display for BinaryOperator {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.NamedExpr
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct NamedExpression {
     pub node: Node,
     pub target: Box<Expression>,
@@ -474,49 +476,49 @@ pub struct NamedExpression {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Yield
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Yield {
     pub node: Node,
     pub value: Option<Box<Expression>>,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.YieldFrom
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct YieldFrom {
     pub node: Node,
     pub value: Box<Expression>,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Starred
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Starred {
     pub node: Node,
     pub value: Box<Expression>,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.GeneratorExp
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Generator {
     pub node: Node,
     pub element: Box<Expression>,
     pub generators: Vec<Comprehension>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ListComp {
     pub node: Node,
     pub element: Box<Expression>,
     pub generators: Vec<Comprehension>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SetComp {
     pub node: Node,
     pub element: Box<Expression>,
     pub generators: Vec<Comprehension>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DictComp {
     pub node: Node,
     pub key: Box<Expression>,
@@ -525,7 +527,7 @@ pub struct DictComp {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.comprehension
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Comprehension {
     pub node: Node,
     pub target: Box<Expression>,
@@ -535,7 +537,7 @@ pub struct Comprehension {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Attribute
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Attribute {
     pub node: Node,
     pub value: Box<Expression>,
@@ -543,7 +545,7 @@ pub struct Attribute {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Subscript
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Subscript {
     pub node: Node,
     pub value: Box<Expression>,
@@ -552,7 +554,7 @@ pub struct Subscript {
 
 // https://docs.python.org/3/library/ast.html#ast.Slice
 // can be used for Subscript
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Slice {
     pub node: Node,
     pub lower: Option<Box<Expression>>,
@@ -561,7 +563,7 @@ pub struct Slice {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Call
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Call {
     pub node: Node,
     pub func: Box<Expression>,
@@ -571,7 +573,7 @@ pub struct Call {
     pub kwargs: Option<Box<Expression>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Keyword {
     pub node: Node,
     pub arg: Option<String>,
@@ -579,14 +581,14 @@ pub struct Keyword {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Await
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Await {
     pub node: Node,
     pub value: Box<Expression>,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Compare
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Compare {
     pub node: Node,
     pub left: Box<Expression>,
@@ -594,7 +596,7 @@ pub struct Compare {
     pub comparators: Vec<Expression>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum ComparisonOperator {
     Eq,
     NotEq,
@@ -609,7 +611,7 @@ pub enum ComparisonOperator {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Lambda
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Lambda {
     pub node: Node,
     pub args: Arguments,
@@ -617,7 +619,7 @@ pub struct Lambda {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.arguments
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Arguments {
     pub node: Node,
     pub posonlyargs: Vec<Arg>,
@@ -629,8 +631,34 @@ pub struct Arguments {
     pub defaults: Vec<Expression>,
 }
 
+impl Arguments {
+    /// The positional-only and regular parameters with their default values.
+    /// Defaults belong to the last positional parameters.
+    pub fn positional_with_defaults(&self) -> impl Iterator<Item = (&Arg, Option<&Expression>)> {
+        let count = self.posonlyargs.len() + self.args.len();
+        let first_default = count.saturating_sub(self.defaults.len());
+        let defaults = std::iter::repeat(None)
+            .take(first_default)
+            .chain(self.defaults.iter().map(Some));
+        self.posonlyargs
+            .iter()
+            .chain(self.args.iter())
+            .zip(defaults)
+    }
+
+    /// The keyword-only parameters with their default values
+    pub fn keyword_only_with_defaults(&self) -> impl Iterator<Item = (&Arg, Option<&Expression>)> {
+        let defaults = self
+            .kw_defaults
+            .iter()
+            .map(Option::as_ref)
+            .chain(std::iter::repeat(None));
+        self.kwonlyargs.iter().zip(defaults)
+    }
+}
+
 // https://docs.python.org/3/library/ast.html#ast.arg
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Arg {
     pub node: Node,
     pub arg: String,
@@ -638,7 +666,7 @@ pub struct Arg {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.IfExp
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct IfExp {
     pub node: Node,
     pub test: Box<Expression>,
@@ -647,7 +675,7 @@ pub struct IfExp {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.FormattedValue
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FormattedValue {
     pub node: Node,
     pub value: Box<Expression>,
@@ -656,14 +684,14 @@ pub struct FormattedValue {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.JoinedStr
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct JoinedStr {
     pub node: Node,
     pub values: Vec<Expression>,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.If
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct If {
     pub node: Node,
     pub test: Box<Expression>,
@@ -678,7 +706,7 @@ impl If {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.While
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct While {
     pub node: Node,
     pub test: Box<Expression>,
@@ -687,7 +715,7 @@ pub struct While {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.For
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct For {
     pub node: Node,
     pub target: Box<Expression>,
@@ -697,7 +725,7 @@ pub struct For {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.AsyncFor
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AsyncFor {
     pub node: Node,
     pub target: Box<Expression>,
@@ -707,7 +735,7 @@ pub struct AsyncFor {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.With
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct With {
     pub node: Node,
     pub items: Vec<WithItem>,
@@ -715,7 +743,7 @@ pub struct With {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.AsyncWith
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AsyncWith {
     pub node: Node,
     pub items: Vec<WithItem>,
@@ -724,7 +752,7 @@ pub struct AsyncWith {
 
 // https://docs.python.org/3/library/ast.html#ast.withitem
 // can be used for With
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct WithItem {
     pub node: Node,
     pub context_expr: Box<Expression>,
@@ -732,7 +760,7 @@ pub struct WithItem {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Try
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Try {
     pub node: Node,
     pub body: Vec<Statement>,
@@ -742,7 +770,7 @@ pub struct Try {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.TryStar
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TryStar {
     pub node: Node,
     pub body: Vec<Statement>,
@@ -752,7 +780,7 @@ pub struct TryStar {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.ExceptHandler
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExceptHandler {
     pub node: Node,
     pub typ: Option<Box<Expression>>,
@@ -761,7 +789,7 @@ pub struct ExceptHandler {
 }
 
 // https://docs.python.org/3/library/ast.html#functiondef
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FunctionDef {
     pub node: Node,
     pub name: String,
@@ -774,7 +802,7 @@ pub struct FunctionDef {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.AsyncFunctionDef
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AsyncFunctionDef {
     pub node: Node,
     pub name: String,
@@ -787,7 +815,7 @@ pub struct AsyncFunctionDef {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.ClassDef
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ClassDef {
     pub node: Node,
     pub name: String,
@@ -799,7 +827,7 @@ pub struct ClassDef {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.Match
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Match {
     pub node: Node,
     pub subject: Box<Expression>,
@@ -807,7 +835,7 @@ pub struct Match {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.match_case
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MatchCase {
     pub node: Node,
     pub pattern: Box<MatchPattern>,
@@ -815,32 +843,68 @@ pub struct MatchCase {
     pub body: Vec<Statement>,
 }
 
-#[derive(Debug, Clone)]
+// https://docs.python.org/3/library/ast.html#pattern-matching
+#[derive(Debug, Clone, Serialize)]
 pub enum MatchPattern {
     MatchValue(MatchValue),
-    MatchSingleton(Box<Expression>),
-    MatchSequence(Vec<MatchPattern>),
-    MatchStar(Box<Expression>),
+    MatchSingleton(MatchSingleton),
+    MatchSequence(MatchSequence),
+    MatchStar(MatchStar),
     MatchMapping(MatchMapping),
     MatchAs(MatchAs),
     MatchClass(MatchClass),
-    MatchOr(Vec<MatchPattern>),
+    MatchOr(MatchOr),
+}
+
+impl GetNode for MatchPattern {
+    fn get_node(&self) -> Node {
+        match self {
+            MatchPattern::MatchValue(p) => p.node,
+            MatchPattern::MatchSingleton(p) => p.node,
+            MatchPattern::MatchSequence(p) => p.node,
+            MatchPattern::MatchStar(p) => p.node,
+            MatchPattern::MatchMapping(p) => p.node,
+            MatchPattern::MatchAs(p) => p.node,
+            MatchPattern::MatchClass(p) => p.node,
+            MatchPattern::MatchOr(p) => p.node,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MatchValue {
     pub node: Node,
     pub value: Box<Expression>,
 }
 
-#[derive(Debug, Clone)]
+// `None`, `True` or `False`, which are compared by identity
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchSingleton {
+    pub node: Node,
+    pub value: Box<Expression>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchSequence {
+    pub node: Node,
+    pub patterns: Vec<MatchPattern>,
+}
+
+// The name is None for `*_`
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchStar {
+    pub node: Node,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct MatchAs {
     pub node: Node,
     pub name: Option<String>,
     pub pattern: Option<Box<MatchPattern>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MatchMapping {
     pub node: Node,
     pub keys: Vec<Expression>,
@@ -848,7 +912,7 @@ pub struct MatchMapping {
     pub rest: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MatchClass {
     pub node: Node,
     pub cls: Box<Expression>,
@@ -857,8 +921,14 @@ pub struct MatchClass {
     pub kwd_patterns: Vec<MatchPattern>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchOr {
+    pub node: Node,
+    pub patterns: Vec<MatchPattern>,
+}
+
 // https://docs.python.org/3/library/ast.html#ast-type-params
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum TypeParam {
     TypeVar(TypeVar),
     ParamSpec(ParamSpec),
@@ -886,7 +956,7 @@ impl TypeParam {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.TypeVar
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TypeVar {
     pub node: Node,
     pub name: String,
@@ -894,21 +964,21 @@ pub struct TypeVar {
 }
 
 // https://docs.python.org/3/library/ast.html#ast.ParamSpec
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ParamSpec {
     pub node: Node,
     pub name: String,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.TypeVarTuple
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TypeVarTuple {
     pub node: Node,
     pub name: String,
 }
 
 // https://docs.python.org/3/library/ast.html#ast.TypeAlias
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TypeAlias {
     pub node: Node,
     pub name: String,
@@ -4,6 +4,7 @@ pub fn is_atom(kind: &Kind) -> bool {
     match kind {
         Kind::Identifier
         | Kind::StringLiteral
+        | Kind::Unicode
         | Kind::RawBytes
         | Kind::Bytes
         | Kind::FStringStart
@@ -21,9 +22,9 @@ pub fn is_atom(kind: &Kind) -> bool {
         | Kind::ImaginaryExponentFloat
         // These might start a enclosured expression
         // https://docs.python.org/3/reference/expressions.html#atoms
-        | Kind::LeftParen
-        | Kind::LeftBracket
-        | Kind::LeftBrace
+        | Kind::LParen
+        | Kind::LBrace
+        | Kind::LBracket
         | Kind::Yield
         | Kind::Ellipsis
         | Kind::None => true,
@@ -31,16 +32,6 @@ pub fn is_atom(kind: &Kind) -> bool {
     }
 }
 
-pub fn is_iterable(expr: &Expression) -> bool {
-    matches!(
-        expr,
-        Expression::List { .. }
-            | Expression::Tuple { .. }
-            | Expression::Set { .. }
-            | Expression::Name { .. }
-    )
-}
-
 /// Checks wether a token kind can start a biwise operation
 /// start of bitwise operation cannot be a await primary
 #[allow(dead_code)]
@@ -54,3 +45,54 @@ pub fn is_bitwise_or_op(cur_kind: &Kind) -> bool {
     }
     true
 }
+
+/// Name of the expression kind as CPython uses it in syntax errors, e.g.
+/// "'tuple' is an illegal expression for augmented assignment"
+pub fn expression_name(expr: &Expression) -> &'static str {
+    match expr {
+        Expression::Constant(_) | Expression::JoinedStr(_) | Expression::FormattedValue(_) => {
+            "literal"
+        }
+        Expression::List(_) => "list",
+        Expression::Tuple(_) => "tuple",
+        Expression::Dict(_) => "dict literal",
+        Expression::Set(_) => "set display",
+        Expression::Name(_) => "name",
+        Expression::BoolOp(_) | Expression::UnaryOp(_) | Expression::BinOp(_) => "expression",
+        Expression::NamedExpr(_) => "named expression",
+        Expression::Yield(_) | Expression::YieldFrom(_) => "yield expression",
+        Expression::Starred(_) => "starred",
+        Expression::Generator(_) => "generator expression",
+        Expression::ListComp(_) => "list comprehension",
+        Expression::SetComp(_) => "set comprehension",
+        Expression::DictComp(_) => "dict comprehension",
+        Expression::Attribute(_) => "attribute",
+        Expression::Subscript(_) => "subscript",
+        Expression::Slice(_) => "slice",
+        Expression::Call(_) => "function call",
+        Expression::Await(_) => "await expression",
+        Expression::Compare(_) => "comparison",
+        Expression::Lambda(_) => "lambda",
+        Expression::IfExp(_) => "conditional expression",
+    }
+}
+
+/// Checks whether a token kind is an augmented assignment operator, e.g. `+=`
+pub fn is_aug_assign(kind: &Kind) -> bool {
+    matches!(
+        kind,
+        Kind::AddAssign
+            | Kind::SubAssign
+            | Kind::MulAssign
+            | Kind::MatrixMulAssign
+            | Kind::DivAssign
+            | Kind::IntDivAssign
+            | Kind::ModAssign
+            | Kind::PowAssign
+            | Kind::BitAndAssign
+            | Kind::BitOrAssign
+            | Kind::BitXorAssign
+            | Kind::ShiftLeftAssign
+            | Kind::ShiftRightAssign
+    )
+}
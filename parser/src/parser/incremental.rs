@@ -0,0 +1,267 @@
+use super::ast::*;
+use crate::text_size::TextSize;
+
+/// A change of the source code: the text between the `start` and `end`
+/// offsets of the old source is replaced with `text`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub start: TextSize,
+    pub end: TextSize,
+    pub text: String,
+}
+
+impl Edit {
+    pub fn new(start: TextSize, end: TextSize, text: &str) -> Self {
+        Self {
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+
+    /// Returns the source with the edit applied
+    pub fn apply(&self, source: &str) -> String {
+        format!(
+            "{}{}{}",
+            &source[..self.start as usize],
+            self.text,
+            &source[self.end as usize..]
+        )
+    }
+
+    /// How far the text after the edit moves
+    pub fn delta(&self) -> i64 {
+        self.text.len() as i64 - i64::from(self.end - self.start)
+    }
+
+    /// Moves an offset after the edit to where it is in the new source
+    pub(crate) fn shift_offset(offset: TextSize, delta: i64) -> TextSize {
+        (i64::from(offset) + delta).clamp(0, i64::from(TextSize::MAX)) as TextSize
+    }
+}
+
+/// Moves the nodes of a syntax tree by a number of bytes, used to reuse
+/// statements after an edit
+pub(crate) trait Shift {
+    fn shift(&mut self, delta: i64);
+}
+
+impl Shift for Node {
+    fn shift(&mut self, delta: i64) {
+        self.start = Edit::shift_offset(self.start, delta);
+        self.end = Edit::shift_offset(self.end, delta);
+    }
+}
+
+impl<T: Shift> Shift for Vec<T> {
+    fn shift(&mut self, delta: i64) {
+        for item in self {
+            item.shift(delta);
+        }
+    }
+}
+
+impl<T: Shift> Shift for Option<T> {
+    fn shift(&mut self, delta: i64) {
+        if let Some(item) = self {
+            item.shift(delta);
+        }
+    }
+}
+
+impl<T: Shift> Shift for Box<T> {
+    fn shift(&mut self, delta: i64) {
+        self.as_mut().shift(delta);
+    }
+}
+
+/// Implements `Shift` for a struct by shifting the listed fields
+macro_rules! shift_fields {
+    ($($name:ident { $($field:ident),* $(,)? })*) => {
+        $(
+            impl Shift for $name {
+                fn shift(&mut self, delta: i64) {
+                    $(self.$field.shift(delta);)*
+                }
+            }
+        )*
+    };
+}
+
+shift_fields! {
+    Assign { node, targets, value }
+    AnnAssign { node, target, annotation, value }
+    AugAssign { node, target, value }
+    Assert { node, test, msg }
+    Pass { node }
+    Delete { node, targets }
+    Return { node, value }
+    Raise { node, exc, cause }
+    Break { node }
+    Continue { node }
+    Import { node, names }
+    Alias { node }
+    ImportFrom { node, names }
+    Global { node }
+    Nonlocal { node }
+    Name { node }
+    List { node, elements }
+    Tuple { node, elements }
+    Dict { node, keys, values }
+    Set { node, elements }
+    BoolOperation { node, values }
+    UnaryOperation { node, operand }
+    BinOp { node, left, right }
+    NamedExpression { node, target, value }
+    Yield { node, value }
+    YieldFrom { node, value }
+    Starred { node, value }
+    Generator { node, element, generators }
+    ListComp { node, element, generators }
+    SetComp { node, element, generators }
+    DictComp { node, key, value, generators }
+    Comprehension { node, target, iter, ifs }
+    Attribute { node, value }
+    Subscript { node, value, slice }
+    Slice { node, lower, upper, step }
+    Call { node, func, args, keywords, starargs, kwargs }
+    Keyword { node, value }
+    Await { node, value }
+    Compare { node, left, comparators }
+    Lambda { node, args, body }
+    Arguments { node, posonlyargs, args, vararg, kwonlyargs, kw_defaults, kwarg, defaults }
+    Arg { node, annotation }
+    IfExp { node, test, body, orelse }
+    FormattedValue { node, value, format_spec }
+    JoinedStr { node, values }
+    If { node, test, body, orelse }
+    While { node, test, body, orelse }
+    For { node, target, iter, body, orelse }
+    AsyncFor { node, target, iter, body, orelse }
+    With { node, items, body }
+    AsyncWith { node, items, body }
+    WithItem { node, context_expr, optional_vars }
+    Try { node, body, handlers, orelse, finalbody }
+    TryStar { node, body, handlers, orelse, finalbody }
+    ExceptHandler { node, typ, body }
+    FunctionDef { node, args, body, decorator_list, returns, type_params }
+    AsyncFunctionDef { node, args, body, decorator_list, returns, type_params }
+    ClassDef { node, bases, keywords, body, decorator_list, type_params }
+    Match { node, subject, cases }
+    MatchCase { node, pattern, guard, body }
+    MatchValue { node, value }
+    MatchSingleton { node, value }
+    MatchSequence { node, patterns }
+    MatchStar { node }
+    MatchAs { node, pattern }
+    MatchMapping { node, keys, patterns }
+    MatchClass { node, cls, patterns, kwd_patterns }
+    MatchOr { node, patterns }
+    TypeVar { node, bound }
+    ParamSpec { node }
+    TypeVarTuple { node }
+    TypeAlias { node, type_params, value }
+}
+
+impl Shift for Constant {
+    fn shift(&mut self, delta: i64) {
+        self.node.shift(delta);
+        if let ConstantValue::Tuple(elements) = &mut self.value {
+            elements.shift(delta);
+        }
+    }
+}
+
+impl Shift for Statement {
+    fn shift(&mut self, delta: i64) {
+        match self {
+            Statement::AssignStatement(s) => s.shift(delta),
+            Statement::AnnAssignStatement(s) => s.shift(delta),
+            Statement::AugAssignStatement(s) => s.shift(delta),
+            Statement::ExpressionStatement(s) => s.shift(delta),
+            Statement::Assert(s) => s.shift(delta),
+            Statement::Pass(s) => s.shift(delta),
+            Statement::Delete(s) => s.shift(delta),
+            Statement::Return(s) => s.shift(delta),
+            Statement::Raise(s) => s.shift(delta),
+            Statement::Break(s) => s.shift(delta),
+            Statement::Continue(s) => s.shift(delta),
+            Statement::Import(s) => s.shift(delta),
+            Statement::ImportFrom(s) => s.shift(delta),
+            Statement::Global(s) => s.shift(delta),
+            Statement::Nonlocal(s) => s.shift(delta),
+            Statement::IfStatement(s) => s.shift(delta),
+            Statement::WhileStatement(s) => s.shift(delta),
+            Statement::ForStatement(s) => s.shift(delta),
+            Statement::AsyncForStatement(s) => s.shift(delta),
+            Statement::WithStatement(s) => s.shift(delta),
+            Statement::AsyncWithStatement(s) => s.shift(delta),
+            Statement::TryStatement(s) => s.shift(delta),
+            Statement::TryStarStatement(s) => s.shift(delta),
+            Statement::FunctionDef(s) => s.shift(delta),
+            Statement::AsyncFunctionDef(s) => s.shift(delta),
+            Statement::ClassDef(s) => s.shift(delta),
+            Statement::Match(s) => s.shift(delta),
+            Statement::TypeAlias(s) => s.shift(delta),
+        }
+    }
+}
+
+impl Shift for Expression {
+    fn shift(&mut self, delta: i64) {
+        match self {
+            Expression::Constant(e) => e.shift(delta),
+            Expression::List(e) => e.shift(delta),
+            Expression::Tuple(e) => e.shift(delta),
+            Expression::Dict(e) => e.shift(delta),
+            Expression::Set(e) => e.shift(delta),
+            Expression::Name(e) => e.shift(delta),
+            Expression::BoolOp(e) => e.shift(delta),
+            Expression::UnaryOp(e) => e.shift(delta),
+            Expression::BinOp(e) => e.shift(delta),
+            Expression::NamedExpr(e) => e.shift(delta),
+            Expression::Yield(e) => e.shift(delta),
+            Expression::YieldFrom(e) => e.shift(delta),
+            Expression::Starred(e) => e.shift(delta),
+            Expression::Generator(e) => e.shift(delta),
+            Expression::ListComp(e) => e.shift(delta),
+            Expression::SetComp(e) => e.shift(delta),
+            Expression::DictComp(e) => e.shift(delta),
+            Expression::Attribute(e) => e.shift(delta),
+            Expression::Subscript(e) => e.shift(delta),
+            Expression::Slice(e) => e.shift(delta),
+            Expression::Call(e) => e.shift(delta),
+            Expression::Await(e) => e.shift(delta),
+            Expression::Compare(e) => e.shift(delta),
+            Expression::Lambda(e) => e.shift(delta),
+            Expression::IfExp(e) => e.shift(delta),
+            Expression::JoinedStr(e) => e.shift(delta),
+            Expression::FormattedValue(e) => e.shift(delta),
+        }
+    }
+}
+
+impl Shift for MatchPattern {
+    fn shift(&mut self, delta: i64) {
+        match self {
+            MatchPattern::MatchValue(p) => p.shift(delta),
+            MatchPattern::MatchSingleton(p) => p.shift(delta),
+            MatchPattern::MatchSequence(p) => p.shift(delta),
+            MatchPattern::MatchStar(p) => p.shift(delta),
+            MatchPattern::MatchMapping(p) => p.shift(delta),
+            MatchPattern::MatchAs(p) => p.shift(delta),
+            MatchPattern::MatchClass(p) => p.shift(delta),
+            MatchPattern::MatchOr(p) => p.shift(delta),
+        }
+    }
+}
+
+impl Shift for TypeParam {
+    fn shift(&mut self, delta: i64) {
+        match self {
+            TypeParam::TypeVar(t) => t.shift(delta),
+            TypeParam::ParamSpec(p) => p.shift(delta),
+            TypeParam::TypeVarTuple(t) => t.shift(delta),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod ast;
 mod expression;
+pub mod incremental;
 mod operator;
 #[allow(clippy::module_inception)]
 pub mod parser;
@@ -1,40 +1,63 @@
-use std::{panic, vec};
+use std::vec;
 
 use miette::Result;
 
 use super::{
-    expression::{is_atom, is_iterable},
+    expression::{expression_name, is_atom, is_aug_assign},
+    incremental::{Edit, Shift},
     operator::{is_bin_arithmetic_op, is_comparison_operator, is_unary_op, map_unary_operator},
-    statement::is_at_compound_statement,
+    statement::{decorators, is_at_compound_statement, is_compound_statement},
     string::concat_string_exprs,
 };
 use crate::{
-    error::ParsingError,
+    error::{LexError, ParsingError},
     lexer::Lexer,
-    parser::{
-        ast::*,
-        string::{extract_string_inside, is_string},
-    },
-    token::{Kind, Token, TokenValue},
+    parser::{ast::*, string::is_string},
+    text_size::{TextSize, text_size},
+    token::{Kind, SoftKeyword, StringKind, Token, TokenValue},
 };
 
+/// How deep brackets, unary operators, lambdas and blocks can be nested
+/// before parsing fails with `TooDeeplyNested`. Deeper nesting would
+/// overflow the stack of the recursive descent parser and of the passes
+/// that walk the tree.
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 200;
+
+/// What kind of input the parser parses, like the modes of CPython's
+/// `compile`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// The statements of a file
+    #[default]
+    Module,
+    /// One statement typed into a REPL, the `single` mode of CPython. The
+    /// statement doesn't need a trailing newline, but a compound statement
+    /// ends only at a blank line. Input that more lines can complete fails
+    /// with `IncompleteInput` instead of a syntax error.
+    Interactive,
+}
+
 #[allow(unused)]
 #[derive(Debug)]
 pub struct Parser {
     source: String,
     lexer: Lexer,
     cur_token: Token,
-    prev_token_end: usize,
+    prev_token_end: TextSize,
     // This var keeps track of how many levels deep we are in a list, tuple or set
     // expression. This is used to determine if we should parse comma separated
     // expressions as tuple or not.
     // This is incremented when we see an opening bracket and decremented when we
     // see a closing bracket.
     nested_expression_list: usize,
+    // How many nested expressions and blocks the parser is inside of
+    depth: usize,
+    max_nesting_depth: usize,
+    mode: Mode,
     pub errors: Vec<ParsingError>,
     curr_line_string: String,
     curr_line_number: u32,
-    curr_line_offset: usize,
+    curr_line_offset: TextSize,
     path: String,
 }
 
@@ -51,6 +74,9 @@ impl Parser {
             cur_token,
             prev_token_end,
             nested_expression_list: 0,
+            depth: 0,
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
+            mode: Mode::Module,
             errors: vec![],
             curr_line_string: String::new(),
             path,
@@ -59,9 +85,291 @@ impl Parser {
         }
     }
 
+    /// Sets how deep expressions and blocks can be nested, see
+    /// `DEFAULT_MAX_NESTING_DEPTH`
+    pub fn set_max_nesting_depth(&mut self, max_nesting_depth: usize) {
+        self.max_nesting_depth = max_nesting_depth;
+    }
+
+    /// Sets what kind of input `parse` parses, see `Mode`
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
     pub fn parse(&mut self) -> Module {
         let node = self.start_node();
         let mut body = vec![];
+        if let Err(err) = self.parse_statements(&mut body, &[]) {
+            // It's better to exit on the first error otherwise we will get
+            // a lot of errors that are not relevant
+            // TODO: Implement resilient parsing
+            self.errors.push(err);
+        }
+        if self.mode == Mode::Interactive {
+            self.check_interactive_input(&body);
+        }
+
+        Module {
+            node: self.finish_node(node),
+            body,
+        }
+    }
+
+    /// Reports a second statement on another line of interactive input as a
+    /// syntax error. A syntax error at the end of the input, or a compound
+    /// statement without a blank line after it, is `IncompleteInput`.
+    fn check_interactive_input(&mut self, body: &[Statement]) {
+        let end = self.source.len();
+        // A syntax error after the first statement is in the second one
+        let second = body
+            .get(1)
+            .map(|statement| statement.get_node().range())
+            .map(|range| (range.start, range.end))
+            .or_else(|| self.errors.first().map(ParsingError::span));
+        if let (Some(first), Some((start, second_end))) = (body.first(), second) {
+            let first_end = first.get_node().range().end;
+            if start > first_end && self.source[first_end..start].contains('\n') {
+                self.errors = vec![ParsingError::InvalidSyntax {
+                    msg: Box::from("Multiple statements found while compiling a single statement"),
+                    input: self.line_at(start).to_string(),
+                    advice: "enter one statement at a time".to_string(),
+                    span: (start, second_end),
+                }];
+                return;
+            }
+        }
+
+        let incomplete = match self.errors.first() {
+            // Only triple quoted strings continue on the next line
+            Some(ParsingError::UnterminatedString { span, .. }) => {
+                let string = self.source[span.0..].trim_start_matches(|c: char| c.is_alphabetic());
+                span.1 + 1 >= end && (string.starts_with("'''") || string.starts_with("\"\"\""))
+            }
+            Some(err) => err.span().0 >= end && self.continues_on_next_line(),
+            None => {
+                body.last().is_some_and(is_compound_statement)
+                    && !ends_with_blank_line(&self.source)
+            }
+        };
+        if incomplete {
+            let (input, span) = match self.errors.first() {
+                Some(err) => (err.input().to_string(), err.span()),
+                None => (String::new(), (end, end)),
+            };
+            self.errors = vec![ParsingError::IncompleteInput { input, span }];
+        }
+    }
+
+    /// Whether the lines after the source can continue its last statement:
+    /// a bracket is open, a block header has no body yet or a decorator no
+    /// definition yet
+    fn continues_on_next_line(&self) -> bool {
+        let mut lexer = Lexer::new(&self.source);
+        let mut brackets = 0;
+        let mut line_start = true;
+        let mut header = false;
+        let mut decorator = false;
+        // The last token that is not a newline, comment or indentation
+        let mut last = Kind::Eof;
+        loop {
+            let token = lexer.next_token();
+            match token.kind {
+                Kind::Eof | Kind::Error => break,
+                Kind::NewLine => {
+                    line_start = true;
+                    continue;
+                }
+                Kind::Comment | Kind::WhiteSpace | Kind::Indent | Kind::Dedent => continue,
+                Kind::LParen | Kind::LBracket | Kind::LBrace => brackets += 1,
+                Kind::RParen | Kind::RBracket | Kind::RBrace => brackets -= 1,
+                _ => (),
+            }
+            if line_start {
+                header = is_at_compound_statement(&token)
+                    || matches!(
+                        token.kind,
+                        Kind::Elif | Kind::Else | Kind::Except | Kind::Finally
+                    )
+                    || matches!(
+                        SoftKeyword::from_token(&token),
+                        Some(SoftKeyword::Match | SoftKeyword::Case)
+                    );
+                decorator = match token.kind {
+                    Kind::MatrixMul => true,
+                    Kind::Def | Kind::Class | Kind::Async => false,
+                    _ => decorator,
+                };
+            }
+            line_start = false;
+            last = token.kind;
+        }
+        brackets > 0 || decorator || header && last == Kind::Colon
+    }
+
+    /// Parses the source as exactly one expression, the `eval` mode of
+    /// CPython. Blank lines, comments and indentation can surround the
+    /// expression, anything after it, e.g. the value of an assignment, is a
+    /// syntax error.
+    pub fn parse_eval(source: &str) -> Result<Expression, ParsingError> {
+        let mut parser = Parser::new(source.to_string(), String::from(""));
+        parser.skip_eval_padding();
+        let expr = parser.parse_expression()?;
+        parser.skip_eval_padding();
+        if !parser.at(Kind::Eof) {
+            let token = parser.cur_token();
+            let found = &parser.source[token.start as usize..token.end as usize];
+            return Err(ParsingError::InvalidSyntax {
+                msg: Box::from(format!(
+                    "Expected the end of the expression but found `{}`",
+                    found
+                )),
+                input: parser.line_at(token.start as usize).to_string(),
+                advice: "statements and more than one expression can't be evaluated".to_string(),
+                span: parser.get_span_on_line(token.start, token.end),
+            });
+        }
+        match parser.errors.into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(expr),
+        }
+    }
+
+    /// The line of the source that the offset is on
+    fn line_at(&self, offset: usize) -> &str {
+        let start = self.source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let end = self.source[offset..]
+            .find('\n')
+            .map_or(self.source.len(), |i| offset + i);
+        &self.source[start..end]
+    }
+
+    fn skip_eval_padding(&mut self) {
+        while matches!(
+            self.cur_kind(),
+            Kind::NewLine | Kind::Indent | Kind::Dedent | Kind::Comment | Kind::WhiteSpace
+        ) {
+            self.bump_any();
+        }
+    }
+
+    /// Parses the source after an edit, the source of the parser is the old
+    /// source with the edit applied. Top level statements of the old module
+    /// that the edit does not touch are reused and only the statements around
+    /// the edit are parsed again. Statements after a syntax error in the
+    /// edited statements are still reused.
+    pub fn reparse(&mut self, old: &Module, edit: &Edit) -> Module {
+        let delta = edit.delta();
+        let new_offset = |offset: TextSize| Edit::shift_offset(offset, delta);
+        let source = self.source.as_bytes();
+        let is_line_start =
+            |offset: TextSize| offset == 0 || source.get(offset as usize - 1) == Some(&b'\n');
+        // Decorated definitions start at the `@` of their first decorator,
+        // which is the first character of its line
+        let start_of = |statement: &Statement| match decorators(statement).first() {
+            Some(decorator) => {
+                let start = decorator.get_node().start;
+                let new_start = if start > edit.end {
+                    new_offset(start)
+                } else {
+                    start
+                };
+                let column = source[..new_start as usize]
+                    .iter()
+                    .rev()
+                    .take_while(|c| **c != b'\n')
+                    .count();
+                start - text_size(column)
+            }
+            None => statement.get_node().start,
+        };
+
+        // The statement before the edit is parsed again because the edit can
+        // continue it, e.g. with a new line in the body of a function
+        let mut first = old
+            .body
+            .iter()
+            .position(|statement| statement.get_node().end >= edit.start)
+            .unwrap_or(old.body.len())
+            .saturating_sub(1);
+        while first > 0 && !is_line_start(start_of(&old.body[first])) {
+            first -= 1;
+        }
+        // The old module ends at its first syntax error, so the last statement
+        // is parsed again to find the statements after it
+        let last = (first + 1..old.body.len()).rev().find(|&i| {
+            let start = start_of(&old.body[i]);
+            start > edit.end && is_line_start(new_offset(start))
+        });
+        let reusable = match last {
+            Some(last) => old.body[first + 1..last]
+                .iter()
+                .filter(|statement| start_of(statement) > edit.end)
+                .collect(),
+            None => vec![],
+        };
+        let offsets = reusable
+            .iter()
+            .map(|statement| new_offset(start_of(statement)))
+            .collect::<Vec<_>>();
+        let last_start = last.map(|last| new_offset(start_of(&old.body[last])));
+
+        let start = if first == 0 {
+            0
+        } else {
+            start_of(&old.body[first])
+        };
+        self.seek(start);
+        let node = if first == 0 {
+            self.start_node()
+        } else {
+            old.node
+        };
+        let mut body = old.body[..first].to_vec();
+        // Parsing continues after a syntax error at the next statement that
+        // can be reused
+        let (reused, error_position) = match self.parse_statements(&mut body, &offsets) {
+            Ok(reused) => (reused, 0),
+            Err(err) => {
+                self.errors.push(err);
+                let position = body
+                    .last()
+                    .map_or(start, |statement| statement.get_node().end);
+                let reused = offsets.partition_point(|offset| *offset <= position);
+                (Some(reused), position)
+            }
+        };
+        if let Some(reused) = reused {
+            body.extend(reusable[reused..].iter().map(|statement| {
+                let mut statement = (*statement).clone();
+                statement.shift(delta);
+                statement
+            }));
+            if let Some(last_start) = last_start.filter(|start| *start > error_position) {
+                self.seek(last_start);
+                if let Err(err) = self.parse_statements(&mut body, &[]) {
+                    self.errors.push(err);
+                }
+            }
+        }
+
+        let end = body
+            .last()
+            .map_or(0, |statement| statement.get_node().end)
+            .max(self.prev_token_end);
+        Module {
+            node: Node::new(node.start, end),
+            body,
+        }
+    }
+
+    /// Parses top level statements until the end of the file or a statement
+    /// that starts at one of the sorted offsets. Returns the index of the
+    /// offset the parser stopped at.
+    fn parse_statements(
+        &mut self,
+        body: &mut Vec<Statement>,
+        offsets: &[TextSize],
+    ) -> Result<Option<usize>, ParsingError> {
         while self.cur_kind() != Kind::Eof {
             // TODO: comments can be parsed and used
             // Also comments can be everywhere
@@ -72,27 +380,50 @@ impl Parser {
                 self.bump_any();
                 continue;
             }
-            let stmt = if is_at_compound_statement(self.cur_token()) {
+            if let Ok(index) = offsets.binary_search(&self.cur_token.start) {
+                return Ok(Some(index));
+            }
+            let stmt = if self.at_compound_statement() {
                 self.parse_compount_statement()
             } else {
                 self.parse_simple_statement()
             };
-            match stmt {
-                Ok(stmt) => body.push(stmt),
-                Err(err) => {
-                    self.errors.push(err);
-                    // It's better to exit on the first error otherwise we will get
-                    // a lot of errors that are not relevant
-                    // TODO: Implement resilient parsing
-                    break;
-                }
-            }
+            body.push(stmt?);
         }
+        Ok(None)
+    }
 
-        Module {
-            node: self.finish_node(node),
-            body,
+    /// Moves the parser to the offset, which must be the start of a top
+    /// level statement at the start of a line
+    fn seek(&mut self, offset: TextSize) {
+        self.lexer.seek(offset);
+        self.cur_token = self.lexer.next_token();
+        self.prev_token_end = offset;
+        self.nested_expression_list = 0;
+        self.depth = 0;
+        self.curr_line_string.clear();
+        self.curr_line_number = text_size(self.source[..offset as usize].matches('\n').count()) + 1;
+        self.curr_line_offset = offset;
+    }
+
+    /// Runs `parse` one nesting level deeper, or fails when the nesting is
+    /// deeper than the limit. Chains of binary operations are parsed in a
+    /// loop and don't count towards the limit.
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParsingError>,
+    ) -> Result<T, ParsingError> {
+        if self.depth >= self.max_nesting_depth {
+            let token = self.cur_token();
+            return Err(ParsingError::TooDeeplyNested {
+                input: self.curr_line_string.clone(),
+                span: self.get_span_on_line(token.start, token.end),
+            });
         }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
     }
 
     fn start_node(&self) -> Node {
@@ -166,7 +497,7 @@ impl Parser {
             self.curr_line_number += 1;
         } else {
             self.curr_line_string
-                .push_str(&self.source[self.prev_token_end..self.cur_token.end]);
+                .push_str(&self.source[self.prev_token_end as usize..self.cur_token.end as usize]);
         }
         self.curr_line_offset = self.cur_token.end;
         {
@@ -181,11 +512,30 @@ impl Parser {
     // TODO: Convert this to a into trait
     fn convert_lexer_error_to_parse(&mut self) -> ParsingError {
         let token = self.cur_token();
-        ParsingError::InvalidSyntax {
-            msg: token.value.to_string().into(),
-            input: self.curr_line_string.clone(),
-            advice: "".to_string(),
-            span: self.get_span_on_line(token.start, token.end),
+        let input = self.curr_line_string.clone();
+        let span = self.get_span_on_line(token.start, token.end);
+        match self.lexer.last_error() {
+            Some(LexError::StringNotTerminated) => ParsingError::UnterminatedString { input, span },
+            Some(LexError::SourceTooLarge) => ParsingError::SourceTooLarge { input, span },
+            Some(LexError::UnindentDoesNotMatchAnyOuterIndentationLevel) => {
+                ParsingError::InvalidIndentation { input, span }
+            }
+            Some(
+                e @ (LexError::InvalidDigitInBinaryLiteral(_)
+                | LexError::InvalidDigitInOctalLiteral(_)
+                | LexError::InvalidDigitInHexadecimalLiteral(_)
+                | LexError::InvalidDigitInDecimalLiteral),
+            ) => ParsingError::InvalidNumber {
+                msg: e.to_string().into(),
+                input,
+                span,
+            },
+            None => ParsingError::InvalidSyntax {
+                msg: token.value.to_string().into(),
+                input,
+                advice: "".to_string(),
+                span,
+            },
         }
     }
 
@@ -206,8 +556,9 @@ impl Parser {
             let found = self.cur_token.kind;
             let node = self.start_node();
             let range = self.finish_node(node);
-            let err = ParsingError::InvalidSyntax {
-                msg: Box::from(format!("Expected {:?} but found {:?}", kind, found)),
+            let err = ParsingError::ExpectedToken {
+                expected: vec![kind],
+                found,
                 input: self.curr_line_string.clone(),
                 advice: "maybe you forgot to put this character".to_string(),
                 span: self.get_span_on_line(range.start, range.end),
@@ -230,15 +581,9 @@ impl Parser {
             let found = self.cur_token.kind;
             let node = self.start_node();
             let range = self.finish_node(node);
-            let mut expected = String::new();
-            for kind in kind {
-                expected.push_str(&format!("{:?}, ", kind));
-            }
-            let err = ParsingError::InvalidSyntax {
-                msg: Box::from(format!(
-                    "Expected one of {:?} but found {:?}",
-                    expected, found
-                )),
+            let err = ParsingError::ExpectedToken {
+                expected: kind,
+                found,
                 input: self.curr_line_string.clone(),
                 advice: "maybe you forgot to put this character".to_string(),
                 span: self.get_span_on_line(range.start, range.end),
@@ -251,6 +596,64 @@ impl Parser {
     }
 
     // deprecated
+    /// Expect the soft keyword or return error
+    fn expect_soft_keyword(&mut self, keyword: SoftKeyword) -> Result<(), ParsingError> {
+        if SoftKeyword::from_token(self.cur_token()) == Some(keyword) {
+            self.bump_any();
+            return Ok(());
+        }
+        let node = self.start_node();
+        let range = self.finish_node(node);
+        let err = ParsingError::InvalidSyntax {
+            msg: Box::from(format!("Expected `{}`", keyword)),
+            input: self.curr_line_string.clone(),
+            advice: String::new(),
+            span: self.get_span_on_line(range.start, range.end),
+        };
+        self.advance_to_next_line_or_semicolon();
+        Err(err)
+    }
+
+    /// Checks if the current token is the soft keyword and the tokens after
+    /// it make it a keyword instead of a name
+    fn at_soft_keyword(&mut self, keyword: SoftKeyword) -> bool {
+        if SoftKeyword::from_token(self.cur_token()) != Some(keyword) {
+            return false;
+        }
+        match keyword {
+            // The line of a match statement or a case block ends with a colon
+            // e.g. `match (a, b):` while `match(a, b)` is a call and
+            // `match = 1` an assignment
+            SoftKeyword::Match | SoftKeyword::Case => self.lexer.lookahead(|lexer| {
+                let mut last = Kind::Eof;
+                loop {
+                    let token = lexer.next_token();
+                    match token.kind {
+                        Kind::NewLine | Kind::Eof | Kind::Error => {
+                            return last == Kind::Colon;
+                        }
+                        // A colon right after the keyword is an annotation,
+                        // `match: int`
+                        Kind::Colon if last == Kind::Eof => return false,
+                        Kind::Comment => {}
+                        kind => last = kind,
+                    }
+                }
+            }),
+            // `type X = int` and `type X[T] = list[T]`
+            SoftKeyword::Type => self.lexer.lookahead(|lexer| {
+                lexer.next_token().kind == Kind::Identifier
+                    && matches!(lexer.next_token().kind, Kind::Assign | Kind::LBracket)
+            }),
+            // `_` is a keyword wherever a pattern is expected
+            SoftKeyword::Underscore => true,
+        }
+    }
+
+    fn at_compound_statement(&mut self) -> bool {
+        is_at_compound_statement(self.cur_token()) || self.at_soft_keyword(SoftKeyword::Match)
+    }
+
     fn unepxted_token(&mut self, node: Node, kind: Kind) -> Result<(), ParsingError> {
         if kind == Kind::Error {
             let err = self.convert_lexer_error_to_parse();
@@ -278,28 +681,20 @@ impl Parser {
         }
         self.bump_any();
         let range = self.finish_node(node);
-        let line_number = self.curr_line_number;
-        let mut expected = String::new();
-        for kind in kinds {
-            expected.push_str(&format!("{:?}, ", kind));
-        }
 
-        ParsingError::InvalidSyntax {
-            msg: Box::from(format!(
-                "Expected one of {:?} but found {:?}",
-                expected,
-                self.cur_kind()
-            )),
+        ParsingError::ExpectedToken {
+            expected: kinds,
+            found: curr_kind,
             input: self.curr_line_string.clone(),
             advice: advice.to_string(),
             span: self.get_span_on_line(range.start, range.end),
         }
     }
 
-    fn get_line_number_of_character_position(&self, pos: usize) -> u32 {
+    fn get_line_number_of_character_position(&self, pos: TextSize) -> u32 {
         let mut line_number = 1;
         for (i, c) in self.source.chars().enumerate() {
-            if i == pos {
+            if i == pos as usize {
                 break;
             }
             if c == '\n' {
@@ -327,9 +722,7 @@ impl Parser {
             Kind::Global => self.parse_global_statement(),
             Kind::Nonlocal => self.parse_nonlocal_statement(),
             _ => {
-                if self.cur_kind() == Kind::Identifier
-                    && self.cur_token().value.to_string() == "type"
-                {
+                if self.at_soft_keyword(SoftKeyword::Type) {
                     self.parse_type_alias_statement()
                 } else if self.cur_kind() == Kind::Indent {
                     let node = self.start_node();
@@ -373,9 +766,9 @@ impl Parser {
             Kind::Def => self.parse_function_definition(vec![]),
             Kind::MatrixMul => self.parse_decorated_function_def_or_class_def(),
             Kind::Class => self.parse_class_definition(vec![]),
-            // match is a soft keyword
-            // https://docs.python.org/3/reference/lexical_analysis.html#soft-keywords
-            Kind::Identifier if self.cur_token().value.to_string() == "match" => {
+            Kind::Identifier
+                if SoftKeyword::from_token(self.cur_token()) == Some(SoftKeyword::Match) =>
+            {
                 self.parse_match_statement()
             }
             Kind::Async => {
@@ -433,53 +826,39 @@ impl Parser {
         let test = Box::new(self.parse_named_expression()?);
         self.expect(Kind::Colon)?;
         let body = self.parse_suite()?;
-        let mut orelse: Option<If> = None;
+        let mut elifs = vec![];
         while self.at(Kind::Elif) {
             let elif_node = self.start_node();
             self.bump(Kind::Elif);
             let elif_test = Box::new(self.parse_named_expression()?);
             self.expect(Kind::Colon)?;
-            let body = self.parse_suite()?;
-            let if_value = If {
-                node: self.finish_node(elif_node),
-                test: elif_test,
-                body,
-                orelse: vec![],
-            };
-            if let Some(val) = &mut orelse {
-                val.update_orelse(vec![Statement::IfStatement(if_value)]);
-            } else {
-                orelse = Some(if_value);
-            }
+            let elif_body = self.parse_suite()?;
+            elifs.push((elif_node, elif_test, elif_body));
         }
 
-        let mut single_else_body: Option<Vec<Statement>> = None;
-        if self.eat(Kind::Else) {
+        let mut orelse = if self.eat(Kind::Else) {
             self.expect(Kind::Colon)?;
-            let else_body = self.parse_suite()?;
-            if let Some(val) = &mut orelse {
-                val.update_orelse(else_body);
-            } else {
-                single_else_body = Some(else_body);
-            }
-        };
-
-        // if we had any else or elif statements, we need to wrap them in a vec
-        // otherwise we just return an empty vec as the else block of the if statement
-        let or_else_vec = if let Some(val) = orelse {
-            vec![Statement::IfStatement(val)]
+            self.parse_suite()?
         } else {
-            match single_else_body {
-                Some(val) => val,
-                None => vec![],
-            }
+            vec![]
         };
 
+        // every elif is an if statement in the else block of the previous one
+        // and spans the rest of the statement
+        for (elif_node, elif_test, elif_body) in elifs.into_iter().rev() {
+            orelse = vec![Statement::IfStatement(If {
+                node: self.finish_node(elif_node),
+                test: elif_test,
+                body: elif_body,
+                orelse,
+            })];
+        }
+
         Ok(Statement::IfStatement(If {
             node: self.finish_node(node),
             test,
             body,
-            orelse: or_else_vec,
+            orelse,
         }))
     }
 
@@ -510,6 +889,7 @@ impl Parser {
         let is_async = self.eat(Kind::Async);
         self.bump(Kind::For);
         let target = Box::new(self.parse_target_list()?);
+        self.check_assignment_target(&target);
         self.expect(Kind::In)?;
         // TODO: I think this would not work for:
         // for a in [1, 2, 3]:
@@ -579,12 +959,12 @@ impl Parser {
     fn parse_with_items(&mut self) -> Result<Vec<WithItem>, ParsingError> {
         let mut items = vec![];
 
-        if self.eat(Kind::LeftParen) {
+        if self.eat(Kind::LParen) {
             items.push(self.parse_with_item()?);
-            while self.eat(Kind::Comma) & !self.at(Kind::RightParen) {
+            while self.eat(Kind::Comma) & !self.at(Kind::RParen) {
                 items.push(self.parse_with_item()?);
             }
-            self.expect(Kind::RightParen)?;
+            self.expect(Kind::RParen)?;
             return Ok(items);
         }
         items.push(self.parse_with_item()?);
@@ -599,7 +979,9 @@ impl Parser {
         let node = self.start_node();
         let context_expr = Box::new(self.parse_expression_2()?);
         let optional_vars = if self.eat(Kind::As) {
-            Some(Box::new(self.parse_target()?))
+            let target = self.parse_target()?;
+            self.check_assignment_target(&target);
+            Some(Box::new(target))
         } else {
             None
         };
@@ -621,7 +1003,7 @@ impl Parser {
             if matches!(self.peek_kind(), Ok(Kind::Mul)) {
                 is_try_star = true;
             }
-            self.parse_except_clauses()?
+            self.parse_except_clauses(is_try_star)?
         } else {
             vec![]
         };
@@ -658,15 +1040,35 @@ impl Parser {
         }
     }
 
-    fn parse_except_clauses(&mut self) -> Result<Vec<ExceptHandler>, ParsingError> {
+    // The first clause decides whether the handlers of a try statement are
+    // `except` or `except*` clauses, the two cannot be mixed
+    fn parse_except_clauses(&mut self, is_star: bool) -> Result<Vec<ExceptHandler>, ParsingError> {
         let mut handlers = vec![];
         while self.at(Kind::Except) {
             let node = self.start_node();
             self.bump(Kind::Except);
-            self.bump(Kind::Mul);
+            let star = self.eat(Kind::Mul);
+            if star != is_star {
+                let span = self.get_span_on_line(node.start, self.cur_token.start);
+                self.errors.push(ParsingError::InvalidSyntax {
+                    msg: Box::from("cannot have both 'except' and 'except*' on the same 'try'"),
+                    input: self.curr_line_string.clone(),
+                    advice: "use either except or except* clauses in a try statement".to_string(),
+                    span,
+                });
+            }
             let typ = if !self.at(Kind::Colon) {
                 Some(Box::new(self.parse_expression_2()?))
             } else {
+                if star {
+                    let span = self.get_span_on_line(node.start, self.cur_token.start);
+                    self.errors.push(ParsingError::InvalidSyntax {
+                        msg: Box::from("expected one or more exception types"),
+                        input: self.curr_line_string.clone(),
+                        advice: "except* needs the types of the exceptions to handle".to_string(),
+                        span,
+                    });
+                }
                 None
             };
             let name = if self.eat(Kind::As) {
@@ -703,14 +1105,14 @@ impl Parser {
         self.expect(Kind::Def)?;
         let name = self.cur_token().value.to_string();
         self.expect(Kind::Identifier)?;
-        let type_params = if self.at(Kind::LeftBrace) {
+        let type_params = if self.at(Kind::LBracket) {
             self.parse_type_parameters()?
         } else {
             vec![]
         };
-        self.expect(Kind::LeftParen)?;
+        self.expect(Kind::LParen)?;
         let args = self.parse_parameters(false)?;
-        self.expect(Kind::RightParen)?;
+        self.expect(Kind::RParen)?;
 
         let return_type = if self.eat(Kind::Arrow) {
             Some(Box::new(self.parse_expression_2()?))
@@ -751,7 +1153,8 @@ impl Parser {
         while self.eat(Kind::MatrixMul) {
             let name = self.parse_named_expression()?;
             decorators.push(name);
-            self.bump(Kind::NewLine);
+            // Lines between decorators can be comments
+            while self.eat(Kind::NewLine) {}
         }
 
         if self.at(Kind::Def) || self.at(Kind::Async) {
@@ -772,14 +1175,14 @@ impl Parser {
         self.expect(Kind::Class)?;
         let name = self.cur_token().value.to_string();
         self.expect(Kind::Identifier)?;
-        let type_params = if self.at(Kind::LeftBrace) {
+        let type_params = if self.at(Kind::LBracket) {
             self.parse_type_parameters()?
         } else {
             vec![]
         };
-        let (bases, keywords) = if self.eat(Kind::LeftParen) {
+        let (bases, keywords) = if self.eat(Kind::LParen) {
             let (bases, keywords) = self.parse_argument_list()?;
-            self.expect(Kind::RightParen)?;
+            self.expect(Kind::RParen)?;
             (bases, keywords)
         } else {
             (vec![], vec![])
@@ -801,9 +1204,7 @@ impl Parser {
     // https://peps.python.org/pep-0622/#appendix-a-full-grammar
     fn parse_match_statement(&mut self) -> Result<Statement, ParsingError> {
         let node = self.start_node();
-        // This identifier is match word
-        // match is a soft keyword
-        self.bump(Kind::Identifier);
+        self.expect_soft_keyword(SoftKeyword::Match)?;
         let subject = Box::new(self.parse_subject()?);
         self.expect(Kind::Colon)?;
         self.expect(Kind::NewLine)?;
@@ -829,7 +1230,7 @@ impl Parser {
     // https://docs.python.org/3/reference/grammar.html
     fn parse_star_named_expression(&mut self) -> Result<Expression, ParsingError> {
         if self.at(Kind::Mul) {
-            self.parse_or_expr()
+            self.parse_starred_item()
         } else {
             self.parse_named_expression()
         }
@@ -863,8 +1264,7 @@ impl Parser {
             }
             let node = self.start_node();
 
-            // This identifier is case word
-            self.expect(Kind::Identifier)?;
+            self.expect_soft_keyword(SoftKeyword::Case)?;
             let pattern = Box::new(self.parse_patterns()?);
             let guard = if self.at(Kind::If) {
                 Some(Box::new(self.parse_guard()?))
@@ -890,69 +1290,83 @@ impl Parser {
     }
 
     // https://docs.python.org/3/reference/compound_stmts.html#grammar-token-python-grammar-patterns
-    // The open sequence pattern is either a pattern or maybe star pattern
-    // Here we expect at least one ( pattern or maybe star pattern )
+    // patterns: open_sequence_pattern | pattern
+    // A comma after the first pattern makes a sequence, e.g. `case a, *rest:`
     fn parse_patterns(&mut self) -> Result<MatchPattern, ParsingError> {
-        let mut patterns = self.parse_open_sequence_pattern()?;
-
-        if patterns.len() == 1 {
-            Ok(patterns.remove(0))
-        } else {
-            Ok(MatchPattern::MatchSequence(patterns))
+        let node = self.start_node();
+        let starred = self.at(Kind::Mul);
+        let pattern = self.parse_maybe_star_pattern()?;
+        if !self.at(Kind::Comma) {
+            if starred {
+                self.star_pattern_error(pattern.get_node());
+            }
+            return Ok(pattern);
         }
+        let mut patterns = vec![pattern];
+        while self.eat(Kind::Comma) {
+            if self.at(Kind::Colon) || self.at(Kind::If) {
+                break;
+            }
+            patterns.push(self.parse_maybe_star_pattern()?);
+        }
+        Ok(self.finish_sequence_pattern(node, patterns))
     }
 
+    // pattern: as_pattern | or_pattern
     fn parse_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
+        let node = self.start_node();
         let or_pattern = self.parse_or_pattern()?;
-
-        if self.at(Kind::As) {
-            let node = self.start_node();
-            let name = Some(self.cur_token().value.to_string());
-            self.bump(Kind::As);
-            Ok(MatchPattern::MatchAs(MatchAs {
-                node: self.finish_node(node),
-                pattern: Some(Box::new(or_pattern)),
-                name,
-            }))
-        } else {
-            Ok(or_pattern)
+        if !self.eat(Kind::As) {
+            return Ok(or_pattern);
+        }
+        if SoftKeyword::from_token(self.cur_token()) == Some(SoftKeyword::Underscore) {
+            let span = self.get_span_on_line(self.cur_token.start, self.cur_token.end);
+            self.errors.push(ParsingError::InvalidSyntax {
+                msg: Box::from("cannot use '_' as a target"),
+                input: self.curr_line_string.clone(),
+                advice: "use the pattern without `as _`".to_string(),
+                span,
+            });
         }
+        let name = self.cur_token().value.to_string();
+        self.expect(Kind::Identifier)?;
+        Ok(MatchPattern::MatchAs(MatchAs {
+            node: self.finish_node(node),
+            name: Some(name),
+            pattern: Some(Box::new(or_pattern)),
+        }))
     }
 
     fn parse_or_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
-        let mut patterns = vec![];
-        patterns.push(self.parse_closed_pattern()?);
-        loop {
-            if !self.eat(Kind::BitOr) {
-                break;
-            }
+        let node = self.start_node();
+        let mut patterns = vec![self.parse_closed_pattern()?];
+        while self.eat(Kind::BitOr) {
             patterns.push(self.parse_closed_pattern()?);
         }
         if patterns.len() == 1 {
             Ok(patterns.remove(0))
         } else {
-            Ok(MatchPattern::MatchOr(patterns))
+            Ok(MatchPattern::MatchOr(MatchOr {
+                node: self.finish_node(node),
+                patterns,
+            }))
         }
     }
 
     fn parse_closed_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
         match self.cur_kind() {
-            Kind::LeftParen => self.parse_sequence_pattern(),
-            Kind::LeftBrace => self.parse_sequence_pattern(),
-            Kind::LeftBracket => self.parse_mapping_pattern(),
+            Kind::LParen => self.parse_group_or_sequence_pattern(),
+            Kind::LBracket => self.parse_sequence_pattern(),
+            Kind::LBrace => self.parse_mapping_pattern(),
             Kind::Identifier => {
-                if matches!(self.peek_kind(), Ok(Kind::Dot)) {
-                    // TODO: use a way to reuse node from value expression
+                if matches!(self.peek_kind(), Ok(Kind::Dot | Kind::LParen)) {
                     let node = self.start_node();
                     let value = self.parse_attr()?;
-                    if self.at(Kind::LeftParen) {
-                        self.parse_class_pattern(value)
+                    if self.at(Kind::LParen) {
+                        self.parse_class_pattern(value, node)
                     } else {
                         self.parse_value_pattern(value, node)
                     }
-                } else if matches!(self.peek_kind(), Ok(Kind::LeftParen)) {
-                    let value = self.parse_attr()?;
-                    self.parse_class_pattern(value)
                 } else {
                     self.parse_capture_or_wildcard_pattern()
                 }
@@ -969,9 +1383,9 @@ impl Parser {
             | Kind::None
             | Kind::True
             | Kind::False
-            | Kind::StringLiteral | Kind::RawBytes | Kind::Bytes
-            // The signed numbers are also allowed
-            | Kind::Minus | Kind::Plus => {
+            | Kind::StringLiteral | Kind::Unicode | Kind::RawBytes | Kind::Bytes
+            // Negative numbers are also allowed
+            | Kind::Minus => {
                 self.parse_literal_pattern()
 
                 },
@@ -980,9 +1394,9 @@ impl Parser {
                 Err(self.unexpected_token_new(
                     node,
                     vec![
-                        Kind::LeftParen,
-                        Kind::LeftBrace,
-                        Kind::LeftBracket,
+                        Kind::LParen,
+                        Kind::LBracket,
+                        Kind::LBrace,
                         Kind::Identifier,
                         Kind::Integer,
                         Kind::Binary,
@@ -997,10 +1411,10 @@ impl Parser {
                         Kind::True,
                         Kind::False,
                         Kind::StringLiteral,
+                        Kind::Unicode,
                         Kind::RawBytes,
                         Kind::Bytes,
                         Kind::Minus,
-                        Kind::Plus,
                     ],
                     "A match pattern starts with these characters",
                 ))
@@ -1009,22 +1423,104 @@ impl Parser {
     }
 
     // https://docs.python.org/3/reference/compound_stmts.html#literal-patterns
+    // None, True and False are compared by identity so they are singletons
     fn parse_literal_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
         let node = self.start_node();
-        let value = Box::new(self.parse_binary_arithmetic_operation()?);
+        if matches!(self.cur_kind(), Kind::None | Kind::True | Kind::False) {
+            let value = Box::new(self.parse_atom()?);
+            return Ok(MatchPattern::MatchSingleton(MatchSingleton {
+                node: self.finish_node(node),
+                value,
+            }));
+        }
+        let value = Box::new(self.parse_literal_expression()?);
         Ok(MatchPattern::MatchValue(MatchValue {
             node: self.finish_node(node),
             value,
         }))
     }
 
+    // The value of a literal pattern or the key of a mapping pattern.
+    // Only strings, signed numbers and complex numbers like `-1 + 2j` are
+    // allowed, not any arithmetic expression.
+    fn parse_literal_expression(&mut self) -> Result<Expression, ParsingError> {
+        let node = self.start_node();
+        if is_string(&self.cur_kind())
+            || matches!(self.cur_kind(), Kind::None | Kind::True | Kind::False)
+        {
+            return self.parse_atom();
+        }
+        let real = self.parse_signed_number()?;
+        let op = match self.cur_kind() {
+            Kind::Plus => BinaryOperator::Add,
+            Kind::Minus => BinaryOperator::Sub,
+            _ => return Ok(real),
+        };
+        self.bump_any();
+        if !matches!(
+            self.cur_kind(),
+            Kind::ImaginaryInteger | Kind::ImaginaryPointFloat | Kind::ImaginaryExponentFloat
+        ) {
+            let node = self.start_node();
+            return Err(self.unexpected_token_new(
+                node,
+                vec![
+                    Kind::ImaginaryInteger,
+                    Kind::ImaginaryPointFloat,
+                    Kind::ImaginaryExponentFloat,
+                ],
+                "The second part of a complex number is an imaginary number",
+            ));
+        }
+        let imaginary = self.parse_atom()?;
+        Ok(Expression::BinOp(Box::new(BinOp {
+            node: self.finish_node(node),
+            op,
+            left: Box::new(real),
+            right: Box::new(imaginary),
+        })))
+    }
+
+    fn parse_signed_number(&mut self) -> Result<Expression, ParsingError> {
+        let node = self.start_node();
+        let negative = self.eat(Kind::Minus);
+        if !matches!(
+            self.cur_kind(),
+            Kind::Integer
+                | Kind::Binary
+                | Kind::Octal
+                | Kind::Hexadecimal
+                | Kind::PointFloat
+                | Kind::ExponentFloat
+                | Kind::ImaginaryInteger
+                | Kind::ImaginaryPointFloat
+                | Kind::ImaginaryExponentFloat
+        ) {
+            let node = self.start_node();
+            return Err(self.unexpected_token_new(
+                node,
+                vec![Kind::Integer, Kind::PointFloat, Kind::ImaginaryInteger],
+                "A literal pattern is a string or a number",
+            ));
+        }
+        let number = self.parse_atom()?;
+        if !negative {
+            return Ok(number);
+        }
+        Ok(Expression::UnaryOp(Box::new(UnaryOperation {
+            node: self.finish_node(node),
+            op: UnaryOperator::USub,
+            operand: Box::new(number),
+        })))
+    }
+
     fn parse_capture_or_wildcard_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
         let capture_value = self.cur_token().value.to_string().clone();
+        let wildcard = SoftKeyword::from_token(self.cur_token()) == Some(SoftKeyword::Underscore);
         let node = self.start_node();
         self.expect(Kind::Identifier)?;
-        // TODO: should also accpet as?
 
-        if capture_value == "_" {
+        if wildcard {
             Ok(MatchPattern::MatchAs(MatchAs {
                 node: self.finish_node(node),
                 name: None,
@@ -1075,43 +1571,65 @@ impl Parser {
         expr
     }
 
-    // TODO: This has precedence over sequence pattern but I'm not sure
-    // what is the right way to use it.
-    fn parse_group_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
-        self.expect(Kind::LeftParen)?;
-        let pattern = self.parse_pattern()?;
-        self.expect(Kind::RightParen)?;
-        Ok(pattern)
+    // https://docs.python.org/3/reference/compound_stmts.html#group-patterns
+    // `(p)` is a group pattern that is the same as `p` while `()`, `(p,)`
+    // and `(p, q)` are sequence patterns
+    fn parse_group_or_sequence_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
+        let node = self.start_node();
+        self.expect(Kind::LParen)?;
+        if self.eat(Kind::RParen) {
+            return Ok(self.finish_sequence_pattern(node, vec![]));
+        }
+        let starred = self.at(Kind::Mul);
+        let pattern = self.parse_maybe_star_pattern()?;
+        if self.eat(Kind::RParen) {
+            if starred {
+                self.star_pattern_error(pattern.get_node());
+            }
+            return Ok(pattern);
+        }
+        let mut patterns = vec![pattern];
+        while self.eat(Kind::Comma) {
+            if self.at(Kind::RParen) {
+                break;
+            }
+            patterns.push(self.parse_maybe_star_pattern()?);
+        }
+        self.expect(Kind::RParen)?;
+        Ok(self.finish_sequence_pattern(node, patterns))
     }
 
     fn parse_mapping_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
         let node = self.start_node();
-        self.expect(Kind::LeftBracket)?;
+        self.expect(Kind::LBrace)?;
         let mut keys = vec![];
         let mut patterns = vec![];
         let mut rest = None;
         loop {
-            if self.eat(Kind::RightBracket) {
+            if self.eat(Kind::RBrace) {
                 break;
             }
             if self.eat(Kind::Pow) {
                 rest = Some(self.cur_token().value.to_string().clone());
-                self.bump(Kind::Identifier);
+                self.expect(Kind::Identifier)?;
                 // consume the trailing comma
                 self.bump(Kind::Comma);
                 // rest is the last element so we expect the closing bracket
-                self.expect(Kind::RightBracket)?;
+                self.expect(Kind::RBrace)?;
                 break;
             } else {
-                // TODO: here we cannot accept all primary expressions
-                // but python docs do not have the full list of what is allowed
-                // so we just do this for now
-                keys.push(self.parse_primary(None)?);
+                // The keys are literals or dotted names of value patterns
+                let key = if self.at(Kind::Identifier) {
+                    self.parse_attr()?
+                } else {
+                    self.parse_literal_expression()?
+                };
+                keys.push(key);
                 self.expect(Kind::Colon)?;
                 patterns.push(self.parse_pattern()?);
             }
 
-            if !self.at(Kind::RightBracket) {
+            if !self.at(Kind::RBrace) {
                 self.expect(Kind::Comma)?;
             }
         }
@@ -1124,29 +1642,19 @@ impl Parser {
         }))
     }
 
-    fn parse_literal_or_value_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
-        if self.cur_kind() == Kind::Identifier && !matches!(self.peek_kind(), Ok(Kind::Colon)) {
-            let node = self.start_node();
-            let value = self.parse_attr()?;
-            self.parse_value_pattern(value, node)
-        } else {
-            self.parse_literal_pattern()
-        }
-    }
-
     fn parse_class_pattern(
         &mut self,
         class_name: Expression,
+        node: Node,
     ) -> Result<MatchPattern, ParsingError> {
-        let node = self.start_node();
         let class = Box::new(class_name);
-        self.expect(Kind::LeftParen)?;
+        self.expect(Kind::LParen)?;
         let mut patterns = vec![];
         let mut kwd_attrs = vec![];
         let mut kwd_patterns = vec![];
         let mut seen_keyword_pattern = false;
         loop {
-            if self.eat(Kind::RightParen) {
+            if self.eat(Kind::RParen) {
                 break;
             }
 
@@ -1158,6 +1666,7 @@ impl Parser {
                 kwd_patterns.push(self.parse_pattern()?);
             } else {
                 if seen_keyword_pattern {
+                    let node = self.finish_node(node);
                     return Err(ParsingError::InvalidSyntax {
                         msg: Box::from("Positional arguments cannot come after keyword arguments."),
                         input: self.curr_line_string.clone(),
@@ -1167,7 +1676,7 @@ impl Parser {
                 }
                 patterns.push(self.parse_pattern()?);
             }
-            if !self.at(Kind::RightParen) {
+            if !self.at(Kind::RParen) {
                 self.expect(Kind::Comma)?;
             }
         }
@@ -1180,56 +1689,64 @@ impl Parser {
         }))
     }
 
+    // https://docs.python.org/3/reference/compound_stmts.html#sequence-patterns
     fn parse_sequence_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
         let node = self.start_node();
-        if self.eat(Kind::LeftBrace) {
-            let pattern = self.parse_maybe_sequence_pattern()?;
-            self.expect(Kind::RightBrace)?;
-            Ok(MatchPattern::MatchSequence(pattern))
-        } else if self.eat(Kind::LeftParen) {
-            let pattern = self.parse_open_sequence_pattern()?;
-            self.expect(Kind::RightParen)?;
-            Ok(MatchPattern::MatchSequence(pattern))
-        } else {
-            Err(self.unexpected_token_new(
-                node,
-                vec![Kind::LeftBrace, Kind::LeftParen],
-                "Write a sequence pattern here",
-            ))
-        }
-    }
-
-    fn parse_open_sequence_pattern(&mut self) -> Result<Vec<MatchPattern>, ParsingError> {
+        self.expect(Kind::LBracket)?;
         let mut patterns = vec![];
-        patterns.push(self.parse_maybe_star_patern()?);
-        loop {
-            if !self.eat(Kind::Comma) {
-                break;
+        while !self.eat(Kind::RBracket) {
+            patterns.push(self.parse_maybe_star_pattern()?);
+            if !self.at(Kind::RBracket) {
+                self.expect(Kind::Comma)?;
             }
-            patterns.push(self.parse_maybe_star_patern()?);
         }
-        Ok(patterns)
+        Ok(self.finish_sequence_pattern(node, patterns))
     }
 
-    fn parse_maybe_sequence_pattern(&mut self) -> Result<Vec<MatchPattern>, ParsingError> {
-        let mut patterns = vec![];
-        loop {
-            if self.at(Kind::RightBrace) {
-                break;
-            }
-            patterns.push(self.parse_maybe_star_patern()?);
-            if !self.at(Kind::RightBrace) {
-                self.expect(Kind::Comma)?;
-            }
+    fn finish_sequence_pattern(&mut self, node: Node, patterns: Vec<MatchPattern>) -> MatchPattern {
+        let node = self.finish_node(node);
+        let stars = patterns
+            .iter()
+            .filter(|pattern| matches!(pattern, MatchPattern::MatchStar(_)))
+            .count();
+        if stars > 1 {
+            self.errors.push(ParsingError::InvalidSyntax {
+                msg: Box::from("multiple starred names in sequence pattern"),
+                input: self.curr_line_string.clone(),
+                advice: "a sequence pattern can have one star pattern".to_string(),
+                span: self.get_span_on_line(node.start, node.end),
+            });
         }
-        Ok(patterns)
+        MatchPattern::MatchSequence(MatchSequence { node, patterns })
     }
-    fn parse_maybe_star_patern(&mut self) -> Result<MatchPattern, ParsingError> {
-        if self.eat(Kind::Mul) {
-            self.parse_capture_or_wildcard_pattern()
-        } else {
-            self.parse_pattern()
+
+    // star_pattern: '*' (capture_pattern | wildcard_pattern)
+    fn parse_maybe_star_pattern(&mut self) -> Result<MatchPattern, ParsingError> {
+        if !self.at(Kind::Mul) {
+            return self.parse_pattern();
         }
+        let node = self.start_node();
+        self.bump(Kind::Mul);
+        let name = if SoftKeyword::from_token(self.cur_token()) == Some(SoftKeyword::Underscore) {
+            None
+        } else {
+            Some(self.cur_token().value.to_string())
+        };
+        self.expect(Kind::Identifier)?;
+        Ok(MatchPattern::MatchStar(MatchStar {
+            node: self.finish_node(node),
+            name,
+        }))
+    }
+
+    // Star patterns are only allowed in sequence patterns
+    fn star_pattern_error(&mut self, node: Node) {
+        self.errors.push(ParsingError::InvalidSyntax {
+            msg: Box::from("can't use starred name here"),
+            input: self.curr_line_string.clone(),
+            advice: "use the star pattern in a sequence pattern, e.g. `[*rest]`".to_string(),
+            span: self.get_span_on_line(node.start, node.end),
+        });
     }
 
     fn parse_assignment_or_expression_statement(&mut self) -> Result<Statement, ParsingError> {
@@ -1257,7 +1774,7 @@ impl Parser {
                 if self.eat(Kind::Comment) || self.consume_whitespace_and_newline() {
                     continue;
                 }
-                let stmt = self.parse_statement()?;
+                let stmt = self.nested(Self::parse_statement)?;
                 stmts.extend(stmt);
             }
             Ok(stmts)
@@ -1271,7 +1788,7 @@ impl Parser {
 
     // https://docs.python.org/3/reference/compound_stmts.html#grammar-token-python-grammar-statement
     fn parse_statement(&mut self) -> Result<Vec<Statement>, ParsingError> {
-        if is_at_compound_statement(self.cur_token()) {
+        if self.at_compound_statement() {
             let comp_stmt = self.parse_compount_statement()?;
             Ok(vec![comp_stmt])
         } else {
@@ -1314,15 +1831,21 @@ impl Parser {
         start: Node,
         lhs: Expression,
     ) -> Result<Statement, ParsingError> {
+        self.check_assignment_target(&lhs);
         let mut targets = vec![lhs];
         self.bump(Kind::Assign);
         let value = loop {
-            let rhs = self.parse_expression()?;
+            let rhs = if self.at(Kind::Yield) {
+                self.parse_yield_expression()?
+            } else {
+                self.parse_expression()?
+            };
             // if there's an assign after the expression we have multiple targets
             // like a = b = 1
             // so we add the rhs to the targets and continue parsing
             // otherwise we break and return the rhs as the value
             if self.eat(Kind::Assign) {
+                self.check_assignment_target(&rhs);
                 targets.push(rhs);
             } else {
                 break rhs;
@@ -1341,7 +1864,42 @@ impl Parser {
         lhs: Expression,
         op: AugAssignOp,
     ) -> Result<Statement, ParsingError> {
+        // Only a single name, attribute or subscript can be updated in place,
+        // a starred target is already reported as a misplaced star
+        if !matches!(
+            lhs,
+            Expression::Name(_)
+                | Expression::Attribute(_)
+                | Expression::Subscript(_)
+                | Expression::Starred(_)
+        ) {
+            let node = lhs.get_node();
+            self.errors.push(ParsingError::InvalidSyntax {
+                msg: Box::from(format!(
+                    "'{}' is an illegal expression for augmented assignment",
+                    expression_name(&lhs)
+                )),
+                input: self.curr_line_string.clone(),
+                advice: "assign to a name, attribute or subscript".to_string(),
+                span: self.get_span_on_line(node.start, node.end),
+            });
+        }
+        self.check_assignment_target(&lhs);
         let value = self.parse_assignment_value()?;
+        // `a += b += 1` and `a += b = 1` are not allowed, the rest of the
+        // chain is parsed to report it once
+        while self.at(Kind::Assign) || is_aug_assign(&self.cur_kind()) {
+            let node = self.start_node();
+            self.bump_any();
+            self.parse_assignment_value()?;
+            let node = self.finish_node(node);
+            self.errors.push(ParsingError::InvalidSyntax {
+                msg: Box::from("augmented assignment cannot be chained"),
+                input: self.curr_line_string.clone(),
+                advice: "split the assignments into separate statements".to_string(),
+                span: self.get_span_on_line(node.start, node.end),
+            });
+        }
 
         Ok(Statement::AugAssignStatement(AugAssign {
             node: self.finish_node(start),
@@ -1356,6 +1914,10 @@ impl Parser {
         start: Node,
         lhs: Expression,
     ) -> Result<Statement, ParsingError> {
+        // A name that is not in parentheses is a simple target, `(a): int`
+        // starts before the name
+        let simple = matches!(&lhs, Expression::Name(n) if n.node.start == start.start);
+        self.check_assignment_target(&lhs);
         self.bump(Kind::Colon);
         let annotation = self.parse_expression_2()?;
         let value = if self.eat(Kind::Assign) {
@@ -1368,8 +1930,7 @@ impl Parser {
             target: lhs,
             annotation,
             value,
-            // TODO: implement simple
-            simple: true,
+            simple,
         }))
     }
 
@@ -1387,6 +1948,7 @@ impl Parser {
             Kind::AddAssign => AugAssignOp::Add,
             Kind::SubAssign => AugAssignOp::Sub,
             Kind::MulAssign => AugAssignOp::Mult,
+            Kind::MatrixMulAssign => AugAssignOp::MatMult,
             Kind::DivAssign => AugAssignOp::Div,
             Kind::IntDivAssign => AugAssignOp::FloorDiv,
             Kind::ModAssign => AugAssignOp::Mod,
@@ -1430,9 +1992,13 @@ impl Parser {
     fn parse_return_statement(&mut self) -> Result<Statement, ParsingError> {
         let node = self.start_node();
         self.bump(Kind::Return);
-        let value = if self.at(Kind::NewLine) {
+        let value = if matches!(
+            self.cur_kind(),
+            Kind::NewLine | Kind::Eof | Kind::SemiColon | Kind::Comment
+        ) {
             None
         } else {
+            // star_expressions, e.g. `return *a, *b`
             Some(self.parse_expression_list()?)
         };
         Ok(Statement::Return(Return {
@@ -1546,7 +2112,7 @@ impl Parser {
         let (module, level) = self.parse_module_name()?;
         self.bump(Kind::Import);
         let mut aliases = vec![];
-        if self.eat(Kind::LeftParen) {
+        if self.eat(Kind::LParen) {
             while self.at(Kind::Identifier) {
                 let alias_name = self.start_node();
                 let name = self.cur_token().value.to_string();
@@ -1557,7 +2123,7 @@ impl Parser {
                     break;
                 }
             }
-            self.expect(Kind::RightParen)?;
+            self.expect(Kind::RParen)?;
         } else if self.at(Kind::Identifier) {
             while self.at(Kind::Identifier) {
                 let alias_name = self.start_node();
@@ -1575,7 +2141,7 @@ impl Parser {
         } else {
             return Err(self.unexpected_token_new(
                import_node,
-               vec![Kind::Identifier, Kind::Mul, Kind::LeftParen],
+               vec![Kind::Identifier, Kind::Mul, Kind::LParen],
                "Use * for importing everthing or use () to specify names to import or specify the name you want to import"
            ));
         }
@@ -1622,8 +2188,12 @@ impl Parser {
             }
             self.bump_any();
         }
+        // The module is optional in relative imports, e.g. `from . import a`
+        if level > 0 && self.at(Kind::Import) {
+            return Ok((String::new(), level));
+        }
         let mut module = self.cur_token().value.to_string();
-        self.expect(Kind::Identifier);
+        self.expect(Kind::Identifier)?;
         while self.eat(Kind::Dot) {
             module.push('.');
             module.push_str(self.cur_token().value.to_string().as_str());
@@ -1635,18 +2205,19 @@ impl Parser {
     // https://docs.python.org/3/library/ast.html#ast.Expr
     fn parse_expression(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
-        let expr = self.parse_expression_2()?;
+        let expr = self.parse_star_expression()?;
 
         let mut exprs = vec![];
         if self.at(Kind::Comma) {
             exprs.push(expr);
             while self.eat(Kind::Comma) {
-                if self.at(Kind::Eof) {
+                if self.at_expression_list_end() {
                     break;
                 }
-                exprs.push(self.parse_expression_2()?);
+                exprs.push(self.parse_star_expression()?);
             }
         } else {
+            self.check_unstarred(&expr);
             return Ok(expr);
         }
 
@@ -1662,7 +2233,7 @@ impl Parser {
         if self.eat(Kind::If) {
             let test = self.parse_or_test()?;
             self.expect(Kind::Else)?;
-            let or_else = self.parse_expression_2()?;
+            let or_else = self.nested(Self::parse_expression_2)?;
             return Ok(Expression::IfExp(Box::new(IfExp {
                 node: self.start_node(),
                 test: Box::new(test),
@@ -1684,13 +2255,15 @@ impl Parser {
             self.expect(Kind::Identifier)?;
             identifier_node = self.finish_node(identifier_node);
             if self.eat(Kind::Walrus) {
+                let target = Expression::Name(Box::new(Name {
+                    node: identifier_node,
+                    id: identifier,
+                }));
+                self.check_assignment_target(&target);
                 let value = self.parse_expression_2()?;
                 return Ok(Expression::NamedExpr(Box::new(NamedExpression {
                     node: self.finish_node(node),
-                    target: Box::new(Expression::Name(Box::new(Name {
-                        node: identifier_node,
-                        id: identifier,
-                    }))),
+                    target: Box::new(target),
                     value: Box::new(value),
                 })));
             }
@@ -1706,22 +2279,21 @@ impl Parser {
     // https://docs.python.org/3/reference/expressions.html#list-displays
     fn parse_list(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
-        self.bump(Kind::LeftBrace);
-        if self.eat(Kind::RightBrace) {
+        self.bump(Kind::LBracket);
+        if self.eat(Kind::RBracket) {
             return Ok(Expression::List(Box::new(List {
                 node: self.finish_node(node),
                 elements: vec![],
             })));
         }
-        let started_with_star = self.at(Kind::Mul);
         let first_elm = self.parse_star_named_expression()?;
-        if !started_with_star
-            && self.at(Kind::For)
+        if self.at(Kind::For)
             && (self.at(Kind::For)
                 || self.at(Kind::Async) && matches!(self.peek_kind(), Ok(Kind::For)))
         {
+            self.check_unstarred(&first_elm);
             let generators = self.parse_comp_for()?;
-            self.expect(Kind::RightBrace)?;
+            self.expect(Kind::RBracket)?;
             return Ok(Expression::ListComp(Box::new(ListComp {
                 node: self.finish_node(node),
                 element: Box::new(first_elm),
@@ -1729,10 +2301,10 @@ impl Parser {
             })));
         }
         self.bump(Kind::Comma);
-        let rest = self.parse_starred_list(Kind::RightBrace)?;
+        let rest = self.parse_starred_list(Kind::RBracket)?;
         let elements = vec![first_elm];
         let elements = elements.into_iter().chain(rest).collect();
-        self.expect(Kind::RightBrace)?;
+        self.expect(Kind::RBracket)?;
         Ok(Expression::List(Box::new(List {
             node: self.finish_node(node),
             elements,
@@ -1742,13 +2314,20 @@ impl Parser {
     // https://docs.python.org/3/reference/expressions.html#parenthesized-forms
     fn parse_paren_form_or_generator(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
-        self.expect(Kind::LeftParen)?;
-        if self.at(Kind::RightParen) {
+        self.expect(Kind::LParen)?;
+        if self.eat(Kind::RParen) {
             return Ok(Expression::Tuple(Box::new(Tuple {
                 node: self.finish_node(node),
                 elements: vec![],
             })));
         }
+        // A yield expression in parentheses can be used as an operand,
+        // e.g. `await (yield)`
+        if self.at(Kind::Yield) {
+            let expr = self.parse_yield_expression()?;
+            self.expect(Kind::RParen)?;
+            return Ok(expr);
+        }
         // paren form starts with either an expression or a star expression
         // Generator starts with an expression
         // we need to first check if we have a generator or a paren form
@@ -1761,19 +2340,16 @@ impl Parser {
         let first_expr =
             if self.at(Kind::Identifier) && matches!(self.peek_kind(), Ok(Kind::Walrus)) {
                 self.parse_named_expression()?
-            } else if self.eat(Kind::Mul) {
-                let expr = self.parse_or_expr()?;
-                Expression::Starred(Box::new(Starred {
-                    node: self.finish_node(node),
-                    value: Box::new(expr),
-                }))
+            } else if self.at(Kind::Mul) {
+                self.parse_starred_item()?
             } else {
                 self.parse_expression_2()?
             };
 
         if matches!(self.cur_kind(), Kind::For) || matches!(self.peek_kind(), Ok(Kind::For)) {
+            self.check_unstarred(&first_expr);
             let generators = self.parse_comp_for()?;
-            self.expect(Kind::RightParen)?;
+            self.expect(Kind::RParen)?;
             return Ok(Expression::Generator(Box::new(Generator {
                 node: self.finish_node(node),
                 element: Box::new(first_expr),
@@ -1781,8 +2357,18 @@ impl Parser {
             })));
         }
 
-        let expr = self.parse_starred_expression(node, first_expr)?;
-        self.expect(Kind::RightParen)?;
+        let mut expr = self.parse_starred_expression(node, first_expr)?;
+        self.expect(Kind::RParen)?;
+        match expr {
+            // `(*a)` is not a tuple, only `(*a,)` is
+            Expression::Starred(starred) => {
+                self.starred_expression_error(starred.node, "can't use starred expression here");
+                return Ok(*starred.value);
+            }
+            // The parentheses are part of the tuple
+            Expression::Tuple(ref mut tuple) => tuple.node = self.finish_node(node),
+            _ => {}
+        }
         Ok(expr)
     }
 
@@ -1796,6 +2382,7 @@ impl Parser {
             let node = self.start_node();
             self.expect(Kind::For)?;
             let target = self.parse_target_list()?;
+            self.check_assignment_target(&target);
             self.expect(Kind::In)?;
             let iter = self.parse_or_test()?;
             let ifs = if self.eat(Kind::If) {
@@ -1827,14 +2414,8 @@ impl Parser {
     // https://docs.python.org/3/reference/simple_stmts.html#grammar-token-python-grammar-target_list
     fn parse_target_list(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
-        let mut targets = vec![];
-        loop {
-            targets.push(self.parse_target()?);
-            if !self.eat(Kind::Comma) || matches!(self.cur_kind(), Kind::NewLine | Kind::Eof) {
-                break;
-            }
-        }
-        if targets.len() == 1 {
+        let (mut targets, seen_comma) = self.parse_targets()?;
+        if targets.len() == 1 && !seen_comma {
             Ok(targets.remove(0))
         } else {
             Ok(Expression::Tuple(Box::new(Tuple {
@@ -1844,14 +2425,55 @@ impl Parser {
         }
     }
 
+    // `__debug__` is a constant, it cannot be the target of an assignment
+    // or be bound by a loop, `with` or `:=`
+    fn check_assignment_target(&mut self, target: &Expression) {
+        match target {
+            Expression::Name(n) if n.id == "__debug__" => {
+                self.errors.push(ParsingError::CannotAssign {
+                    name: n.id.clone(),
+                    input: self.curr_line_string.clone(),
+                    span: self.get_span_on_line(n.node.start, n.node.end),
+                });
+            }
+            Expression::Tuple(t) => t
+                .elements
+                .iter()
+                .for_each(|e| self.check_assignment_target(e)),
+            Expression::List(l) => l
+                .elements
+                .iter()
+                .for_each(|e| self.check_assignment_target(e)),
+            Expression::Starred(s) => self.check_assignment_target(&s.value),
+            _ => {}
+        }
+    }
+
+    // Comma separated targets, which may end with a trailing comma. Returns
+    // whether a comma was seen because `a,` is a tuple but `a` is not
+    fn parse_targets(&mut self) -> Result<(Vec<Expression>, bool), ParsingError> {
+        let mut targets = vec![self.parse_target()?];
+        let mut seen_comma = false;
+        while self.eat(Kind::Comma) {
+            seen_comma = true;
+            if !matches!(
+                self.cur_kind(),
+                Kind::Identifier | Kind::LBracket | Kind::LParen | Kind::Mul
+            ) {
+                break;
+            }
+            targets.push(self.parse_target()?);
+        }
+        Ok((targets, seen_comma))
+    }
+
     // https://docs.python.org/3/reference/simple_stmts.html#grammar-token-python-grammar-target
     fn parse_target(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
-        let mut targets = vec![];
         let target = match self.cur_kind() {
             Kind::Identifier => match self.peek_kind() {
                 // TODO: atom cannot be all the atoms like string, number
-                Ok(Kind::LeftBrace) => {
+                Ok(Kind::LBracket) => {
                     let atom = self.parse_atom()?;
                     self.parse_subscript(node, atom)?
                 }
@@ -1871,32 +2493,20 @@ impl Parser {
                     })));
                 }
             },
-            Kind::LeftBrace => {
-                let mut elements = vec![];
-                self.bump(Kind::LeftBrace);
-                loop {
-                    elements.push(self.parse_target()?);
-                    if !self.eat(Kind::Comma) {
-                        break;
-                    }
-                }
-                self.expect(Kind::RightBrace)?;
+            Kind::LBracket => {
+                self.bump(Kind::LBracket);
+                let (elements, _) = self.parse_targets()?;
+                self.expect(Kind::RBracket)?;
                 Expression::List(Box::new(List {
                     node: self.finish_node(node),
                     elements,
                 }))
             }
-            Kind::LeftParen => {
-                let mut targets = vec![];
-                self.bump(Kind::LeftParen);
-                loop {
-                    targets.push(self.parse_target()?);
-                    if !self.eat(Kind::Comma) {
-                        break;
-                    }
-                }
-                self.expect(Kind::RightParen)?;
-                if targets.len() == 1 {
+            Kind::LParen => {
+                self.bump(Kind::LParen);
+                let (mut targets, seen_comma) = self.parse_targets()?;
+                self.expect(Kind::RParen)?;
+                if targets.len() == 1 && !seen_comma {
                     targets.pop().unwrap()
                 } else {
                     Expression::Tuple(Box::new(Tuple {
@@ -1905,39 +2515,23 @@ impl Parser {
                     }))
                 }
             }
-            Kind::Mul => Expression::Starred(Box::new(Starred {
-                node: self.finish_node(node),
-                value: Box::new(self.parse_target()?),
-            })),
-            _ => panic!("invalid target"),
-        };
-        targets.push(target);
-        while self.eat(Kind::Comma) {
-            // check if current kind can be start of a target
-            if !matches!(
-                self.cur_kind(),
-                Kind::Identifier | Kind::LeftBrace | Kind::LeftParen | Kind::Mul
-            ) {
-                break;
+            Kind::Mul => {
+                self.bump(Kind::Mul);
+                let value = self.parse_target()?;
+                Expression::Starred(Box::new(Starred {
+                    node: self.finish_node(node),
+                    value: Box::new(value),
+                }))
             }
-            let next_target = self.parse_target()?;
-            targets.push(next_target);
-        }
-
-        if targets.len() == 1 {
-            Ok(targets.remove(0))
-        } else {
-            Ok(Expression::Tuple(Box::new(Tuple {
-                node: self.finish_node(node),
-                elements: targets,
-            })))
-        }
+            _ => return Err(self.unepxted_token(node, self.cur_kind()).err().unwrap()),
+        };
+        Ok(target)
     }
 
     fn parse_dict_or_set(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
-        self.bump(Kind::LeftBracket);
-        if self.eat(Kind::RightBracket) {
+        self.bump(Kind::LBrace);
+        if self.eat(Kind::RBrace) {
             return Ok(Expression::Dict(Box::new(Dict {
                 node: self.finish_node(node),
                 keys: vec![],
@@ -1952,10 +2546,11 @@ impl Parser {
         let first_key_or_element = self.parse_star_named_expression()?;
         if matches!(
             self.cur_kind(),
-            Kind::Comma | Kind::RightBracket | Kind::Async | Kind::For | Kind::Walrus
+            Kind::Comma | Kind::RBrace | Kind::Async | Kind::For | Kind::Walrus
         ) {
             self.parse_set(node, first_key_or_element)
         } else {
+            self.check_unstarred(&first_key_or_element);
             self.expect(Kind::Colon)?;
             let first_value = self.parse_expression_2()?;
             self.parse_dict(node, Some(first_key_or_element), first_value)
@@ -1964,14 +2559,14 @@ impl Parser {
 
     // https://docs.python.org/3/reference/expressions.html#set-displays
     fn parse_set(&mut self, node: Node, first_elm: Expression) -> Result<Expression, ParsingError> {
-        if !matches!(first_elm, Expression::Starred(_))
-            && self.at(Kind::For)
+        if self.at(Kind::For)
             && (self.at(Kind::For)
                 || self.at(Kind::Async) && matches!(self.peek_kind(), Ok(Kind::For)))
         {
+            self.check_unstarred(&first_elm);
             let generators = self.parse_comp_for()?;
             self.consume_whitespace_and_newline();
-            self.expect(Kind::RightBracket)?;
+            self.expect(Kind::RBrace)?;
             return Ok(Expression::SetComp(Box::new(SetComp {
                 node: self.finish_node(node),
                 element: Box::new(first_elm),
@@ -1979,10 +2574,10 @@ impl Parser {
             })));
         }
         self.bump(Kind::Comma);
-        let rest = self.parse_starred_list(Kind::RightBracket)?;
+        let rest = self.parse_starred_list(Kind::RBrace)?;
         let mut elements = vec![first_elm];
         elements.extend(rest);
-        self.expect(Kind::RightBracket)?;
+        self.expect(Kind::RBrace)?;
         Ok(Expression::Set(Box::new(Set {
             node: self.finish_node(node),
             elements,
@@ -2008,7 +2603,7 @@ impl Parser {
 
             // make sure the first key is some
             let generators = self.parse_comp_for()?;
-            self.expect(Kind::RightBracket)?;
+            self.expect(Kind::RBrace)?;
             Ok(Expression::DictComp(Box::new(DictComp {
                 node: self.finish_node(node),
                 key: Box::new(key),
@@ -2018,7 +2613,7 @@ impl Parser {
         } else {
             // we already consumed the first pair
             // so if there are more pairs we need to consume the comma
-            if !self.at(Kind::RightBracket) {
+            if !self.at(Kind::RBrace) {
                 self.expect(Kind::Comma)?;
                 self.consume_whitespace_and_newline();
             }
@@ -2027,7 +2622,7 @@ impl Parser {
                 None => vec![],
             };
             let mut values = vec![first_value];
-            while !self.eat(Kind::RightBracket) {
+            while !self.eat(Kind::RBrace) {
                 let (key, value) = self.parse_double_starred_kv_pair()?;
 
                 if let Some(k) = key {
@@ -2035,7 +2630,7 @@ impl Parser {
                 }
 
                 values.push(value);
-                if !self.at(Kind::RightBracket) {
+                if !self.at(Kind::RBrace) {
                     self.expect(Kind::Comma)?;
                     self.consume_whitespace_and_newline();
                 }
@@ -2073,7 +2668,7 @@ impl Parser {
 
     // https://docs.python.org/3/reference/expressions.html#expression-lists
     // termination_kind is used to know when to stop parsing the list
-    // for example to parse a tuple the termination_kind is Kind::RightParen
+    // for example to parse a tuple the termination_kind is Kind::RParen
     // caller is responsible to consume the first & last occurrence of the
     // termination_kind
     fn parse_starred_list(
@@ -2096,14 +2691,9 @@ impl Parser {
 
     // https://docs.python.org/3/reference/expressions.html#expression-lists
     fn parse_starred_item(&mut self) -> Result<Expression, ParsingError> {
-        let mut node = self.start_node();
+        let node = self.start_node();
         if self.eat(Kind::Mul) {
-            let starred_value_kind = self.cur_kind();
             let expr = self.parse_or_expr()?;
-            node = self.finish_node(node);
-            if !is_iterable(&expr) {
-                self.unepxted_token(node, starred_value_kind);
-            }
             return Ok(Expression::Starred(Box::new(Starred {
                 node: self.finish_node(node),
                 value: Box::new(expr),
@@ -2112,13 +2702,75 @@ impl Parser {
         self.parse_named_expression()
     }
 
+    // Tokens that can follow the trailing comma of an expression list,
+    // e.g. `x = 1,`
+    fn at_expression_list_end(&self) -> bool {
+        matches!(
+            self.cur_kind(),
+            Kind::NewLine
+                | Kind::Eof
+                | Kind::SemiColon
+                | Kind::Comment
+                | Kind::Assign
+                | Kind::Colon
+                | Kind::RParen
+                | Kind::RBracket
+                | Kind::RBrace
+        )
+    }
+
+    // star_expression: '*' bitwise_or | expression
+    fn parse_star_expression(&mut self) -> Result<Expression, ParsingError> {
+        if self.at(Kind::Mul) {
+            self.parse_starred_item()
+        } else {
+            self.parse_expression_2()
+        }
+    }
+
+    // Starred items are only valid as elements of calls, displays, tuples and
+    // assignment targets, so a starred expression standing on its own is an error
+    fn check_unstarred(&mut self, expr: &Expression) {
+        if let Expression::Starred(starred) = expr {
+            self.starred_expression_error(starred.node, "can't use starred expression here");
+        }
+    }
+
+    // Places that allow unpacking parse the stars themselves, so a star
+    // that reaches an operand is misplaced, e.g. `x = -*a` or `[**a]`
+    fn parse_misplaced_starred(&mut self) -> Result<Expression, ParsingError> {
+        let node = self.start_node();
+        let double_starred = self.at(Kind::Pow);
+        self.bump_any();
+        let value = self.parse_or_expr()?;
+        let node = self.finish_node(node);
+        if double_starred {
+            self.starred_expression_error(node, "can't use double starred expression here");
+            return Ok(value);
+        }
+        self.starred_expression_error(node, "can't use starred expression here");
+        Ok(Expression::Starred(Box::new(Starred {
+            node,
+            value: Box::new(value),
+        })))
+    }
+
+    fn starred_expression_error(&mut self, node: Node, msg: &str) {
+        self.errors.push(ParsingError::InvalidSyntax {
+            msg: Box::from(msg),
+            input: self.curr_line_string.clone(),
+            advice: "unpack it into a list, tuple, set or call instead, e.g. `[*a]`".to_string(),
+            span: self.get_span_on_line(node.start, node.end),
+        });
+    }
+
     // https://docs.python.org/3/reference/expressions.html#conditional-expressions
     fn parse_expression_2(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
         if self.eat(Kind::Lambda) {
             let params_list = self.parse_parameters(true).expect("lambda params");
             self.expect(Kind::Colon)?;
-            let expr = self.parse_expression_2()?;
+            let expr = self.nested(Self::parse_expression_2)?;
 
             return Ok(Expression::Lambda(Box::new(Lambda {
                 node: self.finish_node(node),
@@ -2131,17 +2783,22 @@ impl Parser {
 
     // https://docs.python.org/3/reference/expressions.html#boolean-operations
     fn parse_or_test(&mut self) -> Result<Expression, ParsingError> {
-        let node = self.start_node();
-        let lhs = self.parse_and_test()?;
-        if self.eat(Kind::Or) {
-            let rhs = self.parse_or_test()?;
-            return Ok(Expression::BoolOp(Box::new(BoolOperation {
-                node: self.finish_node(node),
+        let mut operands = vec![(self.start_node(), self.parse_and_test()?)];
+        while self.eat(Kind::Or) {
+            operands.push((self.start_node(), self.parse_and_test()?));
+        }
+        // `a or b or c` is `a or (b or c)`, the operations are built from
+        // the right without recursing for every operand
+        let end = self.prev_token_end;
+        let (_, mut rhs) = operands.pop().expect("or test has an operand");
+        while let Some((node, lhs)) = operands.pop() {
+            rhs = Expression::BoolOp(Box::new(BoolOperation {
+                node: Node::new(node.start, end),
                 op: BooleanOperator::Or,
                 values: vec![lhs, rhs],
-            })));
+            }));
         }
-        Ok(lhs)
+        Ok(rhs)
     }
 
     // https://docs.python.org/3/reference/expressions.html#boolean-operations
@@ -2165,7 +2822,7 @@ impl Parser {
         let node = self.start_node();
         if self.at(Kind::Not) {
             self.bump(Kind::Not);
-            let operand = self.parse_not_test()?;
+            let operand = self.nested(Self::parse_not_test)?;
             return Ok(Expression::UnaryOp(Box::new(UnaryOperation {
                 node: self.finish_node(node),
                 op: UnaryOperator::Not,
@@ -2235,7 +2892,6 @@ impl Parser {
     fn parse_and_expr(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
         let mut shift_expr = self.parse_shift_expr()?;
-
         while self.eat(Kind::BitAnd) {
             let rhs = self.parse_shift_expr()?;
             shift_expr = Expression::BinOp(Box::new(BinOp {
@@ -2293,7 +2949,7 @@ impl Parser {
         if is_unary_op(&self.cur_kind()) {
             let op = map_unary_operator(&self.cur_kind());
             self.bump_any();
-            let operand = self.parse_unary_arithmetric_operation()?;
+            let operand = self.nested(Self::parse_unary_arithmetric_operation)?;
             return Ok(Expression::UnaryOp(Box::new(UnaryOperation {
                 node: self.finish_node(node),
                 op,
@@ -2317,7 +2973,7 @@ impl Parser {
             self.parse_primary(None)
         };
         if self.eat(Kind::Pow) {
-            let exponent = self.parse_unary_arithmetric_operation()?;
+            let exponent = self.nested(Self::parse_unary_arithmetric_operation)?;
             return Ok(Expression::BinOp(Box::new(BinOp {
                 node: self.finish_node(node),
                 op: BinaryOperator::Pow,
@@ -2338,6 +2994,8 @@ impl Parser {
             base
         } else if is_atom(&self.cur_kind()) {
             self.parse_atom()?
+        } else if self.at(Kind::Mul) || self.at(Kind::Pow) {
+            self.parse_misplaced_starred()?
         } else {
             return Err(self.unepxted_token(node, self.cur_kind()).err().unwrap());
         };
@@ -2345,65 +3003,14 @@ impl Parser {
         let mut primary = if self.at(Kind::Dot) {
             // TODO: does not handle cases like a.b[0].c
             self.parse_atribute_ref(node, atom_or_primary)
-        } else if self.at(Kind::LeftBrace) {
+        } else if self.at(Kind::LBracket) {
             // https://docs.python.org/3/reference/expressions.html#slicings
-            self.parse_subscript(node, atom_or_primary)
-        } else if self.eat(Kind::LeftParen) {
+            self.nested(|parser| parser.parse_subscript(node, atom_or_primary))
+        } else if self.eat(Kind::LParen) {
             self.bump(Kind::NewLine);
-            // parse call
             // https://docs.python.org/3/reference/expressions.html#calls
-            let mut positional_args = vec![];
-            let mut keyword_args = vec![];
-            let mut seen_keyword = false;
-
-            loop {
-                if self.at(Kind::RightParen) {
-                    break;
-                }
-                if self.at(Kind::Identifier) && matches!(self.peek_kind(), Ok(Kind::Assign)) {
-                    seen_keyword = true;
-                    let keyword_arg = self.parse_keyword_item()?;
-                    keyword_args.push(keyword_arg);
-                } else if self.at(Kind::Mul) {
-                    let star_arg_node = self.start_node();
-                    self.bump(Kind::Mul);
-                    let star_arg = Expression::Starred(Box::new(Starred {
-                        node: self.finish_node(star_arg_node),
-                        value: Box::new(self.parse_expression_2()?),
-                    }));
-                    positional_args.push(star_arg);
-                } else if self.at(Kind::Pow) {
-                    let kwarg_node = self.start_node();
-                    self.bump(Kind::Pow);
-                    seen_keyword = true;
-                    let kwarg = Keyword {
-                        node: self.finish_node(kwarg_node),
-                        arg: None,
-                        value: Box::new(self.parse_expression_2()?),
-                    };
-                    keyword_args.push(kwarg);
-                } else {
-                    if seen_keyword {
-                        let node_end = self.finish_node(node);
-                        return Err(ParsingError::InvalidSyntax {
-                            msg: Box::from(
-                                "Positional arguments cannot come after keyword arguments.",
-                            ),
-                            input: self.curr_line_string.clone(),
-                            advice: "you can only use arguments in form a=b here.".to_string(),
-                            span: self.get_span_on_line(node_end.start, node_end.end),
-                        });
-                    }
-                    let arg = self.parse_named_expression()?;
-                    positional_args.push(arg);
-                }
-                if !self.eat(Kind::Comma) {
-                    break;
-                }
-            }
-
-            self.bump(Kind::Comma);
-            self.expect(Kind::RightParen)?;
+            let (positional_args, keyword_args) = self.nested(Self::parse_argument_list)?;
+            self.expect(Kind::RParen)?;
 
             Ok(Expression::Call(Box::new(Call {
                 node: self.finish_node(node),
@@ -2417,11 +3024,8 @@ impl Parser {
             Ok(atom_or_primary)
         };
 
-        if matches!(
-            self.cur_kind(),
-            Kind::LeftBrace | Kind::LeftParen | Kind::Dot
-        ) {
-            primary = self.parse_primary(Some(primary?));
+        if matches!(self.cur_kind(), Kind::LBracket | Kind::LParen | Kind::Dot) {
+            primary = self.nested(|parser| parser.parse_primary(Some(primary?)));
         }
 
         primary
@@ -2431,46 +3035,62 @@ impl Parser {
     // returns args, keywords
     fn parse_argument_list(&mut self) -> Result<(Vec<Expression>, Vec<Keyword>), ParsingError> {
         let mut seen_keyword = false;
+        let mut seen_keyword_unpacking = false;
         let mut positional_args = vec![];
-        let mut keyword_args = vec![];
+        let mut keyword_args: Vec<Keyword> = vec![];
         loop {
             let node = self.start_node();
-            if self.at(Kind::RightParen) {
+            if self.at(Kind::RParen) {
                 break;
             }
-            if self.at(Kind::Identifier) && matches!(self.peek_kind(), Ok(Kind::Assign)) {
+            if matches!(
+                self.cur_kind(),
+                Kind::Identifier | Kind::True | Kind::False | Kind::None
+            ) && matches!(self.peek_kind(), Ok(Kind::Assign))
+            {
                 seen_keyword = true;
                 let keyword_arg = self.parse_keyword_item()?;
+                self.check_keyword_argument(&keyword_arg, &keyword_args);
                 keyword_args.push(keyword_arg);
             } else if self.at(Kind::Mul) {
                 let star_arg_node = self.start_node();
                 self.bump(Kind::Mul);
-                let star_arg = Expression::Starred(Box::new(Starred {
-                    node: self.finish_node(star_arg_node),
-                    value: Box::new(self.parse_expression_2()?),
-                }));
-                positional_args.push(star_arg);
+                let value = Box::new(self.parse_expression_2()?);
+                let star_arg_node = self.finish_node(star_arg_node);
+                if seen_keyword_unpacking {
+                    self.errors
+                        .push(ParsingError::UnpackingAfterKeywordUnpacking {
+                            input: self.curr_line_string.clone(),
+                            span: self.get_span_on_line(star_arg_node.start, star_arg_node.end),
+                        });
+                }
+                positional_args.push(Expression::Starred(Box::new(Starred {
+                    node: star_arg_node,
+                    value,
+                })));
             } else if self.at(Kind::Pow) {
                 let kwarg_node = self.start_node();
                 self.bump(Kind::Pow);
                 seen_keyword = true;
+                seen_keyword_unpacking = true;
+                let value = Box::new(self.parse_expression_2()?);
                 let kwarg = Keyword {
                     node: self.finish_node(kwarg_node),
                     arg: None,
-                    value: Box::new(self.parse_expression_2()?),
+                    value,
                 };
                 keyword_args.push(kwarg);
             } else {
+                let arg = self.parse_named_expression()?;
+                let arg_node = self.finish_node(node);
                 if seen_keyword {
-                    let node_end = self.finish_node(node);
-                    return Err(ParsingError::InvalidSyntax {
-                        msg: Box::from("Positional arguments cannot come after keyword arguments."),
-                        input: self.curr_line_string.clone(),
-                        advice: "you can only use arguments in form a=b here.".to_string(),
-                        span: self.get_span_on_line(node_end.start, node_end.end),
-                    });
+                    self.errors
+                        .push(ParsingError::PositionalArgumentAfterKeyword {
+                            unpacking: seen_keyword_unpacking,
+                            input: self.curr_line_string.clone(),
+                            span: self.get_span_on_line(arg_node.start, arg_node.end),
+                        });
                 }
-                let arg = self.parse_named_expression()?;
                 positional_args.push(arg);
             }
             if !self.eat(Kind::Comma) {
@@ -2480,6 +3100,28 @@ impl Parser {
         Ok((positional_args, keyword_args))
     }
 
+    // Keywords cannot be repeated or name a constant, e.g. `f(a=1, a=2)` or
+    // `f(__debug__=1)`
+    fn check_keyword_argument(&mut self, keyword: &Keyword, previous: &[Keyword]) {
+        let Some(arg) = &keyword.arg else {
+            return;
+        };
+        let span = self.get_span_on_line(keyword.node.start, keyword.node.end);
+        if matches!(arg.as_str(), "__debug__" | "True" | "False" | "None") {
+            self.errors.push(ParsingError::CannotAssign {
+                name: arg.clone(),
+                input: self.curr_line_string.clone(),
+                span,
+            });
+        } else if previous.iter().any(|k| k.arg.as_ref() == Some(arg)) {
+            self.errors.push(ParsingError::RepeatedKeywordArgument {
+                name: arg.clone(),
+                input: self.curr_line_string.clone(),
+                span,
+            });
+        }
+    }
+
     fn parse_atribute_ref(
         &mut self,
         node: Node,
@@ -2504,7 +3146,7 @@ impl Parser {
         value: Expression,
     ) -> Result<Expression, ParsingError> {
         let mut expr = Ok(value);
-        while self.eat(Kind::LeftBrace) {
+        while self.eat(Kind::LBracket) {
             let slice = self.parse_slice_list()?;
             expr = Ok(Expression::Subscript(Box::new(Subscript {
                 node: self.finish_node(node),
@@ -2518,25 +3160,23 @@ impl Parser {
     // https://docs.python.org/3/reference/expressions.html#atoms
     fn parse_atom(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
-        if self.at(Kind::Yield) {
-            self.parse_yield_expression()
-        } else if self.at(Kind::LeftBrace) {
+        if self.at(Kind::LBracket) {
             self.nested_expression_list += 1;
-            let list_expr = self.parse_list();
+            let list_expr = self.nested(Self::parse_list);
             self.nested_expression_list -= 1;
-            return list_expr;
-        } else if self.at(Kind::LeftBracket) {
+            list_expr
+        } else if self.at(Kind::LBrace) {
             self.nested_expression_list += 1;
-            let dict_or_set_expr = self.parse_dict_or_set();
+            let dict_or_set_expr = self.nested(Self::parse_dict_or_set);
             self.nested_expression_list -= 1;
-            return dict_or_set_expr;
-        } else if self.at(Kind::LeftParen) {
+            dict_or_set_expr
+        } else if self.at(Kind::LParen) {
             self.nested_expression_list += 1;
-            let tuple_or_named_expr = self.parse_paren_form_or_generator();
+            let tuple_or_named_expr = self.nested(Self::parse_paren_form_or_generator);
             self.nested_expression_list -= 1;
-            return tuple_or_named_expr;
+            tuple_or_named_expr
         } else if self.at(Kind::Identifier) {
-            return self.parse_identifier();
+            self.parse_identifier()
         } else if is_atom(&self.cur_kind()) {
             // value must be cloned to be assigned to the node
             let token_value = self.cur_token().value.clone();
@@ -2552,11 +3192,22 @@ impl Parser {
             if is_string(&token_kind) {
                 loop {
                     if is_string(&self.cur_kind()) {
+                        let str_node = self.start_node();
                         let token_value = self.cur_token().value.clone();
                         let token_kind = self.cur_kind();
                         self.bump_any();
-                        let next_str = self.map_to_atom(node, &token_kind, token_value)?;
-                        expr = concat_string_exprs(expr, next_str)?;
+                        let next_str = self.map_to_atom(str_node, &token_kind, token_value)?;
+                        expr = match concat_string_exprs(expr, next_str) {
+                            Some(expr) => expr,
+                            None => {
+                                return Err(ParsingError::InvalidSyntax {
+                                    msg: Box::from("cannot mix bytes and nonbytes literals"),
+                                    input: self.curr_line_string.clone(),
+                                    advice: "all or none of the literals must be bytes".to_string(),
+                                    span: self.get_span_on_line(node.start, self.prev_token_end),
+                                });
+                            }
+                        };
                     } else if self.eat(Kind::WhiteSpace) {
                         continue;
                     } else if self.at(Kind::Indent)
@@ -2577,9 +3228,9 @@ impl Parser {
                 }
             }
 
-            return Ok(expr);
+            Ok(expr)
         } else {
-            return Err(self.unepxted_token(node, self.cur_kind()).err().unwrap());
+            Err(self.unepxted_token(node, self.cur_kind()).err().unwrap())
         }
     }
 
@@ -2605,15 +3256,23 @@ impl Parser {
                 value: Box::new(value),
             })));
         }
-        if self.eat(Kind::NewLine) || self.at(Kind::Eof) {
-            return Ok(Expression::Yield(Box::new(Yield {
-                node: self.finish_node(yield_node),
-                value: None,
-            })));
-        }
-        let value = match self.parse_expression_list() {
-            Ok(expr) => Some(Box::new(expr)),
-            _ => None,
+        // A bare yield is followed by the end of the statement or of the
+        // parentheses around it
+        let value = if matches!(
+            self.cur_kind(),
+            Kind::NewLine
+                | Kind::Eof
+                | Kind::SemiColon
+                | Kind::Comment
+                | Kind::RParen
+                | Kind::RBracket
+                | Kind::RBrace
+                | Kind::Assign
+                | Kind::Colon
+        ) {
+            None
+        } else {
+            Some(Box::new(self.parse_expression_list()?))
         };
         Ok(Expression::Yield(Box::new(Yield {
             node: self.finish_node(yield_node),
@@ -2625,13 +3284,20 @@ impl Parser {
     fn parse_expression_list(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
         let mut expressions = vec![];
-        expressions.push(self.parse_expression_2()?);
-        while self.eat(Kind::Comma) && !self.at(Kind::Eof) {
-            let expr = self.parse_expression_2()?;
+        expressions.push(self.parse_star_expression()?);
+        let mut seen_comma = false;
+        while self.eat(Kind::Comma) {
+            seen_comma = true;
+            if self.at_expression_list_end() {
+                break;
+            }
+            let expr = self.parse_star_expression()?;
             expressions.push(expr);
         }
-        if expressions.len() == 1 {
-            return Ok(expressions.pop().unwrap());
+        if expressions.len() == 1 && !seen_comma {
+            let expr = expressions.pop().unwrap();
+            self.check_unstarred(&expr);
+            return Ok(expr);
         }
         Ok(Expression::Tuple(Box::new(Tuple {
             node: self.finish_node(node),
@@ -2650,14 +3316,14 @@ impl Parser {
         // it, it's a tuple
         let mut seen_comma = false;
         elements.push(first_elm);
-        while !self.at(Kind::Eof) && !self.at(Kind::RightParen) {
+        while !self.at(Kind::Eof) && !self.at(Kind::RParen) {
             self.expect(Kind::Comma)?;
-            if self.at(Kind::RightParen) {
+            seen_comma = true;
+            if self.at(Kind::RParen) {
                 break;
             }
             let expr = self.parse_starred_item()?;
             elements.push(expr);
-            seen_comma = true;
         }
         if elements.len() == 1 && !seen_comma {
             return Ok(elements.pop().unwrap());
@@ -2673,10 +3339,16 @@ impl Parser {
     fn parse_slice_list(&mut self) -> Result<Expression, ParsingError> {
         let node = self.start_node();
         let mut elements = vec![];
+        let mut seen_starred = false;
+        let mut seen_comma = false;
         // TODO: This EOF check should not be here.
-        while !self.at(Kind::Eof) && !self.at(Kind::RightBrace) {
+        while !self.at(Kind::Eof) && !self.at(Kind::RBracket) {
             if self.at(Kind::Colon) {
                 elements.push(self.parse_proper_slice(None)?);
+            } else if self.at(Kind::Mul) {
+                // Starred slices are tuples, e.g. `tuple[*Ts]` (PEP 646)
+                elements.push(self.parse_unpacked_type()?);
+                seen_starred = true;
             } else {
                 let expr = self.parse_expression_2()?;
                 if self.at(Kind::Colon) {
@@ -2688,14 +3360,26 @@ impl Parser {
             if !self.eat(Kind::Comma) {
                 break;
             }
+            seen_comma = true;
         }
-        self.expect(Kind::RightBrace)?;
-        if elements.len() == 1 {
+        // The brackets are not part of the tuple, `a[1,]` has the slice `1,`
+        let node = self.finish_node(node);
+        self.expect(Kind::RBracket)?;
+        if elements.len() == 1 && !seen_starred && !seen_comma {
             return Ok(elements.pop().unwrap());
         }
-        Ok(Expression::Tuple(Box::new(Tuple {
+        Ok(Expression::Tuple(Box::new(Tuple { node, elements })))
+    }
+
+    // An unpacked TypeVarTuple or tuple type, e.g. `*Ts` or `*tuple[int, ...]`
+    // https://peps.python.org/pep-0646/#grammar-changes
+    fn parse_unpacked_type(&mut self) -> Result<Expression, ParsingError> {
+        let node = self.start_node();
+        self.bump(Kind::Mul);
+        let value = self.parse_or_expr()?;
+        Ok(Expression::Starred(Box::new(Starred {
             node: self.finish_node(node),
-            elements,
+            value: Box::new(value),
         })))
     }
 
@@ -2714,7 +3398,7 @@ impl Parser {
             Some(Box::new(self.parse_expression_2()?))
         };
         let upper = if self.eat(Kind::Colon) {
-            if self.at(Kind::RightBrace) || self.at(Kind::Colon) {
+            if self.at(Kind::RBracket) || self.at(Kind::Colon) {
                 None
             } else {
                 Some(Box::new(self.parse_expression_2()?))
@@ -2723,7 +3407,7 @@ impl Parser {
             None
         };
         let step = if self.eat(Kind::Colon) {
-            if self.at(Kind::RightBrace) {
+            if self.at(Kind::RBracket) {
                 None
             } else {
                 Some(Box::new(self.parse_expression_2()?))
@@ -2753,18 +3437,22 @@ impl Parser {
             Kind::Integer => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
                 value: ConstantValue::Int(value.to_string()),
+                kind: None,
             })),
             Kind::None => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
                 value: ConstantValue::None,
+                kind: None,
             })),
             Kind::True => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
                 value: ConstantValue::Bool(true),
+                kind: None,
             })),
             Kind::False => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
                 value: ConstantValue::Bool(false),
+                kind: None,
             })),
             Kind::ImaginaryInteger => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
@@ -2772,41 +3460,28 @@ impl Parser {
                     real: "0".to_string(),
                     imaginary: value.to_string(),
                 },
+                kind: None,
             })),
-            Kind::Bytes => {
-                let bytes_val = extract_string_inside(
-                    value
-                        .to_string()
-                        .strip_prefix('b')
-                        .expect("bytes literal must start with b")
-                        .to_string(),
-                )
-                .into_bytes();
-                Expression::Constant(Box::new(Constant {
-                    node: self.finish_node(start),
-                    value: ConstantValue::Bytes(bytes_val),
-                }))
-            }
-            Kind::StringLiteral => {
-                let string_val = extract_string_inside(value.to_string());
-                Expression::Constant(Box::new(Constant {
-                    node: self.finish_node(start),
-                    value: ConstantValue::Str(string_val),
-                }))
-            }
-
-            Kind::RawBytes => {
-                // rb or br appear in the beginning of raw bytes
-                let bytes_val =
-                    extract_string_inside(value.to_string().chars().skip(2).collect::<String>())
-                        .into_bytes();
+            Kind::StringLiteral | Kind::Unicode | Kind::Bytes | Kind::RawBytes => {
+                let TokenValue::String { value, kind } = value else {
+                    unreachable!("string tokens have a string value")
+                };
+                let value = if kind.bytes {
+                    ConstantValue::Bytes(value.into_bytes())
+                } else {
+                    ConstantValue::Str(value)
+                };
                 Expression::Constant(Box::new(Constant {
                     node: self.finish_node(start),
-                    value: ConstantValue::Bytes(bytes_val),
+                    value,
+                    kind: Some(kind),
                 }))
             }
-            Kind::FStringStart => {
-                let fstring = self.parse_fstring()?;
+            Kind::FStringStart | Kind::RawFStringStart => {
+                let TokenValue::String { kind, .. } = value else {
+                    unreachable!("string tokens have a string value")
+                };
+                let fstring = self.parse_fstring(kind)?;
                 Expression::JoinedStr(Box::new(JoinedStr {
                     node: self.finish_node(start),
                     values: fstring,
@@ -2815,10 +3490,12 @@ impl Parser {
             Kind::PointFloat => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
                 value: ConstantValue::Float(value.to_string()),
+                kind: None,
             })),
             Kind::ExponentFloat => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
                 value: ConstantValue::Float(value.to_string()),
+                kind: None,
             })),
             Kind::ImaginaryPointFloat => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
@@ -2826,6 +3503,7 @@ impl Parser {
                     real: "0".to_string(),
                     imaginary: value.to_string(),
                 },
+                kind: None,
             })),
             Kind::ImaginaryExponentFloat => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
@@ -2833,10 +3511,12 @@ impl Parser {
                     real: "0".to_string(),
                     imaginary: value.to_string(),
                 },
+                kind: None,
             })),
             Kind::Ellipsis => Expression::Constant(Box::new(Constant {
                 node: self.finish_node(start),
                 value: ConstantValue::Ellipsis,
+                kind: None,
             })),
             _ => {
                 return Err(self.unepxted_token(start, self.cur_kind()).err().unwrap());
@@ -2900,9 +3580,14 @@ impl Parser {
 
     fn parse_keyword_item(&mut self) -> Result<Keyword, ParsingError> {
         let node = self.start_node();
-        let arg = self.cur_token().value.to_string();
-        self.expect(Kind::Identifier);
-        self.expect(Kind::Assign);
+        // Constants are accepted as names to report assignments to them
+        let arg = if self.at(Kind::Identifier) {
+            self.cur_token().value.to_string()
+        } else {
+            self.cur_kind().to_string()
+        };
+        self.bump_any();
+        self.expect(Kind::Assign)?;
         let value = Box::new(self.parse_expression_2()?);
         Ok(Keyword {
             node: self.finish_node(node),
@@ -2924,10 +3609,16 @@ impl Parser {
         let mut kwonlyargs = vec![];
         let mut kw_defaults = vec![];
         let mut defaults = vec![];
+        // Parameters are separated by single commas, `def f(a,)` is
+        // allowed but `def f(,)` is not
+        let mut after_parameter = false;
 
         loop {
+            if after_parameter && !self.at(Kind::Comma) {
+                break;
+            }
             if self.is_def_parameter() {
-                let (param, default) = self.parse_parameter(is_lambda)?;
+                let (param, default) = self.parse_parameter(is_lambda, false)?;
                 if seen_vararg {
                     kwonlyargs.push(param);
                 } else if seen_kwarg {
@@ -2963,7 +3654,12 @@ impl Parser {
                 // after seeing vararg the must_have_default is reset
                 // until we see a default value again
                 must_have_default = false;
-                let (param, default) = self.parse_parameter(is_lambda)?;
+                // A bare `*` only marks the following parameters as keyword-only
+                if !self.at(Kind::Identifier) {
+                    after_parameter = true;
+                    continue;
+                }
+                let (param, default) = self.parse_parameter(is_lambda, true)?;
                 // default is not allowed for vararg
                 if default.is_some() {
                     return Err(ParsingError::InvalidSyntax {
@@ -2976,7 +3672,7 @@ impl Parser {
                 vararg = Some(param);
             } else if self.eat(Kind::Pow) {
                 seen_kwarg = true;
-                let (param, default) = self.parse_parameter(is_lambda)?;
+                let (param, default) = self.parse_parameter(is_lambda, false)?;
                 // default is not allowed for kwarg
                 if default.is_some() {
                     return Err(ParsingError::InvalidSyntax {
@@ -2987,7 +3683,13 @@ impl Parser {
                     });
                 }
                 kwarg = Some(param);
-            } else if self.eat(Kind::Comma) {
+            } else if self.at(Kind::Comma) {
+                if !after_parameter {
+                    let node = self.start_node();
+                    return Err(self.unepxted_token(node, Kind::Comma).err().unwrap());
+                }
+                self.bump(Kind::Comma);
+                after_parameter = false;
                 continue;
             } else if self.eat(Kind::Div) {
                 // copy the current args to posonlyargs
@@ -2996,6 +3698,7 @@ impl Parser {
             } else {
                 break;
             }
+            after_parameter = true;
         }
         // return Parameter
 
@@ -3017,9 +3720,12 @@ impl Parser {
         // || matches!(self.peek_kind(), Ok(Kind::Colon))
     }
 
+    // `star_annotation` allows a starred annotation, which is only valid for
+    // the var-positional parameter, e.g. `*args: *Ts`
     fn parse_parameter(
         &mut self,
         is_lambda: bool,
+        star_annotation: bool,
     ) -> Result<(Arg, Option<Expression>), ParsingError> {
         let node = self.start_node();
         let arg = self.cur_token().value.to_string();
@@ -3027,7 +3733,11 @@ impl Parser {
         // Lambda parameters cannot have annotations
         let annotation = if self.at(Kind::Colon) && !is_lambda {
             self.bump(Kind::Colon);
-            Some(self.parse_expression_2()?)
+            if star_annotation && self.at(Kind::Mul) {
+                Some(self.parse_unpacked_type()?)
+            } else {
+                Some(self.parse_expression_2()?)
+            }
         } else {
             None
         };
@@ -3047,23 +3757,21 @@ impl Parser {
     }
 
     // the FStringStart token is consumed by the caller
-    fn parse_fstring(&mut self) -> Result<Vec<Expression>, ParsingError> {
+    fn parse_fstring(&mut self, kind: StringKind) -> Result<Vec<Expression>, ParsingError> {
         let mut expressions = vec![];
         while self.cur_kind() != Kind::FStringEnd {
             match self.cur_kind() {
                 Kind::FStringMiddle => {
+                    let text_node = self.start_node();
                     let str_val = self.cur_token().value.to_string().clone();
                     self.bump(Kind::FStringMiddle);
                     expressions.push(Expression::Constant(Box::new(Constant {
-                        node: self.start_node(),
+                        node: self.finish_node(text_node),
                         value: ConstantValue::Str(str_val),
+                        kind: Some(kind),
                     })));
                 }
-                Kind::LeftBracket => {
-                    self.bump(Kind::LeftBracket);
-                    expressions.push(self.parse_expression()?);
-                    self.expect(Kind::RightBracket)?;
-                }
+                Kind::LBrace => expressions.push(self.parse_fstring_replacement_field(kind)?),
                 _ => {
                     return Err(self
                         .unepxted_token(self.start_node(), self.cur_kind())
@@ -3076,18 +3784,90 @@ impl Parser {
         Ok(expressions)
     }
 
+    // https://docs.python.org/3/reference/lexical_analysis.html#formatted-string-literals
+    // A field without a conversion and a format spec is the expression
+    // itself, other fields are `FormattedValue`s.
+    fn parse_fstring_replacement_field(
+        &mut self,
+        kind: StringKind,
+    ) -> Result<Expression, ParsingError> {
+        let node = self.start_node();
+        self.expect(Kind::LBrace)?;
+        let value = self.parse_expression()?;
+        let conversion = if self.eat(Kind::Exclamation) {
+            let conversion_node = self.start_node();
+            let conversion = match self.cur_token().value.to_string().as_str() {
+                "s" => 's',
+                "r" => 'r',
+                "a" => 'a',
+                _ => {
+                    let err = ParsingError::InvalidSyntax {
+                        msg: Box::from("f-string: invalid conversion character"),
+                        input: self.curr_line_string.clone(),
+                        advice: "expected 's', 'r', or 'a'".to_string(),
+                        span: self.get_span_on_line(conversion_node.start, self.cur_token().end),
+                    };
+                    return Err(err);
+                }
+            };
+            self.expect(Kind::Identifier)?;
+            Some(conversion as i32)
+        } else {
+            None
+        };
+        let format_spec = if self.at(Kind::Colon) {
+            let spec_node = self.start_node();
+            self.bump(Kind::Colon);
+            let mut values = vec![];
+            while !self.at(Kind::RBrace) {
+                match self.cur_kind() {
+                    Kind::FStringMiddle => {
+                        let text_node = self.start_node();
+                        let text = self.cur_token().value.to_string();
+                        self.bump(Kind::FStringMiddle);
+                        values.push(Expression::Constant(Box::new(Constant {
+                            node: self.finish_node(text_node),
+                            value: ConstantValue::Str(text),
+                            kind: Some(kind),
+                        })));
+                    }
+                    Kind::LBrace => values.push(self.parse_fstring_replacement_field(kind)?),
+                    kind => {
+                        return Err(self.unepxted_token(self.start_node(), kind).err().unwrap());
+                    }
+                }
+            }
+            Some(Box::new(Expression::JoinedStr(Box::new(JoinedStr {
+                node: self.finish_node(spec_node),
+                values,
+            }))))
+        } else {
+            None
+        };
+        self.expect(Kind::RBrace)?;
+        if conversion.is_none() && format_spec.is_none() {
+            return Ok(value);
+        }
+        Ok(Expression::FormattedValue(Box::new(FormattedValue {
+            node: self.finish_node(node),
+            value: Box::new(value),
+            conversion,
+            format_spec,
+        })))
+    }
+
     // This function is just here to make it easier to refactor the spans.
     // I don't know how the spans and line number should be handled here
-    fn get_span_on_line(&self, start: usize, end: usize) -> (usize, usize) {
-        (start, end)
+    fn get_span_on_line(&self, start: TextSize, end: TextSize) -> (usize, usize) {
+        (start as usize, end as usize)
     }
 
     // https://docs.python.org/3/reference/compound_stmts.html#type-parameter-lists
     fn parse_type_parameters(&mut self) -> Result<Vec<TypeParam>, ParsingError> {
         let node = self.start_node();
-        self.expect(Kind::LeftBrace)?;
+        self.expect(Kind::LBracket)?;
         let mut type_params = vec![];
-        while !self.eat(Kind::RightBrace) {
+        while !self.eat(Kind::RBracket) {
             match self.cur_kind() {
                 Kind::Identifier => {
                     let node = self.start_node();
@@ -3129,12 +3909,12 @@ impl Parser {
                 _ => {
                     return Err(self.unexpected_token_new(
                         node,
-                        vec![Kind::Identifier, Kind::Pow, Kind::Mul, Kind::RightBracket],
+                        vec![Kind::Identifier, Kind::Pow, Kind::Mul, Kind::RBrace],
                         "",
                     ));
                 }
             }
-            if !self.at(Kind::RightBrace) {
+            if !self.at(Kind::RBracket) {
                 self.expect(Kind::Comma)?;
             }
         }
@@ -3150,13 +3930,11 @@ impl Parser {
 
     fn parse_type_alias_statement(&mut self) -> std::result::Result<Statement, ParsingError> {
         let node = self.start_node();
-        self.expect(Kind::Identifier)?;
+        self.expect_soft_keyword(SoftKeyword::Type)?;
         let name = self.cur_token().value.to_string();
         self.expect(Kind::Identifier)?;
-        let type_params = if self.eat(Kind::LeftBrace) {
-            let type_params = self.parse_type_parameters()?;
-            self.expect(Kind::RightBrace)?;
-            type_params
+        let type_params = if self.at(Kind::LBracket) {
+            self.parse_type_parameters()?
         } else {
             vec![]
         };
@@ -3171,6 +3949,15 @@ impl Parser {
     }
 }
 
+/// Whether the last line of the source that ends with a newline is blank
+fn ends_with_blank_line(source: &str) -> bool {
+    source
+        .split_inclusive('\n')
+        .rev()
+        .find(|line| line.ends_with('\n'))
+        .is_some_and(|line| line.trim().is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -3388,9 +4175,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_return_statement() {
+        for test_case in &["return", "return *a, *b", "return (*a, *b)", "return a,"] {
+            let mut parser = Parser::new(test_case.to_string(), String::from(""));
+            let program = parser.parse();
+
+            insta::with_settings!({
+                    description => test_case.to_string(), // the template source code
+                    omit_expression => true // do not include the default expression
+                }, {
+                    assert_debug_snapshot!(program);
+            });
+        }
+    }
+
     #[test]
     fn test_yield_expression() {
-        for test_case in &["yield", "yield a", "yield from a"] {
+        for test_case in &[
+            "yield",
+            "yield a",
+            "yield from a",
+            "x = yield",
+            "x = (yield a) + 1",
+            "await (yield from a)",
+            "f((yield), b)",
+            "yield *a, b",
+        ] {
             let mut parser = Parser::new(test_case.to_string(), String::from(""));
             let program = parser.parse();
 
@@ -3406,9 +4217,197 @@ mod tests {
     #[test]
     fn test_starred() {
         {
-            let test_case = &"(*a)";
+            let test_case = &"(*a,)";
+            let mut parser = Parser::new(test_case.to_string(), String::from(""));
+            let program = parser.parse();
+
+            insta::with_settings!({
+                    description => test_case.to_string(), // the template source code
+                    omit_expression => true // do not include the default expression
+                }, {
+                    assert_debug_snapshot!(program);
+            });
+        }
+    }
+
+    #[test]
+    fn test_starred_validation() {
+        let starred = "can't use starred expression here";
+        let double_starred = "can't use double starred expression here";
+        let test_cases = [
+            ("a, *b = c", vec![]),
+            ("x = *a, *b", vec![]),
+            ("x = (*a,)", vec![]),
+            ("x = [*a, *f()]", vec![]),
+            ("x = {*a, *b}", vec![]),
+            ("x = {**a, 'b': 1}", vec![]),
+            ("f(*a, **b)", vec![]),
+            ("for *a, b in c: pass", vec![]),
+            ("x[*a]", vec![]),
+            ("(*a)", vec![starred]),
+            ("*a", vec![starred]),
+            ("x = *a", vec![starred]),
+            ("x = -*a", vec![starred]),
+            ("x = [*a for a in b]", vec![starred]),
+            ("x = {*a for a in b}", vec![starred]),
+            ("x = (*a for a in b)", vec![starred]),
+            ("x = {*a: 1}", vec![starred]),
+            ("x = [**a]", vec![double_starred]),
+            ("x = **a", vec![double_starred]),
+        ];
+        for (source, expected) in test_cases {
+            let mut parser = Parser::new(format!("{}\n", source), String::from(""));
+            parser.parse();
+            let messages = parser
+                .errors
+                .iter()
+                .map(|error| match error {
+                    ParsingError::InvalidSyntax { msg, .. } => msg.to_string(),
+                    error => format!("{:?}", error),
+                })
+                .collect::<Vec<String>>();
+            assert_eq!(messages, expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_argument_validation() {
+        let test_cases = [
+            ("f(a, *b, c=1, *d, **e, f=2)", vec![]),
+            ("f(a=1, a=2)", vec!["keyword argument repeated: a"]),
+            ("f(__debug__=1)", vec!["cannot assign to __debug__"]),
+            ("f(True=1)", vec!["cannot assign to True"]),
+            (
+                "f(a=1, b)",
+                vec!["positional argument follows keyword argument"],
+            ),
+            (
+                "f(**a, b)",
+                vec!["positional argument follows keyword argument unpacking"],
+            ),
+            (
+                "f(**a, *b)",
+                vec!["iterable argument unpacking follows keyword argument unpacking"],
+            ),
+            (
+                "class A(B, metaclass=M, metaclass=N): pass",
+                vec!["keyword argument repeated: metaclass"],
+            ),
+        ];
+        for (source, expected) in test_cases {
+            let mut parser = Parser::new(format!("{}\n", source), String::from(""));
+            parser.parse();
+            let messages = parser
+                .errors
+                .iter()
+                .map(ParsingError::message)
+                .collect::<Vec<String>>();
+            assert_eq!(messages, expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_debug_assignment() {
+        let test_cases = [
+            "__debug__ = 1",
+            "a = __debug__ = 1",
+            "a, *__debug__ = b",
+            "[a, (b, __debug__)] = c",
+            "__debug__ += 1",
+            "__debug__: bool = True",
+            "for __debug__ in a: pass",
+            "with a as __debug__: pass",
+            "[a for __debug__ in b]",
+            "(__debug__ := 1)",
+        ];
+        for source in test_cases {
+            let mut parser = Parser::new(format!("{}\n", source), String::from(""));
+            parser.parse();
+            assert!(
+                matches!(
+                    parser.errors.as_slice(),
+                    [ParsingError::CannotAssign { name, .. }] if name == "__debug__"
+                ),
+                "{}: {:?}",
+                source,
+                parser.errors
+            );
+        }
+
+        let mut parser = Parser::new("a = __debug__\n".to_string(), String::from(""));
+        parser.parse();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+    }
+
+    #[test]
+    fn test_aug_assign_validation() {
+        let test_cases = [
+            ("a += 1", vec![]),
+            ("(a) -= 1", vec![]),
+            ("a.b *= 1", vec![]),
+            ("a[1] @= b", vec![]),
+            (
+                "a, b += 1",
+                vec!["'tuple' is an illegal expression for augmented assignment"],
+            ),
+            (
+                "[a] += 1",
+                vec!["'list' is an illegal expression for augmented assignment"],
+            ),
+            (
+                "f() += 1",
+                vec!["'function call' is an illegal expression for augmented assignment"],
+            ),
+            ("*a += 1", vec!["can't use starred expression here"]),
+            (
+                "a += b += 1",
+                vec!["augmented assignment cannot be chained"],
+            ),
+            ("a += b = 1", vec!["augmented assignment cannot be chained"]),
+        ];
+        for (source, expected) in test_cases {
+            let mut parser = Parser::new(format!("{}\n", source), String::from(""));
+            parser.parse();
+            let messages = parser
+                .errors
+                .iter()
+                .map(|error| match error {
+                    ParsingError::InvalidSyntax { msg, .. } => msg.to_string(),
+                    error => format!("{:?}", error),
+                })
+                .collect::<Vec<String>>();
+            assert_eq!(messages, expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_unpacked_type() {
+        for test_case in &[
+            "x: tuple[int, *Ts]",
+            "x: tuple[*tuple[int, ...]]",
+            "def f(*args: *Ts): pass",
+        ] {
+            let mut parser = Parser::new(test_case.to_string(), String::from(""));
+            let program = parser.parse();
+
+            insta::with_settings!({
+                    description => test_case.to_string(), // the template source code
+                    omit_expression => true // do not include the default expression
+                }, {
+                    assert_debug_snapshot!(program);
+            });
+        }
+    }
+
+    #[test]
+    fn test_decorator_followed_by_comment() {
+        for test_case in &[
+            "@overload\n# comment\n\ndef f(): pass",
+            "@a\n# comment\n@b\nclass A: pass",
+        ] {
             let mut parser = Parser::new(test_case.to_string(), String::from(""));
             let program = parser.parse();
+            assert!(parser.errors.is_empty());
 
             insta::with_settings!({
                     description => test_case.to_string(), // the template source code
@@ -3419,6 +4418,366 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reparse() {
+        let source = "import os\n\n\ndef f(a):\n    return a\n\n\nx = f(1)\ny = 2\n\nclass A:\n    pass\n\nz = [\n    1,\n]\n";
+        let edit_at = |text: &str, offset: usize, removed: &str, inserted: &str| {
+            let start = text_size(source.find(text).unwrap() + offset);
+            Edit::new(start, start + text_size(removed.len()), inserted)
+        };
+        for edit in [
+            // Inside a function body
+            edit_at("return a", 7, "a", "a + 1"),
+            // A new line in the body of the previous statement
+            edit_at("\n\n\nx =", 1, "", "    b = a\n"),
+            // A statement between reused statements
+            edit_at("y = 2", 4, "2", "3"),
+            // Inside a class body
+            edit_at("    pass", 4, "pass", "b = 1"),
+            // A new statement between two statements
+            edit_at("class A", 0, "", "w = 0\n"),
+            // Removing a statement
+            edit_at("class A", 0, "class A:\n    pass\n", ""),
+            // The first and the last statement
+            edit_at("import os", 7, "os", "sys"),
+            edit_at("    1,", 4, "1", "1, 2"),
+            // Appending to the end of the file
+            Edit::new(
+                text_size(source.len()),
+                text_size(source.len()),
+                "print(z)\n",
+            ),
+        ] {
+            let old = Parser::new(source.to_string(), String::from("")).parse();
+            let new_source = edit.apply(source);
+            let mut parser = Parser::new(new_source.clone(), String::from(""));
+            let reparsed = parser.reparse(&old, &edit);
+            let parsed = Parser::new(new_source, String::from("")).parse();
+            assert!(parser.errors.is_empty());
+            assert_eq!(format!("{:?}", reparsed), format!("{:?}", parsed));
+        }
+    }
+
+    #[test]
+    fn test_reparse_decorated() {
+        let source = "@decorator\ndef f(a):\n    return a\n\n\n@dataclass(frozen=True)\n@other\nclass A:\n    x: int\n\n\ny = 2\n";
+        let edit_at = |text: &str, offset: usize, removed: &str, inserted: &str| {
+            let start = text_size(source.find(text).unwrap() + offset);
+            Edit::new(start, start + text_size(removed.len()), inserted)
+        };
+        for edit in [
+            // After a decorated function
+            edit_at("\n\n\n@dataclass", 1, "", "z = 1\n"),
+            // Inside the body of a decorated function
+            edit_at("return a", 7, "a", "a + 1"),
+            // After a decorated class
+            edit_at("y = 2", 4, "2", "3"),
+            // Inside the body of a decorated class
+            edit_at("    x: int", 7, "int", "str"),
+            // Between the decorators of a class
+            edit_at("@other", 1, "other", "another"),
+        ] {
+            let old = Parser::new(source.to_string(), String::from("")).parse();
+            let new_source = edit.apply(source);
+            let mut parser = Parser::new(new_source.clone(), String::from(""));
+            let reparsed = parser.reparse(&old, &edit);
+            let parsed = Parser::new(new_source, String::from("")).parse();
+            assert!(parser.errors.is_empty());
+            assert_eq!(format!("{:?}", reparsed), format!("{:?}", parsed));
+        }
+    }
+
+    #[test]
+    fn test_reparse_syntax_error() {
+        let source = "a = 1\nb = 2\nc = 3\nd = 4\n";
+        let old = Parser::new(source.to_string(), String::from("")).parse();
+        let edit = Edit::new(10, 11, "(");
+        let mut parser = Parser::new(edit.apply(source), String::from(""));
+        let reparsed = parser.reparse(&old, &edit);
+        assert_eq!(parser.errors.len(), 1);
+        // The statements after the invalid statement are kept
+        let names = reparsed
+            .body
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::AssignStatement(a) => match &a.targets[0] {
+                    Expression::Name(n) => Some(n.id.as_str()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "c", "d"]);
+    }
+
+    #[test]
+    fn test_soft_keywords() {
+        let test_cases = [
+            ("match (a, b):\n    case (1, 2):\n        pass\n", "match"),
+            ("match(a, b):\n    case _:\n        pass\n", "match"),
+            (
+                "match (a,\n       b):  # comment\n    case _:\n        pass\n",
+                "match",
+            ),
+            ("match(a, b)\n", "expression"),
+            ("match (a, b)\n", "expression"),
+            ("match = 1\n", "assignment"),
+            ("match.attribute = 1\n", "assignment"),
+            ("match: int = 1\n", "annotated assignment"),
+            ("case = 1\n", "assignment"),
+            ("type = int\n", "assignment"),
+            ("type(a)\n", "expression"),
+            ("type X = int\n", "type alias"),
+            ("type X[T] = list[T]\n", "type alias"),
+            ("_ = 1\n", "assignment"),
+        ];
+        for (source, expected) in test_cases {
+            let mut parser = Parser::new(source.to_string(), String::from(""));
+            let module = parser.parse();
+            assert!(parser.errors.is_empty(), "{}: {:?}", source, parser.errors);
+            let statement = match &module.body[0] {
+                Statement::Match(_) => "match",
+                Statement::ExpressionStatement(_) => "expression",
+                Statement::AssignStatement(_) => "assignment",
+                Statement::AnnAssignStatement(_) => "annotated assignment",
+                Statement::TypeAlias(_) => "type alias",
+                _ => "other",
+            };
+            assert_eq!(statement, expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_shebang_and_encoding_cookie() {
+        for source in [
+            "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\nimport sys\n",
+            "#!/usr/bin/env python3\n\n# coding: latin-1\ndef f():\n    return 1\n",
+            "\u{feff}#!/usr/bin/env python\r\n# coding=utf-8\r\nif x:\r\n    pass\r\n",
+            "  #!/usr/bin/env python\n  # coding: utf-8\nx = 1\n",
+            "#!/usr/bin/env python",
+        ] {
+            let mut parser = Parser::new(source.to_string(), String::from(""));
+            let module = parser.parse();
+            assert!(
+                parser.errors.is_empty(),
+                "{:?}: {:?}",
+                source,
+                parser.errors
+            );
+            let expected = usize::from(source.lines().any(|line| !line.trim().starts_with('#')));
+            assert_eq!(module.body.len(), expected, "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn test_interactive_mode() {
+        let parse = |source: &str| {
+            let mut parser = Parser::new(source.to_string(), String::from(""));
+            parser.set_mode(Mode::Interactive);
+            parser.parse();
+            parser.errors
+        };
+        for source in [
+            "x = 1",
+            "x = 1\n",
+            "print(x)  # comment",
+            "",
+            "if x:\n    y = 1\n\n",
+            "def f():\n    return 1\n    \n",
+            "x = (1,\n     2)",
+        ] {
+            let errors = parse(source);
+            assert!(errors.is_empty(), "{:?}: {:?}", source, errors);
+        }
+        for source in [
+            "foo(1,",
+            "x = [1,\n 2",
+            "def f(a,  # comment\n",
+            "if x:",
+            "if x:\n",
+            "if x:\n    y = 1\n",
+            "if x:\n    y = (1,\n",
+            "class A:\n    def f(self):\n",
+            "for x in y: pass",
+            "@decorator\n",
+            "x = \"\"\"docs\n",
+        ] {
+            let errors = parse(source);
+            assert!(
+                matches!(errors.as_slice(), [ParsingError::IncompleteInput { .. }]),
+                "{:?}: {:?}",
+                source,
+                errors
+            );
+        }
+        for source in [
+            "x = ",
+            "x = )",
+            "if x",
+            "x = 'abc",
+            "x = 1\ny = 2",
+            "x = 1\ny = (2,",
+        ] {
+            let errors = parse(source);
+            assert!(
+                !errors.is_empty()
+                    && !errors
+                        .iter()
+                        .any(|err| matches!(err, ParsingError::IncompleteInput { .. })),
+                "{:?}: {:?}",
+                source,
+                errors
+            );
+        }
+
+        // Modules don't need a blank line after a block
+        let mut parser = Parser::new("if x:\n    y = 1".to_string(), String::from(""));
+        parser.parse();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+    }
+
+    #[test]
+    fn test_parse_eval() {
+        for (source, expected) in [
+            ("a + 1", "a + 1"),
+            ("List[int]", "List[int]"),
+            ("  f(x)\n\n", "f(x)"),
+            ("# comment\n(a,\n b)\n", "(a,\n b)"),
+            ("1, *rest", "1, *rest"),
+            ("lambda x: x.name == 'a'", "lambda x: x.name == 'a'"),
+        ] {
+            let expr =
+                Parser::parse_eval(source).unwrap_or_else(|err| panic!("{:?}: {:?}", source, err));
+            let node = expr.get_node();
+            assert_eq!(&source[node.range()], expected);
+        }
+        for source in [
+            "",
+            "x = 1",
+            "import os",
+            "a\nb",
+            "a b",
+            "pass",
+            "*a",
+            "f(**)",
+        ] {
+            assert!(Parser::parse_eval(source).is_err(), "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn test_string_kinds() {
+        // The prefix, whether the string is triple quoted, and the value
+        let constant = |source: &str| match Parser::parse_eval(source).unwrap() {
+            Expression::Constant(constant) => {
+                let kind = constant.kind.unwrap();
+                (kind.prefix(), kind.triple_quoted, constant.value)
+            }
+            expr => panic!("{:?} is not a constant: {:?}", source, expr),
+        };
+        assert_eq!(
+            constant("'a'"),
+            (String::new(), false, ConstantValue::Str("a".into()))
+        );
+        assert_eq!(
+            constant(r"R'\d'"),
+            ("r".into(), false, ConstantValue::Str(r"\d".into()))
+        );
+        assert_eq!(
+            constant("U\"\"\"a\"\"\""),
+            ("u".into(), true, ConstantValue::Str("a".into()))
+        );
+        assert_eq!(
+            constant("Br'a'"),
+            ("rb".into(), false, ConstantValue::Bytes(b"a".to_vec()))
+        );
+        // Concatenated strings have the kind of the first one
+        assert_eq!(
+            constant("'''a''' r'b'"),
+            (String::new(), true, ConstantValue::Str("ab".into()))
+        );
+
+        let Expression::JoinedStr(fstring) = Parser::parse_eval("rf'a{b}'").unwrap() else {
+            panic!("rf'a{{b}}' is not an f-string");
+        };
+        let Expression::Constant(part) = &fstring.values[0] else {
+            panic!("the f-string does not start with a constant");
+        };
+        assert_eq!(part.kind.map(|kind| kind.prefix()), Some("rf".into()));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        // Frames of unoptimized builds are much larger than the frames of
+        // release builds, the default test thread stack is too small for
+        // the default limit
+        std::thread::Builder::new()
+            .stack_size(16 << 20)
+            .spawn(check_nesting_limit)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    fn check_nesting_limit() {
+        let nested = |open: &str, close: &str, depth: usize| {
+            format!("{}a{}\n", open.repeat(depth), close.repeat(depth))
+        };
+        let too_deep = [
+            nested("(", ")", 10_000),
+            nested("[", "]", 10_000),
+            nested("f(", ")", 10_000),
+            nested("a[", "]", 10_000),
+            format!("x = {}1\n", "-".repeat(10_000)),
+            format!("x = {}a\n", "not ".repeat(10_000)),
+            format!("x = {}a\n", "lambda: ".repeat(10_000)),
+        ];
+        for source in too_deep {
+            let mut parser = Parser::new(source.clone(), String::from(""));
+            parser.parse();
+            assert!(
+                matches!(
+                    parser.errors.as_slice(),
+                    [ParsingError::TooDeeplyNested { .. }]
+                ),
+                "{}: {:?}",
+                &source[..10],
+                parser.errors
+            );
+        }
+
+        let source = nested("(", ")", 50);
+        let mut parser = Parser::new(source.clone(), String::from(""));
+        parser.parse();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+        parser = Parser::new(source, String::from(""));
+        parser.set_max_nesting_depth(10);
+        parser.parse();
+        assert!(matches!(
+            parser.errors.as_slice(),
+            [ParsingError::TooDeeplyNested { .. }]
+        ));
+
+        // Flat chains of operators are not nested, CPython accepts them
+        for source in [
+            format!("x = {}\"a\"\n", "\"a\" + ".repeat(1_000)),
+            format!("x = {}1\n", "1 + ".repeat(1_000)),
+            format!("x = {}a\n", "a or ".repeat(1_000)),
+            format!("x = {}a\n", "a & ".repeat(1_000)),
+            format!("x = {}a\n", "a | ".repeat(1_000)),
+            format!("x = a{}\n", ".b".repeat(1_000)),
+        ] {
+            let mut parser = Parser::new(source.clone(), String::from(""));
+            parser.parse();
+            assert!(
+                parser.errors.is_empty(),
+                "{}: {:?}",
+                &source[..10],
+                parser.errors
+            );
+        }
+    }
+
     #[test]
     fn test_await_expression() {
         {
@@ -3486,6 +4845,7 @@ mod tests {
             "lambda a, *b, c, **d: a",
             "lambda a=1 : a",
             "lambda a=1 : a,",
+            "lambda a, *, b=1: a",
         ] {
             let mut parser = Parser::new(test_case.to_string(), String::from(""));
             let program = parser.parse();
@@ -3534,6 +4894,8 @@ mod tests {
             "f'a' 'b' 'c'",
             "'d' f'a' 'b'",
             "f'a_{1}' 'b' ",
+            "f'{x}' 'tail'",
+            "'head' f'{x}' 'tail' f'{y}'",
         ] {
             let mut parser = Parser::new(test_case.to_string(), String::from(""));
             let program = parser.parse();
@@ -3547,6 +4909,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bytes_and_str_concatenation() {
+        for source in ["b'a' 'b'", "'a' b'b'", "f'{x}' b'b'", "b'a' b'b' 'c'"] {
+            let err = Parser::parse_eval(source).unwrap_err();
+            assert!(
+                matches!(&err, ParsingError::InvalidSyntax { msg, .. } if msg.contains("bytes")),
+                "{:?}: {:?}",
+                source,
+                err
+            );
+            assert_eq!(err.span(), (0, source.len()), "{:?}", source);
+        }
+    }
+
     #[test]
     fn test_fstring() {
         for test_case in &[
@@ -3554,6 +4930,10 @@ mod tests {
             "f'hello_{a}'",
             "f'hello_{a} {b}'",
             "f'hello_{a} {b} {c}'",
+            "f'{a!r}'",
+            "f'{a:>10}'",
+            "f'{a!s:{width}.2f}'",
+            "f'{a[1:2]}'",
             // unsupported
             // "f'hello_{f'''{a}'''}'",
         ] {
@@ -3622,6 +5002,15 @@ elif a is c:
                 pass
 else:
                 pass
+",
+            "if a:
+    pass
+elif b:
+    pass
+elif c:
+    pass
+else:
+    pass
 ",
         ] {
             let mut parser = Parser::new(test_case.to_string(), String::from(""));
@@ -3707,6 +5096,38 @@ except *Exception as e:
         }
     }
 
+    #[test]
+    fn test_except_star_validation() {
+        let test_cases = [
+            ("try:\n    pass\nexcept* ValueError:\n    pass\n", vec![]),
+            (
+                "try:\n    pass\nexcept* ValueError:\n    pass\nexcept TypeError:\n    pass\n",
+                vec!["cannot have both 'except' and 'except*' on the same 'try'"],
+            ),
+            (
+                "try:\n    pass\nexcept ValueError:\n    pass\nexcept* TypeError:\n    pass\n",
+                vec!["cannot have both 'except' and 'except*' on the same 'try'"],
+            ),
+            (
+                "try:\n    pass\nexcept*:\n    pass\n",
+                vec!["expected one or more exception types"],
+            ),
+        ];
+        for (source, expected) in test_cases {
+            let mut parser = Parser::new(source.to_string(), String::from(""));
+            parser.parse();
+            let messages = parser
+                .errors
+                .iter()
+                .map(|error| match error {
+                    ParsingError::InvalidSyntax { msg, .. } => msg.to_string(),
+                    error => format!("{:?}", error),
+                })
+                .collect::<Vec<String>>();
+            assert_eq!(messages, expected, "{}", source);
+        }
+    }
+
     #[test]
     fn test_ellipsis_statement() {
         for test_case in &[
@@ -3814,4 +5235,61 @@ except *Exception as e:
             }
         });
     }
+
+    // Parses the files of the conformance suite, which are vendored from
+    // CPython's Lib/test, and fails with the constructs that are not
+    // supported. Files that are expected to fail are listed in
+    // expected_failures.txt
+    #[test]
+    fn test_conformance() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/conformance");
+        let expected_failures = fs::read_to_string(dir.join("expected_failures.txt")).unwrap();
+        let expected_failures = expected_failures
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_whitespace().next())
+            .collect::<Vec<&str>>();
+        let mut paths = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "py"))
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        let mut unsupported = vec![];
+        let mut fixed = vec![];
+        for path in paths {
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            let source = fs::read_to_string(&path).unwrap();
+            let mut parser = Parser::new(source.clone(), name.clone());
+            parser.parse();
+            let expected_to_fail = expected_failures.contains(&name.as_str());
+            match parser.errors.first() {
+                Some(err) if !expected_to_fail => {
+                    let offset = err.span().0.min(source.len());
+                    let line = source[..offset].matches('\n').count();
+                    let code = source.lines().nth(line).unwrap_or_default().trim();
+                    unsupported.push(format!(
+                        "{}:{}: {} in `{}`",
+                        name,
+                        line + 1,
+                        err.message(),
+                        code
+                    ));
+                }
+                None if expected_to_fail => fixed.push(name),
+                _ => {}
+            }
+        }
+        assert!(
+            unsupported.is_empty(),
+            "unsupported constructs:\n{}",
+            unsupported.join("\n")
+        );
+        assert!(
+            fixed.is_empty(),
+            "files that parse now, remove them from expected_failures.txt: {}",
+            fixed.join(", ")
+        );
+    }
 }
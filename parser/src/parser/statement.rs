@@ -1,8 +1,12 @@
-use crate::token::{Kind, Token};
+use crate::{
+    parser::ast::{Expression, Statement},
+    token::{Kind, Token},
+};
 
 // This is a duplication of logic, not sure if it's worth it
+// Match statements start with a soft keyword, the parser checks them
 pub fn is_at_compound_statement(token: &Token) -> bool {
-    let kind_is_statement = match token.kind {
+    match token.kind {
         Kind::If
         | Kind::While
         | Kind::For
@@ -14,14 +18,36 @@ pub fn is_at_compound_statement(token: &Token) -> bool {
         | Kind::MatrixMul => true,
         | Kind::Async => true,
         _ => false,
-    };
-    if kind_is_statement {
-        return true;
     }
+}
 
-    if Kind::Identifier == token.kind && token.value.to_string() == "match" {
-        return true;
-    }
+/// Whether the statement has a block, which in interactive input ends only
+/// at a blank line
+pub fn is_compound_statement(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::IfStatement(_)
+            | Statement::WhileStatement(_)
+            | Statement::ForStatement(_)
+            | Statement::AsyncForStatement(_)
+            | Statement::WithStatement(_)
+            | Statement::AsyncWithStatement(_)
+            | Statement::TryStatement(_)
+            | Statement::TryStarStatement(_)
+            | Statement::FunctionDef(_)
+            | Statement::AsyncFunctionDef(_)
+            | Statement::ClassDef(_)
+            | Statement::Match(_)
+    )
+}
 
-    false
+/// The decorators of a function or class definition, the node of the
+/// definition starts after them at `def` or `class`
+pub fn decorators(statement: &Statement) -> &[Expression] {
+    match statement {
+        Statement::FunctionDef(f) => &f.decorator_list,
+        Statement::AsyncFunctionDef(f) => &f.decorator_list,
+        Statement::ClassDef(c) => &c.decorator_list,
+        _ => &[],
+    }
 }
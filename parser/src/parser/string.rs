@@ -1,152 +1,79 @@
-use miette::Result;
-
-use super::ast::Node;
 use crate::{
-    error::ParsingError,
-    parser::ast::{Expression, JoinedStr},
+    parser::ast::{Constant, ConstantValue, Expression, GetNode, JoinedStr, Node},
     token::Kind,
 };
-pub fn extract_string_inside(val: String) -> String {
-    let delimiters = vec!["\"\"\"", "\"", "'''", "'"];
-    let mut result = String::new();
-    let is_raw = val.starts_with('r') || val.starts_with('R');
-    // drop first char if raw and put in val
-    let val = if is_raw {
-        val.chars().skip(1).collect::<String>()
-    } else {
-        val
-    };
-
-    for delimiter in delimiters {
-        // TODO: The string value data structure should be changed so we can be sure
-        // that the string is enclosed with the delimiter and not check for it here
-        if let Some(val) = val.strip_prefix(delimiter) {
-            let message = format!("String must be enclosed with {}", delimiter);
-            result = val.strip_suffix(delimiter).expect(&message).to_string();
-            break;
-        }
-    }
-    // add r back if raw ternary
-    if is_raw {
-        result = format!("r\"{}\"", result);
-    }
-
-    result
-}
 
 pub fn is_string(kind: &Kind) -> bool {
     matches!(
         kind,
-        Kind::StringLiteral | Kind::RawBytes | Kind::Bytes | Kind::FStringStart
+        Kind::StringLiteral
+            | Kind::Unicode
+            | Kind::RawBytes
+            | Kind::Bytes
+            | Kind::FStringStart
+            | Kind::RawFStringStart
     )
 }
 
-pub fn concat_string_exprs(lhs: Expression, rhs: Expression) -> Result<Expression, ParsingError> {
-    use crate::parser::ast::{Constant, ConstantValue};
+/// Concatenates adjacent string literals, e.g. `"a" f"{b}"`. The result
+/// covers both literals and is an f-string if one of them is. Returns `None`
+/// if bytes are concatenated with strings, which is a syntax error.
+/// https://docs.python.org/3/reference/lexical_analysis.html#string-literal-concatenation
+pub fn concat_string_exprs(lhs: Expression, rhs: Expression) -> Option<Expression> {
+    let node = Node::new(lhs.get_node().start, rhs.get_node().end);
     match (lhs, rhs) {
         (Expression::Constant(lhs), Expression::Constant(rhs)) => {
-            let node = Node {
-                start: lhs.node.start,
-                end: rhs.node.end,
-            };
-            let concatnated_string = match (lhs.value, rhs.value) {
-                (ConstantValue::Str(lhs_val), ConstantValue::Str(rhs_val)) => {
-                    Expression::Constant(Box::new(Constant {
-                        node,
-                        value: ConstantValue::Str(lhs_val + &rhs_val),
-                    }))
+            let value = match (lhs.value, rhs.value) {
+                (ConstantValue::Str(lhs), ConstantValue::Str(rhs)) => {
+                    ConstantValue::Str(lhs + &rhs)
                 }
                 (ConstantValue::Bytes(mut lhs), ConstantValue::Bytes(rhs)) => {
-                    lhs.append(&mut rhs.clone());
-                    Expression::Constant(Box::new(Constant {
-                        node,
-                        value: ConstantValue::Bytes(lhs),
-                    }))
-                }
-                (ConstantValue::Bytes(_lhs), _) => {
-                    return Err(ParsingError::InvalidSyntax {
-                        msg: "Cannot concat bytes and string".into(),
-                        input: "test".into(),
-                        advice: "test".into(),
-                        span: (0, 0),
-                    });
+                    lhs.extend(rhs);
+                    ConstantValue::Bytes(lhs)
                 }
-                (_, ConstantValue::Bytes(_rhs)) => {
-                    return Err(ParsingError::InvalidSyntax {
-                        msg: "Can only concat bytes with other bytes".into(),
-                        input: "test".into(),
-                        advice: "test".into(),
-                        span: (0, 0),
-                    });
-                }
-                _ => panic!("Cannot concat string"),
+                _ => return None,
             };
-            Ok(concatnated_string)
-        }
-        (Expression::JoinedStr(fstring_lhs), Expression::JoinedStr(fstring_rhs)) => {
-            let mut values = fstring_lhs.values;
-            values.extend(fstring_rhs.values);
-            Ok(Expression::JoinedStr(Box::new(JoinedStr {
-                node: Node {
-                    start: fstring_lhs.node.start,
-                    end: fstring_rhs.node.end,
-                },
-                values,
+            Some(Expression::Constant(Box::new(Constant {
+                node,
+                value,
+                kind: lhs.kind,
             })))
         }
-        (Expression::JoinedStr(fstring_lhs), Expression::Constant(const_rhs)) => {
-            let mut values = fstring_lhs.values;
-            match const_rhs.value {
-                ConstantValue::Str(rhs_val) => {
-                    values.push(Expression::Constant(Box::new(Constant {
-                        node: const_rhs.node,
-                        value: ConstantValue::Str(rhs_val),
-                    })));
-                }
-                ConstantValue::Bytes(_) => {
-                    return Err(ParsingError::InvalidSyntax {
-                        msg: "Cannot concat string and bytes".into(),
-                        input: "test".into(),
-                        advice: "test".into(),
-                        span: (0, 0),
-                    });
-                }
-                _ => panic!("Cannot concat string"),
+        (lhs, rhs) => {
+            let mut values = vec![];
+            for part in fstring_parts(lhs)?.into_iter().chain(fstring_parts(rhs)?) {
+                push_fstring_part(&mut values, part);
             }
-            Ok(Expression::JoinedStr(Box::new(JoinedStr {
-                node: Node {
-                    start: fstring_lhs.node.start,
-                    end: const_rhs.node.end,
-                },
-                values,
-            })))
+            Some(Expression::JoinedStr(Box::new(JoinedStr { node, values })))
         }
-        (Expression::Constant(const_lhs), Expression::JoinedStr(fstring_rhs)) => {
-            let const_expr = match const_lhs.value {
-                ConstantValue::Str(rhs_val) => Expression::Constant(Box::new(Constant {
-                    node: const_lhs.node,
-                    value: ConstantValue::Str(rhs_val),
-                })),
-                ConstantValue::Bytes(_) => {
-                    return Err(ParsingError::InvalidSyntax {
-                        msg: "Cannot concat string and bytes".into(),
-                        input: "test".into(),
-                        advice: "test".into(),
-                        span: (0, 0),
-                    });
-                }
-                _ => panic!("Cannot concat string"),
-            };
-            let mut values = vec![const_expr];
-            values.extend(fstring_rhs.values);
-            Ok(Expression::JoinedStr(Box::new(JoinedStr {
-                node: Node {
-                    start: const_lhs.node.start,
-                    end: fstring_rhs.node.end,
-                },
-                values,
-            })))
+    }
+}
+
+/// The parts of an f-string, or the string itself as the only part. Bytes
+/// have no parts because they can't be in an f-string.
+fn fstring_parts(expr: Expression) -> Option<Vec<Expression>> {
+    match expr {
+        Expression::JoinedStr(fstring) => Some(fstring.values),
+        Expression::Constant(constant) if matches!(constant.value, ConstantValue::Str(_)) => {
+            Some(vec![Expression::Constant(constant)])
+        }
+        _ => None,
+    }
+}
+
+/// Adds a part to the end of an f-string. A string after a string is merged
+/// into it, so that the parts alternate between text and replacement fields.
+fn push_fstring_part(values: &mut Vec<Expression>, part: Expression) {
+    if let (Some(Expression::Constant(last)), Expression::Constant(constant)) =
+        (values.last_mut(), &part)
+    {
+        if let (ConstantValue::Str(last_value), ConstantValue::Str(value)) =
+            (&mut last.value, &constant.value)
+        {
+            last_value.push_str(value);
+            last.node.end = constant.node.end;
+            return;
         }
-        _ => panic!("Cannot concat string"),
     }
+    values.push(part);
 }
@@ -0,0 +1,14 @@
+// Offsets and line numbers in a source are `u32`, which keeps tokens and
+// nodes small. The lexer rejects sources longer than `MAX_SOURCE_LEN` bytes
+// with `SourceTooLarge` instead of letting their offsets wrap around.
+
+/// Byte offset in a source, or the length of a part of it
+pub type TextSize = u32;
+
+/// Length in bytes of the longest source that can be lexed, 4 GiB
+pub const MAX_SOURCE_LEN: usize = TextSize::MAX as usize;
+
+/// Converts an offset in a source that is at most `MAX_SOURCE_LEN` long
+pub(crate) fn text_size(offset: usize) -> TextSize {
+    TextSize::try_from(offset).expect("offsets of lexed sources fit in `TextSize`")
+}
@@ -1,12 +1,16 @@
 use std::fmt::Display;
 
+use serde::Serialize;
+
+use crate::text_size::TextSize;
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub kind: Kind,
     // Value might be deleted in the future
     pub value: TokenValue,
-    pub start: usize,
-    pub end: usize,
+    pub start: TextSize,
+    pub end: TextSize,
 }
 
 impl Display for Token {
@@ -30,7 +34,7 @@ impl Display for Token {
 }
 
 // https://docs.python.org/3/reference/lexical_analysis.html
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Kind {
     // Line structure
     NewLine, // \n
@@ -130,14 +134,15 @@ pub enum Kind {
     NotEq,      // !=
 
     // Delimiters
-    LeftParen,        // (
-    RightParen,       // )
-    LeftBrace,        // [
-    RightBrace,       // ]
-    LeftBracket,      // {
-    RightBracket,     // }
+    LParen,           // (
+    RParen,           // )
+    LBracket,         // [
+    RBracket,         // ]
+    LBrace,           // {
+    RBrace,           // }
     Comma,            // ,
     Colon,            // :
+    Exclamation,      // ! of the conversion of an f-string replacement field
     Dot,              // .
     SemiColon,        // ;
     Assign,           // =
@@ -170,7 +175,204 @@ pub enum Kind {
     Eof,
 }
 
+/// The keywords of Python and their kinds
+pub const KEYWORDS: &[(&str, Kind)] = &[
+    ("False", Kind::False),
+    ("None", Kind::None),
+    ("True", Kind::True),
+    ("and", Kind::And),
+    ("as", Kind::As),
+    ("assert", Kind::Assert),
+    ("async", Kind::Async),
+    ("await", Kind::Await),
+    ("break", Kind::Break),
+    ("class", Kind::Class),
+    ("continue", Kind::Continue),
+    ("def", Kind::Def),
+    ("del", Kind::Del),
+    ("elif", Kind::Elif),
+    ("else", Kind::Else),
+    ("except", Kind::Except),
+    ("finally", Kind::Finally),
+    ("for", Kind::For),
+    ("from", Kind::From),
+    ("global", Kind::Global),
+    ("if", Kind::If),
+    ("import", Kind::Import),
+    ("in", Kind::In),
+    ("is", Kind::Is),
+    ("lambda", Kind::Lambda),
+    ("nonlocal", Kind::Nonlocal),
+    ("not", Kind::Not),
+    ("or", Kind::Or),
+    ("pass", Kind::Pass),
+    ("raise", Kind::Raise),
+    ("return", Kind::Return),
+    ("try", Kind::Try),
+    ("while", Kind::While),
+    ("with", Kind::With),
+    ("yield", Kind::Yield),
+];
+
+/// Identifiers that are keywords only in some places, e.g. `match` at the start
+/// of a match statement. The lexer emits them as identifiers and the parser
+/// decides from the tokens around them whether they are keywords.
+/// https://docs.python.org/3/reference/lexical_analysis.html#soft-keywords
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SoftKeyword {
+    Match,
+    Case,
+    Type,
+    Underscore,
+}
+
+impl SoftKeyword {
+    /// Returns the soft keyword that the token spells, the token can still be
+    /// a name
+    pub fn from_token(token: &Token) -> Option<Self> {
+        match (&token.kind, &token.value) {
+            (Kind::Identifier, TokenValue::Str(value)) => match value.as_str() {
+                "match" => Some(SoftKeyword::Match),
+                "case" => Some(SoftKeyword::Case),
+                "type" => Some(SoftKeyword::Type),
+                "_" => Some(SoftKeyword::Underscore),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SoftKeyword::Match => "match",
+            SoftKeyword::Case => "case",
+            SoftKeyword::Type => "type",
+            SoftKeyword::Underscore => "_",
+        }
+    }
+}
+
+impl Display for SoftKeyword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The operators and delimiters of Python and their kinds
+pub const PUNCTUATION: &[(&str, Kind)] = &[
+    ("+", Kind::Plus),
+    ("-", Kind::Minus),
+    ("*", Kind::Mul),
+    ("**", Kind::Pow),
+    ("/", Kind::Div),
+    ("//", Kind::IntDiv),
+    ("%", Kind::Mod),
+    ("@", Kind::MatrixMul),
+    ("<<", Kind::LeftShift),
+    (">>", Kind::RightShift),
+    ("&", Kind::BitAnd),
+    ("|", Kind::BitOr),
+    ("^", Kind::BitXor),
+    ("~", Kind::BitNot),
+    (":=", Kind::Walrus),
+    ("<", Kind::Less),
+    (">", Kind::Greater),
+    ("<=", Kind::LessEq),
+    (">=", Kind::GreaterEq),
+    ("==", Kind::Eq),
+    ("!=", Kind::NotEq),
+    ("(", Kind::LParen),
+    (")", Kind::RParen),
+    ("[", Kind::LBracket),
+    ("]", Kind::RBracket),
+    ("{", Kind::LBrace),
+    ("}", Kind::RBrace),
+    (",", Kind::Comma),
+    (":", Kind::Colon),
+    ("!", Kind::Exclamation),
+    (".", Kind::Dot),
+    (";", Kind::SemiColon),
+    ("=", Kind::Assign),
+    ("->", Kind::Arrow),
+    ("+=", Kind::AddAssign),
+    ("-=", Kind::SubAssign),
+    ("*=", Kind::MulAssign),
+    ("/=", Kind::DivAssign),
+    ("%=", Kind::ModAssign),
+    ("@=", Kind::MatrixMulAssign),
+    ("&=", Kind::BitAndAssign),
+    ("|=", Kind::BitOrAssign),
+    ("^=", Kind::BitXorAssign),
+    ("//=", Kind::IntDivAssign),
+    ("<<=", Kind::ShiftLeftAssign),
+    (">>=", Kind::ShiftRightAssign),
+    ("**=", Kind::PowAssign),
+    ("...", Kind::Ellipsis),
+    ("\\", Kind::BackSlash),
+];
+
+// The names of the bracket kinds used to be swapped, `LeftBrace` was `[` and
+// `LeftBracket` was `{`. The old names are kept for code written against them.
+#[allow(non_upper_case_globals)]
+impl Kind {
+    #[deprecated(note = "use `Kind::LParen`")]
+    pub const LeftParen: Kind = Kind::LParen;
+    #[deprecated(note = "use `Kind::RParen`")]
+    pub const RightParen: Kind = Kind::RParen;
+    #[deprecated(note = "`LeftBrace` is `[`, use `Kind::LBracket`")]
+    pub const LeftBrace: Kind = Kind::LBracket;
+    #[deprecated(note = "`RightBrace` is `]`, use `Kind::RBracket`")]
+    pub const RightBrace: Kind = Kind::RBracket;
+    #[deprecated(note = "`LeftBracket` is `{`, use `Kind::LBrace`")]
+    pub const LeftBracket: Kind = Kind::LBrace;
+    #[deprecated(note = "`RightBracket` is `}`, use `Kind::RBrace`")]
+    pub const RightBracket: Kind = Kind::RBrace;
+}
+
 impl Kind {
+    pub fn is_keyword(self) -> bool {
+        KEYWORDS.iter().any(|(_, kind)| *kind == self)
+    }
+
+    /// Operators as listed in the language reference, delimiters such as `=`
+    /// and `+=` are not operators
+    pub fn is_operator(self) -> bool {
+        matches!(
+            self,
+            Kind::Plus
+                | Kind::Minus
+                | Kind::Mul
+                | Kind::Pow
+                | Kind::Div
+                | Kind::IntDiv
+                | Kind::Mod
+                | Kind::MatrixMul
+                | Kind::LeftShift
+                | Kind::RightShift
+                | Kind::BitAnd
+                | Kind::BitOr
+                | Kind::BitXor
+                | Kind::BitNot
+                | Kind::Walrus
+                | Kind::Less
+                | Kind::Greater
+                | Kind::LessEq
+                | Kind::GreaterEq
+                | Kind::Eq
+                | Kind::NotEq
+        )
+    }
+
+    /// The source text of keywords, operators and delimiters. Other kinds
+    /// like identifiers and literals do not have a fixed text.
+    pub fn lexeme(self) -> Option<&'static str> {
+        KEYWORDS
+            .iter()
+            .chain(PUNCTUATION)
+            .find(|(_, kind)| *kind == self)
+            .map(|(lexeme, _)| *lexeme)
+    }
+
     #[allow(clippy::too_many_lines)]
     #[must_use]
     pub fn to_str(self) -> &'static str {
@@ -208,14 +410,15 @@ impl Kind {
             Kind::NotEq => "!=",
             Kind::LessEq => "<=",
             Kind::Walrus => ":=",
-            Kind::LeftParen => "(",
-            Kind::RightParen => ")",
-            Kind::LeftBrace => "[",
-            Kind::RightBrace => "]",
-            Kind::LeftBracket => "{",
-            Kind::RightBracket => "}",
+            Kind::LParen => "(",
+            Kind::RParen => ")",
+            Kind::LBracket => "[",
+            Kind::RBracket => "]",
+            Kind::LBrace => "{",
+            Kind::RBrace => "}",
             Kind::Comma => ",",
             Kind::Colon => ":",
+            Kind::Exclamation => "!",
             Kind::Comment => "Comment",
             Kind::Dot => ".",
             Kind::SemiColon => ";",
@@ -290,11 +493,26 @@ impl Kind {
     }
 }
 
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.lexeme() {
+            Some(lexeme) => write!(f, "{}", lexeme),
+            None => write!(f, "{}", self.to_str()),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenValue {
     None,
     Number(String), // TODO: String because we don't need the value yet
     Str(String),
+    /// A string literal or the start of an f-string. The value is the text
+    /// between the quotes, it is empty for the start of an f-string.
+    String {
+        value: String,
+        kind: StringKind,
+    },
     Indent(usize),
 }
 
@@ -304,7 +522,71 @@ impl Display for TokenValue {
             TokenValue::None => write!(f, "None"),
             TokenValue::Number(n) => write!(f, "{}", n),
             TokenValue::Str(s) => write!(f, "{}", s),
+            TokenValue::String { value, kind } => {
+                let quotes = kind.quotes();
+                if kind.fstring {
+                    write!(f, "{}{}", kind.prefix(), quotes)
+                } else {
+                    write!(f, "{}{}{}{}", kind.prefix(), quotes, value, quotes)
+                }
+            }
             TokenValue::Indent(i) => write!(f, "{}", i),
         }
     }
 }
+
+/// The prefix and the quotes of a string literal
+/// https://docs.python.org/3/reference/lexical_analysis.html#string-and-bytes-literals
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct StringKind {
+    pub raw: bool,
+    pub bytes: bool,
+    pub unicode: bool,
+    pub fstring: bool,
+    pub triple_quoted: bool,
+    /// `'` or `"`
+    pub quote: char,
+}
+
+impl StringKind {
+    /// Reads the kind from the source text of a string literal, or of the
+    /// start of an f-string, and returns it with the text between the quotes
+    pub fn from_literal(literal: &str) -> (Self, &str) {
+        let quote_start = literal.find(['\'', '"']).unwrap_or(literal.len());
+        let (prefix, rest) = literal.split_at(quote_start);
+        let quote = rest.chars().next().unwrap_or('"');
+        let triple_quoted = rest.starts_with(&quote.to_string().repeat(3));
+        let prefix = prefix.to_ascii_lowercase();
+        let kind = StringKind {
+            raw: prefix.contains('r'),
+            bytes: prefix.contains('b'),
+            unicode: prefix.contains('u'),
+            fstring: prefix.contains('f'),
+            triple_quoted,
+            quote,
+        };
+        let quotes = kind.quotes();
+        let value = rest.strip_prefix(&quotes).unwrap_or(rest);
+        (kind, value.strip_suffix(&quotes).unwrap_or(value))
+    }
+
+    /// The prefix letters in lowercase, e.g. `rb`
+    pub fn prefix(&self) -> String {
+        [
+            (self.raw, 'r'),
+            (self.bytes, 'b'),
+            (self.unicode, 'u'),
+            (self.fstring, 'f'),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, letter)| letter)
+        .collect()
+    }
+
+    /// The opening and closing quotes, e.g. `"""`
+    pub fn quotes(&self) -> String {
+        let count = if self.triple_quoted { 3 } else { 1 };
+        self.quote.to_string().repeat(count)
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    token::{Kind, Token},
     Lexer,
+    token::{Kind, Token},
 };
 
 // Helper function to lex a file until the end
@@ -13,3 +13,10 @@ f: tuple[int, int] = e
 g: tuple[int,
          int] = 1, 2
 
+
+
+(h): int = 1
+
+i.j: int
+
+k[0]: int = 1
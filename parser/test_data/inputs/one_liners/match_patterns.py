@@ -0,0 +1,45 @@
+match x:
+    case -1 | 1 + 2j | -1.5 - 2j:
+        pass
+
+match x:
+    case "a" "b" | b"c":
+        pass
+
+match x:
+    case (1 | 2) as number:
+        pass
+
+match x:
+    case (a):
+        pass
+    case ():
+        pass
+    case (a,):
+        pass
+
+match x:
+    case [a, *_, b]:
+        pass
+    case (*rest, a):
+        pass
+
+match x:
+    case a, *rest:
+        pass
+
+match x:
+    case a,:
+        pass
+
+match x:
+    case {"key": value, 1: a, -1: b, x.y: c, **rest}:
+        pass
+
+match x:
+    case Point(1, y=[a, *b]) | mod.Point():
+        pass
+
+match x:
+    case [1, [2, (3, *x)]] as whole if whole:
+        pass
@@ -0,0 +1,8 @@
+from . import a
+from .. import (b, c)
+from ... import d as e
+from .... import *
+from .....f.g import h
+from ...... import i
+
+from import a
@@ -0,0 +1,35 @@
+()
+
+(,)
+
+(a,)
+
+{**a}
+
+[*a]
+
+[a,]
+
+{a,}
+
+{a: 1,}
+
+f(a,)
+
+f(*a, **b,)
+
+def f(a,): pass
+
+def f(,): pass
+
+lambda a,: a
+
+x = 1,
+
+x, = a
+
+for a, in b: pass
+
+a[1,]
+
+class A(B,): pass
@@ -111,9 +111,8 @@ pub trait TraversalVisitor {
         }
         for with_items in &w.items {
             self.visit_expr(&with_items.context_expr);
-            match &with_items.optional_vars {
-                Some(items) => self.visit_expr(items),
-                None => (),
+            if let Some(items) = &with_items.optional_vars {
+                self.visit_expr(items)
             }
         }
     }
@@ -124,9 +123,8 @@ pub trait TraversalVisitor {
         }
         for with_items in &w.items {
             self.visit_expr(&with_items.context_expr);
-            match &with_items.optional_vars {
-                Some(items) => self.visit_expr(items),
-                None => (),
+            if let Some(items) = &with_items.optional_vars {
+                self.visit_expr(items)
             }
         }
     }
@@ -199,13 +197,13 @@ pub trait TraversalVisitor {
     fn visit_match_pattern(&mut self, _m: &parser::ast::MatchPattern) {
         match _m {
             MatchPattern::MatchValue(m) => self.visit_expr(&m.value),
-            MatchPattern::MatchSingleton(m) => self.visit_expr(m),
+            MatchPattern::MatchSingleton(m) => self.visit_expr(&m.value),
             MatchPattern::MatchSequence(m) => {
-                for item in m.iter() {
+                for item in m.patterns.iter() {
                     self.visit_match_pattern(item);
                 }
             }
-            MatchPattern::MatchStar(m) => self.visit_expr(m),
+            MatchPattern::MatchStar(_) => {}
             MatchPattern::MatchMapping(m) => {
                 for key in &m.keys {
                     self.visit_expr(key);
@@ -226,7 +224,7 @@ pub trait TraversalVisitor {
                 }
             }
             MatchPattern::MatchOr(m) => {
-                for pattern in m.iter() {
+                for pattern in m.patterns.iter() {
                     self.visit_match_pattern(pattern);
                 }
             }
@@ -313,6 +311,7 @@ pub trait TraversalVisitor {
         todo!()
     }
 
+    #[allow(unused)]
     fn visit_alias(&mut self, _a: &Alias) {
         todo!()
     }
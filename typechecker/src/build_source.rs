@@ -1,5 +1,10 @@
 use std::path::{Path, PathBuf};
 
+use crate::{
+    file_system::{FileSystem, OsFileSystem},
+    notebook::Notebook,
+};
+
 #[derive(Debug, Clone)]
 /// BuildSource is a struct that holds metadata about a source file
 /// that is being analyzed.
@@ -17,7 +22,22 @@ pub struct BuildSource {
 
 impl BuildSource {
     pub fn from_path(path: PathBuf, followed: bool) -> Result<Self, std::io::Error> {
-        let source = std::fs::read_to_string(&path)?;
+        Self::from_file_system(&OsFileSystem, path, followed)
+    }
+
+    /// Reads the source from the file system, which can contain files that
+    /// are not saved to disk
+    pub fn from_file_system(
+        file_system: &dyn FileSystem,
+        path: PathBuf,
+        followed: bool,
+    ) -> Result<Self, std::io::Error> {
+        let mut source = file_system.read_to_string(&path)?;
+        if Notebook::is_notebook(&path) {
+            source = Notebook::from_json(&source)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+                .source();
+        }
         let module = get_module_name(&path);
         Ok(BuildSource {
             path,
@@ -28,6 +48,6 @@ impl BuildSource {
     }
 }
 
-fn get_module_name(path: &Path) -> String {
+pub(crate) fn get_module_name(path: &Path) -> String {
     path.to_str().unwrap_or_default().replace(['/', '\\'], ".")
 }
@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use enderpy_python_parser::error::ParsingError;
+use enderpy_python_parser::{TextSize, error::ParsingError, token::Kind};
+use serde_json::json;
+
+use crate::error_code::ErrorCode;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Diagnostic {
@@ -9,15 +12,159 @@ pub struct Diagnostic {
     /// The message to display to the user, to explain the suggested fix.
     pub suggestion: Option<String>,
     pub range: Range,
+    /// Stable code identifying the kind of the diagnostic
+    pub code: ErrorCode,
+    /// Edits that fix the problem, if the checker knows how to fix it.
+    pub fix: Option<Fix>,
+    /// Secondary locations in the same file that explain the diagnostic, e.g.
+    /// where a variable was first defined.
+    pub labels: Vec<(Range, String)>,
+}
+
+/// Describes whether a fix can be applied without a user reviewing it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Applicability {
+    /// The fix preserves the meaning of the code and can be applied automatically.
+    Safe,
+    /// The fix may change the meaning of the code and should only be offered to the user.
+    Unsafe,
 }
 
+/// Replaces the text between the byte offsets of `span` with `new_text`.
+/// Insertions are edits with an empty span.
 #[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TextEdit {
+    pub span: CharacterSpan,
+    pub new_text: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Fix {
+    /// Short description of the fix shown to the user, e.g. in a code action.
+    pub message: String,
+    pub edits: Vec<TextEdit>,
+    pub applicability: Applicability,
+}
+
+impl Fix {
+    pub fn insert(
+        message: &str,
+        offset: TextSize,
+        text: &str,
+        applicability: Applicability,
+    ) -> Self {
+        Fix {
+            message: message.to_string(),
+            edits: vec![TextEdit {
+                span: CharacterSpan(offset, offset),
+                new_text: text.to_string(),
+            }],
+            applicability,
+        }
+    }
+
+    pub fn delete(
+        message: &str,
+        start: TextSize,
+        end: TextSize,
+        applicability: Applicability,
+    ) -> Self {
+        Fix {
+            message: message.to_string(),
+            edits: vec![TextEdit {
+                span: CharacterSpan(start, end),
+                new_text: String::new(),
+            }],
+            applicability,
+        }
+    }
+
+    /// Fixes for parser errors where the missing token is unambiguous.
+    pub fn for_parsing_error(error: &ParsingError) -> Option<Self> {
+        match error {
+            ParsingError::ExpectedToken { expected, span, .. } if expected.len() == 1 => {
+                let text = match expected[0] {
+                    Kind::Colon => ":",
+                    Kind::RParen => ")",
+                    Kind::RBracket => "]",
+                    Kind::RBrace => "}",
+                    _ => return None,
+                };
+                // The span starts at the unexpected token and ends after the
+                // last token that was parsed successfully.
+                let offset = span.0.min(span.1);
+                Some(Fix::insert(
+                    &format!("Insert `{}`", text),
+                    TextSize::try_from(offset).ok()?,
+                    text,
+                    Applicability::Safe,
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Result of applying fixes to a source.
+#[derive(Debug)]
+pub struct FixedSource<'a> {
+    pub source: String,
+    /// Number of fixes whose edits are in `source`
+    pub applied: usize,
+    /// Fixes that were skipped because they overlap a fix that was applied
+    pub conflicts: Vec<&'a Fix>,
+}
+
+/// Applies the edits of `fixes` to `source`.
+/// Fixes overlapping a fix that was already applied are skipped and returned
+/// as conflicts, fixes with edits outside of the source are ignored.
+pub fn apply_fixes<'a>(source: &str, fixes: &[&'a Fix]) -> FixedSource<'a> {
+    let mut fixes = fixes.to_vec();
+    fixes.sort_by_key(|fix| fix.edits.iter().map(|e| e.span.0).min().unwrap_or(0));
+
+    let mut output = String::with_capacity(source.len());
+    let mut last_pos = 0;
+    let mut applied = 0;
+    let mut conflicts = vec![];
+    for fix in fixes {
+        let mut edits = fix.edits.iter().collect::<Vec<_>>();
+        edits.sort_by_key(|e| e.span.0);
+        let valid = !edits.is_empty()
+            && edits
+                .iter()
+                .all(|e| e.span.0 <= e.span.1 && e.span.1 as usize <= source.len())
+            && edits
+                .windows(2)
+                .all(|pair| pair[0].span.1 <= pair[1].span.0);
+        if !valid {
+            continue;
+        }
+        if (edits[0].span.0 as usize) < last_pos {
+            conflicts.push(fix);
+            continue;
+        }
+        for edit in edits {
+            output.push_str(&source[last_pos..edit.span.0 as usize]);
+            output.push_str(&edit.new_text);
+            last_pos = edit.span.1 as usize;
+        }
+        applied += 1;
+    }
+    output.push_str(&source[last_pos..]);
+    FixedSource {
+        source: output,
+        applied,
+        conflicts,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct Range {
     pub start: Position,
     pub end: Position,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct Position {
     pub line: u32,
     pub character: u32,
@@ -30,30 +177,123 @@ impl Display for Position {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct CharacterSpan(pub usize, pub usize);
+pub struct CharacterSpan(pub TextSize, pub TextSize);
 
-impl From<ParsingError> for Diagnostic {
-    fn from(error: ParsingError) -> Self {
-        match error {
-            ParsingError::InvalidSyntax {
-                msg,
-                input: _,
-                advice,
-                span,
-            } => Diagnostic {
-                body: msg.to_string(),
-                suggestion: Some(advice),
-                range: Range {
-                    start: Position {
-                        line: 0,
-                        character: span.0 as u32,
-                    },
-                    end: Position {
-                        line: 0,
-                        character: span.1 as u32,
-                    },
-                },
+impl Diagnostic {
+    pub fn from_parsing_error(error: &ParsingError, line_index: &LineIndex, source: &str) -> Self {
+        let span = error.span();
+        Diagnostic {
+            body: error.message(),
+            suggestion: error.advice().map(|advice| advice.to_string()),
+            range: Range {
+                start: line_index.position(source, offset(span.0)),
+                end: line_index.position(source, offset(span.1)),
             },
+            code: ErrorCode::from(error),
+            fix: Fix::for_parsing_error(error),
+            labels: vec![],
+        }
+    }
+
+    /// Serializes the code, message and range of the diagnostic, the labels
+    /// are serialized as `relatedInformation` like in LSP
+    pub fn to_json(&self) -> serde_json::Value {
+        let related_information = self
+            .labels
+            .iter()
+            .map(|(range, message)| {
+                json!({
+                    "message": message,
+                    "start": range.start.to_json(),
+                    "end": range.end.to_json(),
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "code": self.code.as_str(),
+            "message": self.body,
+            "start": self.range.start.to_json(),
+            "end": self.range.end.to_json(),
+            "relatedInformation": related_information,
+        })
+    }
+}
+
+impl Position {
+    fn to_json(&self) -> serde_json::Value {
+        json!({ "line": self.line, "character": self.character })
+    }
+}
+
+/// Offsets where the lines of a source start, to convert byte offsets to
+/// positions. Characters are counted in UTF-16 code units like in LSP and
+/// SARIF.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineIndex {
+    line_starts: Vec<TextSize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.match_indices('\n').map(|(i, _)| offset(i + 1)));
+        LineIndex { line_starts }
+    }
+
+    /// Zero based line and character of the offset in the source that the
+    /// index was created for
+    pub fn position(&self, source: &str, offset: TextSize) -> Position {
+        let offset = (offset as usize).min(source.len());
+        let line = self.line(offset as TextSize);
+        let line_start = self.line_starts.get(line as usize).copied().unwrap_or(0) as usize;
+        // Offsets inside a character are counted in bytes
+        let character = source
+            .get(line_start..offset)
+            .map_or(offset - line_start, |text| text.encode_utf16().count());
+        Position {
+            line,
+            character: character as u32,
         }
     }
+    /// Zero based line of the offset, offsets past the end of the source are
+    /// on the last line
+    pub fn line(&self, offset: TextSize) -> u32 {
+        self.line_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1) as u32
+    }
+}
+
+/// Offsets in sources that were parsed fit in `TextSize`, larger offsets are
+/// clamped to the largest one
+fn offset(offset: usize) -> TextSize {
+    TextSize::try_from(offset).unwrap_or(TextSize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_fixes() {
+        let source = "a = (1\nb = 2\n";
+        let close = Fix::insert("Insert `)`", 6, ")", Applicability::Safe);
+        let delete = Fix::delete("Delete `b = 2`", 7, 12, Applicability::Safe);
+        let fixed = apply_fixes(source, &[&delete, &close]);
+        assert_eq!(fixed.source, "a = (1)\n\n");
+        assert_eq!(fixed.applied, 2);
+        assert!(fixed.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_apply_fixes_conflicts() {
+        let source = "x = 1 + 2\n";
+        let first = Fix::delete("Delete `1 + `", 4, 8, Applicability::Safe);
+        let second = Fix::delete("Delete `+ 2`", 6, 9, Applicability::Safe);
+        let outside = Fix::insert("Insert `)`", 100, ")", Applicability::Safe);
+        let fixed = apply_fixes(source, &[&second, &outside, &first]);
+        assert_eq!(fixed.source, "x = 2\n");
+        assert_eq!(fixed.applied, 1);
+        assert_eq!(fixed.conflicts, vec![&second]);
+    }
 }
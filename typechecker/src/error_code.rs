@@ -0,0 +1,1025 @@
+use std::{fmt::Display, str::FromStr};
+
+use enderpy_python_parser::error::ParsingError;
+use serde::Deserialize;
+
+/// Stable identifier of a diagnostic reported by enderpy.
+///
+/// Codes starting with `E0` come from the lexer and the parser and codes
+/// starting with `E2` come from the type checker. Codes are never reused once
+/// they are published.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ErrorCode {
+    InvalidSyntax,
+    ExpectedToken,
+    UnterminatedString,
+    InvalidIndentation,
+    InvalidNumber,
+    TooDeeplyNested,
+    SourceTooLarge,
+    RepeatedKeywordArgument,
+    InvalidArgumentOrder,
+    CannotAssign,
+    TypeError,
+    NotCallable,
+    InvalidUnaryOperand,
+    UnsupportedOperator,
+    ComprehensionVariableLeak,
+    NameUsedAfterDel,
+    InvalidAssignmentExpression,
+    StarImport,
+    InvalidBaseClass,
+    UnknownAttribute,
+    InvalidArguments,
+    SlotsViolation,
+    FinalReassignment,
+    ClassVariableAssignment,
+    InvalidTypeArguments,
+    AssertTypeMismatch,
+    NonExhaustive,
+    InvalidScopeDeclaration,
+    InvalidGeneratorAnnotation,
+    InvalidProperty,
+    InvalidOverride,
+    InvalidInitReturn,
+    OptionalMemberAccess,
+    InvalidUnpacking,
+    InvalidFormatString,
+    InvalidException,
+    InvalidDefault,
+    AlwaysTrueCondition,
+    UnsortedImports,
+    MissingDocstring,
+    DocstringParameters,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 41] = [
+        ErrorCode::InvalidSyntax,
+        ErrorCode::ExpectedToken,
+        ErrorCode::UnterminatedString,
+        ErrorCode::InvalidIndentation,
+        ErrorCode::InvalidNumber,
+        ErrorCode::TooDeeplyNested,
+        ErrorCode::SourceTooLarge,
+        ErrorCode::RepeatedKeywordArgument,
+        ErrorCode::InvalidArgumentOrder,
+        ErrorCode::CannotAssign,
+        ErrorCode::TypeError,
+        ErrorCode::NotCallable,
+        ErrorCode::InvalidUnaryOperand,
+        ErrorCode::UnsupportedOperator,
+        ErrorCode::ComprehensionVariableLeak,
+        ErrorCode::NameUsedAfterDel,
+        ErrorCode::InvalidAssignmentExpression,
+        ErrorCode::StarImport,
+        ErrorCode::InvalidBaseClass,
+        ErrorCode::UnknownAttribute,
+        ErrorCode::InvalidArguments,
+        ErrorCode::SlotsViolation,
+        ErrorCode::FinalReassignment,
+        ErrorCode::ClassVariableAssignment,
+        ErrorCode::InvalidTypeArguments,
+        ErrorCode::AssertTypeMismatch,
+        ErrorCode::NonExhaustive,
+        ErrorCode::InvalidScopeDeclaration,
+        ErrorCode::InvalidGeneratorAnnotation,
+        ErrorCode::InvalidProperty,
+        ErrorCode::InvalidOverride,
+        ErrorCode::InvalidInitReturn,
+        ErrorCode::OptionalMemberAccess,
+        ErrorCode::InvalidUnpacking,
+        ErrorCode::InvalidFormatString,
+        ErrorCode::InvalidException,
+        ErrorCode::InvalidDefault,
+        ErrorCode::AlwaysTrueCondition,
+        ErrorCode::UnsortedImports,
+        ErrorCode::MissingDocstring,
+        ErrorCode::DocstringParameters,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidSyntax => "E001",
+            ErrorCode::ExpectedToken => "E002",
+            ErrorCode::UnterminatedString => "E003",
+            ErrorCode::InvalidIndentation => "E004",
+            ErrorCode::InvalidNumber => "E005",
+            ErrorCode::TooDeeplyNested => "E006",
+            ErrorCode::SourceTooLarge => "E007",
+            ErrorCode::RepeatedKeywordArgument => "E008",
+            ErrorCode::InvalidArgumentOrder => "E009",
+            ErrorCode::CannotAssign => "E010",
+            ErrorCode::TypeError => "E200",
+            ErrorCode::NotCallable => "E201",
+            ErrorCode::InvalidUnaryOperand => "E202",
+            ErrorCode::UnsupportedOperator => "E203",
+            ErrorCode::ComprehensionVariableLeak => "E204",
+            ErrorCode::NameUsedAfterDel => "E205",
+            ErrorCode::InvalidAssignmentExpression => "E206",
+            ErrorCode::StarImport => "E207",
+            ErrorCode::InvalidBaseClass => "E208",
+            ErrorCode::UnknownAttribute => "E209",
+            ErrorCode::InvalidArguments => "E210",
+            ErrorCode::SlotsViolation => "E211",
+            ErrorCode::FinalReassignment => "E212",
+            ErrorCode::ClassVariableAssignment => "E213",
+            ErrorCode::InvalidTypeArguments => "E214",
+            ErrorCode::AssertTypeMismatch => "E215",
+            ErrorCode::NonExhaustive => "E216",
+            ErrorCode::InvalidScopeDeclaration => "E217",
+            ErrorCode::InvalidGeneratorAnnotation => "E218",
+            ErrorCode::InvalidProperty => "E219",
+            ErrorCode::InvalidOverride => "E220",
+            ErrorCode::InvalidInitReturn => "E221",
+            ErrorCode::OptionalMemberAccess => "E222",
+            ErrorCode::InvalidUnpacking => "E223",
+            ErrorCode::InvalidFormatString => "E224",
+            ErrorCode::InvalidException => "E225",
+            ErrorCode::InvalidDefault => "E226",
+            ErrorCode::AlwaysTrueCondition => "E227",
+            ErrorCode::UnsortedImports => "E228",
+            ErrorCode::MissingDocstring => "E229",
+            ErrorCode::DocstringParameters => "E230",
+        }
+    }
+
+    /// Short human readable name of the code
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidSyntax => "invalid-syntax",
+            ErrorCode::ExpectedToken => "expected-token",
+            ErrorCode::UnterminatedString => "unterminated-string",
+            ErrorCode::InvalidIndentation => "invalid-indentation",
+            ErrorCode::InvalidNumber => "invalid-number",
+            ErrorCode::TooDeeplyNested => "too-deeply-nested",
+            ErrorCode::SourceTooLarge => "source-too-large",
+            ErrorCode::RepeatedKeywordArgument => "repeated-keyword-argument",
+            ErrorCode::InvalidArgumentOrder => "invalid-argument-order",
+            ErrorCode::CannotAssign => "cannot-assign",
+            ErrorCode::TypeError => "type-error",
+            ErrorCode::NotCallable => "not-callable",
+            ErrorCode::InvalidUnaryOperand => "invalid-unary-operand",
+            ErrorCode::UnsupportedOperator => "unsupported-operator",
+            ErrorCode::ComprehensionVariableLeak => "comprehension-variable-leak",
+            ErrorCode::NameUsedAfterDel => "name-used-after-del",
+            ErrorCode::InvalidAssignmentExpression => "invalid-assignment-expression",
+            ErrorCode::StarImport => "star-import",
+            ErrorCode::InvalidBaseClass => "invalid-base-class",
+            ErrorCode::UnknownAttribute => "unknown-attribute",
+            ErrorCode::InvalidArguments => "invalid-arguments",
+            ErrorCode::SlotsViolation => "slots-violation",
+            ErrorCode::FinalReassignment => "final-reassignment",
+            ErrorCode::ClassVariableAssignment => "class-variable-assignment",
+            ErrorCode::InvalidTypeArguments => "invalid-type-arguments",
+            ErrorCode::AssertTypeMismatch => "assert-type-mismatch",
+            ErrorCode::NonExhaustive => "non-exhaustive",
+            ErrorCode::InvalidScopeDeclaration => "invalid-scope-declaration",
+            ErrorCode::InvalidGeneratorAnnotation => "invalid-generator-annotation",
+            ErrorCode::InvalidProperty => "invalid-property",
+            ErrorCode::InvalidOverride => "invalid-override",
+            ErrorCode::InvalidInitReturn => "invalid-init-return",
+            ErrorCode::OptionalMemberAccess => "optional-member-access",
+            ErrorCode::InvalidUnpacking => "invalid-unpacking",
+            ErrorCode::InvalidFormatString => "invalid-format-string",
+            ErrorCode::InvalidException => "invalid-exception",
+            ErrorCode::InvalidDefault => "invalid-default",
+            ErrorCode::AlwaysTrueCondition => "always-true-condition",
+            ErrorCode::UnsortedImports => "unsorted-imports",
+            ErrorCode::MissingDocstring => "missing-docstring",
+            ErrorCode::DocstringParameters => "docstring-parameters",
+        }
+    }
+
+    /// Link to the explanation of the code in the documentation
+    pub fn url(&self) -> String {
+        format!(
+            "https://github.com/Glyphack/enderpy/blob/main/docs/error-codes.md#{}-{}",
+            self.as_str().to_ascii_lowercase(),
+            self.name()
+        )
+    }
+
+    /// Longer description of the error with an example fix.
+    /// Used by `enderpy explain`.
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidSyntax => {
+                "The code is not valid Python syntax.
+
+Example:
+
+    x = = 1
+
+Fix:
+
+    x = 1"
+            }
+            ErrorCode::ExpectedToken => {
+                "The parser expected a specific token but found a different one.
+This usually means a character such as `:`, `)` or a name is missing.
+
+Example:
+
+    if x
+        pass
+
+Fix:
+
+    if x:
+        pass"
+            }
+            ErrorCode::UnterminatedString => {
+                "A string literal is missing its closing quote.
+
+Example:
+
+    name = 'enderpy
+
+Fix:
+
+    name = 'enderpy'"
+            }
+            ErrorCode::InvalidIndentation => {
+                "A dedented line does not line up with any enclosing block.
+
+Example:
+
+    if True:
+            x = 1
+        y = 2
+
+Fix:
+
+    if True:
+        x = 1
+        y = 2"
+            }
+            ErrorCode::InvalidNumber => {
+                "A numeric literal contains a digit that is not allowed for its base.
+
+Example:
+
+    mask = 0b102
+
+Fix:
+
+    mask = 0b101"
+            }
+            ErrorCode::TooDeeplyNested => {
+                "Brackets, unary operators, lambdas or blocks are nested deeper than the
+nesting limit of the parser. The limit is set with `max_nesting_depth`.
+
+Example:
+
+    value = ((((((((((1))))))))))
+
+Fix:
+
+    value = 1"
+            }
+            ErrorCode::SourceTooLarge => {
+                "The file is larger than 4 GiB. Offsets in the file do not fit in
+32 bits, so the file is not parsed.
+
+Fix:
+
+    Split the file into smaller modules"
+            }
+            ErrorCode::RepeatedKeywordArgument => {
+                "A keyword argument is passed more than once in a call or a class
+definition.
+
+Example:
+
+    connect(host=\"a\", host=\"b\")
+
+Fix:
+
+    connect(host=\"b\")"
+            }
+            ErrorCode::InvalidArgumentOrder => {
+                "The arguments of a call are in the wrong order. Positional arguments
+come before keyword arguments and `*` unpacking comes before `**`
+unpacking.
+
+Example:
+
+    print(sep=\", \", value)
+
+Fix:
+
+    print(value, sep=\", \")"
+            }
+            ErrorCode::CannotAssign => {
+                "A name that cannot be assigned is the target of an assignment, a
+loop, a `with` statement or a keyword argument. `__debug__`, `True`,
+`False` and `None` cannot be assigned.
+
+Example:
+
+    __debug__ = False
+
+Fix:
+
+    debug = False"
+            }
+            ErrorCode::TypeError => {
+                "The type of an expression could not be evaluated.
+
+Check that the expression is valid and all names it uses are defined."
+            }
+            ErrorCode::NotCallable => {
+                "A value is called but its type is not callable.
+
+Example:
+
+    count = 1
+    count()
+
+Fix:
+
+    def count() -> int: ...
+    count()"
+            }
+            ErrorCode::InvalidUnaryOperand => {
+                "A unary operator is applied to a type that does not support it.
+
+Example:
+
+    ~1.5
+
+Fix:
+
+    ~1"
+            }
+            ErrorCode::UnsupportedOperator => {
+                "A binary operator is used with operand types that do not support it.
+
+Example:
+
+    total = 1 + \"2\"
+
+Fix:
+
+    total = 1 + int(\"2\")"
+            }
+            ErrorCode::ComprehensionVariableLeak => {
+                "A variable of a comprehension is used after the comprehension.
+Comprehensions have their own scope, their variables are not defined outside.
+
+Example:
+
+    squares = [n * n for n in range(10)]
+    print(n)
+
+Fix:
+
+    for n in range(10):
+        pass
+    print(n)"
+            }
+            ErrorCode::NameUsedAfterDel => {
+                "A name is used after it was removed with `del`.
+The name bound by `except E as name` is deleted at the end of the handler too.
+
+Example:
+
+    cache = {}
+    del cache
+    cache.clear()
+
+Fix:
+
+    cache = {}
+    cache.clear()
+    del cache"
+            }
+            ErrorCode::InvalidAssignmentExpression => {
+                "An assignment expression (`:=`) is used where Python does not allow it.
+Inside a comprehension the target is bound in the enclosing function or module,
+so it cannot be used in a class body, in a comprehension iterable or to rebind
+an iteration variable.
+
+Example:
+
+    class Config:
+        flags = [last := f for f in range(3)]
+
+Fix:
+
+    class Config:
+        flags = list(range(3))
+        last = flags[-1]"
+            }
+            ErrorCode::StarImport => {
+                "A module is imported with `from module import *`. Only reported when star
+import warnings are enabled.
+Star imports hide where names come from and can shadow other names.
+
+Example:
+
+    from os.path import *
+
+Fix:
+
+    from os.path import join, exists"
+            }
+            ErrorCode::InvalidBaseClass => {
+                "A base class expression does not evaluate to a class.
+
+Example:
+
+    def make_base(): ...
+
+    class Model(make_base):
+        pass
+
+Fix:
+
+    class Model(make_base()):
+        pass"
+            }
+            ErrorCode::UnknownAttribute => {
+                "An attribute is accessed on a class that does not define or inherit it.
+Every class inherits the attributes of `object` and gets the attributes of its
+metaclass `type`.
+
+Example:
+
+    class Point:
+        x = 0
+
+    Point.y
+
+Fix:
+
+    Point.x"
+            }
+            ErrorCode::InvalidArguments => {
+                "The arguments of a call do not match the parameters of the called
+function. Too many positional arguments, a missing argument, an unknown
+keyword, an argument that is passed twice, a positional-only parameter
+passed by keyword, a keyword-only parameter passed positionally and an
+argument whose type is not assignable to the annotation of its parameter are
+reported.
+
+Example:
+
+    class Base:
+        def __init__(self, name):
+            self.name = name
+
+    class Child(Base):
+        def __init__(self):
+            super().__init__()
+
+Fix:
+
+    class Child(Base):
+        def __init__(self):
+            super().__init__(\"child\")"
+            }
+            ErrorCode::SlotsViolation => {
+                "An attribute is assigned on an instance of a class that uses `__slots__`
+but no class in its MRO declares the attribute as a slot. Instances of such
+classes have no `__dict__` and the assignment raises an AttributeError.
+
+Example:
+
+    class Point:
+        __slots__ = (\"x\",)
+
+        def __init__(self):
+            self.x = 0
+            self.y = 0
+
+Fix:
+
+    class Point:
+        __slots__ = (\"x\", \"y\")
+
+        def __init__(self):
+            self.x = 0
+            self.y = 0"
+            }
+            ErrorCode::FinalReassignment => {
+                "A name declared with `typing.Final` is assigned again or overridden in a
+subclass. With the `uppercase_constants` setting module level UPPER_CASE names
+are treated as constants too.
+
+Example:
+
+    from typing import Final
+
+    MAX_SIZE: Final = 10
+    MAX_SIZE = 20
+
+Fix:
+
+    from typing import Final
+
+    MAX_SIZE: Final = 20"
+            }
+            ErrorCode::ClassVariableAssignment => {
+                "A name annotated with `typing.ClassVar` is assigned through an instance.
+Class variables belong to the class, an assignment through `self` creates an
+instance attribute that shadows the class variable instead of updating it.
+
+Example:
+
+    from typing import ClassVar
+
+    class Counter:
+        count: ClassVar[int] = 0
+
+        def increment(self):
+            self.count += 1
+
+Fix:
+
+    from typing import ClassVar
+
+    class Counter:
+        count: ClassVar[int] = 0
+
+        def increment(self):
+            Counter.count += 1"
+            }
+            ErrorCode::InvalidTypeArguments => {
+                "A generic class is given the wrong number of type arguments or a
+TypeVarTuple is used incorrectly. A TypeVarTuple must be unpacked with `*Ts`
+or `Unpack[Ts]`, only TypeVarTuples and tuples can be unpacked and a list of
+type arguments can contain at most one unpacked TypeVarTuple or unbounded
+tuple.
+
+Example:
+
+    from typing import Generic, TypeVar, TypeVarTuple
+
+    T = TypeVar(\"T\")
+    Ts = TypeVarTuple(\"Ts\")
+
+    class Box(Generic[T]): ...
+
+    def first(values: tuple[Ts]) -> Box[int, str]: ...
+
+Fix:
+
+    def first(values: tuple[*Ts]) -> Box[int]: ..."
+            }
+            ErrorCode::AssertTypeMismatch => {
+                "The inferred type of the first argument of `assert_type` is not the
+type given as the second argument.
+
+Example:
+
+    from typing import assert_type
+
+    def double(x: int):
+        assert_type(x * 2, str)
+
+Fix:
+
+    def double(x: int):
+        assert_type(x * 2, int)"
+            }
+            ErrorCode::NonExhaustive => {
+                "`assert_never` is reached with a value that is not `Never`. The cases
+before it do not handle every member of the type of the value.
+
+Example:
+
+    from typing import Literal, assert_never
+
+    def describe(mode: Literal[\"r\", \"w\"]):
+        if mode == \"r\":
+            return \"read\"
+        else:
+            assert_never(mode)
+
+Fix:
+
+    def describe(mode: Literal[\"r\", \"w\"]):
+        if mode == \"r\":
+            return \"read\"
+        elif mode == \"w\":
+            return \"write\"
+        else:
+            assert_never(mode)"
+            }
+            ErrorCode::InvalidScopeDeclaration => {
+                "A `global` or `nonlocal` statement comes after the name is used or
+assigned in the scope, names a parameter, or the name is annotated. The
+statement applies to the whole scope, so it must come before any use of the
+name.
+
+Example:
+
+    counter = 0
+
+    def increment():
+        counter += 1
+        global counter
+
+Fix:
+
+    counter = 0
+
+    def increment():
+        global counter
+        counter += 1"
+            }
+            ErrorCode::InvalidGeneratorAnnotation => {
+                "The return annotation of a generator function is not a type of the
+values that calling the function creates. A function that contains `yield`
+returns a generator, so it must be annotated with `Generator`, `Iterator` or
+`Iterable`.
+
+Example:
+
+    def countdown(n: int) -> list[int]:
+        while n > 0:
+            yield n
+            n -= 1
+
+Fix:
+
+    from typing import Iterator
+
+    def countdown(n: int) -> Iterator[int]:
+        while n > 0:
+            yield n
+            n -= 1"
+            }
+            ErrorCode::InvalidProperty => {
+                "A property is defined or assigned incorrectly. A setter must follow the
+getter of the property and accept the type that the getter returns. A property
+without a setter cannot be assigned and the assigned value must match the type
+of the value parameter of the setter.
+
+Example:
+
+    class Circle:
+        @property
+        def radius(self) -> float:
+            return self._radius
+
+    Circle().radius = 2.0
+
+Fix:
+
+    class Circle:
+        @property
+        def radius(self) -> float:
+            return self._radius
+
+        @radius.setter
+        def radius(self, value: float):
+            self._radius = value
+
+    Circle().radius = 2.0"
+            }
+            ErrorCode::InvalidOverride => {
+                "A method overrides a method of a base class with a signature that does
+not accept every call the base method accepts, or a method decorated with
+`@override` does not override a method of a base class. An override must
+accept the parameters of the base method with the same names and defaults,
+parameter types at least as wide and a return type at least as narrow.
+
+Example:
+
+    class Shape:
+        def scale(self, factor: float) -> \"Shape\": ...
+
+    class Square(Shape):
+        def scale(self, factor: int, center: bool) -> \"Square\": ...
+
+Fix:
+
+    class Square(Shape):
+        def scale(self, factor: float, center: bool = False) -> \"Square\": ..."
+            }
+            ErrorCode::InvalidInitReturn => {
+                "The `__init__` method of a class is annotated to return a value. Calling
+the class returns the new instance, the value returned by `__init__` must be
+None.
+
+Example:
+
+    class Connection:
+        def __init__(self, url: str) -> \"Connection\":
+            self.url = url
+
+Fix:
+
+    class Connection:
+        def __init__(self, url: str) -> None:
+            self.url = url"
+            }
+            ErrorCode::OptionalMemberAccess => {
+                "An attribute is accessed or a value is called while its type includes
+None. This is only reported with the `strict_optional` setting.
+
+Example:
+
+    def greet(name: str | None) -> str:
+        return name.upper()
+
+Fix:
+
+    def greet(name: str | None) -> str:
+        if name is not None:
+            return name.upper()
+        return \"\""
+            }
+            ErrorCode::InvalidUnpacking => {
+                "A value is unpacked into a different number of targets than the number of
+values it contains, or the value cannot be iterated.
+
+Example:
+
+    x, y = 1, 2, 3
+
+Fix:
+
+    x, y, z = 1, 2, 3"
+            }
+            ErrorCode::InvalidFormatString => {
+                "A format string is not valid or does not match the values that it formats.
+This checks `%` format strings, `str.format` calls and the format specs of
+f-strings and is only reported with the `check_format_strings` setting.
+
+Example:
+
+    \"%s: %d\" % (name,)
+    \"{:d}\".format(1.5)
+
+Fix:
+
+    \"%s: %d\" % (name, count)
+    \"{:.0f}\".format(1.5)"
+            }
+            ErrorCode::InvalidException => {
+                "A value that does not derive from BaseException is raised, used as the cause
+of an exception or caught in an except clause.
+
+Example:
+
+    raise \"not found\"
+
+Fix:
+
+    raise LookupError(\"not found\")"
+            }
+            ErrorCode::InvalidDefault => {
+                "The default value of a parameter is not assignable to the annotation of
+the parameter. A `None` default is only reported for annotations that do
+not include None with the `strict_optional` setting.
+
+Example:
+
+    def repeat(text: str, times: int = \"2\") -> str:
+        return text * int(times)
+
+Fix:
+
+    def repeat(text: str, times: int = 2) -> str:
+        return text * times"
+            }
+            ErrorCode::AlwaysTrueCondition => {
+                "A function is used as a condition. Functions are always true, the
+function was probably meant to be called.
+
+Example:
+
+    def is_ready() -> bool:
+        return True
+
+    if is_ready:
+        start()
+
+Fix:
+
+    if is_ready():
+        start()"
+            }
+            ErrorCode::UnsortedImports => {
+                "The imports at the top of a module are not sorted. Only reported when
+import sorting is enabled.
+Imports are grouped into `__future__`, standard library, third-party,
+first-party and relative imports, separated by a blank line. Within a group
+`import x` statements come before `from x import y` statements and both are
+sorted by module.
+
+Example:
+
+    import requests
+    from os import path
+    import sys
+
+Fix:
+
+    import sys
+    from os import path
+
+    import requests"
+            }
+            ErrorCode::MissingDocstring => {
+                "A public module, class or function has no docstring. Only reported when a
+docstring style is set. Names starting with an underscore are not public.
+
+Example:
+
+    def fetch(url: str) -> bytes:
+        ...
+
+Fix:
+
+    def fetch(url: str) -> bytes:
+        \"\"\"Downloads the content at the url.\"\"\"
+        ..."
+            }
+            ErrorCode::DocstringParameters => {
+                "The parameter section of a docstring does not match the signature. Only
+reported when a docstring style is set and the docstring has a parameter
+section.
+
+Example:
+
+    def fetch(url: str, timeout: float) -> bytes:
+        \"\"\"Downloads the content at the url.
+
+        Args:
+            address: Where to download from.
+        \"\"\"
+
+Fix:
+
+    def fetch(url: str, timeout: float) -> bytes:
+        \"\"\"Downloads the content at the url.
+
+        Args:
+            url: Where to download from.
+            timeout: Seconds to wait for a response.
+        \"\"\""
+            }
+        }
+    }
+
+    /// Returns the code of an error reported by the type evaluator.
+    /// Errors without a known code are reported as `TypeError`.
+    pub fn from_report(report: &miette::Report) -> Self {
+        report
+            .code()
+            .and_then(|code| code.to_string().parse().ok())
+            .unwrap_or(ErrorCode::TypeError)
+    }
+}
+
+impl From<&ParsingError> for ErrorCode {
+    fn from(error: &ParsingError) -> Self {
+        match error {
+            ParsingError::InvalidSyntax { .. } => ErrorCode::InvalidSyntax,
+            ParsingError::ExpectedToken { .. } => ErrorCode::ExpectedToken,
+            ParsingError::UnterminatedString { .. } => ErrorCode::UnterminatedString,
+            ParsingError::InvalidIndentation { .. } => ErrorCode::InvalidIndentation,
+            ParsingError::InvalidNumber { .. } => ErrorCode::InvalidNumber,
+            ParsingError::TooDeeplyNested { .. } => ErrorCode::TooDeeplyNested,
+            ParsingError::SourceTooLarge { .. } => ErrorCode::SourceTooLarge,
+            ParsingError::RepeatedKeywordArgument { .. } => ErrorCode::RepeatedKeywordArgument,
+            ParsingError::PositionalArgumentAfterKeyword { .. }
+            | ParsingError::UnpackingAfterKeywordUnpacking { .. } => {
+                ErrorCode::InvalidArgumentOrder
+            }
+            ParsingError::CannotAssign { .. } => ErrorCode::CannotAssign,
+            // Files are not parsed as interactive input
+            ParsingError::IncompleteInput { .. } => ErrorCode::InvalidSyntax,
+        }
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        ErrorCode::ALL
+            .iter()
+            .find(|code| code.as_str().eq_ignore_ascii_case(s) || code.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown error code {}", s))
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Codes of the diagnostics that are reported. A selector is a code, a prefix
+/// of codes or the name of a code, e.g. `E215`, `E2` or `assert-type-mismatch`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct RuleSelection {
+    /// Only the selected codes are reported, every code when empty
+    #[serde(default)]
+    pub select: Vec<String>,
+    /// Ignored codes are not reported even when they are selected
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+impl RuleSelection {
+    pub fn is_reported(&self, code: ErrorCode) -> bool {
+        let matches = |selector: &String| selector_matches(selector, code);
+        (self.select.is_empty() || self.select.iter().any(matches))
+            && !self.ignore.iter().any(matches)
+    }
+
+    /// Selectors that match no code
+    pub fn unknown_selectors(&self) -> Vec<&str> {
+        self.select
+            .iter()
+            .chain(self.ignore.iter())
+            .filter(|selector| {
+                !ErrorCode::ALL
+                    .iter()
+                    .any(|code| selector_matches(selector, *code))
+            })
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The selectors of `overrides` replace these, e.g. `--select` on the
+    /// command line replaces `select` of the config file and keeps its
+    /// `ignore`
+    pub fn merge(self, overrides: RuleSelection) -> RuleSelection {
+        let pick = |base: Vec<String>, over: Vec<String>| if over.is_empty() { base } else { over };
+        RuleSelection {
+            select: pick(self.select, overrides.select),
+            ignore: pick(self.ignore, overrides.ignore),
+        }
+    }
+}
+
+fn selector_matches(selector: &str, code: ErrorCode) -> bool {
+    let selector = selector.trim();
+    !selector.is_empty()
+        && (code.as_str().starts_with(&selector.to_ascii_uppercase()) || code.name() == selector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(select: &[&str], ignore: &[&str]) -> RuleSelection {
+        RuleSelection {
+            select: select.iter().map(|s| s.to_string()).collect(),
+            ignore: ignore.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_every_code_is_documented() {
+        // `url` links to the anchor of the heading
+        let docs = include_str!("../../docs/error-codes.md");
+        for code in ErrorCode::ALL {
+            let heading = format!("\n## {} {}\n", code.as_str(), code.name());
+            assert!(docs.contains(&heading), "{} is not documented", code);
+        }
+    }
+
+    #[test]
+    fn test_rule_selection() {
+        let all = RuleSelection::default();
+        assert!(all.is_reported(ErrorCode::InvalidSyntax));
+        assert!(all.is_reported(ErrorCode::AssertTypeMismatch));
+
+        let checker = selection(&["E2"], &["e203", "star-import"]);
+        assert!(!checker.is_reported(ErrorCode::InvalidSyntax));
+        assert!(checker.is_reported(ErrorCode::NotCallable));
+        assert!(!checker.is_reported(ErrorCode::UnsupportedOperator));
+        assert!(!checker.is_reported(ErrorCode::StarImport));
+        assert!(checker.unknown_selectors().is_empty());
+
+        assert_eq!(
+            selection(&["E0", "W2"], &["not-a-code"]).unknown_selectors(),
+            vec!["W2", "not-a-code"]
+        );
+    }
+
+    #[test]
+    fn test_merge_rule_selection() {
+        let config = selection(&["E2"], &["E203"]);
+        assert_eq!(
+            config.clone().merge(selection(&["E0"], &[])),
+            selection(&["E0"], &["E203"])
+        );
+        assert_eq!(
+            config.clone().merge(selection(&[], &["E201"])),
+            selection(&["E2"], &["E201"])
+        );
+        assert_eq!(config.clone().merge(RuleSelection::default()), config);
+    }
+}
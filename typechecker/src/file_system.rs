@@ -0,0 +1,108 @@
+// Access to the files of a build.
+// Sources and the files that imports resolve to are read through a
+// `FileSystem` so that editors can check the unsaved content of their buffers
+// instead of the files on disk.
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+pub trait FileSystem: Debug + Send + Sync {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    fn is_file(&self, path: &Path) -> bool;
+
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Last modification time of the file
+    fn modified(&self, path: &Path) -> io::Result<SystemTime>;
+}
+
+/// The file system of the operating system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        std::fs::metadata(path)?.modified()
+    }
+}
+
+/// In-memory files on top of another file system. A file in the overlay
+/// shadows the file with the same path in the base file system and the
+/// directories of the overlay files exist even when they are not on disk.
+#[derive(Debug, Clone)]
+pub struct OverlayFileSystem {
+    base: Arc<dyn FileSystem>,
+    files: HashMap<PathBuf, String>,
+}
+
+impl OverlayFileSystem {
+    pub fn new(base: Arc<dyn FileSystem>) -> Self {
+        Self {
+            base,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Sets the content of the file at `path`, e.g. the unsaved content of an
+    /// editor buffer
+    pub fn set_file(&mut self, path: PathBuf, content: String) {
+        self.files.insert(path, content);
+    }
+
+    /// Removes the file from the overlay, the file is read from the base file
+    /// system again
+    pub fn remove_file(&mut self, path: &Path) -> Option<String> {
+        self.files.remove(path)
+    }
+}
+
+impl FileSystem for OverlayFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self.files.get(path) {
+            Some(content) => Ok(content.clone()),
+            None => self.base.read_to_string(path),
+        }
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path) || self.base.is_file(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.files
+            .keys()
+            .any(|file| file.parent().is_some_and(|dir| dir.starts_with(path)))
+            || self.base.is_dir(path)
+    }
+
+    /// Files of the overlay change without being saved, they have no
+    /// modification time
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        if self.files.contains_key(path) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "files of the overlay have no modification time",
+            ));
+        }
+        self.base.modified(path)
+    }
+}
@@ -0,0 +1,179 @@
+// Inheritance trees of the classes in the built modules.
+// Base classes are resolved by name, first in the module that defines the
+// class and then in the rest of the project. Bases that cannot be resolved
+// (e.g. classes from modules that were not built) are kept as leaves.
+
+use std::{collections::HashSet, fmt::Display};
+
+use enderpy_python_parser::TextSize;
+
+use crate::{
+    state::State,
+    symbol_table::{Class, Declaration},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassNode {
+    pub name: String,
+    /// Module that defines the class, None if the class could not be resolved
+    pub module_name: Option<String>,
+    /// Base classes when the node is an ancestor, subclasses otherwise
+    pub children: Vec<ClassNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassHierarchy {
+    pub name: String,
+    pub module_name: String,
+    pub ancestors: Vec<ClassNode>,
+    pub subclasses: Vec<ClassNode>,
+}
+
+struct ClassEntry {
+    module_name: String,
+    class: Class,
+}
+
+pub(crate) fn class_hierarchy<'a>(
+    modules: impl Iterator<Item = &'a State>,
+    class_name: &str,
+) -> Vec<ClassHierarchy> {
+    let mut classes = vec![];
+    for state in modules {
+        let symbol_table = state.get_symbol_table();
+        for scope in symbol_table.iter_scopes() {
+            for symbol in scope.symbols().values() {
+                for declaration in &symbol.declarations {
+                    if let Declaration::Class(c) = declaration {
+                        if c.declaration_path.module_name == "builtins" {
+                            continue;
+                        }
+                        classes.push(ClassEntry {
+                            module_name: state.file.module_name(),
+                            class: c.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    classes.sort_by(|a, b| {
+        (&a.module_name, a.class.declaration_path.node.start)
+            .cmp(&(&b.module_name, b.class.declaration_path.node.start))
+    });
+
+    classes
+        .iter()
+        .filter(|entry| entry.class.name == class_name)
+        .map(|entry| ClassHierarchy {
+            name: entry.class.name.clone(),
+            module_name: entry.module_name.clone(),
+            ancestors: ancestors(&classes, entry, &mut HashSet::new()),
+            subclasses: subclasses(&classes, entry, &mut HashSet::new()),
+        })
+        .collect()
+}
+
+fn resolve_base<'a>(
+    classes: &'a [ClassEntry],
+    entry: &ClassEntry,
+    base: &str,
+) -> Option<&'a ClassEntry> {
+    let name = base.rsplit('.').next().unwrap_or(base);
+    classes
+        .iter()
+        .find(|c| c.class.name == name && c.module_name == entry.module_name)
+        .or_else(|| classes.iter().find(|c| c.class.name == name))
+}
+
+fn key(entry: &ClassEntry) -> (String, TextSize) {
+    (
+        entry.module_name.clone(),
+        entry.class.declaration_path.node.start,
+    )
+}
+
+fn ancestors(
+    classes: &[ClassEntry],
+    entry: &ClassEntry,
+    visited: &mut HashSet<(String, TextSize)>,
+) -> Vec<ClassNode> {
+    visited.insert(key(entry));
+    let mut nodes = vec![];
+    for base in &entry.class.bases {
+        let node = match resolve_base(classes, entry, base) {
+            Some(base_entry) if !visited.contains(&key(base_entry)) => ClassNode {
+                name: base_entry.class.name.clone(),
+                module_name: Some(base_entry.module_name.clone()),
+                children: ancestors(classes, base_entry, visited),
+            },
+            _ => ClassNode {
+                name: base.clone(),
+                module_name: None,
+                children: vec![],
+            },
+        };
+        nodes.push(node);
+    }
+    visited.remove(&key(entry));
+    nodes
+}
+
+fn subclasses(
+    classes: &[ClassEntry],
+    entry: &ClassEntry,
+    visited: &mut HashSet<(String, TextSize)>,
+) -> Vec<ClassNode> {
+    visited.insert(key(entry));
+    let mut nodes = vec![];
+    for candidate in classes {
+        if visited.contains(&key(candidate)) {
+            continue;
+        }
+        let is_subclass = candidate.class.bases.iter().any(|base| {
+            resolve_base(classes, candidate, base).is_some_and(|b| key(b) == key(entry))
+        });
+        if is_subclass {
+            nodes.push(ClassNode {
+                name: candidate.class.name.clone(),
+                module_name: Some(candidate.module_name.clone()),
+                children: subclasses(classes, candidate, visited),
+            });
+        }
+    }
+    visited.remove(&key(entry));
+    nodes
+}
+
+impl Display for ClassNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_node(self, f, "")
+    }
+}
+
+fn fmt_node(node: &ClassNode, f: &mut std::fmt::Formatter<'_>, indent: &str) -> std::fmt::Result {
+    match &node.module_name {
+        Some(module_name) => writeln!(f, "{}{} ({})", indent, node.name, module_name)?,
+        None => writeln!(f, "{}{}", indent, node.name)?,
+    }
+    let indent = format!("{}    ", indent);
+    for child in &node.children {
+        fmt_node(child, f, &indent)?;
+    }
+    Ok(())
+}
+
+impl Display for ClassHierarchy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} ({})", self.name, self.module_name)?;
+        writeln!(f, "ancestors:")?;
+        for ancestor in &self.ancestors {
+            fmt_node(ancestor, f, "    ")?;
+        }
+        writeln!(f, "subclasses:")?;
+        for subclass in &self.subclasses {
+            fmt_node(subclass, f, "    ")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+// Checks that the imports at the top of a module are sorted and grouped into
+// sections like isort does. The imports are reordered as whole lines, so
+// comments above an import and at the end of its last line move with it.
+// Blocks where an import shares a line with another statement are skipped.
+
+use enderpy_python_parser::{
+    TextSize,
+    ast::{GetNode, Statement},
+};
+
+use crate::{
+    diagnostic::{Applicability, CharacterSpan, Diagnostic, Fix, TextEdit},
+    error_code::ErrorCode,
+    ruff_python_import_resolver::import_result::ImportType,
+    state::State,
+};
+
+/// Sections of an import block in the order they must appear in. Sections
+/// are separated by a blank line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Section {
+    Future,
+    StandardLibrary,
+    ThirdParty,
+    FirstParty,
+    /// Relative imports
+    Local,
+}
+
+/// An import statement with the comment lines above it
+struct ImportLines {
+    section: Section,
+    is_from: bool,
+    module: String,
+    text: String,
+}
+
+/// Diagnostics with a fix for the import blocks of the module that are not
+/// sorted
+pub fn unsorted_imports(state: &State) -> Vec<Diagnostic> {
+    let source = state.file.source();
+    import_blocks(&state.file.body)
+        .into_iter()
+        .filter_map(|block| unsorted_block(state, &source, &block))
+        .collect()
+}
+
+/// Runs of consecutive import statements in the module body
+fn import_blocks(body: &[Statement]) -> Vec<Vec<&Statement>> {
+    let mut blocks: Vec<Vec<&Statement>> = vec![];
+    let mut in_block = false;
+    for stmt in body {
+        let is_import = matches!(stmt, Statement::Import(_) | Statement::ImportFrom(_));
+        match blocks.last_mut() {
+            Some(block) if is_import && in_block => block.push(stmt),
+            _ if is_import => blocks.push(vec![stmt]),
+            _ => (),
+        }
+        in_block = is_import;
+    }
+    blocks
+}
+
+fn unsorted_block(state: &State, source: &str, block: &[&Statement]) -> Option<Diagnostic> {
+    if block.len() < 2 {
+        return None;
+    }
+    let block_start = line_start(source, block[0].get_node().range().start);
+    let mut block_end = block_start;
+    let mut imports = vec![];
+    for stmt in block {
+        let node = stmt.get_node().range();
+        let start = line_start(source, node.start);
+        let end = line_end(source, node.start, node.end);
+        let rest_of_line = source[node.end..end].trim();
+        if !source[start..node.start].trim().is_empty()
+            || !(rest_of_line.is_empty() || rest_of_line.starts_with('#'))
+        {
+            return None;
+        }
+        // Only comments and blank lines can be between the statements
+        let mut text = String::new();
+        for line in source[block_end..start].lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if !line.starts_with('#') {
+                return None;
+            }
+            text.push_str(line);
+            text.push('\n');
+        }
+        text.push_str(&source[start..end]);
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        let (is_from, module) = match stmt {
+            Statement::Import(import) => (false, import.names.first()?.name.clone()),
+            Statement::ImportFrom(import) => (
+                true,
+                format!("{}{}", ".".repeat(import.level), import.module),
+            ),
+            _ => return None,
+        };
+        imports.push(ImportLines {
+            section: section(state, &module),
+            is_from,
+            module,
+            text,
+        });
+        block_end = end;
+    }
+
+    imports.sort_by_key(|import| (import.section, import.is_from, import.module.to_lowercase()));
+    let mut expected = String::new();
+    for (i, import) in imports.iter().enumerate() {
+        if i > 0 && imports[i - 1].section != import.section {
+            expected.push('\n');
+        }
+        expected.push_str(&import.text);
+    }
+    let actual = &source[block_start..block_end];
+    if !actual.ends_with('\n') {
+        expected.pop();
+    }
+    if expected == actual {
+        return None;
+    }
+
+    let span = CharacterSpan(block_start as TextSize, block_end as TextSize);
+    Some(Diagnostic {
+        body: "Imports are not sorted".to_string(),
+        suggestion: Some(
+            "group the imports into `__future__`, standard library, third-party, first-party and relative imports and sort them by module".to_string(),
+        ),
+        range: state.file.get_range(&span),
+        code: ErrorCode::UnsortedImports,
+        fix: Some(Fix {
+            message: "Sort imports".to_string(),
+            edits: vec![TextEdit {
+                span,
+                new_text: expected,
+            }],
+            applicability: Applicability::Safe,
+        }),
+        labels: vec![],
+    })
+}
+
+/// Section of the module, determined by where the import resolver found it.
+/// Modules that are not found are third-party.
+fn section(state: &State, module: &str) -> Section {
+    if module == "__future__" {
+        return Section::Future;
+    }
+    if module.starts_with('.') {
+        return Section::Local;
+    }
+    match state.imports.get(module).map(|result| result.import_type) {
+        Some(ImportType::BuiltIn) => Section::StandardLibrary,
+        Some(ImportType::Local) => Section::FirstParty,
+        Some(ImportType::ThirdParty) | None => Section::ThirdParty,
+    }
+}
+
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Offset after the newline of the line that the node ends on
+fn line_end(source: &str, start: usize, end: usize) -> usize {
+    // The end of a node can be the offset of the newline that ends it
+    let from = end.saturating_sub(1).max(start);
+    source[from..]
+        .find('\n')
+        .map_or(source.len(), |i| from + i + 1)
+}
@@ -1,15 +1,28 @@
 mod ast_visitor;
 mod ast_visitor_generic;
+mod import_sorting;
 mod nodes;
 mod ruff_python_import_resolver;
 mod semanal_utils;
+mod star_import;
 mod state;
 mod symbol_table;
 mod type_check;
+mod typeshed_cache;
 
 pub mod build;
 pub mod build_source;
+pub mod cancellation;
 pub mod diagnostic;
+pub mod error_code;
+pub mod file_system;
+pub mod hierarchy;
+pub mod metrics;
+pub mod module_info;
+pub mod notebook;
+pub mod plugin;
 pub mod project;
 pub mod semantic_analyzer;
 pub mod settings;
+pub mod symbol_query;
+pub mod unused;
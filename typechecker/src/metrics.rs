@@ -0,0 +1,318 @@
+// Code metrics computed from the AST.
+// Cyclomatic complexity follows the usual definition of 1 + number of
+// decision points. Decision points are branches (if, elif, while, for,
+// except, match case), conditional expressions, boolean operators and
+// comprehension clauses.
+
+use std::path::PathBuf;
+
+use enderpy_python_parser::{Parser, TextSize, ast::*};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionMetrics {
+    /// Name of the function including the enclosing classes and functions,
+    /// e.g. `Class.method`
+    pub name: String,
+    /// 1-based line of the function definition
+    pub line: u32,
+    pub cyclomatic_complexity: usize,
+    pub statements: usize,
+    pub max_nesting_depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileMetrics {
+    pub path: PathBuf,
+    pub statements: usize,
+    pub functions: Vec<FunctionMetrics>,
+}
+
+impl FileMetrics {
+    pub fn from_source(path: PathBuf, source: &str) -> Self {
+        let mut parser = Parser::new(source.to_string(), path.to_string_lossy().to_string());
+        let module = parser.parse();
+        let mut collector = MetricsCollector {
+            source,
+            scope: vec![],
+            functions: vec![],
+            statements: 0,
+        };
+        collector.visit_body(&module.body);
+        FileMetrics {
+            path,
+            statements: collector.statements,
+            functions: collector.functions,
+        }
+    }
+}
+
+/// Metrics of a block of statements
+#[derive(Default)]
+struct BlockMetrics {
+    complexity: usize,
+    statements: usize,
+    depth: usize,
+}
+
+impl BlockMetrics {
+    fn add(&mut self, other: BlockMetrics) {
+        self.complexity += other.complexity;
+        self.statements += other.statements;
+        self.depth = self.depth.max(other.depth);
+    }
+
+    fn nested(&mut self, other: BlockMetrics) {
+        self.complexity += other.complexity;
+        self.statements += other.statements;
+        self.depth = self.depth.max(other.depth + 1);
+    }
+}
+
+struct MetricsCollector<'a> {
+    source: &'a str,
+    scope: Vec<String>,
+    functions: Vec<FunctionMetrics>,
+    /// Number of statements in the file
+    statements: usize,
+}
+
+impl<'a> MetricsCollector<'a> {
+    fn line(&self, pos: TextSize) -> u32 {
+        let end = (pos as usize).min(self.source.len());
+        self.source.as_bytes()[..end]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count() as u32
+            + 1
+    }
+
+    fn visit_body(&mut self, body: &[Statement]) -> BlockMetrics {
+        let mut metrics = BlockMetrics::default();
+        for stmt in body {
+            metrics.add(self.visit_stmt(stmt));
+        }
+        metrics
+    }
+
+    fn visit_function(
+        &mut self,
+        name: &str,
+        node: Node,
+        args: &Arguments,
+        decorators: &[Expression],
+        body: &[Statement],
+    ) {
+        self.scope.push(name.to_string());
+        let mut metrics = self.visit_body(body);
+        let defaults = args
+            .defaults
+            .iter()
+            .chain(args.kw_defaults.iter().flatten());
+        for expr in defaults.chain(decorators.iter()) {
+            metrics.complexity += expr_complexity(expr);
+        }
+        self.functions.push(FunctionMetrics {
+            name: self.scope.join("."),
+            line: self.line(node.start),
+            cyclomatic_complexity: metrics.complexity + 1,
+            statements: metrics.statements,
+            max_nesting_depth: metrics.depth,
+        });
+        self.scope.pop();
+    }
+
+    fn visit_stmt(&mut self, stmt: &Statement) -> BlockMetrics {
+        self.statements += 1;
+        let mut metrics = BlockMetrics {
+            statements: 1,
+            ..Default::default()
+        };
+        match stmt {
+            Statement::FunctionDef(f) => {
+                self.visit_function(&f.name, f.node, &f.args, &f.decorator_list, &f.body);
+            }
+            Statement::AsyncFunctionDef(f) => {
+                self.visit_function(&f.name, f.node, &f.args, &f.decorator_list, &f.body);
+            }
+            Statement::ClassDef(c) => {
+                self.scope.push(c.name.clone());
+                // Class bodies are not part of the enclosing function but
+                // the methods are collected
+                self.visit_body(&c.body);
+                self.scope.pop();
+            }
+            Statement::IfStatement(i) => {
+                metrics.complexity += 1 + expr_complexity(&i.test);
+                metrics.nested(self.visit_body(&i.body));
+                match i.orelse.as_slice() {
+                    // `elif` branches are on the same level as the `if`
+                    [Statement::IfStatement(elif)]
+                        if self.source[elif.node.start as usize..].starts_with("elif") =>
+                    {
+                        self.statements -= 1;
+                        let mut elif_metrics = self.visit_stmt(&i.orelse[0]);
+                        elif_metrics.statements -= 1;
+                        metrics.add(elif_metrics);
+                    }
+                    orelse => metrics.nested(self.visit_body(orelse)),
+                }
+            }
+            Statement::WhileStatement(w) => {
+                metrics.complexity += 1 + expr_complexity(&w.test);
+                metrics.nested(self.visit_body(&w.body));
+                metrics.nested(self.visit_body(&w.orelse));
+            }
+            Statement::ForStatement(f) => {
+                metrics.complexity += 1 + expr_complexity(&f.iter);
+                metrics.nested(self.visit_body(&f.body));
+                metrics.nested(self.visit_body(&f.orelse));
+            }
+            Statement::AsyncForStatement(f) => {
+                metrics.complexity += 1 + expr_complexity(&f.iter);
+                metrics.nested(self.visit_body(&f.body));
+                metrics.nested(self.visit_body(&f.orelse));
+            }
+            Statement::WithStatement(w) => {
+                for item in &w.items {
+                    metrics.complexity += expr_complexity(&item.context_expr);
+                }
+                metrics.nested(self.visit_body(&w.body));
+            }
+            Statement::AsyncWithStatement(w) => {
+                for item in &w.items {
+                    metrics.complexity += expr_complexity(&item.context_expr);
+                }
+                metrics.nested(self.visit_body(&w.body));
+            }
+            Statement::TryStatement(t) => {
+                self.visit_try(&mut metrics, &t.body, &t.handlers, &t.orelse, &t.finalbody);
+            }
+            Statement::TryStarStatement(t) => {
+                self.visit_try(&mut metrics, &t.body, &t.handlers, &t.orelse, &t.finalbody);
+            }
+            Statement::Match(m) => {
+                metrics.complexity += expr_complexity(&m.subject);
+                for case in &m.cases {
+                    metrics.complexity += 1;
+                    if let Some(guard) = &case.guard {
+                        metrics.complexity += expr_complexity(guard);
+                    }
+                    metrics.nested(self.visit_body(&case.body));
+                }
+            }
+            Statement::ExpressionStatement(e) => metrics.complexity += expr_complexity(e),
+            Statement::AssignStatement(a) => metrics.complexity += expr_complexity(&a.value),
+            Statement::AnnAssignStatement(a) => {
+                if let Some(value) = &a.value {
+                    metrics.complexity += expr_complexity(value);
+                }
+            }
+            Statement::AugAssignStatement(a) => metrics.complexity += expr_complexity(&a.value),
+            Statement::Assert(a) => metrics.complexity += expr_complexity(&a.test),
+            Statement::Return(r) => {
+                if let Some(value) = &r.value {
+                    metrics.complexity += expr_complexity(value);
+                }
+            }
+            Statement::Raise(r) => {
+                if let Some(exc) = &r.exc {
+                    metrics.complexity += expr_complexity(exc);
+                }
+            }
+            Statement::Import(_)
+            | Statement::ImportFrom(_)
+            | Statement::Pass(_)
+            | Statement::Delete(_)
+            | Statement::Break(_)
+            | Statement::Continue(_)
+            | Statement::Global(_)
+            | Statement::Nonlocal(_)
+            | Statement::TypeAlias(_) => {}
+        }
+        metrics
+    }
+
+    fn visit_try(
+        &mut self,
+        metrics: &mut BlockMetrics,
+        body: &[Statement],
+        handlers: &[ExceptHandler],
+        orelse: &[Statement],
+        finalbody: &[Statement],
+    ) {
+        metrics.nested(self.visit_body(body));
+        for handler in handlers {
+            metrics.complexity += 1;
+            metrics.nested(self.visit_body(&handler.body));
+        }
+        metrics.nested(self.visit_body(orelse));
+        metrics.nested(self.visit_body(finalbody));
+    }
+}
+
+/// Number of decision points inside an expression
+fn expr_complexity(expr: &Expression) -> usize {
+    let sum = |exprs: &[Expression]| exprs.iter().map(expr_complexity).sum::<usize>();
+    let comprehensions = |generators: &[Comprehension]| {
+        generators
+            .iter()
+            .map(|g| 1 + g.ifs.len() + expr_complexity(&g.iter) + sum(&g.ifs))
+            .sum::<usize>()
+    };
+    match expr {
+        Expression::BoolOp(b) => b.values.len().saturating_sub(1) + sum(&b.values),
+        Expression::IfExp(i) => {
+            1 + expr_complexity(&i.test) + expr_complexity(&i.body) + expr_complexity(&i.orelse)
+        }
+        Expression::Generator(g) => comprehensions(&g.generators) + expr_complexity(&g.element),
+        Expression::ListComp(l) => comprehensions(&l.generators) + expr_complexity(&l.element),
+        Expression::SetComp(s) => comprehensions(&s.generators) + expr_complexity(&s.element),
+        Expression::DictComp(d) => {
+            comprehensions(&d.generators) + expr_complexity(&d.key) + expr_complexity(&d.value)
+        }
+        Expression::List(l) => sum(&l.elements),
+        Expression::Tuple(t) => sum(&t.elements),
+        Expression::Set(s) => sum(&s.elements),
+        Expression::Dict(d) => sum(&d.keys) + sum(&d.values),
+        Expression::UnaryOp(u) => expr_complexity(&u.operand),
+        Expression::BinOp(b) => expr_complexity(&b.left) + expr_complexity(&b.right),
+        Expression::NamedExpr(n) => expr_complexity(&n.value),
+        Expression::Yield(y) => y.value.as_ref().map_or(0, |v| expr_complexity(v)),
+        Expression::YieldFrom(y) => expr_complexity(&y.value),
+        Expression::Starred(s) => expr_complexity(&s.value),
+        Expression::Attribute(a) => expr_complexity(&a.value),
+        Expression::Subscript(s) => expr_complexity(&s.value) + expr_complexity(&s.slice),
+        Expression::Call(c) => {
+            expr_complexity(&c.func)
+                + sum(&c.args)
+                + c.keywords
+                    .iter()
+                    .map(|k| expr_complexity(&k.value))
+                    .sum::<usize>()
+        }
+        Expression::Await(a) => expr_complexity(&a.value),
+        Expression::Compare(c) => expr_complexity(&c.left) + sum(&c.comparators),
+        Expression::Lambda(l) => expr_complexity(&l.body),
+        Expression::JoinedStr(j) => sum(&j.values),
+        Expression::FormattedValue(f) => expr_complexity(&f.value),
+        Expression::Slice(_) | Expression::Constant(_) | Expression::Name(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let contents = include_str!("../testdata/inputs/metrics.py");
+        let metrics = FileMetrics::from_source(PathBuf::from("test.py"), contents);
+        let mut settings = insta::Settings::clone_current();
+        settings.set_snapshot_path("../testdata/output/");
+        settings.set_description(contents);
+        settings.bind(|| {
+            insta::assert_debug_snapshot!(metrics);
+        });
+    }
+}
@@ -0,0 +1,46 @@
+// Statistics of the modules of a build. They help to find out why checking a
+// project is slow or why a module was pulled into the build by an import.
+
+use std::{path::PathBuf, time::Duration};
+
+use serde::Serialize;
+
+use crate::{state::State, symbol_table::SymbolTableType};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleInfo {
+    pub module_name: String,
+    pub path: PathBuf,
+    /// Number of lines of the source
+    pub lines: usize,
+    /// Time spent parsing the source. Zero when the time cannot be measured,
+    /// e.g. in wasm.
+    pub parse_time: Duration,
+    /// Number of symbols declared in all scopes of the module
+    pub symbols: usize,
+    pub diagnostics: usize,
+    /// Number of imports that were resolved to a file
+    pub resolved_imports: usize,
+    /// False when the AST was dropped after the symbol table was built,
+    /// which is done for imported modules that are not checked
+    pub ast_retained: bool,
+}
+
+pub(crate) fn module_info(state: &State) -> ModuleInfo {
+    let symbols = state
+        .symbol_table()
+        .iter_scopes()
+        .filter(|scope| scope.symbol_table_type != SymbolTableType::BUILTIN)
+        .map(|scope| scope.symbols().len())
+        .sum();
+    ModuleInfo {
+        module_name: state.file.module_name(),
+        path: state.file.path(),
+        lines: state.file.build_source.source.lines().count(),
+        parse_time: state.file.parse_time,
+        symbols,
+        diagnostics: state.diagnostics.len(),
+        resolved_imports: state.imports.len(),
+        ast_retained: state.file.has_ast(),
+    }
+}
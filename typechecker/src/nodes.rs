@@ -5,13 +5,18 @@
 // here, so this has the minimum amount of nodes needed to
 // get the type checker working. But can be expanded.
 
-use std::path::PathBuf;
+use std::{borrow::Cow, path::PathBuf, time::Duration};
 
 use enderpy_python_parser as parser;
 use enderpy_python_parser::ast::{Import, ImportFrom, Module, Statement};
-use parser::error::ParsingError;
+use parser::{Parser, TextSize, error::ParsingError};
 
-use crate::{ast_visitor::TraversalVisitor, build_source::BuildSource, diagnostic::Position};
+use crate::{
+    ast_visitor::TraversalVisitor,
+    build_source::{BuildSource, get_module_name},
+    diagnostic::{CharacterSpan, LineIndex, Position, Range},
+    settings::Settings,
+};
 
 #[derive(Clone, Debug)]
 pub enum ImportKinds {
@@ -26,11 +31,17 @@ pub struct EnderpyFile {
     // high level definitions inside the file
     pub defs: Vec<Statement>,
 
-    // All high level statements inside the file
+    // All high level statements inside the file, empty when the AST is dropped
     pub body: Vec<Statement>,
+    // Whether the statements were dropped to save memory
+    ast_dropped: bool,
     pub build_source: Box<BuildSource>,
     // Parser Errors
     pub errors: Vec<ParsingError>,
+    // Time spent parsing the file, set by the build manager
+    pub parse_time: Duration,
+    // Start offsets of the lines to convert offsets to positions
+    pub line_index: LineIndex,
 }
 
 impl EnderpyFile {
@@ -39,8 +50,11 @@ impl EnderpyFile {
             defs: vec![],
             imports: vec![],
             body: vec![],
-            build_source,
+            ast_dropped: false,
             errors,
+            parse_time: Duration::ZERO,
+            line_index: LineIndex::new(&build_source.source),
+            build_source,
         };
 
         for stmt in &ast.body {
@@ -51,6 +65,68 @@ impl EnderpyFile {
         file
     }
 
+    /// Parses the source of a file that is not read from disk, the module
+    /// name is derived from the path like for files that are
+    pub fn from_source(path: PathBuf, source: String) -> Self {
+        let mut parser = Parser::new(source.clone(), path.to_str().unwrap_or_default().into());
+        let ast = parser.parse();
+        let build_source = BuildSource {
+            module: get_module_name(&path),
+            path,
+            source,
+            followed: false,
+        };
+        Self::from(ast, Box::new(build_source), parser.errors)
+    }
+
+    /// Top level statements of the file, the source is parsed again when the
+    /// AST was dropped
+    pub fn ast(&self, options: &Settings) -> Cow<'_, [Statement]> {
+        if self.ast_dropped {
+            Cow::Owned(self.parse_body(options))
+        } else {
+            Cow::Borrowed(&self.body)
+        }
+    }
+
+    pub fn has_ast(&self) -> bool {
+        !self.ast_dropped
+    }
+
+    /// Frees the statements of the file. Imported modules only need their
+    /// symbol table after it is built, `ast` parses the source again for
+    /// the passes that need the statements.
+    pub fn drop_ast(&mut self) {
+        self.body = vec![];
+        self.defs = vec![];
+        self.ast_dropped = true;
+    }
+
+    /// Parses the source again if the AST was dropped
+    pub fn restore_ast(&mut self, options: &Settings) {
+        if self.ast_dropped {
+            self.body = self.parse_body(options);
+            self.ast_dropped = false;
+        }
+    }
+
+    /// Parses the source with the same options the build manager parsed it with
+    fn parse_body(&self, options: &Settings) -> Vec<Statement> {
+        let path = self.build_source.path.to_str().unwrap_or_default();
+        let mut parser = Parser::new(self.build_source.source.clone(), path.into());
+        parser.set_max_nesting_depth(options.max_nesting_depth);
+        parser.parse().body
+    }
+
+    /// Imports of the file, including the ones in nested blocks
+    pub fn imports(&self) -> &[ImportKinds] {
+        &self.imports
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
     pub fn module_name(&self) -> String {
         self.build_source.module.clone()
     }
@@ -63,23 +139,29 @@ impl EnderpyFile {
         self.build_source.source.clone()
     }
 
-    pub fn get_position(&self, pos: usize) -> Position {
-        let mut line_number = 0;
-        let mut line_start = 0;
-        for (i, c) in self.build_source.source.chars().enumerate() {
-            if i == pos {
-                break;
-            }
-            if c == '\n' {
-                line_number += 1;
-                line_start = i;
-            }
-        }
-        Position {
-            line: line_number,
-            character: (pos - line_start - 1) as u32,
+    pub fn get_position(&self, pos: TextSize) -> Position {
+        self.line_index.position(&self.build_source.source, pos)
+    }
+
+    /// Zero based line of the offset
+    pub fn get_line(&self, pos: TextSize) -> u32 {
+        self.line_index.line(pos)
+    }
+
+    pub fn get_range(&self, span: &CharacterSpan) -> Range {
+        Range {
+            start: self.get_position(span.0),
+            end: self.get_position(span.1),
         }
     }
+
+    /// Converts the secondary labels of an error to ranges in the file
+    pub fn get_labels(&self, labels: &[(CharacterSpan, String)]) -> Vec<(Range, String)> {
+        labels
+            .iter()
+            .map(|(span, message)| (self.get_range(span), message.clone()))
+            .collect()
+    }
 }
 
 impl TraversalVisitor for EnderpyFile {
@@ -186,9 +268,8 @@ impl TraversalVisitor for EnderpyFile {
         }
         for with_items in &w.items {
             self.visit_expr(&with_items.context_expr);
-            match &with_items.optional_vars {
-                Some(items) => self.visit_expr(items),
-                None => (),
+            if let Some(items) = &with_items.optional_vars {
+                self.visit_expr(items)
             }
         }
     }
@@ -337,3 +418,70 @@ impl TraversalVisitor for EnderpyFile {
 
     fn visit_type_alias(&mut self, _t: &parser::ast::TypeAlias) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_source() {
+        let source = "import os\n\nif True:\n    from a import b\nx = 1\n";
+        let file = EnderpyFile::from_source(PathBuf::from("pkg/mod.py"), source.to_string());
+
+        assert_eq!(file.module_name(), "pkg.mod.py");
+        assert!(file.errors.is_empty());
+        assert_eq!(file.ast(&Settings::default()).len(), 3);
+        assert_eq!(file.imports().len(), 2);
+        assert_eq!(file.get_line(0), 0);
+        assert_eq!(file.get_line(source.find("from").unwrap() as TextSize), 3);
+        assert_eq!(file.get_line(source.len() as TextSize), 5);
+        assert_eq!(
+            file.line_index()
+                .line(source.find('x').unwrap() as TextSize),
+            4
+        );
+    }
+
+    #[test]
+    fn test_drop_ast() {
+        let source = "import os\n\ndef f():\n    pass\n";
+        let mut file = EnderpyFile::from_source(PathBuf::from("mod.py"), source.to_string());
+        let statements = file.body.clone();
+
+        file.drop_ast();
+        assert!(!file.has_ast());
+        assert!(file.body.is_empty());
+        assert_eq!(file.imports().len(), 1);
+        assert_eq!(file.ast(&Settings::default()).len(), 2);
+
+        file.restore_ast(&Settings::default());
+        assert!(file.has_ast());
+        assert_eq!(format!("{:?}", file.body), format!("{:?}", statements));
+    }
+
+    #[test]
+    fn test_restore_ast_nesting_depth() {
+        let source = format!("x = {}1{}\n", "(".repeat(20), ")".repeat(20));
+        let options = Settings {
+            max_nesting_depth: 10,
+            ..Default::default()
+        };
+        let mut parser = Parser::new(source.clone(), "mod.py".into());
+        parser.set_max_nesting_depth(options.max_nesting_depth);
+        let ast = parser.parse();
+        let build_source = BuildSource {
+            module: String::from("mod"),
+            path: PathBuf::from("mod.py"),
+            source,
+            followed: false,
+        };
+        let mut file = EnderpyFile::from(ast, Box::new(build_source), parser.errors);
+        let statements = format!("{:?}", file.body);
+
+        // The source is parsed again with the same nesting limit
+        file.drop_ast();
+        assert_eq!(format!("{:?}", file.ast(&options)), statements);
+        file.restore_ast(&options);
+        assert_eq!(format!("{:?}", file.body), statements);
+    }
+}
@@ -0,0 +1,160 @@
+// Support for Jupyter notebooks.
+// The code cells of a notebook are concatenated into a single python source
+// so that the parser and the checker can run on it like on any other file.
+// Each cell starts on a new line, the start line of every cell is kept to map
+// positions in the concatenated source back to the cells.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::diagnostic::{Position, Range};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotebookCell {
+    /// 1-based number of the cell among the code cells of the notebook, this
+    /// is the number shown to users
+    pub number: usize,
+    /// 0-based line of the concatenated source where the cell starts
+    pub start_line: u32,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notebook {
+    pub cells: Vec<NotebookCell>,
+}
+
+#[derive(Deserialize)]
+struct RawNotebook {
+    cells: Vec<RawCell>,
+}
+
+#[derive(Deserialize)]
+struct RawCell {
+    cell_type: String,
+    source: RawSource,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawSource {
+    Lines(Vec<String>),
+    Text(String),
+}
+
+impl Notebook {
+    pub fn is_notebook(path: &Path) -> bool {
+        path.extension().is_some_and(|ext| ext == "ipynb")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let raw: RawNotebook = serde_json::from_str(json)?;
+        let mut cells = vec![];
+        let mut start_line = 0;
+        for cell in raw.cells {
+            if cell.cell_type != "code" {
+                continue;
+            }
+            let source = match cell.source {
+                RawSource::Lines(lines) => lines.concat(),
+                RawSource::Text(text) => text,
+            };
+            let source = comment_magics(source.trim_end_matches('\n'));
+            let lines = source.lines().count().max(1) as u32;
+            cells.push(NotebookCell {
+                number: cells.len() + 1,
+                start_line,
+                source,
+            });
+            start_line += lines;
+        }
+        Ok(Notebook { cells })
+    }
+
+    /// Python source of all code cells, one cell after another
+    pub fn source(&self) -> String {
+        let mut source = String::new();
+        for cell in &self.cells {
+            if cell.source.is_empty() {
+                source.push('\n');
+                continue;
+            }
+            source.push_str(&cell.source);
+            source.push('\n');
+        }
+        source
+    }
+
+    /// Maps a 0-based line of the concatenated source to the cell containing
+    /// it and the 0-based line inside that cell
+    pub fn cell_position(&self, line: u32) -> Option<(&NotebookCell, u32)> {
+        let cell = self
+            .cells
+            .iter()
+            .take_while(|cell| cell.start_line <= line)
+            .last()?;
+        Some((cell, line - cell.start_line))
+    }
+
+    /// Maps a range of the concatenated source to the cell containing its
+    /// start and the range relative to that cell
+    pub fn cell_range(&self, range: &Range) -> Option<(&NotebookCell, Range)> {
+        let (cell, start_line) = self.cell_position(range.start.line)?;
+        let range = Range {
+            start: Position {
+                line: start_line,
+                character: range.start.character,
+            },
+            end: Position {
+                line: range.end.line.saturating_sub(cell.start_line),
+                character: range.end.character,
+            },
+        };
+        Some((cell, range))
+    }
+}
+
+/// IPython magics and shell commands are not python, they are commented out
+/// so that the positions of the other lines do not change
+fn comment_magics(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('%') || trimmed.starts_with('!') {
+                let indent = line.len() - trimmed.len();
+                format!("{}#{}", &line[..indent], &trimmed[1..])
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notebook_cells() {
+        let json = r##"{
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title"]},
+                {"cell_type": "code", "source": ["%matplotlib inline\n", "import os\n", "x = 1"]},
+                {"cell_type": "code", "source": "def f():\n    return x\n"},
+                {"cell_type": "code", "source": []}
+            ]
+        }"##;
+        let notebook = Notebook::from_json(json).unwrap();
+        assert_eq!(
+            notebook.source(),
+            "#matplotlib inline\nimport os\nx = 1\ndef f():\n    return x\n\n"
+        );
+        let (cell, line) = notebook.cell_position(4).unwrap();
+        assert_eq!((cell.number, line), (2, 1));
+        let (cell, line) = notebook.cell_position(5).unwrap();
+        assert_eq!((cell.number, line), (3, 0));
+    }
+}
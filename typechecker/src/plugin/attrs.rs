@@ -0,0 +1,236 @@
+// Support for attrs classes. The decorators of attrs generate an `__init__`
+// from the attributes of the class body and of the attrs classes it
+// inherits from.
+
+use enderpy_python_parser::ast::{Call, ClassDef, ConstantValue, Expression, Statement};
+
+use super::{
+    CheckerPlugin, InitParameter, PluginContext, add_parameter, init_arguments, is_typing,
+};
+
+/// Decorators that read annotated attributes unless `auto_attribs=False`
+const DEFINE: &[&str] = &[
+    "attr.define",
+    "attr.mutable",
+    "attr.frozen",
+    "attrs.define",
+    "attrs.mutable",
+    "attrs.frozen",
+];
+/// Decorators that only read attributes assigned with `attr.ib()` unless
+/// `auto_attribs=True`
+const ATTRS: &[&str] = &["attr.s", "attr.attrs", "attr.dataclass"];
+const FIELD: &[&str] = &[
+    "attr.ib",
+    "attr.attr",
+    "attr.attrib",
+    "attr.field",
+    "attrs.field",
+];
+
+/// Checks the arguments of calls that construct attrs classes
+#[derive(Debug, Default)]
+pub struct AttrsPlugin;
+
+impl CheckerPlugin for AttrsPlugin {
+    fn on_call(&self, call: &Call, ctx: &mut PluginContext) {
+        let Expression::Name(n) = call.func.as_ref() else {
+            return;
+        };
+        let Some(class) = ctx.lookup_class_def(&n.id, n.node.start) else {
+            return;
+        };
+        let Some(options) = attrs_options(class, ctx) else {
+            return;
+        };
+        let defines_init = class
+            .body
+            .iter()
+            .any(|stmt| matches!(stmt, Statement::FunctionDef(f) if f.name == "__init__"));
+        if !options.init || defines_init {
+            return;
+        }
+        let Some(parameters) = class_fields(class, ctx).and_then(|f| init_arguments(&f, false))
+        else {
+            return;
+        };
+        ctx.check_call_arguments(call, &parameters, &class.name);
+    }
+}
+
+/// Options of the attrs decorator of a class
+#[derive(Debug, Clone, Copy)]
+struct AttrsOptions {
+    /// Annotated attributes are fields even without `attr.ib()`
+    auto_attribs: Option<bool>,
+    /// `init=False` skips generating `__init__`
+    init: bool,
+    kw_only: bool,
+}
+
+fn attrs_options(class: &ClassDef, ctx: &PluginContext) -> Option<AttrsOptions> {
+    class.decorator_list.iter().find_map(|decorator| {
+        let (func, keywords) = match decorator {
+            Expression::Call(c) => (c.func.as_ref(), c.keywords.as_slice()),
+            decorator => (decorator, [].as_slice()),
+        };
+        let name = ctx.full_name(func)?;
+        let mut options = AttrsOptions {
+            auto_attribs: match name.as_str() {
+                name if DEFINE.contains(&name) => None,
+                "attr.dataclass" => Some(true),
+                name if ATTRS.contains(&name) => Some(false),
+                _ => return None,
+            },
+            init: true,
+            kw_only: false,
+        };
+        for keyword in keywords {
+            let value = match keyword.value.as_ref() {
+                Expression::Constant(c) => match c.value {
+                    ConstantValue::Bool(b) => b,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            match keyword.arg.as_deref() {
+                Some("auto_attribs") => options.auto_attribs = Some(value),
+                Some("init") => options.init = value,
+                Some("kw_only") => options.kw_only = value,
+                _ => {}
+            }
+        }
+        Some(options)
+    })
+}
+
+/// Parameters of the `__init__` that attrs generates for the class, the
+/// fields of the bases come first. Returns None when a base class is not
+/// known, e.g. an imported class whose fields cannot be seen.
+fn class_fields(class: &ClassDef, ctx: &PluginContext) -> Option<Vec<InitParameter>> {
+    let mut fields = vec![];
+    for base in &class.bases {
+        if ctx.full_name(base).as_deref() == Some("builtins.object") {
+            continue;
+        }
+        let Expression::Name(base) = base else {
+            return None;
+        };
+        let base_class = ctx.lookup_class_def(&base.id, base.node.start)?;
+        for field in class_fields(base_class, ctx)? {
+            add_parameter(&mut fields, field);
+        }
+    }
+    // Classes that are not decorated inherit the fields of their bases
+    let Some(options) = attrs_options(class, ctx) else {
+        return Some(fields);
+    };
+
+    let attributes = class
+        .body
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Statement::AnnAssignStatement(a) => match &a.target {
+                Expression::Name(n) if !is_typing(ctx, &a.annotation, "ClassVar") => {
+                    Some((n.id.as_str(), true, a.value.as_ref()))
+                }
+                _ => None,
+            },
+            Statement::AssignStatement(a) => match a.targets.as_slice() {
+                [Expression::Name(n)] => Some((n.id.as_str(), false, Some(&a.value))),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    // Without `auto_attribs` attrs uses the annotated attributes when no
+    // attribute is assigned `attr.ib()` without an annotation
+    let auto_attribs = options.auto_attribs.unwrap_or_else(|| {
+        !attributes
+            .iter()
+            .any(|(_, annotated, value)| !annotated && field_call(ctx, *value).is_some())
+    });
+
+    for (name, annotated, value) in attributes {
+        let call = field_call(ctx, value);
+        if call.is_none() && !(auto_attribs && annotated) {
+            continue;
+        }
+        let options = FieldOptions::new(call, options.kw_only);
+        if !options.init {
+            continue;
+        }
+        add_parameter(
+            &mut fields,
+            InitParameter {
+                // attrs strips the leading underscores of private attributes
+                name: options
+                    .alias
+                    .unwrap_or_else(|| name.trim_start_matches('_').to_string()),
+                has_default: options.has_default || (call.is_none() && value.is_some()),
+                kw_only: options.kw_only,
+            },
+        );
+    }
+    Some(fields)
+}
+
+/// The value of the attribute if it is an `attr.ib()` or `field()` call
+fn field_call<'e>(ctx: &PluginContext, value: Option<&'e Expression>) -> Option<&'e Call> {
+    match value {
+        Some(Expression::Call(c))
+            if ctx
+                .full_name(&c.func)
+                .is_some_and(|name| FIELD.contains(&name.as_str())) =>
+        {
+            Some(c)
+        }
+        _ => None,
+    }
+}
+
+/// Options of an `attr.ib()` or `field()` call
+struct FieldOptions {
+    init: bool,
+    has_default: bool,
+    kw_only: bool,
+    alias: Option<String>,
+}
+
+impl FieldOptions {
+    fn new(call: Option<&Call>, kw_only: bool) -> Self {
+        let mut options = FieldOptions {
+            init: true,
+            has_default: false,
+            kw_only,
+            alias: None,
+        };
+        let Some(call) = call else {
+            return options;
+        };
+        for keyword in &call.keywords {
+            let value = keyword.value.as_ref();
+            let flag = |default| match value {
+                Expression::Constant(c) => match c.value {
+                    ConstantValue::Bool(b) => b,
+                    _ => default,
+                },
+                _ => default,
+            };
+            match keyword.arg.as_deref() {
+                Some("default" | "factory") => options.has_default = true,
+                Some("init") => options.init = flag(true),
+                Some("kw_only") => options.kw_only = flag(options.kw_only),
+                Some("alias") => {
+                    if let Expression::Constant(c) = value {
+                        if let ConstantValue::Str(alias) = &c.value {
+                            options.alias = Some(alias.clone());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        options
+    }
+}
@@ -0,0 +1,245 @@
+// Plugins extend the checker with knowledge of frameworks whose behaviour
+// cannot be expressed in type stubs, e.g. classes whose `__init__` is
+// generated from the class body. Plugins are registered with the build
+// manager and are called while the modules are type checked.
+
+mod attrs;
+mod pydantic;
+
+use std::{fmt::Debug, path::PathBuf};
+
+use enderpy_python_parser::{
+    TextSize,
+    ast::{Arg, Arguments, Call, ClassDef, Constant, ConstantValue, Expression, Node, Statement},
+};
+
+use crate::{
+    diagnostic::CharacterSpan,
+    error_code::ErrorCode,
+    symbol_table::{Declaration, LookupSymbolRequest},
+    type_check::checker::{TypeChecker, call_argument_errors},
+};
+
+pub use attrs::AttrsPlugin;
+pub use pydantic::PydanticPlugin;
+
+/// Plugins for popular libraries that every build manager uses
+pub(crate) fn builtin_plugins() -> Vec<Box<dyn CheckerPlugin>> {
+    vec![Box::new(PydanticPlugin), Box::new(AttrsPlugin)]
+}
+
+/// Callbacks that the checker calls for the module being checked. All
+/// callbacks do nothing by default.
+pub trait CheckerPlugin: Debug + Send + Sync {
+    /// Called after the checker visited a class definition
+    fn on_class(&self, _class: &ClassDef, _ctx: &mut PluginContext) {}
+
+    /// Called after the checker visited a call expression
+    fn on_call(&self, _call: &Call, _ctx: &mut PluginContext) {}
+
+    /// Called after all statements of the module were checked
+    fn on_module_done(&self, _ctx: &mut PluginContext) {}
+}
+
+/// Gives plugins access to the module being checked and reports their
+/// errors as diagnostics of the module.
+pub struct PluginContext<'c, 'a> {
+    checker: &'c mut TypeChecker<'a>,
+}
+
+impl<'c, 'a> PluginContext<'c, 'a> {
+    pub(crate) fn new(checker: &'c mut TypeChecker<'a>) -> Self {
+        PluginContext { checker }
+    }
+
+    pub fn module_name(&self) -> String {
+        self.checker.module.file.module_name()
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.checker.module.file.path()
+    }
+
+    pub fn source(&self) -> &'a str {
+        &self.checker.module.file.build_source.source
+    }
+
+    /// Top level statements of the module
+    pub fn body(&self) -> &'a [Statement] {
+        &self.checker.module.file.body
+    }
+
+    /// Fully qualified name of a name or attribute expression, e.g.
+    /// `pydantic.BaseModel` for `BaseModel` after
+    /// `from pydantic import BaseModel`. Returns None when the expression
+    /// does not refer to a declaration.
+    pub fn full_name(&self, expr: &Expression) -> Option<String> {
+        match expr {
+            Expression::Name(n) => {
+                let symbol_table = &self.checker.type_evaluator.symbol_table;
+                let symbol = symbol_table.lookup_in_scope(LookupSymbolRequest {
+                    name: n.id.clone(),
+                    position: Some(n.node.start),
+                })?;
+                match symbol.declaration_until_position(n.node.start)? {
+                    Declaration::Alias(alias) => {
+                        if let Some(import_from) = &alias.import_from_node {
+                            let symbol_name = alias.symbol_name.as_ref()?;
+                            return Some(format!("{}.{}", import_from.module, symbol_name));
+                        }
+                        // `import a.b` binds `a` and `import a.b as c` binds `c`
+                        alias.import_node.as_ref()?.names.iter().find_map(|name| {
+                            match &name.asname {
+                                Some(asname) if asname == &n.id => Some(name.name.clone()),
+                                None if name.name.split('.').next() == Some(n.id.as_str()) => {
+                                    Some(n.id.clone())
+                                }
+                                _ => None,
+                            }
+                        })
+                    }
+                    decl => Some(format!("{}.{}", decl.declaration_path().module_name, n.id)),
+                }
+            }
+            Expression::Attribute(a) => {
+                let value = self.full_name(&a.value)?;
+                Some(format!("{}.{}", value, a.attr))
+            }
+            _ => None,
+        }
+    }
+
+    /// Definition of the class that the name refers to at the position, if
+    /// the class is defined in this module
+    pub fn lookup_class_def(&self, name: &str, position: TextSize) -> Option<&'a ClassDef> {
+        let class = self
+            .checker
+            .type_evaluator
+            .symbol_table
+            .lookup_class(name, position)?;
+        find_class_def(self.body(), class.declaration_path.node)
+    }
+
+    pub fn report(&mut self, code: ErrorCode, msg: &str, node: Node) {
+        self.report_with_labels(code, msg, node, vec![]);
+    }
+
+    /// Reports an error with secondary labels, e.g. pointing to the
+    /// declaration that the error is about
+    pub fn report_with_labels(
+        &mut self,
+        code: ErrorCode,
+        msg: &str,
+        node: Node,
+        labels: Vec<(Node, String)>,
+    ) {
+        let labels = labels
+            .into_iter()
+            .map(|(node, message)| (CharacterSpan(node.start, node.end), message))
+            .collect();
+        self.checker
+            .make_error_with_labels(code, msg, node.start, node.end, labels);
+    }
+
+    /// Reports the arguments of the call that do not match the parameters,
+    /// e.g. of an `__init__` method that the plugin generates. `name` is the
+    /// name of the called function in the messages.
+    pub fn check_call_arguments(&mut self, call: &Call, parameters: &Arguments, name: &str) {
+        for (msg, label) in call_argument_errors(call, parameters, false, name) {
+            let labels = label.into_iter().collect();
+            self.report_with_labels(ErrorCode::InvalidArguments, &msg, call.node, labels);
+        }
+    }
+}
+
+fn find_class_def(body: &[Statement], node: Node) -> Option<&ClassDef> {
+    body.iter().find_map(|stmt| match stmt {
+        Statement::ClassDef(c) if c.node == node => Some(c),
+        Statement::ClassDef(c) => find_class_def(&c.body, node),
+        Statement::FunctionDef(f) => find_class_def(&f.body, node),
+        Statement::AsyncFunctionDef(f) => find_class_def(&f.body, node),
+        Statement::IfStatement(i) => {
+            find_class_def(&i.body, node).or_else(|| find_class_def(&i.orelse, node))
+        }
+        _ => None,
+    })
+}
+
+/// A parameter of an `__init__` method that a plugin generates from the
+/// fields of a class
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InitParameter {
+    name: String,
+    has_default: bool,
+    kw_only: bool,
+}
+
+/// Adds the parameter, a field that is declared again in a subclass keeps
+/// the position of the field in the base class
+fn add_parameter(parameters: &mut Vec<InitParameter>, parameter: InitParameter) {
+    match parameters.iter_mut().find(|p| p.name == parameter.name) {
+        Some(existing) => *existing = parameter,
+        None => parameters.push(parameter),
+    }
+}
+
+/// Arguments of the generated `__init__`, `accepts_extra` adds a `**kwargs`
+/// parameter. Returns None when a positional parameter without a default
+/// follows one with a default, the class definition fails in that case.
+fn init_arguments(parameters: &[InitParameter], accepts_extra: bool) -> Option<Arguments> {
+    let arg = |name: &str| Arg {
+        node: Node::default(),
+        arg: name.to_string(),
+        annotation: None,
+    };
+    // The values of the defaults are not used when checking calls
+    let default = || {
+        Expression::Constant(Box::new(Constant {
+            node: Node::default(),
+            value: ConstantValue::None,
+            kind: None,
+        }))
+    };
+    let mut arguments = Arguments {
+        node: Node::default(),
+        posonlyargs: vec![],
+        args: vec![],
+        vararg: None,
+        kwonlyargs: vec![],
+        kw_defaults: vec![],
+        kwarg: accepts_extra.then(|| arg("kwargs")),
+        defaults: vec![],
+    };
+    for parameter in parameters {
+        if parameter.kw_only {
+            arguments.kwonlyargs.push(arg(&parameter.name));
+            arguments
+                .kw_defaults
+                .push(parameter.has_default.then(default));
+        } else {
+            if !parameter.has_default && !arguments.defaults.is_empty() {
+                return None;
+            }
+            arguments.args.push(arg(&parameter.name));
+            if parameter.has_default {
+                arguments.defaults.push(default());
+            }
+        }
+    }
+    Some(arguments)
+}
+
+/// Whether the expression is `typing.<member>`, also when it is subscripted
+fn is_typing(ctx: &PluginContext, expr: &Expression, member: &str) -> bool {
+    let expr = match expr {
+        Expression::Subscript(s) => &s.value,
+        expr => expr,
+    };
+    ctx.full_name(expr).is_some_and(|name| {
+        name == format!("typing.{}", member) || name == format!("typing_extensions.{}", member)
+    })
+}
+
+fn is_constant(expr: &Expression, value: &ConstantValue) -> bool {
+    matches!(expr, Expression::Constant(c) if &c.value == value)
+}
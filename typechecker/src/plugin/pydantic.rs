@@ -0,0 +1,194 @@
+// Support for pydantic models. `BaseModel` generates an `__init__` that
+// takes a keyword-only parameter for each field declared in the class body
+// and in the bases of the model.
+
+use enderpy_python_parser::ast::{Call, ClassDef, ConstantValue, Expression, Statement};
+
+use super::{
+    CheckerPlugin, InitParameter, PluginContext, add_parameter, init_arguments, is_constant,
+    is_typing,
+};
+
+const BASE_MODEL: &[&str] = &["pydantic.BaseModel", "pydantic.main.BaseModel"];
+const FIELD: &[&str] = &["pydantic.Field", "pydantic.fields.Field"];
+
+/// Checks the arguments of calls that construct pydantic models
+#[derive(Debug, Default)]
+pub struct PydanticPlugin;
+
+impl CheckerPlugin for PydanticPlugin {
+    fn on_call(&self, call: &Call, ctx: &mut PluginContext) {
+        let Expression::Name(n) = call.func.as_ref() else {
+            return;
+        };
+        let Some(class) = ctx.lookup_class_def(&n.id, n.node.start) else {
+            return;
+        };
+        let Some(fields) = model_fields(class, ctx) else {
+            return;
+        };
+        // Unknown keyword arguments are ignored unless the model forbids them
+        let Some(parameters) = init_arguments(&fields, !forbids_extra(class, ctx)) else {
+            return;
+        };
+        ctx.check_call_arguments(call, &parameters, &class.name);
+    }
+}
+
+/// Fields of the model in the order of the parameters of `__init__`.
+/// Returns None when the class is not a model or when a base class is not
+/// known, e.g. an imported model whose fields cannot be seen.
+fn model_fields(class: &ClassDef, ctx: &PluginContext) -> Option<Vec<InitParameter>> {
+    let mut fields: Vec<InitParameter> = vec![];
+    let mut is_model = false;
+    for base in &class.bases {
+        if ctx
+            .full_name(base)
+            .is_some_and(|name| BASE_MODEL.contains(&name.as_str()))
+        {
+            is_model = true;
+            continue;
+        }
+        let Expression::Name(base) = base else {
+            return None;
+        };
+        // Bases are looked up before the class is bound, so a class cannot
+        // be its own base
+        let base_class = ctx.lookup_class_def(&base.id, base.node.start)?;
+        for field in model_fields(base_class, ctx)? {
+            add_parameter(&mut fields, field);
+        }
+        is_model = true;
+    }
+    if !is_model {
+        return None;
+    }
+
+    for stmt in &class.body {
+        let Statement::AnnAssignStatement(a) = stmt else {
+            continue;
+        };
+        let Expression::Name(target) = &a.target else {
+            continue;
+        };
+        // Private attributes and the configuration are not fields
+        if target.id.starts_with('_')
+            || target.id == "model_config"
+            || is_typing(ctx, &a.annotation, "ClassVar")
+        {
+            continue;
+        }
+        let (alias, has_default) = match &a.value {
+            Some(Expression::Call(c)) if is_field_call(ctx, c) => field_options(c),
+            Some(_) => (None, true),
+            None => (None, false),
+        };
+        add_parameter(
+            &mut fields,
+            InitParameter {
+                // The parameter is named after the alias of the field
+                name: alias.unwrap_or_else(|| target.id.clone()),
+                // Like in pydantic 1, optional fields default to None
+                has_default: has_default || is_optional(ctx, &a.annotation),
+                kw_only: true,
+            },
+        );
+    }
+    Some(fields)
+}
+
+fn is_field_call(ctx: &PluginContext, call: &Call) -> bool {
+    ctx.full_name(&call.func)
+        .is_some_and(|name| FIELD.contains(&name.as_str()))
+}
+
+/// Alias of a `Field(...)` call and whether it sets a default. The default
+/// is the first positional argument, `...` marks a required field.
+fn field_options(call: &Call) -> (Option<String>, bool) {
+    let mut has_default = call
+        .args
+        .first()
+        .is_some_and(|default| !is_constant(default, &ConstantValue::Ellipsis));
+    let mut alias = None;
+    for keyword in &call.keywords {
+        match (keyword.arg.as_deref(), keyword.value.as_ref()) {
+            (Some("default"), value) => has_default = !is_constant(value, &ConstantValue::Ellipsis),
+            (Some("default_factory"), _) => has_default = true,
+            (Some("alias"), Expression::Constant(c)) => {
+                if let ConstantValue::Str(value) = &c.value {
+                    alias = Some(value.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    (alias, has_default)
+}
+
+/// Whether the model sets `extra="forbid"` in its configuration. Other
+/// models accept and ignore unknown keyword arguments.
+fn forbids_extra(class: &ClassDef, ctx: &PluginContext) -> bool {
+    let forbid = |expr: &Expression| is_constant(expr, &ConstantValue::Str("forbid".into()));
+    if class
+        .keywords
+        .iter()
+        .any(|keyword| keyword.arg.as_deref() == Some("extra") && forbid(&keyword.value))
+    {
+        return true;
+    }
+    for stmt in &class.body {
+        let value = match stmt {
+            Statement::AssignStatement(a) if is_model_config(&a.targets) => &a.value,
+            Statement::AnnAssignStatement(a) => match (&a.target, &a.value) {
+                (Expression::Name(n), Some(value)) if n.id == "model_config" => value,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let configured = match value {
+            // model_config = ConfigDict(extra="forbid")
+            Expression::Call(c) => c
+                .keywords
+                .iter()
+                .find(|keyword| keyword.arg.as_deref() == Some("extra"))
+                .map(|keyword| forbid(&keyword.value)),
+            // model_config = {"extra": "forbid"}
+            Expression::Dict(d) => d
+                .keys
+                .iter()
+                .zip(d.values.iter())
+                .find(|(key, _)| is_constant(key, &ConstantValue::Str("extra".into())))
+                .map(|(_, value)| forbid(value)),
+            _ => None,
+        };
+        if let Some(configured) = configured {
+            return configured;
+        }
+    }
+    // The configuration is inherited from the bases
+    class.bases.iter().any(|base| match base {
+        Expression::Name(n) => ctx
+            .lookup_class_def(&n.id, n.node.start)
+            .is_some_and(|base| forbids_extra(base, ctx)),
+        _ => false,
+    })
+}
+
+fn is_model_config(targets: &[Expression]) -> bool {
+    matches!(targets, [Expression::Name(n)] if n.id == "model_config")
+}
+
+/// Whether the annotation accepts None, e.g. `Optional[int]`, `int | None`
+/// or `Union[int, None]`
+fn is_optional(ctx: &PluginContext, annotation: &Expression) -> bool {
+    match annotation {
+        Expression::Constant(c) => c.value == ConstantValue::None,
+        Expression::Subscript(s) if is_typing(ctx, &s.value, "Optional") => true,
+        Expression::Subscript(s) if is_typing(ctx, &s.value, "Union") => match s.slice.as_ref() {
+            Expression::Tuple(t) => t.elements.iter().any(|e| is_optional(ctx, e)),
+            slice => is_optional(ctx, slice),
+        },
+        Expression::BinOp(b) => is_optional(ctx, &b.left) || is_optional(ctx, &b.right),
+        _ => false,
+    }
+}
@@ -1,4 +1,14 @@
 use std::path::Path;
+
+#[cfg(feature = "native")]
+use config::{Config, ConfigError, File, FileFormat};
+
+#[cfg(feature = "native")]
+use serde::{Deserialize, de::DeserializeOwned};
+
+#[cfg(feature = "native")]
+use crate::{error_code::RuleSelection, settings::DocstringStyle};
+
 const PROJECT_ROOT_MARKERS: [&str; 1] = ["pyproject.toml"];
 
 pub fn find_project_root(path: &Path) -> &Path {
@@ -16,3 +26,97 @@ pub fn find_project_root(path: &Path) -> &Path {
         }
     }
 }
+
+/// Reads `select` and `ignore` of the `[tool.enderpy]` table of the
+/// `pyproject.toml` in the project root. Every code is reported when the
+/// file or the table does not exist.
+#[cfg(feature = "native")]
+pub fn read_rule_selection(root: &Path) -> Result<RuleSelection, ConfigError> {
+    read_tool_table(root)
+}
+
+/// Reads `docstring-style` of the `[tool.enderpy]` table of the
+/// `pyproject.toml` in the project root.
+#[cfg(feature = "native")]
+pub fn read_docstring_style(root: &Path) -> Result<Option<DocstringStyle>, ConfigError> {
+    #[derive(Default, Deserialize)]
+    struct DocstringOptions {
+        #[serde(rename = "docstring-style", default)]
+        docstring_style: Option<DocstringStyle>,
+    }
+    read_tool_table::<DocstringOptions>(root).map(|options| options.docstring_style)
+}
+
+#[cfg(feature = "native")]
+fn read_tool_table<T: DeserializeOwned + Default>(root: &Path) -> Result<T, ConfigError> {
+    let path = root.join("pyproject.toml");
+    if !path.is_file() {
+        return Ok(T::default());
+    }
+    let config = Config::builder()
+        .add_source(File::from(path).format(FileFormat::Toml))
+        .build()?;
+    match config.get::<T>("tool.enderpy") {
+        Ok(table) => Ok(table),
+        Err(ConfigError::NotFound(_)) => Ok(T::default()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_read_rule_selection() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            read_rule_selection(dir.path()).unwrap(),
+            RuleSelection::default()
+        );
+
+        let pyproject = dir.path().join("pyproject.toml");
+        fs::write(&pyproject, "[project]\nname = \"app\"\n").unwrap();
+        assert_eq!(
+            read_rule_selection(dir.path()).unwrap(),
+            RuleSelection::default()
+        );
+
+        fs::write(
+            &pyproject,
+            "[tool.enderpy]\nselect = [\"E2\"]\nignore = [\"E203\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_rule_selection(dir.path()).unwrap(),
+            RuleSelection {
+                select: vec!["E2".to_string()],
+                ignore: vec!["E203".to_string()],
+            }
+        );
+
+        fs::write(&pyproject, "[tool.enderpy]\nselect = \"E2\"\n").unwrap();
+        assert!(read_rule_selection(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_read_docstring_style() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_docstring_style(dir.path()).unwrap(), None);
+
+        let pyproject = dir.path().join("pyproject.toml");
+        fs::write(&pyproject, "[tool.enderpy]\nselect = [\"E2\"]\n").unwrap();
+        assert_eq!(read_docstring_style(dir.path()).unwrap(), None);
+
+        fs::write(&pyproject, "[tool.enderpy]\ndocstring-style = \"numpy\"\n").unwrap();
+        assert_eq!(
+            read_docstring_style(dir.path()).unwrap(),
+            Some(DocstringStyle::Numpy)
+        );
+
+        fs::write(&pyproject, "[tool.enderpy]\ndocstring-style = \"pep257\"\n").unwrap();
+        assert!(read_docstring_style(dir.path()).is_err());
+    }
+}
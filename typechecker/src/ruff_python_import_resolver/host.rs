@@ -1,9 +1,10 @@
 //! Expose the host environment to the resolver.
 
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
-use crate::ruff_python_import_resolver::{
-    python_platform::PythonPlatform, python_version::PythonVersion,
+use crate::{
+    file_system::{FileSystem, OsFileSystem},
+    ruff_python_import_resolver::{python_platform::PythonPlatform, python_version::PythonVersion},
 };
 
 /// A trait to expose the host environment to the resolver.
@@ -16,16 +17,27 @@ pub trait Host {
 
     /// The OS platform to use when resolving Python modules.
     fn python_platform(&self) -> PythonPlatform;
+
+    /// The file system to look up modules in.
+    fn file_system(&self) -> &dyn FileSystem;
 }
 
 /// A host that exposes a fixed set of search paths.
 pub struct StaticHost {
     search_paths: Vec<PathBuf>,
+    file_system: Arc<dyn FileSystem>,
 }
 
 impl StaticHost {
     pub fn new(search_paths: Vec<PathBuf>) -> Self {
-        Self { search_paths }
+        Self::with_file_system(search_paths, Arc::new(OsFileSystem))
+    }
+
+    pub fn with_file_system(search_paths: Vec<PathBuf>, file_system: Arc<dyn FileSystem>) -> Self {
+        Self {
+            search_paths,
+            file_system,
+        }
     }
 }
 
@@ -41,4 +53,8 @@ impl Host for StaticHost {
     fn python_platform(&self) -> PythonPlatform {
         PythonPlatform::Darwin
     }
+
+    fn file_system(&self) -> &dyn FileSystem {
+        self.file_system.as_ref()
+    }
 }
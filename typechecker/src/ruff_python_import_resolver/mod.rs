@@ -16,7 +16,7 @@ mod search;
 #[cfg(test)]
 mod tests {
     use std::{
-        fs::{create_dir_all, File},
+        fs::{File, create_dir_all},
         io::{self, Write},
         path::{Path, PathBuf},
     };
@@ -101,6 +101,7 @@ mod tests {
                 .skip_while(|c| *c == '.')
                 .collect::<String>()
                 .split('.')
+                .filter(|part| !part.is_empty())
                 .map(std::string::ToString::to_string)
                 .collect(),
             imported_symbols: Vec::new(),
@@ -138,7 +139,7 @@ mod tests {
             let $value = std::format!("{:#?}", $value).replace("\\\\", "/");
             // `insta::assert_snapshot` uses the debug representation of the string, which
             // would be a single line containing `\n`
-            insta::assert_display_snapshot!($value);
+            insta::assert_snapshot!($value);
         }};
     }
 
@@ -805,6 +806,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn relative_import_without_module() -> io::Result<()> {
+        setup();
+
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+
+        let init = empty(root.join("pkg/__init__.py"))?;
+        let file = empty(root.join("pkg/file.py"))?;
+
+        let result = resolve_options(file, ".", root, ResolverOptions::default());
+
+        assert!(result.is_import_found);
+        assert_eq!(result.resolved_paths, vec![init]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn relative_import_beyond_three_levels() -> io::Result<()> {
+        setup();
+
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+
+        let target = empty(root.join("a/target.py"))?;
+        let file = empty(root.join("a/b/c/d/file.py"))?;
+
+        let result = resolve_options(&file, "....target", root, ResolverOptions::default());
+        assert!(result.is_import_found);
+        assert_eq!(result.resolved_paths, vec![target]);
+
+        let result = resolve_options(&file, ".....target", root, ResolverOptions::default());
+        assert!(!result.is_import_found);
+
+        Ok(())
+    }
+
     #[test]
     fn airflow_standard_library() {
         setup();
@@ -42,12 +42,11 @@ impl From<&ImportFrom> for ImportModuleDescriptor {
     fn from(import_from: &ImportFrom) -> Self {
         ImportModuleDescriptor {
             leading_dots: import_from.level,
+            // The module of a relative import can be empty, e.g. `from . import a`
             name_parts: import_from
                 .module
-                .chars()
-                .skip_while(|c| *c == '.')
-                .collect::<String>()
                 .split('.')
+                .filter(|part| !part.is_empty())
                 .map(std::string::ToString::to_string)
                 .collect(),
             imported_symbols: import_from.names.iter().map(|x| x.name.clone()).collect(),
@@ -1,11 +1,32 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+
 /// Enum to represent a Python platform.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
 pub enum PythonPlatform {
+    #[serde(rename = "darwin")]
     Darwin,
+    #[serde(rename = "linux")]
     Linux,
+    #[serde(rename = "win32")]
     Windows,
 }
 
+impl Default for PythonPlatform {
+    /// The platform enderpy runs on. Other Unix platforms are checked as
+    /// Linux.
+    fn default() -> Self {
+        if cfg!(target_os = "macos") {
+            PythonPlatform::Darwin
+        } else if cfg!(target_os = "windows") {
+            PythonPlatform::Windows
+        } else {
+            PythonPlatform::Linux
+        }
+    }
+}
+
 impl PythonPlatform {
     /// Returns the platform-specific library names. These are the candidate
     /// names for the top-level subdirectory within a virtual environment
@@ -18,4 +39,29 @@ impl PythonPlatform {
             PythonPlatform::Windows => &["Lib"],
         }
     }
+
+    /// The value of `sys.platform` on this platform.
+    pub fn sys_platform(&self) -> &'static str {
+        match self {
+            PythonPlatform::Darwin => "darwin",
+            PythonPlatform::Linux => "linux",
+            PythonPlatform::Windows => "win32",
+        }
+    }
+}
+
+impl FromStr for PythonPlatform {
+    type Err = String;
+
+    /// Parses the values of `sys.platform`, e.g. `linux`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            PythonPlatform::Darwin,
+            PythonPlatform::Linux,
+            PythonPlatform::Windows,
+        ]
+        .into_iter()
+        .find(|platform| platform.sys_platform() == s.trim())
+        .ok_or_else(|| format!("unsupported platform {}", s))
+    }
 }
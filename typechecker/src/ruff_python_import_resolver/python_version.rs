@@ -1,15 +1,35 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+
 /// Enum to represent a Python version.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Deserialize)]
 pub enum PythonVersion {
+    #[serde(rename = "3.7")]
     Py37,
+    #[serde(rename = "3.8")]
     Py38,
+    #[serde(rename = "3.9")]
     Py39,
+    #[serde(rename = "3.10")]
     Py310,
+    #[serde(rename = "3.11")]
     Py311,
+    #[default]
+    #[serde(rename = "3.12")]
     Py312,
 }
 
 impl PythonVersion {
+    pub const ALL: [PythonVersion; 6] = [
+        PythonVersion::Py37,
+        PythonVersion::Py38,
+        PythonVersion::Py39,
+        PythonVersion::Py310,
+        PythonVersion::Py311,
+        PythonVersion::Py312,
+    ];
+
     /// The directory name (e.g., in a virtual environment) for this Python
     /// version.
     pub(crate) fn dir(self) -> &'static str {
@@ -22,4 +42,32 @@ impl PythonVersion {
             PythonVersion::Py312 => "python3.12",
         }
     }
+
+    /// The major and minor version, e.g. `(3, 12)`.
+    pub fn version_info(self) -> (u32, u32) {
+        match self {
+            PythonVersion::Py37 => (3, 7),
+            PythonVersion::Py38 => (3, 8),
+            PythonVersion::Py39 => (3, 9),
+            PythonVersion::Py310 => (3, 10),
+            PythonVersion::Py311 => (3, 11),
+            PythonVersion::Py312 => (3, 12),
+        }
+    }
+}
+
+impl FromStr for PythonVersion {
+    type Err = String;
+
+    /// Parses versions written as `3.12`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PythonVersion::ALL
+            .iter()
+            .find(|version| {
+                let (major, minor) = version.version_info();
+                format!("{}.{}", major, minor) == s.trim()
+            })
+            .copied()
+            .ok_or_else(|| format!("unsupported python version {}", s))
+    }
 }
@@ -7,17 +7,20 @@ use std::{
 
 use log::debug;
 
-use crate::ruff_python_import_resolver::{
-    config::Config,
-    execution_environment::ExecutionEnvironment,
-    host,
-    implicit_imports::ImplicitImports,
-    import_result::{ImportResult, ImportType},
-    module_descriptor::ImportModuleDescriptor,
-    native_module, py_typed, search,
+use crate::{
+    file_system::FileSystem,
+    ruff_python_import_resolver::{
+        config::Config,
+        execution_environment::ExecutionEnvironment,
+        host,
+        implicit_imports::ImplicitImports,
+        import_result::{ImportResult, ImportType},
+        module_descriptor::ImportModuleDescriptor,
+        native_module, py_typed, search,
+    },
 };
 
-#[allow(clippy::fn_params_excessive_bools)]
+#[allow(clippy::fn_params_excessive_bools, clippy::too_many_arguments)]
 fn resolve_module_descriptor(
     root: &Path,
     module_descriptor: &ImportModuleDescriptor,
@@ -26,6 +29,7 @@ fn resolve_module_descriptor(
     use_stub_package: bool,
     allow_pyi: bool,
     look_for_py_typed: bool,
+    fs: &dyn FileSystem,
 ) -> ImportResult {
     if use_stub_package {
         debug!("Attempting to resolve stub package using root path: {root:?}");
@@ -51,10 +55,10 @@ fn resolve_module_descriptor(
         let py_file_path = dir_path.join("__init__.py");
         let pyi_file_path = dir_path.join("__init__.pyi");
 
-        if allow_pyi && pyi_file_path.is_file() {
+        if allow_pyi && fs.is_file(&pyi_file_path) {
             debug!("Resolved import with file: {pyi_file_path:?}");
             resolved_paths.push(pyi_file_path.clone());
-        } else if py_file_path.is_file() {
+        } else if fs.is_file(&py_file_path) {
             debug!("Resolved import with file: {py_file_path:?}");
             resolved_paths.push(py_file_path.clone());
         } else {
@@ -79,7 +83,7 @@ fn resolve_module_descriptor(
                 dir_path.join(part)
             };
 
-            let found_directory = module_dir_path.is_dir();
+            let found_directory = fs.is_dir(&module_dir_path);
             if found_directory {
                 if is_first_part {
                     package_directory = Some(module_dir_path.clone());
@@ -90,14 +94,14 @@ fn resolve_module_descriptor(
                 let pyi_file_path = module_dir_path.join("__init__.pyi");
                 is_init_file_present = false;
 
-                if allow_pyi && pyi_file_path.is_file() {
+                if allow_pyi && fs.is_file(&pyi_file_path) {
                     debug!("Resolved import with file: {pyi_file_path:?}");
                     resolved_paths.push(pyi_file_path.clone());
                     if is_last_part {
                         is_stub_file = true;
                     }
                     is_init_file_present = true;
-                } else if py_file_path.is_file() {
+                } else if fs.is_file(&py_file_path) {
                     debug!("Resolved import with file: {py_file_path:?}");
                     resolved_paths.push(py_file_path.clone());
                     is_init_file_present = true;
@@ -135,17 +139,17 @@ fn resolve_module_descriptor(
             let py_file_path = module_dir_path.with_extension("py");
             let pyi_file_path = module_dir_path.with_extension("pyi");
 
-            if allow_pyi && pyi_file_path.is_file() {
+            if allow_pyi && fs.is_file(&pyi_file_path) {
                 debug!("Resolved import with file: {pyi_file_path:?}");
                 resolved_paths.push(pyi_file_path);
                 if is_last_part {
                     is_stub_file = true;
                 }
-            } else if py_file_path.is_file() {
+            } else if fs.is_file(&py_file_path) {
                 debug!("Resolved import with file: {py_file_path:?}");
                 resolved_paths.push(py_file_path);
             } else {
-                if allow_native_lib && dir_path.is_dir() {
+                if allow_native_lib && fs.is_dir(&dir_path) {
                     // We couldn't find a `.py[i]` file; search for a native library.
                     if let Some(module_name) = module_dir_path.file_name().and_then(OsStr::to_str) {
                         if let Ok(Some(native_lib_path)) =
@@ -176,7 +180,8 @@ fn resolve_module_descriptor(
     let import_found = if allow_partial {
         !resolved_paths.is_empty()
     } else {
-        resolved_paths.len() == module_descriptor.name_parts.len()
+        // `from . import foo` resolves the package without any name parts
+        resolved_paths.len() >= module_descriptor.name_parts.len()
     };
 
     let is_partly_resolved = if resolved_paths.is_empty() {
@@ -212,7 +217,7 @@ fn resolve_module_descriptor(
 /// defined in [PEP 420].
 ///
 /// [PEP 420]: https://peps.python.org/pep-0420/
-#[allow(clippy::fn_params_excessive_bools)]
+#[allow(clippy::fn_params_excessive_bools, clippy::too_many_arguments)]
 fn resolve_absolute_import(
     root: &Path,
     module_descriptor: &ImportModuleDescriptor,
@@ -221,6 +226,7 @@ fn resolve_absolute_import(
     use_stub_package: bool,
     allow_pyi: bool,
     look_for_py_typed: bool,
+    fs: &dyn FileSystem,
 ) -> ImportResult {
     if allow_pyi && use_stub_package {
         // Search for packaged stubs first. PEP 561 indicates that package authors can
@@ -234,6 +240,7 @@ fn resolve_absolute_import(
             true,
             true,
             true,
+            fs,
         );
 
         if import_result.package_directory.is_some() {
@@ -254,6 +261,7 @@ fn resolve_absolute_import(
         false,
         allow_pyi,
         look_for_py_typed,
+        fs,
     )
 }
 
@@ -285,6 +293,7 @@ fn resolve_best_absolute_import<Host: host::Host>(
                 true,
                 allow_pyi,
                 false,
+                host.file_system(),
             );
 
             if typings_import.is_import_found {
@@ -329,6 +338,7 @@ fn resolve_best_absolute_import<Host: host::Host>(
         true,
         allow_pyi,
         false,
+        host.file_system(),
     );
     local_import.import_type = ImportType::Local;
 
@@ -346,6 +356,7 @@ fn resolve_best_absolute_import<Host: host::Host>(
             true,
             allow_pyi,
             false,
+            host.file_system(),
         );
         local_import.import_type = ImportType::Local;
 
@@ -368,6 +379,7 @@ fn resolve_best_absolute_import<Host: host::Host>(
             true,
             allow_pyi,
             true,
+            host.file_system(),
         );
         third_party_import.import_type = ImportType::ThirdParty;
 
@@ -462,6 +474,7 @@ fn find_typeshed_path<Host: host::Host>(
                 false,
                 true,
                 false,
+                host.file_system(),
             );
             if import_info.is_import_found {
                 import_info.import_type = if is_std_lib {
@@ -598,6 +611,7 @@ fn pick_best_import(
 fn resolve_relative_import(
     source_file: &Path,
     module_descriptor: &ImportModuleDescriptor,
+    fs: &dyn FileSystem,
 ) -> Option<ImportResult> {
     // Determine which search path this file is part of.
     let mut directory = source_file;
@@ -614,6 +628,7 @@ fn resolve_relative_import(
         false,
         true,
         false,
+        fs,
     );
 
     if abs_import.is_stub_file {
@@ -628,6 +643,7 @@ fn resolve_relative_import(
             false,
             false,
             false,
+            fs,
         )));
     }
 
@@ -647,7 +663,8 @@ fn resolve_import_strict<Host: host::Host>(
     if module_descriptor.leading_dots > 0 {
         debug!("Resolving relative import for: {import_name}");
 
-        let relative_import = resolve_relative_import(source_file, module_descriptor);
+        let relative_import =
+            resolve_relative_import(source_file, module_descriptor, host.file_system());
 
         if let Some(mut relative_import) = relative_import {
             relative_import.is_relative = true;
@@ -728,8 +745,16 @@ pub fn resolve_import<Host: host::Host>(
 
         debug!("Resolving absolute import in parent: {}", parent.display());
 
-        let mut result =
-            resolve_absolute_import(parent, module_descriptor, false, false, false, true, false);
+        let mut result = resolve_absolute_import(
+            parent,
+            module_descriptor,
+            false,
+            false,
+            false,
+            true,
+            false,
+            host.file_system(),
+        );
 
         if result.is_import_found {
             if let Some(implicit_imports) = result
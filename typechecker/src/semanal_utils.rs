@@ -1 +1,459 @@
+use std::{cmp::Ordering, collections::HashMap};
 
+use enderpy_python_parser::ast::{
+    BinOp, BinaryOperator, BooleanOperator, ComparisonOperator, Constant, ConstantValue,
+    Expression, GetNode, List, Name, Starred, Subscript, Tuple, UnaryOperator,
+};
+
+use crate::{
+    settings::{PythonPlatform, PythonVersion},
+    symbol_table::{ClassTypeParameter, Dataclass, TypeParameterKind},
+};
+
+/// Returns the dotted name of a name or attribute expression, e.g. `a.b.C`.
+/// Returns None for any other expression.
+pub fn dotted_name(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Name(n) => Some(n.id.clone()),
+        Expression::Attribute(a) => Some(format!("{}.{}", dotted_name(&a.value)?, a.attr)),
+        _ => None,
+    }
+}
+
+/// Returns the attribute name of an attribute of `self`, e.g. `x` for `self.x`
+pub fn self_attribute(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Attribute(a) if matches!(&*a.value, Expression::Name(n) if n.id == "self") => {
+            Some(a.attr.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the annotation is the type qualifier, e.g. `Final`,
+/// `Final[int]` or `typing.Final` for the qualifier `Final`
+pub fn is_type_qualifier(annotation: &Expression, qualifier: &str) -> bool {
+    match annotation {
+        Expression::Name(n) => n.id == qualifier,
+        Expression::Attribute(a) => {
+            a.attr == qualifier
+                && matches!(
+                    dotted_name(&a.value).as_deref(),
+                    Some("typing" | "typing_extensions")
+                )
+        }
+        Expression::Subscript(s) => is_type_qualifier(&s.value, qualifier),
+        _ => false,
+    }
+}
+
+/// Returns true for names that follow the UPPER_CASE naming convention of
+/// constants, e.g. `MAX_SIZE`
+pub fn is_constant_name(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Returns the type parameters of a `Generic[...]` or `Protocol[...]` base
+/// class, e.g. `T` and `*Ts` in `Generic[T, *Ts]`. Returns None for other
+/// base classes. Parameters that are not unpacked are returned as `TypeVar`
+/// without a default because ParamSpecs and defaults are only known from the
+/// declarations of the parameters.
+pub fn generic_parameters(base: &Expression) -> Option<Vec<ClassTypeParameter>> {
+    let Expression::Subscript(s) = base else {
+        return None;
+    };
+    if !is_type_qualifier(&s.value, "Generic") && !is_type_qualifier(&s.value, "Protocol") {
+        return None;
+    }
+    let parameters = type_arguments(&s.slice)
+        .iter()
+        .filter_map(|argument| {
+            let (name, kind) = match unpacked_type(argument) {
+                Some(value) => (value, TypeParameterKind::TypeVarTuple),
+                None => (argument, TypeParameterKind::TypeVar),
+            };
+            match name {
+                Expression::Name(n) => Some(ClassTypeParameter {
+                    name: n.id.clone(),
+                    kind,
+                    has_default: false,
+                }),
+                _ => None,
+            }
+        })
+        .collect();
+    Some(parameters)
+}
+
+/// Type arguments of a subscript, e.g. `int` and `str` in `dict[int, str]`
+pub fn type_arguments(slice: &Expression) -> &[Expression] {
+    match slice {
+        Expression::Tuple(t) => &t.elements,
+        slice => std::slice::from_ref(slice),
+    }
+}
+
+/// Replaces the names of a type annotation with the expressions they are
+/// bound to, e.g. `tuple[T, T]` with `T` bound to `int` is `tuple[int, int]`
+pub fn substitute_names(
+    annotation: &Expression,
+    bindings: &HashMap<&str, &Expression>,
+) -> Expression {
+    let substitute = |expr: &Expression| Box::new(substitute_names(expr, bindings));
+    match annotation {
+        Expression::Name(n) => match bindings.get(n.id.as_str()) {
+            Some(bound) => (*bound).clone(),
+            None => annotation.clone(),
+        },
+        Expression::Subscript(s) => Expression::Subscript(Box::new(Subscript {
+            node: s.node,
+            value: substitute(&s.value),
+            slice: substitute(&s.slice),
+        })),
+        Expression::Tuple(t) => Expression::Tuple(Box::new(Tuple {
+            node: t.node,
+            elements: t
+                .elements
+                .iter()
+                .map(|e| substitute_names(e, bindings))
+                .collect(),
+        })),
+        Expression::List(l) => Expression::List(Box::new(List {
+            node: l.node,
+            elements: l
+                .elements
+                .iter()
+                .map(|e| substitute_names(e, bindings))
+                .collect(),
+        })),
+        Expression::BinOp(b) => Expression::BinOp(Box::new(BinOp {
+            node: b.node,
+            op: b.op.clone(),
+            left: substitute(&b.left),
+            right: substitute(&b.right),
+        })),
+        Expression::Starred(s) => Expression::Starred(Box::new(Starred {
+            node: s.node,
+            value: substitute(&s.value),
+        })),
+        _ => annotation.clone(),
+    }
+}
+
+/// Returns the unpacked type of `*Ts` or `Unpack[Ts]`
+pub fn unpacked_type(argument: &Expression) -> Option<&Expression> {
+    match argument {
+        Expression::Starred(s) => Some(&s.value),
+        Expression::Subscript(s) if is_type_qualifier(&s.value, "Unpack") => Some(&s.slice),
+        _ => None,
+    }
+}
+
+/// Returns true for `tuple` and `typing.Tuple`
+pub fn is_tuple_type(expr: &Expression) -> bool {
+    matches!(expr, Expression::Name(n) if n.id == "tuple") || is_type_qualifier(expr, "Tuple")
+}
+
+/// Returns true for tuple types of any length, e.g. `tuple[int, ...]`
+pub fn is_unbounded_tuple(expr: &Expression) -> bool {
+    let Expression::Subscript(s) = expr else {
+        return false;
+    };
+    is_tuple_type(&s.value)
+        && matches!(
+            type_arguments(&s.slice),
+            [_, Expression::Constant(c)] if matches!(c.value, ConstantValue::Ellipsis)
+        )
+}
+
+/// Returns the options of a `@dataclass` decorator, e.g. `@dataclass` or
+/// `@dataclasses.dataclass(kw_only=True)`. Returns None for other decorators.
+pub fn dataclass_decorator(decorator: &Expression) -> Option<Dataclass> {
+    if let Expression::Call(call) = decorator {
+        let mut dataclass = dataclass_decorator(&call.func)?;
+        for keyword in &call.keywords {
+            let value = match keyword.value.as_ref() {
+                Expression::Constant(c) => match c.value {
+                    ConstantValue::Bool(b) => b,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            match keyword.arg.as_deref() {
+                Some("init") => dataclass.init = value,
+                Some("kw_only") => dataclass.kw_only = value,
+                _ => {}
+            }
+        }
+        return Some(dataclass);
+    }
+    match dotted_name(decorator).as_deref() {
+        Some("dataclass" | "dataclasses.dataclass") => Some(Dataclass {
+            init: true,
+            kw_only: false,
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the attribute names declared by the value of `__slots__`, which
+/// can be a single string or a list, tuple, set or dict of strings.
+/// Returns None when the names cannot be determined statically.
+pub fn slot_names(value: &Expression) -> Option<Vec<String>> {
+    let elements = match value {
+        Expression::List(l) => &l.elements,
+        Expression::Tuple(t) => &t.elements,
+        Expression::Set(s) => &s.elements,
+        Expression::Dict(d) => &d.keys,
+        _ => return Some(vec![string_value(value)?]),
+    };
+    elements.iter().map(string_value).collect()
+}
+
+/// Returns the annotation of the name bound by `except* E as name`. The
+/// handler receives an exception group of the exceptions that matched, e.g.
+/// `ExceptionGroup[A | B]` for `except* (A, B)`. Returns None when the
+/// exception types are not names.
+pub fn exception_group_annotation(typ: &Expression) -> Option<Expression> {
+    let types = match typ {
+        Expression::Name(_) | Expression::Attribute(_) => vec![typ.clone()],
+        Expression::Tuple(t)
+            if t.elements
+                .iter()
+                .all(|e| matches!(e, Expression::Name(_) | Expression::Attribute(_))) =>
+        {
+            t.elements.clone()
+        }
+        _ => return None,
+    };
+    let node = typ.get_node();
+    let slice = types.into_iter().reduce(|left, right| {
+        Expression::BinOp(Box::new(BinOp {
+            node,
+            op: BinaryOperator::BitOr,
+            left: Box::new(left),
+            right: Box::new(right),
+        }))
+    })?;
+    Some(Expression::Subscript(Box::new(Subscript {
+        node,
+        value: Box::new(Expression::Name(Box::new(Name {
+            node,
+            id: String::from("ExceptionGroup"),
+        }))),
+        slice: Box::new(slice),
+    })))
+}
+
+/// Returns the elements of a tuple or list display without starred elements,
+/// e.g. `1, "a"`. The number of values that it unpacks to is known.
+pub fn literal_elements(value: &Expression) -> Option<&[Expression]> {
+    let elements = match value {
+        Expression::Tuple(t) => &t.elements,
+        Expression::List(l) => &l.elements,
+        _ => return None,
+    };
+    if elements.iter().any(|e| matches!(e, Expression::Starred(_))) {
+        return None;
+    }
+    Some(elements)
+}
+
+/// Returns the expression that the target at `index` of `a, *b, c = value`
+/// is assigned, where `count` is the number of targets and `star` the index
+/// of the starred target. Elements of displays are taken directly, the
+/// starred target of a display gets a list of the remaining elements and
+/// other values are indexed, e.g. `value[0]` and `value[-1]`. Returns None
+/// when the number of values does not match or the starred target takes
+/// the values of an expression.
+pub fn unpacked_value(
+    value: &Expression,
+    count: usize,
+    star: Option<usize>,
+    index: usize,
+) -> Option<Expression> {
+    let node = value.get_node();
+    if let Some(elements) = literal_elements(value) {
+        return match star {
+            None if elements.len() == count => Some(elements[index].clone()),
+            None => None,
+            Some(_) if elements.len() + 1 < count => None,
+            Some(star) if index < star => Some(elements[index].clone()),
+            Some(star) if index == star => Some(Expression::List(Box::new(List {
+                node,
+                elements: elements[star..elements.len() + star + 1 - count].to_vec(),
+            }))),
+            Some(_) => Some(elements[elements.len() + index - count].clone()),
+        };
+    }
+    let position = match star {
+        Some(star) if index == star => return None,
+        Some(star) if index > star => format!("-{}", count - index),
+        _ => index.to_string(),
+    };
+    Some(Expression::Subscript(Box::new(Subscript {
+        node,
+        value: Box::new(value.clone()),
+        slice: Box::new(Expression::Constant(Box::new(Constant {
+            node,
+            value: ConstantValue::Int(position),
+            kind: None,
+        }))),
+    })))
+}
+
+/// Evaluates a condition on `sys.version_info` or `sys.platform` for the
+/// target version and platform, e.g. `sys.version_info >= (3, 10)` or
+/// `sys.platform == "linux"`. Literals, comparisons of literals and
+/// `TYPE_CHECKING` are folded too, e.g. `if False:`. Returns None when the
+/// condition cannot be decided before running the code.
+pub fn static_condition(
+    test: &Expression,
+    version: PythonVersion,
+    platform: PythonPlatform,
+) -> Option<bool> {
+    match test {
+        Expression::UnaryOp(u) if matches!(u.op, UnaryOperator::Not) => {
+            static_condition(&u.operand, version, platform).map(|value| !value)
+        }
+        Expression::BoolOp(b) => {
+            // `and` is decided by a false operand and `or` by a true one
+            let decisive = matches!(b.op, BooleanOperator::Or);
+            let mut result = Some(!decisive);
+            for value in &b.values {
+                match static_condition(value, version, platform) {
+                    Some(value) if value == decisive => return Some(decisive),
+                    Some(_) => {}
+                    None => result = None,
+                }
+            }
+            result
+        }
+        Expression::Compare(c) => {
+            let ([op], [comparator]) = (c.ops.as_slice(), c.comparators.as_slice()) else {
+                return None;
+            };
+            match dotted_name(&c.left).as_deref() {
+                Some("sys.version_info") => {
+                    ordering_holds(compare_version(version, comparator)?, op)
+                }
+                Some("sys.platform") => {
+                    let is_platform = platform.sys_platform() == string_value(comparator)?;
+                    match op {
+                        ComparisonOperator::Eq => Some(is_platform),
+                        ComparisonOperator::NotEq => Some(!is_platform),
+                        _ => None,
+                    }
+                }
+                _ => compare_constants(&c.left, op, comparator),
+            }
+        }
+        Expression::Constant(c) => constant_truthiness(&c.value),
+        // Type checkers assume that `typing.TYPE_CHECKING` is true
+        Expression::Name(_) | Expression::Attribute(_) => match dotted_name(test).as_deref() {
+            Some("TYPE_CHECKING" | "typing.TYPE_CHECKING") => Some(true),
+            _ => None,
+        },
+        // sys.platform.startswith("linux")
+        Expression::Call(c) => {
+            let Expression::Attribute(a) = c.func.as_ref() else {
+                return None;
+            };
+            if a.attr != "startswith" || dotted_name(&a.value).as_deref() != Some("sys.platform") {
+                return None;
+            }
+            let [prefix] = c.args.as_slice() else {
+                return None;
+            };
+            Some(platform.sys_platform().starts_with(&string_value(prefix)?))
+        }
+        _ => None,
+    }
+}
+
+/// Compares `sys.version_info` of the target version with a tuple of
+/// integers. Returns None when the result depends on the micro version.
+fn compare_version(version: PythonVersion, tuple: &Expression) -> Option<Ordering> {
+    let Expression::Tuple(t) = tuple else {
+        return None;
+    };
+    let numbers = t
+        .elements
+        .iter()
+        .map(|element| match element {
+            Expression::Constant(c) => match &c.value {
+                ConstantValue::Int(i) => i.parse::<u32>().ok(),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Option<Vec<u32>>>()?;
+    let (major, minor) = version.version_info();
+    let length = numbers.len().min(2);
+    match [major, minor][..length].cmp(&numbers[..length]) {
+        Ordering::Equal if numbers.len() > 2 => None,
+        // sys.version_info is longer than the tuple, e.g. (3, 12, 0, "final", 0)
+        Ordering::Equal => Some(Ordering::Greater),
+        ordering => Some(ordering),
+    }
+}
+
+/// Whether the constant is true in a condition, None for constants whose
+/// value is not parsed, e.g. `0x0`
+fn constant_truthiness(value: &ConstantValue) -> Option<bool> {
+    match value {
+        ConstantValue::None => Some(false),
+        ConstantValue::Ellipsis => Some(true),
+        ConstantValue::Bool(b) => Some(*b),
+        ConstantValue::Str(s) => Some(!s.is_empty()),
+        ConstantValue::Bytes(b) => Some(!b.is_empty()),
+        ConstantValue::Int(i) => i.parse::<i64>().ok().map(|i| i != 0),
+        _ => None,
+    }
+}
+
+/// Compares two literals of the same type, e.g. `1 == 1` or `"a" < "b"`
+fn compare_constants(
+    left: &Expression,
+    op: &ComparisonOperator,
+    right: &Expression,
+) -> Option<bool> {
+    let (Expression::Constant(left), Expression::Constant(right)) = (left, right) else {
+        return None;
+    };
+    let ordering = match (&left.value, &right.value) {
+        (ConstantValue::Bool(left), ConstantValue::Bool(right)) => left.cmp(right),
+        (ConstantValue::Str(left), ConstantValue::Str(right)) => left.cmp(right),
+        (ConstantValue::Int(left), ConstantValue::Int(right)) => {
+            left.parse::<i64>().ok()?.cmp(&right.parse::<i64>().ok()?)
+        }
+        _ => return None,
+    };
+    ordering_holds(ordering, op)
+}
+
+/// Whether the comparison holds for operands with the ordering
+fn ordering_holds(ordering: Ordering, op: &ComparisonOperator) -> Option<bool> {
+    match op {
+        ComparisonOperator::Lt => Some(ordering.is_lt()),
+        ComparisonOperator::LtE => Some(ordering.is_le()),
+        ComparisonOperator::Gt => Some(ordering.is_gt()),
+        ComparisonOperator::GtE => Some(ordering.is_ge()),
+        ComparisonOperator::Eq => Some(ordering.is_eq()),
+        ComparisonOperator::NotEq => Some(ordering.is_ne()),
+        _ => None,
+    }
+}
+
+fn string_value(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Constant(c) => match &c.value {
+            ConstantValue::Str(s) => Some(s.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
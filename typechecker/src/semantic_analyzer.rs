@@ -1,19 +1,32 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use enderpy_python_parser as parser;
 use enderpy_python_parser::ast::Expression;
-use parser::ast::{GetNode, Statement};
+use parser::{
+    TextSize,
+    ast::{GetNode, Statement, TypeParam},
+};
 
 use crate::{
     ast_visitor::TraversalVisitor,
+    diagnostic::CharacterSpan,
+    error_code::ErrorCode,
     nodes::EnderpyFile,
     ruff_python_import_resolver::{
         import_result::ImportResult, module_descriptor::ImportModuleDescriptor,
     },
+    semanal_utils::{
+        dataclass_decorator, dotted_name, exception_group_annotation, generic_parameters,
+        is_constant_name, is_type_qualifier, self_attribute, slot_names, static_condition,
+        unpacked_value,
+    },
+    settings::{PythonPlatform, PythonVersion},
     symbol_table::{
-        Alias, Class, Declaration, DeclarationPath, Function, Paramter, SymbolScope, SymbolTable,
-        SymbolTableNode, SymbolTableScope, SymbolTableType, TypeAlias, Variable,
+        Alias, Class, ClassTypeParameter, Declaration, DeclarationPath, Deletion, Function,
+        LookupSymbolRequest, Paramter, SymbolScope, SymbolTable, SymbolTableNode, SymbolTableScope,
+        SymbolTableType, TypeAlias, TypeParameterKind, Variable,
     },
+    type_check::checker::TypeCheckError,
 };
 
 #[allow(unused)]
@@ -28,36 +41,237 @@ pub struct SemanticAnalyzer {
     /// import os -> imports.get("os")
     /// from os import path -> imports.get("os")
     pub imports: HashMap<String, ImportResult>,
-    // TODO: Replace errors with another type
-    errors: Vec<String>,
+    /// Errors in binding rules, e.g. invalid assignment expressions
+    pub errors: Vec<TypeCheckError>,
+    /// Treat module level UPPER_CASE names as constants
+    pub uppercase_constants: bool,
+    /// Target version and platform, branches of `sys.version_info` and
+    /// `sys.platform` checks that do not match them are skipped
+    pub python_version: PythonVersion,
+    pub python_platform: PythonPlatform,
 
     scope: SymbolScope,
+    /// Ranges of the conditional blocks (if, loop, try and match bodies) that
+    /// enclose the statement being visited
+    branches: Vec<parser::ast::Node>,
+    /// True while visiting the iterable of a comprehension
+    in_comprehension_iterable: bool,
+    /// Names that are read in each scope, by scope id. A `global` or
+    /// `nonlocal` statement cannot come after them.
+    used_names: HashSet<(usize, String)>,
+    /// Yield and yield from expressions of each function that encloses the
+    /// expression being visited, the innermost function is the last
+    yields: Vec<(Vec<parser::ast::Yield>, Vec<parser::ast::YieldFrom>)>,
+    /// Return statements of each function that encloses the statement being
+    /// visited, the innermost function is the last
+    returns: Vec<Vec<parser::ast::Return>>,
 }
 
 #[allow(unused)]
 impl SemanticAnalyzer {
     pub fn new(file: EnderpyFile, imports: HashMap<String, ImportResult>) -> Self {
-        let globals = SymbolTable::global();
+        let globals = SymbolTable::global(file.module_name());
         log::debug!("Creating semantic analyzer for {}", file.module_name());
         SemanticAnalyzer {
             globals,
             file,
             imports,
             errors: vec![],
+            uppercase_constants: false,
+            python_version: PythonVersion::default(),
+            python_platform: PythonPlatform::default(),
             scope: SymbolScope::Global,
+            branches: vec![],
+            in_comprehension_iterable: false,
+            used_names: HashSet::new(),
+            yields: vec![],
+            returns: vec![],
         }
     }
 
+    /// Reports a `global` or `nonlocal` declaration of a name that the scope
+    /// already binds or reads
+    fn check_scope_declaration(&mut self, name: &str, keyword: &str, node: parser::ast::Node) {
+        let declarations = self
+            .globals
+            .current_scope_symbol(name)
+            .map(|symbol| &symbol.declarations);
+        let msg = match declarations {
+            Some(declarations)
+                if declarations
+                    .iter()
+                    .any(|decl| matches!(decl, Declaration::Parameter(_))) =>
+            {
+                format!("name '{}' is parameter and {}", name, keyword)
+            }
+            Some(declarations)
+                if declarations.iter().any(
+                    |decl| matches!(decl, Declaration::Variable(v) if v.type_annotation.is_some()),
+                ) =>
+            {
+                format!("annotated name '{}' can't be {}", name, keyword)
+            }
+            Some(_) => format!(
+                "name '{}' is assigned to before {} declaration",
+                name, keyword
+            ),
+            None if self
+                .used_names
+                .contains(&(self.globals.current_scope_id(), name.to_string())) =>
+            {
+                format!("name '{}' is used prior to {} declaration", name, keyword)
+            }
+            None => return,
+        };
+        // Point to where the scope first bound the name
+        let labels = declarations
+            .and_then(|declarations| declarations.first())
+            .map(|decl| {
+                let decl_node = decl.declaration_path().node;
+                (
+                    CharacterSpan(decl_node.start, decl_node.end),
+                    format!("'{}' is first bound here", name),
+                )
+            })
+            .into_iter()
+            .collect();
+        self.make_error_with_labels(ErrorCode::InvalidScopeDeclaration, &msg, node, labels);
+    }
+
+    fn make_error(&mut self, code: ErrorCode, msg: &str, node: parser::ast::Node) {
+        self.make_error_with_labels(code, msg, node, vec![]);
+    }
+
+    fn make_error_with_labels(
+        &mut self,
+        code: ErrorCode,
+        msg: &str,
+        node: parser::ast::Node,
+        labels: Vec<(CharacterSpan, String)>,
+    ) {
+        self.errors.push(TypeCheckError {
+            msg: msg.to_string(),
+            span: CharacterSpan(node.start, node.end),
+            code,
+            labels,
+            help: None,
+        });
+    }
+
     fn create_symbol(&mut self, name: String, decl: Declaration) {
         let symbol_node = SymbolTableNode {
             name,
             declarations: vec![decl],
+            deletions: vec![],
         };
         self.globals.add_symbol(symbol_node)
     }
 
+    /// Visits a block that may not run, e.g. the body of an if statement
+    fn visit_branch(&mut self, body: &[Statement]) {
+        let (Some(first), Some(last)) = (body.first(), body.last()) else {
+            return;
+        };
+        self.branches.push(parser::ast::Node::new(
+            first.get_node().start,
+            last.get_node().end,
+        ));
+        for stmt in body {
+            self.visit_stmt(stmt);
+        }
+        self.branches.pop();
+    }
+
+    /// Records that the test holds, or does not hold when `positive` is
+    /// false, from `start` until the end of the block. The start is not
+    /// taken from the first statement because the node of a call starts at
+    /// its parenthesis, after the expression that is called.
+    fn add_narrowing(
+        &mut self,
+        test: &Expression,
+        positive: bool,
+        start: TextSize,
+        block: &[Statement],
+    ) {
+        let Some(last) = block.last() else {
+            return;
+        };
+        let node = parser::ast::Node::new(start, last.get_node().end);
+        self.globals.add_narrowing(test.clone(), positive, node);
+    }
+
+    /// Records a loop from `start` until the end of its body
+    fn add_loop(&mut self, start: TextSize, body: &[Statement]) {
+        let Some(last) = body.last() else {
+            return;
+        };
+        let node = parser::ast::Node::new(start, last.get_node().end);
+        self.globals.add_loop(node);
+    }
+
+    fn delete_target(&mut self, target: &Expression) {
+        match target {
+            Expression::Name(n) => {
+                let deletion = Deletion {
+                    node: n.node,
+                    branch: self.branches.last().copied(),
+                };
+                self.globals.add_deletion(&n.id, deletion);
+            }
+            Expression::Tuple(t) => {
+                for elm in &t.elements {
+                    self.delete_target(elm);
+                }
+            }
+            Expression::List(l) => {
+                for elm in &l.elements {
+                    self.delete_target(elm);
+                }
+            }
+            _ => self.visit_expr(target),
+        }
+    }
+
+    /// `except E as e` binds `e` for the handler body only, Python deletes
+    /// the name when the handler finishes.
+    fn visit_except_handler(&mut self, handler: &parser::ast::ExceptHandler, is_star: bool) {
+        if let Some(typ) = &handler.typ {
+            self.visit_expr(typ);
+        }
+        if let Some(name) = &handler.name {
+            let type_annotation = match handler.typ.as_deref() {
+                Some(typ) if is_star => exception_group_annotation(typ),
+                typ => typ
+                    .filter(|typ| matches!(typ, Expression::Name(_) | Expression::Attribute(_)))
+                    .cloned(),
+            };
+            let declaration_path = DeclarationPath {
+                module_name: self.file.module_name().clone(),
+                node: handler.node,
+            };
+            self.create_symbol(
+                name.clone(),
+                Declaration::Variable(Variable {
+                    declaration_path,
+                    scope: SymbolScope::Global,
+                    type_annotation,
+                    inferred_type_source: None,
+                    is_constant: false,
+                }),
+            );
+        }
+        self.visit_branch(&handler.body);
+        if let Some(name) = &handler.name {
+            let deletion = Deletion {
+                node: parser::ast::Node::new(handler.node.end, handler.node.end),
+                branch: self.branches.last().copied(),
+            };
+            self.globals.add_deletion(name, deletion);
+        }
+    }
+
     fn current_scope(&self) -> &SymbolTableType {
-        return self.globals.current_scope_type();
+        self.globals.current_scope_type()
     }
 
     fn is_inside_class(&self) -> bool {
@@ -73,24 +287,27 @@ impl SemanticAnalyzer {
     ) {
         match target {
             Expression::Name(n) => {
+                let is_final = type_annotation
+                    .as_ref()
+                    .is_some_and(|annotation| is_type_qualifier(annotation, "Final"));
+                let is_constant = is_final
+                    || (self.uppercase_constants
+                        && matches!(self.current_scope(), SymbolTableType::Module)
+                        && is_constant_name(&n.id));
                 let decl = Declaration::Variable(Variable {
                     declaration_path,
                     scope: SymbolScope::Global,
                     type_annotation,
                     inferred_type_source: value,
-                    is_constant: false,
+                    is_constant,
                 });
                 self.create_symbol(n.id.clone(), decl)
             }
             Expression::Tuple(t) => {
-                for elm in t.elements.iter() {
-                    self.create_variable_declaration_symbol(
-                        elm,
-                        value.clone(),
-                        declaration_path.clone(),
-                        type_annotation.clone(),
-                    )
-                }
+                self.create_unpacking_declarations(&t.elements, value, declaration_path)
+            }
+            Expression::List(l) => {
+                self.create_unpacking_declarations(&l.elements, value, declaration_path)
             }
             Expression::Attribute(_) => {}
             // TODO: Add oher expressions that can be assigned
@@ -98,6 +315,92 @@ impl SemanticAnalyzer {
         }
     }
 
+    /// Declares the targets of `a, *b, c = value`, each target is inferred
+    /// from the value that it is assigned
+    fn create_unpacking_declarations(
+        &mut self,
+        targets: &[Expression],
+        value: Option<Expression>,
+        declaration_path: DeclarationPath,
+    ) {
+        let star = targets
+            .iter()
+            .position(|target| matches!(target, Expression::Starred(_)));
+        for (index, target) in targets.iter().enumerate() {
+            let element = value
+                .as_ref()
+                .and_then(|value| unpacked_value(value, targets.len(), star, index));
+            let target = match target {
+                Expression::Starred(s) => &s.value,
+                target => target,
+            };
+            self.create_variable_declaration_symbol(target, element, declaration_path.clone(), None)
+        }
+    }
+
+    /// Declares the targets of a comprehension in a new scope.
+    /// The iterable of the first `for` is evaluated in the enclosing scope.
+    fn visit_comprehension(
+        &mut self,
+        name: &str,
+        node: parser::ast::Node,
+        generators: &[parser::ast::Comprehension],
+        elements: &[&Expression],
+    ) {
+        if let Some(first) = generators.first() {
+            self.visit_comprehension_iterable(&first.iter);
+        }
+        self.globals.enter_scope(SymbolTableScope::new(
+            SymbolTableType::Comprehension,
+            name.to_string(),
+            node,
+        ));
+        // The elements come before the generators but are evaluated last,
+        // the range starts after the opening bracket so that the targets
+        // anchored at the start of the comprehension are narrowed
+        let elements_node = parser::ast::Node::new(
+            node.start + 1,
+            generators.first().map_or(node.end, |g| g.node.start),
+        );
+        for (i, generator) in generators.iter().enumerate() {
+            if i > 0 {
+                self.visit_comprehension_iterable(&generator.iter);
+            }
+            // Targets are bound before the element is evaluated, so the
+            // declaration is anchored at the start of the comprehension.
+            let declaration_path = DeclarationPath {
+                module_name: self.file.module_name().clone(),
+                node,
+            };
+            self.create_variable_declaration_symbol(
+                &generator.target,
+                None,
+                declaration_path,
+                None,
+            );
+            for if_expr in &generator.ifs {
+                self.visit_expr(if_expr);
+                // The condition holds in the rest of the comprehension and in
+                // the elements
+                let rest = parser::ast::Node::new(if_expr.get_node().end, node.end);
+                self.globals.add_narrowing(if_expr.clone(), true, rest);
+                self.globals
+                    .add_narrowing(if_expr.clone(), true, elements_node);
+            }
+        }
+        for element in elements {
+            self.visit_expr(element);
+        }
+        self.globals.exit_scope();
+    }
+
+    fn visit_comprehension_iterable(&mut self, iter: &Expression) {
+        let in_comprehension_iterable = self.in_comprehension_iterable;
+        self.in_comprehension_iterable = true;
+        self.visit_expr(iter);
+        self.in_comprehension_iterable = in_comprehension_iterable;
+    }
+
     fn add_arguments_definitions(&mut self, args: &parser::ast::Arguments) {
         let defaults_len = args.defaults.len();
         for (pos_only, index) in args.posonlyargs.iter().zip(0..args.posonlyargs.len()) {
@@ -285,7 +588,12 @@ impl TraversalVisitor for SemanticAnalyzer {
                 import_result,
             });
 
-            self.create_symbol(alias.name(), declaration);
+            // `import a.b.c` binds `a`, the submodules are its attributes
+            let name = match &alias.asname {
+                Some(asname) => asname.clone(),
+                None => alias.name.split('.').next().unwrap_or_default().to_string(),
+            };
+            self.create_symbol(name, declaration);
         }
     }
 
@@ -305,7 +613,7 @@ impl TraversalVisitor for SemanticAnalyzer {
                 declaration_path,
                 import_from_node: Some(_i.clone()),
                 import_node: None,
-                symbol_name: Some(alias.name()),
+                symbol_name: Some(alias.name.clone()),
                 import_result: module_import_result,
             });
 
@@ -314,30 +622,48 @@ impl TraversalVisitor for SemanticAnalyzer {
     }
 
     fn visit_if(&mut self, i: &parser::ast::If) {
-        for stmt in &i.body {
-            self.visit_stmt(stmt);
-        }
-        for stmt in &i.orelse {
+        // Assignment expressions in the test are bound before the branches
+        self.visit_expr(&i.test);
+        // Only the branch that runs on the target version and platform
+        // declares symbols, e.g. for `if sys.version_info >= (3, 10):`
+        let block = match static_condition(&i.test, self.python_version, self.python_platform) {
+            Some(true) => &i.body,
+            Some(false) => &i.orelse,
+            None => {
+                let body_end = i.body.last().map_or(i.node.start, |s| s.get_node().end);
+                self.add_narrowing(&i.test, true, i.test.get_node().end, &i.body);
+                self.add_narrowing(&i.test, false, body_end, &i.orelse);
+                self.visit_branch(&i.body);
+                self.visit_branch(&i.orelse);
+                return;
+            }
+        };
+        for stmt in block {
             self.visit_stmt(stmt);
         }
     }
 
     fn visit_while(&mut self, w: &parser::ast::While) {
-        for stmt in &w.body {
-            self.visit_stmt(stmt)
-        }
+        // The test holds in the body and does not hold in the else branch,
+        // which runs when the loop ends without `break`
+        let body_end = w.body.last().map_or(w.node.start, |s| s.get_node().end);
+        self.add_narrowing(&w.test, true, w.test.get_node().end, &w.body);
+        self.add_narrowing(&w.test, false, body_end, &w.orelse);
+        self.add_loop(w.test.get_node().start, &w.body);
+        self.visit_expr(&w.test);
+        self.visit_branch(&w.body);
+        self.visit_branch(&w.orelse);
     }
 
     fn visit_for(&mut self, f: &parser::ast::For) {
-        for stmt in &f.body {
-            self.visit_stmt(stmt);
-        }
+        // The iterable is evaluated once, before the loop
+        self.add_loop(f.iter.get_node().end, &f.body);
+        self.visit_branch(&f.body);
     }
 
     fn visit_async_for(&mut self, f: &parser::ast::AsyncFor) {
-        for stmt in &f.body {
-            self.visit_stmt(stmt);
-        }
+        self.add_loop(f.iter.get_node().end, &f.body);
+        self.visit_branch(&f.body);
     }
 
     fn visit_with(&mut self, w: &parser::ast::With) {
@@ -346,9 +672,8 @@ impl TraversalVisitor for SemanticAnalyzer {
         }
         for with_items in &w.items {
             self.visit_expr(&with_items.context_expr);
-            match &with_items.optional_vars {
-                Some(items) => self.visit_expr(items),
-                None => (),
+            if let Some(items) = &with_items.optional_vars {
+                self.visit_expr(items)
             }
         }
     }
@@ -359,49 +684,32 @@ impl TraversalVisitor for SemanticAnalyzer {
         }
         for with_items in &w.items {
             self.visit_expr(&with_items.context_expr);
-            match &with_items.optional_vars {
-                Some(items) => self.visit_expr(items),
-                None => (),
+            if let Some(items) = &with_items.optional_vars {
+                self.visit_expr(items)
             }
         }
     }
 
     fn visit_try(&mut self, t: &parser::ast::Try) {
-        for stmt in &t.body {
-            self.visit_stmt(stmt);
-        }
-        for stmt in &t.orelse {
-            self.visit_stmt(stmt);
+        self.visit_branch(&t.body);
+        for handler in &t.handlers {
+            self.visit_except_handler(handler, false);
         }
+        self.visit_branch(&t.orelse);
         for stmt in &t.finalbody {
             self.visit_stmt(stmt);
         }
-        // TODO: need to visit exception handler name and type but let's keep it simple
-        // for now
-        for handler in &t.handlers {
-            for stmt in &handler.body {
-                self.visit_stmt(stmt);
-            }
-        }
     }
 
     fn visit_try_star(&mut self, t: &parser::ast::TryStar) {
-        for stmt in &t.body {
-            self.visit_stmt(stmt);
-        }
-        for stmt in &t.orelse {
-            self.visit_stmt(stmt);
+        self.visit_branch(&t.body);
+        for handler in &t.handlers {
+            self.visit_except_handler(handler, true);
         }
+        self.visit_branch(&t.orelse);
         for stmt in &t.finalbody {
             self.visit_stmt(stmt);
         }
-        // TODO: need to visit exception handler name and type but let's keep it simple
-        // for now
-        for handler in &t.handlers {
-            for stmt in &handler.body {
-                self.visit_stmt(stmt);
-            }
-        }
     }
 
     fn visit_function_def(&mut self, f: &parser::ast::FunctionDef) {
@@ -412,25 +720,22 @@ impl TraversalVisitor for SemanticAnalyzer {
         self.globals.enter_scope(SymbolTableScope::new(
             crate::symbol_table::SymbolTableType::Function,
             f.name.clone(),
-            0,
+            f.node,
         ));
 
         self.add_arguments_definitions(&f.args);
 
-        let mut return_statements = vec![];
-        let mut yeild_statements = vec![];
         let mut raise_statements = vec![];
+        self.yields.push((vec![], vec![]));
+        self.returns.push(vec![]);
         for stmt in &f.body {
             self.visit_stmt(stmt);
-            match &stmt {
-                parser::ast::Statement::Raise(r) => raise_statements.push(r.clone()),
-                parser::ast::Statement::Return(r) => return_statements.push(r.clone()),
-                parser::ast::Statement::ExpressionStatement(parser::ast::Expression::Yield(y)) => {
-                    yeild_statements.push(*y.clone())
-                }
-                _ => (),
+            if let parser::ast::Statement::Raise(r) = &stmt {
+                raise_statements.push(r.clone());
             }
         }
+        let return_statements = self.returns.pop().unwrap_or_default();
+        let (yield_statements, yield_from_statements) = self.yields.pop().unwrap_or_default();
 
         for type_parameter in &f.type_params {
             let declaration_path = DeclarationPath {
@@ -451,9 +756,10 @@ impl TraversalVisitor for SemanticAnalyzer {
             declaration_path,
             function_node: f.clone(),
             is_method: self.is_inside_class(),
-            is_generator: !yeild_statements.is_empty(),
+            is_generator: !yield_statements.is_empty() || !yield_from_statements.is_empty(),
             return_statements,
-            yeild_statements,
+            yield_statements,
+            yield_from_statements,
             raise_statements,
         });
         self.create_symbol(f.name.clone(), function_declaration);
@@ -471,6 +777,30 @@ impl TraversalVisitor for SemanticAnalyzer {
                 type_alias_node: t.clone(),
             }),
         );
+        self.globals.add_type_alias_value(t.value.get_node());
+        if t.type_params.is_empty() {
+            return;
+        }
+        // The type parameters are only visible in the value
+        self.globals.enter_scope(SymbolTableScope::new(
+            SymbolTableType::TypeAlias,
+            t.name.clone(),
+            t.node,
+        ));
+        for type_parameter in &t.type_params {
+            let declaration_path = DeclarationPath {
+                module_name: self.file.module_name().clone(),
+                node: type_parameter.get_node(),
+            };
+            self.create_symbol(
+                type_parameter.get_name(),
+                Declaration::TypeParameter(crate::symbol_table::TypeParameter {
+                    declaration_path,
+                    type_parameter_node: type_parameter.clone(),
+                }),
+            );
+        }
+        self.globals.exit_scope();
     }
 
     fn visit_async_function_def(&mut self, _f: &parser::ast::AsyncFunctionDef) {}
@@ -483,7 +813,7 @@ impl TraversalVisitor for SemanticAnalyzer {
         self.globals.enter_scope(SymbolTableScope::new(
             SymbolTableType::Class,
             c.name.clone(),
-            c.node.start,
+            c.node,
         ));
 
         for type_parameter in &c.type_params {
@@ -500,23 +830,58 @@ impl TraversalVisitor for SemanticAnalyzer {
             );
         }
         let mut methods = vec![];
-        let mut attributes = HashMap::new();
+        let mut attributes = BTreeMap::new();
+        let mut attribute_annotations = BTreeMap::new();
+        let mut slots = None;
+        let mut class_variables = vec![];
+        let is_slots =
+            |target: &Expression| matches!(target, Expression::Name(n) if n.id == "__slots__");
 
         for stmt in &c.body {
+            match stmt {
+                parser::ast::Statement::AssignStatement(a) if a.targets.iter().any(is_slots) => {
+                    slots = slot_names(&a.value);
+                }
+                parser::ast::Statement::AnnAssignStatement(a) if is_slots(&a.target) => {
+                    slots = a.value.as_ref().and_then(slot_names);
+                }
+                parser::ast::Statement::AnnAssignStatement(a)
+                    if is_type_qualifier(&a.annotation, "ClassVar") =>
+                {
+                    if let Expression::Name(n) = &a.target {
+                        class_variables.push(n.id.clone());
+                    }
+                }
+                _ => {}
+            }
+            if let parser::ast::Statement::AnnAssignStatement(a) = stmt {
+                match &a.target {
+                    Expression::Name(n) if a.simple => {
+                        attribute_annotations.insert(n.id.clone(), a.annotation.clone());
+                    }
+                    _ => {}
+                }
+            }
             if let parser::ast::Statement::FunctionDef(f) = stmt {
                 if f.name == "__init__" {
                     for stmt in &f.body {
-                        if let parser::ast::Statement::AssignStatement(assign) = stmt {
-                            for target in &assign.targets {
-                                if let parser::ast::Expression::Attribute(attr) = target {
-                                    if let parser::ast::Expression::Name(ref n) = *attr.value {
-                                        if n.id == "self" {
-                                            attributes
-                                                .insert(attr.attr.clone(), assign.value.clone());
-                                        }
+                        match stmt {
+                            parser::ast::Statement::AssignStatement(assign) => {
+                                for target in &assign.targets {
+                                    if let Some(attribute) = self_attribute(target) {
+                                        attributes.insert(attribute, assign.value.clone());
+                                    }
+                                }
+                            }
+                            parser::ast::Statement::AnnAssignStatement(a) => {
+                                if let Some(attribute) = self_attribute(&a.target) {
+                                    if let Some(value) = &a.value {
+                                        attributes.insert(attribute.clone(), value.clone());
                                     }
+                                    attribute_annotations.insert(attribute, a.annotation.clone());
                                 }
                             }
+                            _ => {}
                         }
                     }
                 }
@@ -527,20 +892,73 @@ impl TraversalVisitor for SemanticAnalyzer {
 
         self.globals.exit_scope();
 
+        let bases = c
+            .bases
+            .iter()
+            .filter_map(dotted_name)
+            .collect::<Vec<String>>();
+        let type_parameters = if c.type_params.is_empty() {
+            let mut type_parameters = c
+                .bases
+                .iter()
+                .find_map(generic_parameters)
+                .unwrap_or_default();
+            for parameter in type_parameters.iter_mut() {
+                let declaration = self
+                    .globals
+                    .lookup_in_scope(LookupSymbolRequest {
+                        name: parameter.name.clone(),
+                        position: None,
+                    })
+                    .and_then(|symbol| symbol.last_declaration());
+                if declaration.is_some_and(|decl| decl.is_type_variable("ParamSpec")) {
+                    parameter.kind = TypeParameterKind::ParamSpec;
+                }
+                parameter.has_default =
+                    declaration.is_some_and(Declaration::has_type_variable_default);
+            }
+            type_parameters
+        } else {
+            c.type_params
+                .iter()
+                .map(|type_param| ClassTypeParameter {
+                    name: type_param.get_name(),
+                    kind: match type_param {
+                        TypeParam::TypeVar(_) => TypeParameterKind::TypeVar,
+                        TypeParam::ParamSpec(_) => TypeParameterKind::ParamSpec,
+                        TypeParam::TypeVarTuple(_) => TypeParameterKind::TypeVarTuple,
+                    },
+                    has_default: false,
+                })
+                .collect()
+        };
+        let dataclass = c.decorator_list.iter().find_map(dataclass_decorator);
+        // The members that `@dataclass` adds are known
+        let has_dynamic_decorators = c
+            .decorator_list
+            .iter()
+            .any(|decorator| dataclass_decorator(decorator).is_none());
+        let has_dynamic_members =
+            has_dynamic_decorators || !c.keywords.is_empty() || bases.len() != c.bases.len();
         let class_declaration = Declaration::Class(Class {
             name: c.name.clone(),
             declaration_path,
+            bases,
             attributes,
+            attribute_annotations,
             methods,
+            has_dynamic_members,
+            slots,
+            class_variables,
+            dataclass,
+            type_parameters,
         });
         self.create_symbol(c.name.clone(), class_declaration);
     }
 
     fn visit_match(&mut self, m: &parser::ast::Match) {
         for case in &m.cases {
-            for stmt in &case.body {
-                self.visit_stmt(stmt);
-            }
+            self.visit_branch(&case.body);
         }
     }
 
@@ -552,51 +970,200 @@ impl TraversalVisitor for SemanticAnalyzer {
         }
     }
 
-    fn visit_tuple(&mut self, _t: &parser::ast::Tuple) {}
+    fn visit_tuple(&mut self, t: &parser::ast::Tuple) {
+        for elm in t.elements.iter() {
+            self.visit_expr(elm);
+        }
+    }
 
-    fn visit_dict(&mut self, _d: &parser::ast::Dict) {}
+    fn visit_dict(&mut self, d: &parser::ast::Dict) {
+        for elm in d.keys.iter().chain(d.values.iter()) {
+            self.visit_expr(elm);
+        }
+    }
 
-    fn visit_set(&mut self, _s: &parser::ast::Set) {}
+    fn visit_set(&mut self, s: &parser::ast::Set) {
+        for elm in s.elements.iter() {
+            self.visit_expr(elm);
+        }
+    }
 
-    fn visit_name(&mut self, _n: &parser::ast::Name) {}
+    fn visit_name(&mut self, n: &parser::ast::Name) {
+        self.used_names
+            .insert((self.globals.current_scope_id(), n.id.clone()));
+    }
 
-    fn visit_bool_op(&mut self, _b: &parser::ast::BoolOperation) {}
+    fn visit_bool_op(&mut self, b: &parser::ast::BoolOperation) {
+        for value in b.values.iter() {
+            self.visit_expr(value);
+        }
+    }
 
-    fn visit_unary_op(&mut self, _u: &parser::ast::UnaryOperation) {}
+    fn visit_unary_op(&mut self, u: &parser::ast::UnaryOperation) {
+        self.visit_expr(&u.operand);
+    }
 
-    fn visit_bin_op(&mut self, _b: &parser::ast::BinOp) {}
+    fn visit_bin_op(&mut self, b: &parser::ast::BinOp) {
+        self.visit_expr(&b.left);
+        self.visit_expr(&b.right);
+    }
 
-    fn visit_named_expr(&mut self, _n: &parser::ast::NamedExpression) {}
+    fn visit_named_expr(&mut self, n: &parser::ast::NamedExpression) {
+        self.visit_expr(&n.value);
+        let Expression::Name(target) = n.target.as_ref() else {
+            return;
+        };
+        if self.in_comprehension_iterable {
+            self.make_error(
+                ErrorCode::InvalidAssignmentExpression,
+                "assignment expression cannot be used in a comprehension iterable expression",
+                n.node,
+            );
+            return;
+        }
+        if matches!(self.current_scope(), SymbolTableType::Comprehension) {
+            if self.globals.is_comprehension_iteration_variable(&target.id) {
+                self.make_error(
+                    ErrorCode::InvalidAssignmentExpression,
+                    &format!(
+                        "assignment expression cannot rebind comprehension iteration variable '{}'",
+                        target.id
+                    ),
+                    n.node,
+                );
+                return;
+            }
+            if matches!(
+                self.globals.named_expr_scope_type(),
+                Some(SymbolTableType::Class)
+            ) {
+                self.make_error(
+                    ErrorCode::InvalidAssignmentExpression,
+                    "assignment expression within a comprehension cannot be used in a class body",
+                    n.node,
+                );
+                return;
+            }
+        }
+        // In a comprehension the element is evaluated after the conditions,
+        // so like the targets the declaration is anchored at the start of
+        // the comprehension
+        let node = if matches!(self.current_scope(), SymbolTableType::Comprehension) {
+            self.globals.current_scope_node()
+        } else {
+            n.node
+        };
+        let declaration_path = DeclarationPath {
+            module_name: self.file.module_name().clone(),
+            node,
+        };
+        let decl = Declaration::Variable(Variable {
+            declaration_path,
+            scope: SymbolScope::Global,
+            type_annotation: None,
+            inferred_type_source: Some(*n.value.clone()),
+            is_constant: false,
+        });
+        self.globals.add_named_expr_symbol(SymbolTableNode {
+            name: target.id.clone(),
+            declarations: vec![decl],
+            deletions: vec![],
+        });
+    }
 
-    fn visit_yield(&mut self, _y: &parser::ast::Yield) {}
+    fn visit_yield(&mut self, y: &parser::ast::Yield) {
+        if let Some((yields, _)) = self.yields.last_mut() {
+            yields.push(y.clone());
+        }
+        if let Some(value) = &y.value {
+            self.visit_expr(value);
+        }
+    }
 
-    fn visit_yield_from(&mut self, _y: &parser::ast::YieldFrom) {}
+    fn visit_yield_from(&mut self, y: &parser::ast::YieldFrom) {
+        if let Some((_, yield_froms)) = self.yields.last_mut() {
+            yield_froms.push(y.clone());
+        }
+        self.visit_expr(&y.value);
+    }
 
-    fn visit_starred(&mut self, _s: &parser::ast::Starred) {}
+    fn visit_starred(&mut self, s: &parser::ast::Starred) {
+        self.visit_expr(&s.value);
+    }
 
-    fn visit_generator(&mut self, _g: &parser::ast::Generator) {}
+    fn visit_generator(&mut self, g: &parser::ast::Generator) {
+        self.visit_comprehension("<genexpr>", g.node, &g.generators, &[&g.element]);
+    }
 
-    fn visit_list_comp(&mut self, _l: &parser::ast::ListComp) {}
+    fn visit_list_comp(&mut self, l: &parser::ast::ListComp) {
+        self.visit_comprehension("<listcomp>", l.node, &l.generators, &[&l.element]);
+    }
 
-    fn visit_set_comp(&mut self, _s: &parser::ast::SetComp) {}
+    fn visit_set_comp(&mut self, s: &parser::ast::SetComp) {
+        self.visit_comprehension("<setcomp>", s.node, &s.generators, &[&s.element]);
+    }
 
-    fn visit_dict_comp(&mut self, _d: &parser::ast::DictComp) {}
+    fn visit_dict_comp(&mut self, d: &parser::ast::DictComp) {
+        self.visit_comprehension("<dictcomp>", d.node, &d.generators, &[&d.key, &d.value]);
+    }
 
-    fn visit_attribute(&mut self, _a: &parser::ast::Attribute) {}
+    fn visit_attribute(&mut self, a: &parser::ast::Attribute) {
+        self.visit_expr(&a.value);
+    }
 
-    fn visit_subscript(&mut self, _s: &parser::ast::Subscript) {}
+    fn visit_subscript(&mut self, s: &parser::ast::Subscript) {
+        self.visit_expr(&s.value);
+        self.visit_expr(&s.slice);
+    }
 
     fn visit_slice(&mut self, _s: &parser::ast::Slice) {}
 
-    fn visit_call(&mut self, _c: &parser::ast::Call) {}
+    fn visit_call(&mut self, c: &parser::ast::Call) {
+        self.visit_expr(&c.func);
+        for arg in c.args.iter() {
+            self.visit_expr(arg);
+        }
+        for keyword in c.keywords.iter() {
+            self.visit_expr(&keyword.value);
+        }
+    }
 
-    fn visit_await(&mut self, _a: &parser::ast::Await) {}
+    fn visit_await(&mut self, a: &parser::ast::Await) {
+        self.visit_expr(&a.value);
+    }
 
-    fn visit_compare(&mut self, _c: &parser::ast::Compare) {}
+    fn visit_compare(&mut self, c: &parser::ast::Compare) {
+        self.visit_expr(&c.left);
+        for comparator in c.comparators.iter() {
+            self.visit_expr(comparator);
+        }
+    }
 
-    fn visit_lambda(&mut self, _l: &parser::ast::Lambda) {}
+    fn visit_lambda(&mut self, l: &parser::ast::Lambda) {
+        // Defaults are evaluated in the enclosing scope
+        let kw_defaults = l.args.kw_defaults.iter().flatten();
+        for default in l.args.defaults.iter().chain(kw_defaults) {
+            self.visit_expr(default);
+        }
+        self.globals.enter_scope(SymbolTableScope::new(
+            SymbolTableType::Lambda,
+            String::from("<lambda>"),
+            l.node,
+        ));
+        self.add_arguments_definitions(&l.args);
+        // A yield in the body makes the lambda a generator, not the function
+        // that contains it
+        self.yields.push((vec![], vec![]));
+        self.visit_expr(&l.body);
+        self.yields.pop();
+        self.globals.exit_scope();
+    }
 
-    fn visit_if_exp(&mut self, _i: &parser::ast::IfExp) {}
+    fn visit_if_exp(&mut self, i: &parser::ast::IfExp) {
+        self.visit_expr(&i.test);
+        self.visit_expr(&i.body);
+        self.visit_expr(&i.orelse);
+    }
 
     fn visit_joined_str(&mut self, _j: &parser::ast::JoinedStr) {}
 
@@ -627,6 +1194,15 @@ impl TraversalVisitor for SemanticAnalyzer {
     fn visit_ann_assign(&mut self, a: &parser::ast::AnnAssign) {
         let value = &a.value;
         let target = &a.target;
+        if let Expression::Name(n) = target {
+            if let Some(keyword) = self.globals.scope_declaration(&n.id) {
+                self.make_error(
+                    ErrorCode::InvalidScopeDeclaration,
+                    &format!("annotated name '{}' can't be {}", n.id, keyword),
+                    a.node,
+                );
+            }
+        }
         let declaration_path = DeclarationPath {
             module_name: self.file.module_name().clone(),
             node: a.node,
@@ -648,13 +1224,32 @@ impl TraversalVisitor for SemanticAnalyzer {
         self.visit_expr(&a.value);
     }
 
-    fn visit_assert(&mut self, _a: &parser::ast::Assert) {}
+    fn visit_assert(&mut self, a: &parser::ast::Assert) {
+        // The test holds after the assert until the end of the enclosing block
+        let end = self
+            .branches
+            .last()
+            .map_or(TextSize::MAX, |branch| branch.end);
+        let node = parser::ast::Node::new(a.node.end, end);
+        self.globals.add_narrowing(a.test.clone(), true, node);
+    }
 
     fn visit_pass(&mut self, _p: &parser::ast::Pass) {}
 
-    fn visit_delete(&mut self, _d: &parser::ast::Delete) {}
+    fn visit_delete(&mut self, d: &parser::ast::Delete) {
+        for target in &d.targets {
+            self.delete_target(target);
+        }
+    }
 
-    fn visit_return(&mut self, _r: &parser::ast::Return) {}
+    fn visit_return(&mut self, r: &parser::ast::Return) {
+        if let Some(returns) = self.returns.last_mut() {
+            returns.push(r.clone());
+        }
+        if let Some(value) = &r.value {
+            self.visit_expr(value);
+        }
+    }
 
     fn visit_raise(&mut self, _r: &parser::ast::Raise) {}
 
@@ -662,7 +1257,17 @@ impl TraversalVisitor for SemanticAnalyzer {
 
     fn visit_continue(&mut self, _c: &parser::ast::Continue) {}
 
-    fn visit_global(&mut self, _g: &parser::ast::Global) {}
+    fn visit_global(&mut self, g: &parser::ast::Global) {
+        for name in &g.names {
+            self.check_scope_declaration(name, "global", g.node);
+            self.globals.declare_global(name.clone());
+        }
+    }
 
-    fn visit_nonlocal(&mut self, _n: &parser::ast::Nonlocal) {}
+    fn visit_nonlocal(&mut self, n: &parser::ast::Nonlocal) {
+        for name in &n.names {
+            self.check_scope_declaration(name, "nonlocal", n.node);
+            self.globals.declare_nonlocal(name.clone());
+        }
+    }
 }
@@ -1,9 +1,15 @@
-use std::{env, path::PathBuf};
+use std::{path::PathBuf, str::FromStr};
 
+#[cfg(feature = "native")]
 use config::{Config, ConfigError, File};
+use enderpy_python_parser::DEFAULT_MAX_NESTING_DEPTH;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
+pub use crate::ruff_python_import_resolver::{
+    python_platform::PythonPlatform, python_version::PythonVersion,
+};
+
+#[derive(Debug, Default, Deserialize)]
 #[allow(unused)]
 pub struct ImportDiscovery {
     pub python_executable: Option<PathBuf>,
@@ -18,20 +24,100 @@ pub struct Settings {
     pub import_discovery: ImportDiscovery,
     // Indicates whether to check imports
     pub follow_imports: FollowImports,
+    /// Report `from x import *` statements
+    #[serde(default)]
+    pub warn_star_imports: bool,
+    /// Report import blocks that are not sorted and grouped like isort does
+    #[serde(default)]
+    pub sort_imports: bool,
+    /// Report public modules, classes and functions without a docstring and
+    /// docstrings whose parameters do not match the signature. Disabled when
+    /// no style is set.
+    #[serde(default)]
+    pub docstring_style: Option<DocstringStyle>,
+    /// Treat module level UPPER_CASE names as constants that cannot be
+    /// reassigned
+    #[serde(default)]
+    pub uppercase_constants: bool,
+    /// Report attribute access and calls on values whose type includes None
+    #[serde(default)]
+    pub strict_optional: bool,
+    /// Infer the element type of list, set and dict displays with elements
+    /// of different types as their common base class instead of their union
+    #[serde(default)]
+    pub join_container_types: bool,
+    /// Check the arguments of `%` format strings and `str.format` calls
+    /// and the format specs of f-strings
+    #[serde(default)]
+    pub check_format_strings: bool,
+    /// Python version that `sys.version_info` checks are evaluated for
+    #[serde(default)]
+    pub python_version: PythonVersion,
+    /// Platform that `sys.platform` checks are evaluated for
+    #[serde(default)]
+    pub python_platform: PythonPlatform,
+    /// How deep expressions and blocks can be nested before parsing fails
+    /// with a too deeply nested error
+    #[serde(default = "default_max_nesting_depth")]
+    pub max_nesting_depth: usize,
 }
 
-#[derive(Debug, Deserialize)]
-#[allow(unused)]
+fn default_max_nesting_depth() -> usize {
+    DEFAULT_MAX_NESTING_DEPTH
+}
+
+/// Convention that the parameter sections of docstrings follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum DocstringStyle {
+    /// `Args:` followed by indented `name (type): description` entries
+    #[serde(rename = "google")]
+    Google,
+    /// `Parameters` underlined with dashes followed by `name : type` entries
+    #[serde(rename = "numpy")]
+    Numpy,
+}
+
+impl FromStr for DocstringStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "google" => Ok(DocstringStyle::Google),
+            "numpy" => Ok(DocstringStyle::Numpy),
+            _ => Err(format!("unsupported docstring style {}", s)),
+        }
+    }
+}
+
+/// How the modules that the build sources import are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 pub enum FollowImports {
+    /// Analyze and report errors in every imported module
     #[serde(rename = "all")]
     All,
+    /// Analyze the imported modules and report errors in the ones that are
+    /// inside the project root
+    #[serde(rename = "normal")]
+    Normal,
+    /// Analyze the imported modules without reporting their errors
+    #[serde(rename = "silent")]
+    Silent,
+    /// Do not analyze imported modules, the imported names are Any. Modules
+    /// of star imports are still analyzed to bind the names.
     #[serde(rename = "skip")]
     Skip,
 }
 
+impl FollowImports {
+    pub fn follows_imports(&self) -> bool {
+        *self != FollowImports::Skip
+    }
+}
+
 impl Settings {
+    #[cfg(feature = "native")]
     pub fn new() -> Result<Self, ConfigError> {
-        let _run_mode = env::var("RUN_MODE").unwrap_or_else(|_| "development".into());
+        let _run_mode = std::env::var("RUN_MODE").unwrap_or_else(|_| "development".into());
         let s = Config::builder()
             // Start off by merging in the "default" configuration file
             .add_source(File::with_name("examples/hierarchical-env/config/default"))
@@ -40,15 +126,36 @@ impl Settings {
         s.try_deserialize()
     }
 
+    /// Settings of the tests, which analyze and report errors in every
+    /// imported module
+    #[cfg(test)]
     pub fn test_settings() -> Self {
+        Settings {
+            follow_imports: FollowImports::All,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Settings {
+    /// Checks only the build sources, without imported modules, typeshed or
+    /// optional rules
+    fn default() -> Self {
         Settings {
             debug: false,
             root: PathBuf::from(""),
-            follow_imports: FollowImports::All,
-            import_discovery: ImportDiscovery {
-                python_executable: None,
-                typeshed_path: None,
-            },
+            import_discovery: ImportDiscovery::default(),
+            follow_imports: FollowImports::Skip,
+            warn_star_imports: false,
+            sort_imports: false,
+            docstring_style: None,
+            uppercase_constants: false,
+            strict_optional: false,
+            join_container_types: false,
+            check_format_strings: false,
+            python_version: PythonVersion::default(),
+            python_platform: PythonPlatform::default(),
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
         }
     }
 }
@@ -0,0 +1,133 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use enderpy_python_parser::ast::{ConstantValue, Expression};
+
+use crate::{
+    state::State,
+    symbol_table::{Alias, Declaration, SymbolTableNode},
+};
+
+/// Name of the symbol that holds the declarations of `from x import *`
+pub const STAR: &str = "*";
+
+/// A `from x import *` of a module
+pub struct StarImport {
+    pub alias: Alias,
+    /// Path of the imported module, None if the import is not resolved
+    pub path: Option<PathBuf>,
+}
+
+/// Star imports in the module scope of the state
+pub fn star_imports(state: &State) -> Vec<StarImport> {
+    let Some(symbol) = state.symbol_table().global_scope().symbols().get(STAR) else {
+        return vec![];
+    };
+    symbol
+        .declarations
+        .iter()
+        .filter_map(|decl| match decl {
+            Declaration::Alias(alias) => Some(StarImport {
+                path: alias.import_result.resolved_paths.last().cloned(),
+                alias: alias.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Names that `from <module> import *` binds for the module at the path.
+/// Uses `__all__` when it is a list or tuple of strings, otherwise every name
+/// that does not start with an underscore, including the names of the star
+/// imports of the module.
+pub fn exported_names(
+    modules: &BTreeMap<String, State>,
+    path: &Path,
+    visited: &mut HashSet<PathBuf>,
+) -> Vec<String> {
+    if !visited.insert(path.to_path_buf()) {
+        return vec![];
+    }
+    let Some(state) = modules.values().find(|state| state.file.path() == path) else {
+        return vec![];
+    };
+    let symbols = state.symbol_table().global_scope().symbols();
+
+    if let Some(all) = symbols.get("__all__") {
+        let mut names = vec![];
+        for decl in &all.declarations {
+            match decl {
+                Declaration::Variable(v) => {
+                    if let Some(source) = &v.inferred_type_source {
+                        names.extend(string_elements(source));
+                    }
+                }
+                // from x import __all__
+                Declaration::Alias(a) => {
+                    if let Some(path) = a.import_result.resolved_paths.last() {
+                        names.extend(exported_names(modules, path, visited));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !names.is_empty() {
+            names.sort();
+            names.dedup();
+            return names;
+        }
+    }
+
+    let mut names = symbols
+        .keys()
+        .filter(|name| name.as_str() != STAR && !name.starts_with('_'))
+        .cloned()
+        .collect::<Vec<String>>();
+    for path in star_imports(state).into_iter().filter_map(|s| s.path) {
+        names.extend(exported_names(modules, &path, visited));
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Alias declarations for the names bound by the star import
+pub fn star_import_symbols(star_import: &StarImport, names: Vec<String>) -> Vec<SymbolTableNode> {
+    names
+        .into_iter()
+        .map(|name| {
+            let declaration = Declaration::Alias(Alias {
+                declaration_path: star_import.alias.declaration_path.clone(),
+                import_from_node: star_import.alias.import_from_node.clone(),
+                import_node: None,
+                symbol_name: Some(name.clone()),
+                import_result: star_import.alias.import_result.clone(),
+            });
+            SymbolTableNode {
+                name,
+                declarations: vec![declaration],
+                deletions: vec![],
+            }
+        })
+        .collect()
+}
+
+fn string_elements(expr: &Expression) -> Vec<String> {
+    let elements = match expr {
+        Expression::List(l) => &l.elements,
+        Expression::Tuple(t) => &t.elements,
+        _ => return vec![],
+    };
+    elements
+        .iter()
+        .filter_map(|elm| match elm {
+            Expression::Constant(c) => match &c.value {
+                ConstantValue::Str(s) => Some(s.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
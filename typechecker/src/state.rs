@@ -9,7 +9,8 @@ use crate::{
         import_result::ImportResult, module_descriptor::ImportModuleDescriptor, resolver,
     },
     semantic_analyzer::SemanticAnalyzer,
-    symbol_table::SymbolTable,
+    settings::Settings,
+    symbol_table::{SymbolTable, SymbolTableNode},
 };
 
 #[derive(Debug, Clone)]
@@ -24,18 +25,31 @@ pub struct State {
 impl State {
     pub fn new(file: EnderpyFile) -> Self {
         Self {
+            symbol_table: SymbolTable::global(file.module_name()),
             file,
-            symbol_table: SymbolTable::global(),
             diagnostics: Vec::new(),
             imports: HashMap::new(),
         }
     }
     /// entry point to fill up the symbol table from the global definitions
-    pub fn populate_symbol_table(&mut self) {
+    pub fn populate_symbol_table(&mut self, options: &Settings) {
         let mut sem_anal = SemanticAnalyzer::new(self.file.clone(), self.imports.clone());
+        sem_anal.uppercase_constants = options.uppercase_constants;
+        sem_anal.python_version = options.python_version;
+        sem_anal.python_platform = options.python_platform;
         for stmt in &self.file.body {
             sem_anal.visit_stmt(stmt)
         }
+        for error in sem_anal.errors {
+            self.diagnostics.push(Diagnostic {
+                body: error.msg,
+                suggestion: Some(error.help.unwrap_or_default()),
+                range: self.file.get_range(&error.span),
+                code: error.code,
+                fix: None,
+                labels: self.file.get_labels(&error.labels),
+            });
+        }
         self.symbol_table = sem_anal.globals
     }
 
@@ -43,6 +57,17 @@ impl State {
         self.symbol_table.clone()
     }
 
+    pub fn symbol_table(&self) -> &SymbolTable {
+        &self.symbol_table
+    }
+
+    /// Adds symbols to the module scope, e.g. the names of star imports
+    pub fn add_global_symbols(&mut self, symbols: Vec<SymbolTableNode>) {
+        for symbol in symbols {
+            self.symbol_table.add_symbol(symbol);
+        }
+    }
+
     pub fn resolve_file_imports(
         &mut self,
         execution_environment: &ruff_python_resolver::execution_environment::ExecutionEnvironment,
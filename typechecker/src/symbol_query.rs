@@ -0,0 +1,138 @@
+// Programmatic access to the symbols of a module.
+// Every declaration of a symbol is reported separately, so a variable that is
+// assigned twice appears twice with different positions.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::Serialize;
+
+use crate::{
+    state::State,
+    symbol_table::{Declaration, SymbolTableType},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    Class,
+    Variable,
+    Parameter,
+    Import,
+    TypeParameter,
+    TypeAlias,
+}
+
+impl SymbolKind {
+    pub const ALL: [SymbolKind; 7] = [
+        SymbolKind::Function,
+        SymbolKind::Class,
+        SymbolKind::Variable,
+        SymbolKind::Parameter,
+        SymbolKind::Import,
+        SymbolKind::TypeParameter,
+        SymbolKind::TypeAlias,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Class => "class",
+            SymbolKind::Variable => "variable",
+            SymbolKind::Parameter => "parameter",
+            SymbolKind::Import => "import",
+            SymbolKind::TypeParameter => "type_parameter",
+            SymbolKind::TypeAlias => "type_alias",
+        }
+    }
+
+    pub(crate) fn of(declaration: &Declaration) -> Self {
+        match declaration {
+            Declaration::Variable(_) => SymbolKind::Variable,
+            Declaration::Function(_) => SymbolKind::Function,
+            Declaration::Class(_) => SymbolKind::Class,
+            Declaration::Alias(_) => SymbolKind::Import,
+            Declaration::Parameter(_) => SymbolKind::Parameter,
+            Declaration::TypeParameter(_) => SymbolKind::TypeParameter,
+            Declaration::TypeAlias(_) => SymbolKind::TypeAlias,
+        }
+    }
+}
+
+impl FromStr for SymbolKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SymbolKind::ALL
+            .iter()
+            .find(|kind| kind.as_str() == s)
+            .copied()
+            .ok_or_else(|| {
+                let kinds = SymbolKind::ALL.map(|kind| kind.as_str()).join(", ");
+                format!("unknown symbol kind {}, expected one of {}", s, kinds)
+            })
+    }
+}
+
+impl Display for SymbolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Filters for `BuildManager::symbols`, `None` matches everything
+#[derive(Debug, Clone, Default)]
+pub struct SymbolQuery {
+    /// Qualified name of the scope, e.g. `global` or `Class.method`
+    pub scope: Option<String>,
+    pub kind: Option<SymbolKind>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Qualified name of the scope that declares the symbol
+    pub scope: String,
+    /// 0-based line of the declaration
+    pub line: u32,
+    /// 0-based character of the declaration
+    pub character: u32,
+}
+
+pub(crate) fn query_symbols(state: &State, query: &SymbolQuery) -> Vec<SymbolInfo> {
+    let symbol_table = state.get_symbol_table();
+    let mut symbols = vec![];
+    for scope in symbol_table.iter_scopes() {
+        if scope.symbol_table_type == SymbolTableType::BUILTIN {
+            continue;
+        }
+        if query
+            .scope
+            .as_ref()
+            .is_some_and(|name| *name != scope.qualified_name)
+        {
+            continue;
+        }
+        for symbol in scope.symbols().values() {
+            for declaration in &symbol.declarations {
+                let kind = SymbolKind::of(declaration);
+                if query.kind.is_some_and(|k| k != kind) {
+                    continue;
+                }
+                let position = state
+                    .file
+                    .get_position(declaration.declaration_path().node.start);
+                symbols.push(SymbolInfo {
+                    name: symbol.name.clone(),
+                    kind,
+                    scope: scope.qualified_name.clone(),
+                    line: position.line,
+                    character: position.character,
+                });
+            }
+        }
+    }
+    symbols.sort_by(|a, b| (a.line, a.character, &a.name).cmp(&(b.line, b.character, &b.name)));
+    symbols
+}
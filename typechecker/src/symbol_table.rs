@@ -1,11 +1,25 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+};
 
-use enderpy_python_parser::ast::{self, Node};
+use enderpy_python_parser::{
+    TextSize,
+    ast::{self, GetNode, Node},
+};
+use serde::Serialize;
 
-use crate::{ruff_python_import_resolver::import_result::ImportResult, type_check::builtins};
+use crate::{
+    ruff_python_import_resolver::import_result::ImportResult,
+    semanal_utils::{dotted_name, is_type_qualifier},
+    symbol_query::SymbolKind,
+    type_check::builtins,
+};
 
 #[derive(Debug, Clone)]
 pub struct SymbolTable {
+    /// Name of the module that the symbol table belongs to
+    module_name: String,
     // Sub tables are scopes inside the current scope
     // after building symbol table is finished this only contains the most outer scope
     scopes: Vec<SymbolTableScope>,
@@ -15,51 +29,138 @@ pub struct SymbolTable {
     /// The distance between the current scope and the scope where the symbol
     /// was defined
     _locals: HashMap<ast::Expression, u8>,
+
+    /// Id of the next scope that is entered. Ids are given in the order the
+    /// scopes are entered so they are the same every time a file is analyzed
+    next_scope_id: usize,
+
+    /// Conditions that narrow the types of names in blocks of code
+    narrowings: Vec<Narrowing>,
+
+    /// Loops whose body can run more than once, the declarations of a loop
+    /// body are visible in the next iteration
+    loops: Vec<Loop>,
+
+    /// Values of `type` statements. They are evaluated when the alias is
+    /// used, so the names in them can be declared after the statement.
+    type_alias_values: Vec<TypeAliasValue>,
 }
 
 #[derive(Debug, Clone)]
 pub struct SymbolTableScope {
+    /// Unique id of the scope in its symbol table, assigned when the scope is entered
     pub id: usize,
-    pub start_pos: usize,
+    /// Offsets of the node that creates the scope. The module and builtin
+    /// scopes have an empty range.
+    pub start_pos: TextSize,
+    pub end_pos: TextSize,
     pub symbol_table_type: SymbolTableType,
     pub name: String,
-    symbols: HashMap<String, SymbolTableNode>,
+    /// Names of the enclosing classes and functions and the scope joined with
+    /// dots, e.g. `Class.method`. The module scope is called `global`.
+    pub qualified_name: String,
+    symbols: BTreeMap<String, SymbolTableNode>,
     parent: Option<usize>,
-}
-
-fn get_id() -> usize {
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    static COUNTER: AtomicUsize = AtomicUsize::new(0);
-    COUNTER.fetch_add(1, Ordering::SeqCst)
+    /// Names declared with `global` in this scope
+    global_names: HashSet<String>,
+    /// Names declared with `nonlocal` in this scope
+    nonlocal_names: HashSet<String>,
 }
 
 impl SymbolTableScope {
-    pub fn new(symbol_table_type: SymbolTableType, name: String, start_line_number: usize) -> Self {
+    pub fn new(symbol_table_type: SymbolTableType, name: String, node: Node) -> Self {
         SymbolTableScope {
-            id: get_id(),
+            id: 0,
             symbol_table_type,
+            qualified_name: name.clone(),
             name,
-            symbols: HashMap::new(),
+            symbols: BTreeMap::new(),
             parent: None,
-            start_pos: start_line_number,
+            global_names: HashSet::new(),
+            nonlocal_names: HashSet::new(),
+            start_pos: node.start,
+            end_pos: node.end,
         }
     }
+
+    pub fn symbols(&self) -> &BTreeMap<String, SymbolTableNode> {
+        &self.symbols
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 #[allow(clippy::upper_case_acronyms)]
 pub enum SymbolTableType {
     /// BUILTIN scope is used for builtins like len, print, etc.
+    #[serde(rename = "builtin")]
     BUILTIN,
     Module,
     Class,
     Function,
+    /// List, set and dict comprehensions and generator expressions
+    Comprehension,
+    Lambda,
+    /// Type parameters of a generic `type` statement, e.g. `T` in
+    /// `type Pair[T] = tuple[T, T]`
+    TypeAlias,
 }
 
 #[derive(Debug, Clone)]
 pub struct SymbolTableNode {
     pub name: String,
     pub declarations: Vec<Declaration>,
+    /// `del` statements that unbind the symbol
+    pub deletions: Vec<Deletion>,
+}
+
+/// An unbinding event created by `del name`
+#[derive(Debug, Clone)]
+pub struct Deletion {
+    pub node: Node,
+    /// Innermost conditional block (if, loop, try or match body) that
+    /// contains the `del`. None when the deletion always runs.
+    pub branch: Option<Node>,
+}
+
+/// A condition that is known to hold in a block of code. The test of an `if`
+/// statement holds in its body and its negation in the `else` branch.
+#[derive(Debug, Clone)]
+pub struct Narrowing {
+    pub test: ast::Expression,
+    /// True when the test holds in the block, false when its negation holds
+    pub positive: bool,
+    /// Range of the block
+    pub node: Node,
+    /// Id of the scope that contains the block
+    pub scope_id: usize,
+}
+
+/// A `while` or `for` loop. The range of a `while` loop includes its test
+/// because the test is evaluated before every iteration.
+#[derive(Debug, Clone)]
+pub struct Loop {
+    pub node: Node,
+    /// Id of the scope that contains the loop
+    pub scope_id: usize,
+}
+
+/// The value of a `type` statement
+#[derive(Debug, Clone)]
+pub struct TypeAliasValue {
+    pub node: Node,
+    /// End of the scope that contains the statement, names in the value are
+    /// looked up there
+    pub scope_end: TextSize,
+}
+
+/// Whether a name is bound at a given position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingState {
+    Bound,
+    Unbound,
+    /// The name is deleted on some code paths only
+    PossiblyUnbound,
 }
 
 #[derive(Debug, Clone)]
@@ -97,6 +198,46 @@ impl Declaration {
             Declaration::TypeAlias(t) => &t.declaration_path,
         }
     }
+
+    /// Returns true for variables that are declared `Final` or are constants
+    /// by naming convention
+    pub fn is_constant(&self) -> bool {
+        matches!(self, Declaration::Variable(v) if v.is_constant)
+    }
+
+    /// Returns true for type variables created with the factory, e.g.
+    /// `Ts = TypeVarTuple("Ts")`, or declared with the type parameter syntax
+    /// of the factory, e.g. `*Ts` for `TypeVarTuple`
+    pub fn is_type_variable(&self, factory: &str) -> bool {
+        match self {
+            Declaration::Variable(v) => match &v.inferred_type_source {
+                Some(ast::Expression::Call(call)) => is_type_qualifier(&call.func, factory),
+                _ => false,
+            },
+            Declaration::TypeParameter(t) => matches!(
+                (&t.type_parameter_node, factory),
+                (ast::TypeParam::TypeVar(_), "TypeVar")
+                    | (ast::TypeParam::ParamSpec(_), "ParamSpec")
+                    | (ast::TypeParam::TypeVarTuple(_), "TypeVarTuple")
+            ),
+            _ => false,
+        }
+    }
+
+    /// Returns true for type variables with a default type, e.g.
+    /// `T = TypeVar("T", default=int)`
+    pub fn has_type_variable_default(&self) -> bool {
+        match self {
+            Declaration::Variable(v) => match &v.inferred_type_source {
+                Some(ast::Expression::Call(call)) => call
+                    .keywords
+                    .iter()
+                    .any(|keyword| keyword.arg.as_deref() == Some("default")),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
 }
 
 impl Display for DeclarationPath {
@@ -120,10 +261,14 @@ pub struct Function {
     pub function_node: ast::FunctionDef,
     pub is_method: bool,
     pub is_generator: bool,
-    /// return statements that are reachable in the top level function body
+    /// return statements of the function body, including the ones nested in
+    /// other statements but not the ones of nested functions
     pub return_statements: Vec<ast::Return>,
-    /// yield statements that are reachable in the top level function body
-    pub yeild_statements: Vec<ast::Yield>,
+    /// yield expressions of the function body, including the ones nested in
+    /// other statements and expressions but not the ones of nested functions
+    pub yield_statements: Vec<ast::Yield>,
+    /// yield from expressions of the function body
+    pub yield_from_statements: Vec<ast::YieldFrom>,
     /// raise statements that are reachable in the top level function body
     pub raise_statements: Vec<ast::Raise>,
 }
@@ -133,14 +278,97 @@ impl Function {
         if !self.is_method {
             return false;
         }
-        for decorator in self.function_node.decorator_list.iter() {
-            if let ast::Expression::Name(n) = &decorator {
-                if &n.id == "abstractmethod" {
-                    return true;
-                }
+        self.has_decorator("abstractmethod")
+    }
+
+    /// Returns true if the function is decorated with the name, e.g.
+    /// `@staticmethod`
+    pub fn has_decorator(&self, name: &str) -> bool {
+        self.function_node
+            .decorator_list
+            .iter()
+            .any(|decorator| matches!(decorator, ast::Expression::Name(n) if n.id == name))
+    }
+
+    /// Returns true if the function is an `@overload` signature
+    pub fn is_overload(&self) -> bool {
+        self.function_node
+            .decorator_list
+            .iter()
+            .any(|decorator| is_type_qualifier(decorator, "overload"))
+    }
+
+    /// Return annotation of a user-defined type guard, `TypeGuard[T]` or
+    /// `TypeIs[T]`, whose first argument is narrowed to `T` by calls of the
+    /// function in conditions
+    pub fn type_guard(&self) -> Option<&ast::Subscript> {
+        match self.function_node.returns.as_deref()? {
+            ast::Expression::Subscript(s)
+                if is_type_qualifier(&s.value, "TypeGuard")
+                    || is_type_qualifier(&s.value, "TypeIs") =>
+            {
+                Some(s)
             }
+            _ => None,
+        }
+    }
+
+    /// Name of the parameter that receives the instance when the function is
+    /// an instance method, e.g. `self`
+    pub fn instance_parameter(&self) -> Option<&str> {
+        if !self.is_method
+            || self.has_decorator("staticmethod")
+            || self.has_decorator("classmethod")
+        {
+            return None;
+        }
+        let args = &self.function_node.args;
+        args.posonlyargs
+            .iter()
+            .chain(args.args.iter())
+            .next()
+            .map(|arg| arg.arg.as_str())
+    }
+
+    /// Returns true if the function is the getter of a property, e.g.
+    /// `@property`
+    pub fn is_property(&self) -> bool {
+        self.is_method && self.has_decorator("property")
+    }
+
+    /// Name of the property when the function is its setter, e.g. `x` for
+    /// `@x.setter`
+    pub fn property_setter(&self) -> Option<&str> {
+        if !self.is_method {
+            return None;
+        }
+        self.function_node
+            .decorator_list
+            .iter()
+            .find_map(|decorator| match decorator {
+                ast::Expression::Attribute(a) if a.attr == "setter" => match a.value.as_ref() {
+                    ast::Expression::Name(n) => Some(n.id.as_str()),
+                    _ => None,
+                },
+                _ => None,
+            })
+    }
+
+    /// Returns true if the method returns the object it is called on, its
+    /// return annotation is `Self` or, without an annotation, every return
+    /// statement returns the instance parameter, e.g. `return self`
+    pub fn returns_self(&self) -> bool {
+        if let Some(returns) = &self.function_node.returns {
+            return is_type_qualifier(returns, "Self");
         }
-        false
+        let Some(instance) = self.instance_parameter() else {
+            return false;
+        };
+        !self.return_statements.is_empty()
+            && self
+                .return_statements
+                .iter()
+                .all(|r| matches!(&r.value, Some(ast::Expression::Name(n)) if n.id == instance))
     }
 }
 
@@ -148,13 +376,108 @@ impl Function {
 pub struct Class {
     pub name: String,
     pub declaration_path: DeclarationPath,
+    // Base classes as written in the class definition, e.g. `mod.Base`
+    pub bases: Vec<String>,
     // Method names, can be used to look up the function in the symbol table
     // of the class
     pub methods: Vec<String>,
     // instance attibutes that are defined in the __init__ method
     // if the attribute is referencing another symbol we need to look up that symbol in the
     // __init__ method
-    pub attributes: HashMap<String, ast::Expression>,
+    pub attributes: BTreeMap<String, ast::Expression>,
+    // Annotations of the attributes declared in the class body, e.g. `x: int`,
+    // or on self in the __init__ method, e.g. `self.x: int = 0`
+    pub attribute_annotations: BTreeMap<String, ast::Expression>,
+    // Decorators, class keywords (e.g. metaclass) and bases that are not
+    // names can add members that are not visible in the class body
+    pub has_dynamic_members: bool,
+    // Names declared in `__slots__`, None when the class does not declare
+    // `__slots__` or they cannot be determined statically
+    pub slots: Option<Vec<String>>,
+    // Names annotated with `ClassVar` in the class body. They are attributes
+    // of the class and cannot be set on instances.
+    pub class_variables: Vec<String>,
+    // Options of the `@dataclass` decorator, None for other classes
+    pub dataclass: Option<Dataclass>,
+    // Type parameters declared with `Generic[...]`, `Protocol[...]` or the
+    // type parameter syntax. Empty when the class does not declare them.
+    pub type_parameters: Vec<ClassTypeParameter>,
+}
+
+/// A type parameter of a generic class, e.g. `T` or `*Ts` in `Generic[T, *Ts]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassTypeParameter {
+    pub name: String,
+    pub kind: TypeParameterKind,
+    /// The type variable has a default type (PEP 696) and can be omitted
+    pub has_default: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeParameterKind {
+    TypeVar,
+    ParamSpec,
+    TypeVarTuple,
+}
+
+/// Options of the `@dataclass` decorator that change the generated `__init__`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dataclass {
+    /// `init=False` skips generating `__init__`
+    pub init: bool,
+    /// `kw_only=True` makes every field a keyword only parameter
+    pub kw_only: bool,
+}
+
+/// A parameter of the `__init__` method that `@dataclass` generates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataclassField {
+    pub name: String,
+    /// Declaration of the field in the class body
+    pub node: Node,
+    pub has_default: bool,
+    pub kw_only: bool,
+}
+
+impl Class {
+    // TODO: This will be removed once we can import the builtins from the stdlib
+    pub(crate) fn builtin(name: &str, methods: &[&str], attributes: &[&str]) -> Self {
+        let placeholder = ast::Expression::Constant(Box::new(ast::Constant {
+            node: Node::default(),
+            value: ast::ConstantValue::Ellipsis,
+            kind: None,
+        }));
+        Class {
+            name: name.to_string(),
+            declaration_path: DeclarationPath {
+                module_name: String::from("builtins"),
+                node: Node { start: 0, end: 0 },
+            },
+            bases: vec![],
+            methods: methods.iter().map(|m| m.to_string()).collect(),
+            attributes: attributes
+                .iter()
+                .map(|a| (a.to_string(), placeholder.clone()))
+                .collect(),
+            attribute_annotations: BTreeMap::new(),
+            has_dynamic_members: false,
+            slots: None,
+            class_variables: vec![],
+            dataclass: None,
+            type_parameters: vec![],
+        }
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        self.declaration_path.module_name == "builtins"
+    }
+
+    pub fn has_own_member(&self, member: &str) -> bool {
+        self.methods.iter().any(|m| m == member)
+            || self.attributes.contains_key(member)
+            || self.attribute_annotations.contains_key(member)
+            || (self.dataclass.is_some() && builtins::DATACLASS_MEMBERS.contains(&member))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -191,9 +514,27 @@ pub struct TypeAlias {
     pub type_alias_node: ast::TypeAlias,
 }
 
+/// Methods of a property of a class
+#[derive(Debug, Clone, Copy)]
+pub struct Property<'a> {
+    pub getter: &'a Function,
+    /// Without a setter the property cannot be assigned
+    pub setter: Option<&'a Function>,
+}
+
+/// Result of looking up a member through `super()`
+#[derive(Debug, Clone, Copy)]
+pub enum SuperMember<'a> {
+    /// The first class after the class of the method in the MRO that
+    /// defines the member
+    Found(&'a Class),
+    /// No class after the class of the method in the MRO defines the member
+    Missing,
+}
+
 pub struct LookupSymbolRequest {
     pub name: String,
-    pub position: Option<usize>,
+    pub position: Option<TextSize>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -205,97 +546,77 @@ pub enum SymbolScope {
 }
 
 impl SymbolTable {
-    pub fn global() -> Self {
+    pub fn global(module_name: String) -> Self {
         let mut builtin_scope = SymbolTableScope {
-            id: get_id(),
+            id: 0,
             symbol_table_type: SymbolTableType::BUILTIN,
-            symbols: HashMap::new(),
+            symbols: BTreeMap::new(),
             name: String::from("builtins"),
+            qualified_name: String::from("builtins"),
             parent: None,
+            global_names: HashSet::new(),
+            nonlocal_names: HashSet::new(),
             start_pos: 0,
+            end_pos: 0,
         };
         // TODO: This will be removed once we can import the builtins from the stdlib
         // Hacky way of putting the builtin in symbol table so I can implement some
         // tests
-        let list_class = Class {
-            name: builtins::LIST_TYPE.to_string(),
-            declaration_path: DeclarationPath {
-                module_name: String::from("builtins"),
-                node: Node { start: 0, end: 0 },
-            },
-            methods: vec![],
-            attributes: HashMap::new(),
-        };
-        builtin_scope.symbols.insert(
-            builtins::LIST_TYPE.to_string(),
-            SymbolTableNode {
-                name: builtins::LIST_TYPE.to_string(),
-                declarations: vec![Declaration::Class(list_class)],
-            },
-        );
-        let tuple_class = Class {
-            name: builtins::TUPLE_TYPE.to_string(),
-            declaration_path: DeclarationPath {
-                module_name: String::from("builtins"),
-                node: Node { start: 0, end: 0 },
-            },
-            methods: vec![],
-            attributes: HashMap::new(),
-        };
-        builtin_scope.symbols.insert(
-            builtins::TUPLE_TYPE.to_string(),
-            SymbolTableNode {
-                name: builtins::TUPLE_TYPE.to_string(),
-                declarations: vec![Declaration::Class(tuple_class)],
-            },
-        );
-        let set_class = Class {
-            name: builtins::SET_TYPE.to_string(),
-            declaration_path: DeclarationPath {
-                module_name: String::from("builtins"),
-                node: Node { start: 0, end: 0 },
-            },
-            methods: vec![],
-            attributes: HashMap::new(),
-        };
-        builtin_scope.symbols.insert(
-            builtins::SET_TYPE.to_string(),
-            SymbolTableNode {
-                name: builtins::SET_TYPE.to_string(),
-                declarations: vec![Declaration::Class(set_class)],
-            },
-        );
-        let dict_class = Class {
-            name: builtins::DICT_TYPE.to_string(),
-            declaration_path: DeclarationPath {
-                module_name: String::from("builtins"),
-                node: Node { start: 0, end: 0 },
-            },
-            methods: vec![],
-            attributes: HashMap::new(),
-        };
-        builtin_scope.symbols.insert(
-            builtins::DICT_TYPE.to_string(),
-            SymbolTableNode {
-                name: builtins::DICT_TYPE.to_string(),
-                declarations: vec![Declaration::Class(dict_class)],
-            },
-        );
+        for class in [
+            Class::builtin(
+                builtins::OBJECT_TYPE,
+                builtins::OBJECT_METHODS,
+                builtins::OBJECT_ATTRIBUTES,
+            ),
+            Class::builtin(
+                builtins::TYPE_TYPE,
+                builtins::TYPE_METHODS,
+                builtins::TYPE_ATTRIBUTES,
+            ),
+            Class::builtin(builtins::LIST_TYPE, &[], &[]),
+            Class::builtin(builtins::TUPLE_TYPE, &[], &[]),
+            Class::builtin(builtins::SET_TYPE, &[], &[]),
+            Class::builtin(builtins::DICT_TYPE, &[], &[]),
+            Class::builtin(builtins::BYTES_TYPE, &[], &[]),
+        ] {
+            builtin_scope.symbols.insert(
+                class.name.clone(),
+                SymbolTableNode {
+                    name: class.name.clone(),
+                    declarations: vec![Declaration::Class(class)],
+                    deletions: vec![],
+                },
+            );
+        }
         let global_scope = SymbolTableScope {
-            id: get_id(),
+            id: 1,
             symbol_table_type: SymbolTableType::Module,
-            symbols: HashMap::new(),
+            symbols: BTreeMap::new(),
             name: String::from("global"),
+            qualified_name: String::from("global"),
             parent: Some(builtin_scope.id),
+            global_names: HashSet::new(),
+            nonlocal_names: HashSet::new(),
             start_pos: 0,
+            end_pos: 0,
         };
         SymbolTable {
+            module_name,
             scopes: vec![builtin_scope, global_scope],
             all_scopes: vec![],
             _locals: HashMap::new(),
+            next_scope_id: 2,
+            narrowings: vec![],
+            loops: vec![],
+            type_alias_values: vec![],
         }
     }
 
+    /// Iterates over every scope of the table, including the ones that were exited
+    pub fn iter_scopes(&self) -> impl Iterator<Item = &SymbolTableScope> {
+        self.scopes.iter().chain(self.all_scopes.iter())
+    }
+
     /// Do not use for lookup operations
     fn current_scope(&self) -> &SymbolTableScope {
         if let Some(scope) = self.scopes.last() {
@@ -306,55 +627,657 @@ impl SymbolTable {
     }
 
     pub fn current_scope_type(&self) -> &SymbolTableType {
-        return &self.current_scope().symbol_table_type;
+        &self.current_scope().symbol_table_type
     }
 
-    /// Retuns scopes until the given position
-    /// the scopes are sorted by start position descending
-    pub fn innermost_scope(&self, pos: usize) -> Option<&SymbolTableScope> {
-        return self
-            .all_scopes
+    /// Returns the innermost scope that contains the position.
+    /// Returns None if the position is not inside any class or function, the
+    /// position is then in the module scope.
+    pub fn innermost_scope(&self, pos: TextSize) -> Option<&SymbolTableScope> {
+        self.all_scopes
             .iter()
-            .filter(|scope| scope.start_pos < pos)
-            .last();
+            .filter(|scope| scope.start_pos < pos && pos < scope.end_pos)
+            .min_by_key(|scope| scope.end_pos - scope.start_pos)
+    }
+
+    fn scope_by_id(&self, id: usize) -> Option<&SymbolTableScope> {
+        self.iter_scopes().find(|scope| scope.id == id)
     }
 
-    /// get innermost scope that contains that line
-    /// search for symbol in that scope
-    /// if not found search in parent scope
-    /// continue until found or no parent scope
+    /// Looks up a name following the Python scoping rules (LEGB).
+    /// The search starts at the innermost scope that contains the position,
+    /// or the current scope if there is no position, and continues with the
+    /// enclosing scopes up to the builtins.
+    /// Class scopes are only searched for code directly inside the class body,
+    /// functions defined in a class do not see the class variables.
+    /// Names declared with `global` are looked up in the module scope and
+    /// names declared with `nonlocal` in the enclosing functions.
     pub fn lookup_in_scope(&self, lookup_request: LookupSymbolRequest) -> Option<&SymbolTableNode> {
-        match lookup_request.position {
-            Some(pos) => {
-                let mut innermost_scope = self.innermost_scope(pos);
-                while let Some(scope) = innermost_scope {
-                    if let Some(symbol) = scope.symbols.get(&lookup_request.name) {
+        let name = &lookup_request.name;
+        let start = match lookup_request.position {
+            Some(pos) => self.innermost_scope(pos).unwrap_or(self.global_scope()),
+            None => self.current_scope(),
+        };
+        let mut scope = Some(start);
+        while let Some(current) = scope {
+            let is_visible =
+                current.id == start.id || current.symbol_table_type != SymbolTableType::Class;
+            if is_visible {
+                if current.global_names.contains(name) {
+                    return self.global_scope().symbols.get(name);
+                }
+                if !current.nonlocal_names.contains(name) {
+                    if let Some(symbol) = current.symbols.get(name) {
                         return Some(symbol);
                     }
-                    // We reach the global scope
-                    if scope.parent.is_none() {
-                        break;
-                    }
-                    innermost_scope = if let Some(parent_id) = scope.parent {
-                        self.all_scopes
-                            .iter()
-                            .filter(|scope| scope.id == parent_id)
-                            .last()
-                    } else {
-                        Some(self.global_scope())
-                    }
                 }
             }
-            None => {
-                if let Some(symbol) = self.current_scope().symbols.get(&lookup_request.name) {
-                    return Some(symbol);
+            scope = current.parent.and_then(|id| self.scope_by_id(id));
+        }
+        None
+    }
+
+    /// Returns true if the name is a variable of a comprehension that ends
+    /// before the position in the scope that contains the position.
+    /// Comprehension variables are not visible after the comprehension.
+    pub fn is_comprehension_variable_before(&self, name: &str, pos: TextSize) -> bool {
+        let enclosing = self.innermost_scope(pos).unwrap_or(self.global_scope());
+        self.all_scopes.iter().any(|scope| {
+            scope.symbol_table_type == SymbolTableType::Comprehension
+                && scope.parent == Some(enclosing.id)
+                && scope.end_pos <= pos
+                && scope.symbols.contains_key(name)
+        })
+    }
+
+    /// Records a `del` of the name in the scope that binds it.
+    /// Deleting a name that was never bound is left to the checker.
+    pub fn add_deletion(&mut self, name: &str, deletion: Deletion) {
+        let index = self.binding_scope_index(name);
+        if let Some(symbol) = index
+            .and_then(|i| self.scopes.get_mut(i))
+            .and_then(|scope| scope.symbols.get_mut(name))
+        {
+            symbol.deletions.push(deletion);
+        }
+    }
+
+    /// Binding state of a name at the position. Only deletions in the scope
+    /// that contains the position are considered, code in nested functions
+    /// runs at a later point.
+    pub fn binding_state(&self, name: &str, pos: TextSize) -> BindingState {
+        let scope = self.innermost_scope(pos).unwrap_or(self.global_scope());
+        match scope.symbols.get(name) {
+            Some(symbol) => symbol.binding_state(pos),
+            None => BindingState::Bound,
+        }
+    }
+
+    /// Returns whether the class or one of its bases has the member.
+    /// Classes without bases implicitly inherit from `object`.
+    /// Returns None when it cannot be decided, e.g. when a base class is
+    /// imported or the class has dynamic members.
+    pub fn class_has_member(&self, class: &Class, member: &str) -> Option<bool> {
+        self.class_has_member_inner(class, member, &mut HashSet::new())
+    }
+
+    fn class_has_member_inner(
+        &self,
+        class: &Class,
+        member: &str,
+        visited: &mut HashSet<TextSize>,
+    ) -> Option<bool> {
+        if class.is_builtin() {
+            // Only object is modeled completely
+            return (class.name == builtins::OBJECT_TYPE).then(|| class.has_own_member(member));
+        }
+        if class.has_dynamic_members || !visited.insert(class.declaration_path.node.start) {
+            return None;
+        }
+        if self.class_defines_member(class, member) {
+            return Some(true);
+        }
+        if class.bases.is_empty() {
+            let object = self.builtin_class(builtins::OBJECT_TYPE)?;
+            return self.class_has_member_inner(object, member, visited);
+        }
+        let mut undecided = false;
+        for base in &class.bases {
+            let base_class = self.lookup_class(base, class.declaration_path.node.start);
+            match base_class.and_then(|c| self.class_has_member_inner(c, member, visited)) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => undecided = true,
+            }
+        }
+        if undecided { None } else { Some(false) }
+    }
+
+    /// Returns true if the member is defined in the body of the class, without
+    /// looking at the bases
+    pub fn class_defines_member(&self, class: &Class, member: &str) -> bool {
+        class.has_own_member(member)
+            || self
+                .class_scope(class)
+                .is_some_and(|scope| scope.symbols.contains_key(member))
+    }
+
+    /// Symbol of a member defined in the body of the class
+    pub fn class_member(&self, class: &Class, member: &str) -> Option<&SymbolTableNode> {
+        self.class_scope(class)?.symbols.get(member)
+    }
+
+    /// Looks up the member in the classes of the MRO of the class and returns
+    /// the class that defines it with its symbol. Only the class itself is
+    /// searched when the MRO cannot be computed.
+    pub fn lookup_class_member<'a>(
+        &'a self,
+        class: &'a Class,
+        member: &str,
+    ) -> Option<(&'a Class, &'a SymbolTableNode)> {
+        let mro = self.mro(class).unwrap_or_else(|| vec![class]);
+        mro.into_iter()
+            .find_map(|c| Some((c, self.class_member(c, member)?)))
+    }
+
+    /// Looks up a property in the classes of the MRO of the class. Returns
+    /// None when the class that defines the member does not define it with
+    /// `@property`.
+    pub fn lookup_property<'a>(&'a self, class: &'a Class, name: &str) -> Option<Property<'a>> {
+        let (_, symbol) = self.lookup_class_member(class, name)?;
+        let mut functions = symbol.declarations.iter().filter_map(|decl| match decl {
+            Declaration::Function(f) => Some(f),
+            _ => None,
+        });
+        let getter = functions.clone().find(|f| f.is_property())?;
+        let setter = functions.rfind(|f| f.property_setter() == Some(name));
+        Some(Property { getter, setter })
+    }
+
+    /// Symbols defined in the body of the class
+    pub fn class_members(&self, class: &Class) -> impl Iterator<Item = &SymbolTableNode> {
+        self.class_scope(class)
+            .into_iter()
+            .flat_map(|scope| scope.symbols.values())
+    }
+
+    /// Parameters of the `__init__` method that `@dataclass` generates for the
+    /// class: the annotated names of the class and its dataclass bases that
+    /// are not `ClassVar`, bases first. Returns None when no `__init__` is
+    /// generated or the fields cannot be determined.
+    pub fn dataclass_fields(&self, class: &Class) -> Option<Vec<DataclassField>> {
+        let dataclass = class.dataclass?;
+        if !dataclass.init || self.class_member(class, "__init__").is_some() {
+            return None;
+        }
+        let mut fields: Vec<DataclassField> = vec![];
+        for c in self.mro(class)?.into_iter().rev() {
+            let Some(options) = c.dataclass else {
+                continue;
+            };
+            let mut variables = self
+                .class_members(c)
+                .filter_map(|symbol| match symbol.declarations.first()? {
+                    Declaration::Variable(v) => Some((symbol, v)),
+                    _ => None,
+                })
+                .filter(|(symbol, v)| {
+                    v.type_annotation.is_some() && !c.class_variables.contains(&symbol.name)
+                })
+                .collect::<Vec<_>>();
+            variables.sort_by_key(|(_, v)| v.declaration_path.node.start);
+            for (symbol, v) in variables {
+                let annotation = v.type_annotation.as_ref()?;
+                // Fields after `_: KW_ONLY` are keyword only, not supported yet
+                if matches!(
+                    dotted_name(annotation).as_deref(),
+                    Some("KW_ONLY" | "dataclasses.KW_ONLY")
+                ) {
+                    return None;
+                }
+                let (init, has_default) = match &v.inferred_type_source {
+                    Some(value) => field_specifier(value),
+                    None => (true, false),
+                };
+                if !init {
+                    continue;
+                }
+                let field = DataclassField {
+                    name: symbol.name.clone(),
+                    node: v.declaration_path.node,
+                    has_default,
+                    kw_only: options.kw_only,
+                };
+                // A field that is redefined keeps the position of the base field
+                match fields.iter_mut().find(|f| f.name == field.name) {
+                    Some(existing) => *existing = field,
+                    None => fields.push(field),
                 }
             }
         }
-        if let Some(symbol) = self.current_scope().symbols.get(&lookup_request.name) {
-            return Some(symbol);
+        Some(fields)
+    }
+
+    /// Returns true if the body of the class declares the member `Final`
+    pub fn is_final_member(&self, class: &Class, member: &str) -> bool {
+        self.class_member(class, member)
+            .is_some_and(|symbol| symbol.declarations.iter().any(Declaration::is_constant))
+    }
+
+    fn class_scope(&self, class: &Class) -> Option<&SymbolTableScope> {
+        if class.is_builtin() {
+            return None;
         }
-        None
+        self.all_scopes.iter().find(|scope| {
+            scope.symbol_table_type == SymbolTableType::Class
+                && scope.start_pos == class.declaration_path.node.start
+        })
+    }
+
+    /// Method resolution order of the class computed with the C3
+    /// linearization. Returns None when the order cannot be computed, e.g.
+    /// when a base class is imported, a builtin other than `object` or the
+    /// bases are inconsistent.
+    pub fn mro<'a>(&'a self, class: &'a Class) -> Option<Vec<&'a Class>> {
+        self.mro_inner(class, &mut HashSet::new())
+    }
+
+    /// The first class in the MRO of the first class that every class
+    /// inherits from, e.g. `Animal` for `Cat` and `Dog`. Returns None when
+    /// the MRO of a class is not known.
+    pub fn common_base<'a>(&'a self, classes: &[&'a Class]) -> Option<&'a Class> {
+        let (first, rest) = classes.split_first()?;
+        let mros = rest
+            .iter()
+            .map(|class| self.mro(class))
+            .collect::<Option<Vec<_>>>()?;
+        self.mro(first)?.into_iter().find(|base| {
+            mros.iter()
+                .all(|mro| mro.iter().any(|class| is_same_class(class, base)))
+        })
+    }
+
+    /// Whether the class derives from `BaseException`. Returns None when it
+    /// cannot be known, e.g. when a base class is imported.
+    pub fn is_exception_class(&self, class: &Class) -> Option<bool> {
+        if class.is_builtin() {
+            return Some(builtins::EXCEPTION_TYPES.contains(&class.name.as_str()));
+        }
+        let start = class.declaration_path.node.start;
+        let mut known = true;
+        for base in &class.bases {
+            let is_exception = match self.lookup_class(base, start) {
+                Some(base) => self.is_exception_class(base),
+                // The builtin exceptions are not declared in the builtin scope
+                None if builtins::EXCEPTION_TYPES.contains(&base.as_str()) => Some(true),
+                None => None,
+            };
+            match is_exception {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => known = false,
+            }
+        }
+        known.then_some(false)
+    }
+
+    fn mro_inner<'a>(
+        &'a self,
+        class: &'a Class,
+        visiting: &mut HashSet<TextSize>,
+    ) -> Option<Vec<&'a Class>> {
+        if class.is_builtin() {
+            return (class.name == builtins::OBJECT_TYPE).then(|| vec![class]);
+        }
+        let start = class.declaration_path.node.start;
+        if class.has_dynamic_members || !visiting.insert(start) {
+            return None;
+        }
+        let bases = if class.bases.is_empty() {
+            vec![self.builtin_class(builtins::OBJECT_TYPE)?]
+        } else {
+            class
+                .bases
+                .iter()
+                .map(|base| self.lookup_class(base, start))
+                .collect::<Option<Vec<&Class>>>()?
+        };
+        let mut sequences = bases
+            .iter()
+            .map(|base| self.mro_inner(base, visiting))
+            .collect::<Option<Vec<Vec<&Class>>>>()?;
+        visiting.remove(&start);
+        sequences.push(bases);
+
+        let mut mro = vec![class];
+        loop {
+            sequences.retain(|sequence| !sequence.is_empty());
+            if sequences.is_empty() {
+                return Some(mro);
+            }
+            // The first head that is not in the tail of any sequence
+            let head = sequences.iter().map(|sequence| sequence[0]).find(|head| {
+                !sequences
+                    .iter()
+                    .any(|sequence| sequence[1..].iter().any(|c| is_same_class(c, head)))
+            })?;
+            for sequence in sequences.iter_mut() {
+                if is_same_class(sequence[0], head) {
+                    sequence.remove(0);
+                }
+            }
+            mro.push(head);
+        }
+    }
+
+    /// Class of the method that contains the position. This is the class
+    /// that a zero argument `super()` at the position is bound to.
+    pub fn enclosing_class(&self, position: TextSize) -> Option<&Class> {
+        let function_scope = self.innermost_scope(position)?;
+        if function_scope.symbol_table_type != SymbolTableType::Function {
+            return None;
+        }
+        let class_scope = self.scope_by_id(function_scope.parent?)?;
+        if class_scope.symbol_table_type != SymbolTableType::Class {
+            return None;
+        }
+        let symbol = self
+            .scope_by_id(class_scope.parent?)?
+            .symbols
+            .get(&class_scope.name)?;
+        symbol.declarations.iter().find_map(|decl| match decl {
+            Declaration::Class(c) if c.declaration_path.node.start == class_scope.start_pos => {
+                Some(c)
+            }
+            _ => None,
+        })
+    }
+
+    /// Function whose body contains the position. Lambdas and
+    /// comprehensions inside the function are not looked through.
+    pub fn enclosing_function(&self, position: TextSize) -> Option<&Function> {
+        let function_scope = self.innermost_scope(position)?;
+        if function_scope.symbol_table_type != SymbolTableType::Function {
+            return None;
+        }
+        let symbol = self
+            .scope_by_id(function_scope.parent?)?
+            .symbols
+            .get(&function_scope.name)?;
+        symbol.declarations.iter().find_map(|decl| match decl {
+            Declaration::Function(f)
+                if f.declaration_path.node.start == function_scope.start_pos =>
+            {
+                Some(f)
+            }
+            _ => None,
+        })
+    }
+
+    /// Method that contains the position and the class that defines it
+    pub fn enclosing_method(&self, position: TextSize) -> Option<(&Class, &Function)> {
+        let class = self.enclosing_class(position)?;
+        let function_scope = self.innermost_scope(position)?;
+        let symbol = self.class_member(class, &function_scope.name)?;
+        symbol.declarations.iter().find_map(|decl| match decl {
+            Declaration::Function(f)
+                if f.declaration_path.node.start == function_scope.start_pos =>
+            {
+                Some((class, f))
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns whether instances of the class can have the attribute when
+    /// the class uses `__slots__`. An attribute is allowed when a class in
+    /// the MRO declares it as a slot or defines it in its body, e.g. a
+    /// property. Returns None when instances have a `__dict__` because a
+    /// class in the MRO does not declare `__slots__` or declares `__dict__`
+    /// as a slot, or when the MRO cannot be computed.
+    pub fn slots_allow_attribute(&self, class: &Class, attribute: &str) -> Option<bool> {
+        let mut allowed = false;
+        for c in self.mro(class)? {
+            if c.is_builtin() {
+                continue;
+            }
+            let slots = c.slots.as_ref()?;
+            if slots.iter().any(|slot| slot == "__dict__") {
+                return None;
+            }
+            allowed |= slots.iter().any(|slot| slot == attribute)
+                || self
+                    .class_scope(c)
+                    .is_some_and(|scope| scope.symbols.contains_key(attribute));
+        }
+        Some(allowed)
+    }
+
+    /// Looks up the member that `super().member` resolves to in the method
+    /// at the position. Returns None when the position is not in a method
+    /// or the MRO of the class cannot be computed.
+    pub fn lookup_super_member(&self, position: TextSize, member: &str) -> Option<SuperMember<'_>> {
+        let class = self.enclosing_class(position)?;
+        let mro = self.mro(class)?;
+        let found = mro
+            .into_iter()
+            .skip(1)
+            .find(|c| self.class_defines_member(c, member));
+        Some(match found {
+            Some(c) => SuperMember::Found(c),
+            None => SuperMember::Missing,
+        })
+    }
+
+    /// Looks up the class a name refers to at the position
+    /// Returns true if the name at the position refers to a TypeVarTuple
+    pub fn is_type_var_tuple(&self, name: &str, position: TextSize) -> bool {
+        self.is_type_variable(name, position, "TypeVarTuple")
+    }
+
+    /// Returns true if the name at the position refers to a type variable
+    /// created with the factory, e.g. `ParamSpec`
+    pub fn is_type_variable(&self, name: &str, position: TextSize, factory: &str) -> bool {
+        self.lookup_in_scope(LookupSymbolRequest {
+            name: name.to_string(),
+            position: Some(position),
+        })
+        .and_then(|symbol| symbol.declaration_until_position(position))
+        .is_some_and(|decl| decl.is_type_variable(factory))
+    }
+
+    pub fn module_name(&self) -> &str {
+        &self.module_name
+    }
+
+    /// Serializes the scopes of the module ordered by id, with their symbols
+    /// and the kind and span of every declaration. The builtin scope is left
+    /// out.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut scopes = self
+            .iter_scopes()
+            .filter(|scope| scope.symbol_table_type != SymbolTableType::BUILTIN)
+            .collect::<Vec<_>>();
+        scopes.sort_by_key(|scope| scope.id);
+        let json = SymbolTableJson {
+            module_name: &self.module_name,
+            scopes: scopes
+                .into_iter()
+                .map(|scope| ScopeJson {
+                    id: scope.id,
+                    name: &scope.name,
+                    qualified_name: &scope.qualified_name,
+                    kind: &scope.symbol_table_type,
+                    // The parent of the module scope is the builtin scope
+                    parent: scope
+                        .parent
+                        .filter(|_| scope.symbol_table_type != SymbolTableType::Module),
+                    span: Span::of(scope.start_pos, scope.end_pos),
+                    symbols: scope
+                        .symbols
+                        .values()
+                        .map(|symbol| SymbolJson {
+                            name: &symbol.name,
+                            declarations: symbol
+                                .declarations
+                                .iter()
+                                .map(|declaration| {
+                                    let node = declaration.declaration_path().node;
+                                    DeclarationJson {
+                                        kind: SymbolKind::of(declaration),
+                                        span: Span::of(node.start, node.end),
+                                    }
+                                })
+                                .collect(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+        serde_json::to_value(json).expect("symbol table is serializable")
+    }
+
+    pub fn lookup_class(&self, name: &str, position: TextSize) -> Option<&Class> {
+        let symbol = self.lookup_in_scope(LookupSymbolRequest {
+            name: name.to_string(),
+            position: Some(position),
+        })?;
+        match symbol.declaration_until_position(self.evaluation_position(position))? {
+            Declaration::Class(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Looks up the `type` statement a name refers to at the position
+    pub fn lookup_type_alias(&self, name: &str, position: TextSize) -> Option<&TypeAlias> {
+        let symbol = self.lookup_in_scope(LookupSymbolRequest {
+            name: name.to_string(),
+            position: Some(position),
+        })?;
+        match symbol.declaration_until_position(self.evaluation_position(position))? {
+            Declaration::TypeAlias(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Adds the value of a `type` statement of the current scope
+    pub fn add_type_alias_value(&mut self, node: Node) {
+        let scope = self.current_scope();
+        let scope_end = match scope.symbol_table_type {
+            SymbolTableType::Module => TextSize::MAX,
+            _ => scope.end_pos,
+        };
+        self.type_alias_values
+            .push(TypeAliasValue { node, scope_end });
+    }
+
+    /// Position that the declarations of a name at the position are looked
+    /// up at. The value of a `type` statement is evaluated lazily, names in
+    /// it see every declaration of the scope of the statement.
+    pub fn evaluation_position(&self, position: TextSize) -> TextSize {
+        self.type_alias_values
+            .iter()
+            .find(|v| v.node.start <= position && position < v.node.end)
+            .map_or(position, |v| v.scope_end)
+    }
+
+    pub fn builtin_class(&self, name: &str) -> Option<&Class> {
+        match self.lookup_in_builtin_scope(name)?.last_declaration()? {
+            Declaration::Class(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Adds a condition for a block of the current scope
+    pub fn add_narrowing(&mut self, test: ast::Expression, positive: bool, node: Node) {
+        let scope_id = self.current_scope().id;
+        self.narrowings.push(Narrowing {
+            test,
+            positive,
+            node,
+            scope_id,
+        });
+    }
+
+    /// Conditions that hold at the position, from the outermost block to the
+    /// innermost. Blocks of enclosing scopes are not included because nested
+    /// functions and classes can run at any time.
+    pub fn narrowings_at(&self, position: TextSize) -> Vec<&Narrowing> {
+        let scope_id = self
+            .innermost_scope(position)
+            .unwrap_or(self.global_scope())
+            .id;
+        let mut narrowings = self
+            .narrowings
+            .iter()
+            .filter(|n| n.scope_id == scope_id && n.node.start <= position && position < n.node.end)
+            .collect::<Vec<&Narrowing>>();
+        narrowings.sort_by_key(|n| n.node.start);
+        narrowings
+    }
+
+    /// Adds a loop of the current scope
+    pub fn add_loop(&mut self, node: Node) {
+        let scope_id = self.current_scope().id;
+        self.loops.push(Loop { node, scope_id });
+    }
+
+    /// Loops that contain the position, from the outermost to the innermost.
+    /// Like narrowings, loops of enclosing scopes are not included.
+    pub fn loops_at(&self, position: TextSize) -> Vec<&Loop> {
+        let scope_id = self
+            .innermost_scope(position)
+            .unwrap_or(self.global_scope())
+            .id;
+        let mut loops = self
+            .loops
+            .iter()
+            .filter(|l| l.scope_id == scope_id && l.node.start <= position && position < l.node.end)
+            .collect::<Vec<&Loop>>();
+        loops.sort_by_key(|l| l.node.start);
+        loops
+    }
+
+    /// Marks a name of the current scope as declared with `global`
+    pub fn declare_global(&mut self, name: String) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.global_names.insert(name);
+        }
+    }
+
+    /// Marks a name of the current scope as declared with `nonlocal`
+    pub fn declare_nonlocal(&mut self, name: String) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.nonlocal_names.insert(name);
+        }
+    }
+
+    /// The keyword that the current scope declares the name with, `global`
+    /// or `nonlocal`
+    pub fn scope_declaration(&self, name: &str) -> Option<&'static str> {
+        let scope = self.current_scope();
+        if scope.global_names.contains(name) {
+            Some("global")
+        } else if scope.nonlocal_names.contains(name) {
+            Some("nonlocal")
+        } else {
+            None
+        }
+    }
+
+    /// Symbol of the name bound in the current scope itself
+    pub fn current_scope_symbol(&self, name: &str) -> Option<&SymbolTableNode> {
+        self.current_scope().symbols.get(name)
+    }
+
+    pub fn current_scope_id(&self) -> usize {
+        self.current_scope().id
+    }
+
+    /// Range of the node that creates the current scope
+    pub fn current_scope_node(&self) -> Node {
+        let scope = self.current_scope();
+        Node::new(scope.start_pos, scope.end_pos)
     }
 
     pub fn lookup_in_builtin_scope(&self, name: &str) -> Option<&SymbolTableNode> {
@@ -362,15 +1285,22 @@ impl SymbolTable {
         builtin_scope.symbols.get(name)
     }
 
-    pub fn enter_scope(&mut self, new_scope: SymbolTableScope) {
+    pub fn enter_scope(&mut self, mut new_scope: SymbolTableScope) {
+        new_scope.id = self.next_scope_id;
+        self.next_scope_id += 1;
+        let current_scope = self.current_scope();
+        new_scope.parent = Some(current_scope.id);
+        if current_scope.symbol_table_type != SymbolTableType::Module {
+            new_scope.qualified_name =
+                format!("{}.{}", current_scope.qualified_name, new_scope.name);
+        }
         self.scopes.push(new_scope);
     }
 
     pub fn global_scope(&self) -> &SymbolTableScope {
         self.scopes
             .iter()
-            .filter(|scope| scope.symbol_table_type == SymbolTableType::Module)
-            .last()
+            .rfind(|scope| scope.symbol_table_type == SymbolTableType::Module)
             .unwrap()
     }
 
@@ -382,13 +1312,91 @@ impl SymbolTable {
         }
     }
 
-    pub fn add_symbol(&mut self, mut symbol_node: SymbolTableNode) {
-        match self.scopes.last_mut() {
+    /// Index in the scope stack of the scope that binds the name when it is
+    /// assigned in the current scope
+    fn binding_scope_index(&self, name: &str) -> Option<usize> {
+        let current = self.scopes.len().checked_sub(1)?;
+        self.binding_scope_index_from(current, name)
+    }
+
+    /// Same as `binding_scope_index` for a name assigned in the scope at
+    /// `current` in the scope stack
+    fn binding_scope_index_from(&self, current: usize, name: &str) -> Option<usize> {
+        let scope = self.scopes.get(current)?;
+        if scope.global_names.contains(name) {
+            return self
+                .scopes
+                .iter()
+                .rposition(|s| s.symbol_table_type == SymbolTableType::Module);
+        }
+        if scope.nonlocal_names.contains(name) {
+            let enclosing_functions = || {
+                self.scopes[..current]
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|(_, s)| s.symbol_table_type == SymbolTableType::Function)
+            };
+            // The nearest enclosing function that binds the name, otherwise
+            // the nearest enclosing function
+            return enclosing_functions()
+                .find(|(_, s)| s.symbols.contains_key(name))
+                .or_else(|| enclosing_functions().next())
+                .map(|(i, _)| i)
+                .or(Some(current));
+        }
+        Some(current)
+    }
+
+    /// Index in the scope stack of the scope that an assignment expression
+    /// target binds in. Comprehensions are skipped (PEP 572).
+    fn named_expr_scope_index(&self) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rposition(|s| s.symbol_table_type != SymbolTableType::Comprehension)
+    }
+
+    /// Type of the scope that the target of an assignment expression in the
+    /// current scope binds in
+    pub fn named_expr_scope_type(&self) -> Option<&SymbolTableType> {
+        self.named_expr_scope_index()
+            .map(|i| &self.scopes[i].symbol_table_type)
+    }
+
+    /// Returns true if the name is an iteration variable of one of the
+    /// comprehensions the current scope is nested in
+    pub fn is_comprehension_iteration_variable(&self, name: &str) -> bool {
+        self.scopes
+            .iter()
+            .rev()
+            .take_while(|s| s.symbol_table_type == SymbolTableType::Comprehension)
+            .any(|s| s.symbols.contains_key(name))
+    }
+
+    /// Adds the target of an assignment expression. Inside comprehensions the
+    /// target is bound in the enclosing function or module scope.
+    pub fn add_named_expr_symbol(&mut self, symbol_node: SymbolTableNode) {
+        let index = self
+            .named_expr_scope_index()
+            .and_then(|i| self.binding_scope_index_from(i, &symbol_node.name));
+        self.insert_symbol(index, symbol_node);
+    }
+
+    pub fn add_symbol(&mut self, symbol_node: SymbolTableNode) {
+        let index = self.binding_scope_index(&symbol_node.name);
+        self.insert_symbol(index, symbol_node);
+    }
+
+    fn insert_symbol(&mut self, index: Option<usize>, mut symbol_node: SymbolTableNode) {
+        match index.and_then(|i| self.scopes.get_mut(i)) {
             Some(scope) => {
                 if let Some(existing_symbol) = scope.symbols.get(&symbol_node.name) {
                     symbol_node
                         .declarations
                         .extend(existing_symbol.declarations.clone());
+                    symbol_node
+                        .deletions
+                        .extend(existing_symbol.deletions.clone());
                 }
                 scope.symbols.insert(symbol_node.name.clone(), symbol_node);
             }
@@ -400,8 +1408,7 @@ impl SymbolTable {
         // builtin scope always exists
         self.scopes
             .iter()
-            .filter(|scope| scope.symbol_table_type == SymbolTableType::BUILTIN)
-            .last()
+            .rfind(|scope| scope.symbol_table_type == SymbolTableType::BUILTIN)
             .unwrap()
     }
 }
@@ -415,11 +1422,37 @@ impl SymbolTableNode {
         self.declarations.last()
     }
 
-    pub fn declaration_until_position(&self, position: usize) -> Option<&Declaration> {
+    /// Signatures of the function declared with `@overload`, in the order
+    /// they are declared. Empty when the function is not overloaded.
+    pub fn overloads(&self) -> Vec<&Function> {
+        let mut overloads = self
+            .declarations
+            .iter()
+            .filter_map(|decl| match decl {
+                Declaration::Function(f) if f.is_overload() => Some(f),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        overloads.sort_by_key(|f| f.declaration_path.node.start);
+        overloads
+    }
+
+    /// The last declaration before the position. A variable is not declared
+    /// while its value is evaluated, so in `x = x + 1` the `x` on the right
+    /// refers to the previous declaration.
+    pub fn declaration_until_position(&self, position: TextSize) -> Option<&Declaration> {
         let mut filtered_declarations = self
             .declarations
             .iter()
-            .filter(|decl| decl.declaration_path().node.start < position)
+            .filter(|decl| match decl {
+                Declaration::Variable(Variable {
+                    inferred_type_source: Some(value),
+                    ..
+                }) if value.get_node().start <= position && position < value.get_node().end => {
+                    false
+                }
+                _ => decl.declaration_path().node.start < position,
+            })
             .collect::<Vec<&Declaration>>();
 
         filtered_declarations.sort_by(|a, b| {
@@ -431,10 +1464,112 @@ impl SymbolTableNode {
 
         filtered_declarations.last().copied()
     }
+
+    /// Binding state at the position based on the last declaration or
+    /// deletion before it
+    pub fn binding_state(&self, position: TextSize) -> BindingState {
+        let last_declaration = self
+            .declaration_until_position(position)
+            .map(|decl| decl.declaration_path().node.start);
+        let last_deletion = self
+            .deletions
+            .iter()
+            .filter(|deletion| deletion.node.end <= position)
+            .max_by_key(|deletion| deletion.node.start);
+
+        match last_deletion {
+            Some(deletion) if last_declaration.is_none_or(|start| start < deletion.node.start) => {
+                match deletion.branch {
+                    Some(branch) if position < branch.start || branch.end < position => {
+                        BindingState::PossiblyUnbound
+                    }
+                    _ => BindingState::Unbound,
+                }
+            }
+            _ => BindingState::Bound,
+        }
+    }
+}
+
+/// Returns whether a dataclass field with the value is an `__init__`
+/// parameter and whether it has a default, e.g. `field(default_factory=list)`
+fn field_specifier(value: &ast::Expression) -> (bool, bool) {
+    let ast::Expression::Call(call) = value else {
+        return (true, true);
+    };
+    if !matches!(
+        dotted_name(&call.func).as_deref(),
+        Some("field" | "dataclasses.field")
+    ) {
+        return (true, true);
+    }
+    let mut init = true;
+    let mut has_default = false;
+    for keyword in &call.keywords {
+        match keyword.arg.as_deref() {
+            Some("default" | "default_factory") => has_default = true,
+            Some("init") => {
+                init = !matches!(
+                    keyword.value.as_ref(),
+                    ast::Expression::Constant(c) if matches!(c.value, ast::ConstantValue::Bool(false))
+                );
+            }
+            _ => {}
+        }
+    }
+    (init, has_default)
+}
+
+fn is_same_class(a: &Class, b: &Class) -> bool {
+    a.declaration_path.module_name == b.declaration_path.module_name
+        && a.declaration_path.node == b.declaration_path.node
+        && a.name == b.name
 }
 
 // implement display for symbol table and sort the symbols by key
 
+#[derive(Serialize)]
+struct SymbolTableJson<'a> {
+    module_name: &'a str,
+    scopes: Vec<ScopeJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct ScopeJson<'a> {
+    id: usize,
+    name: &'a str,
+    qualified_name: &'a str,
+    kind: &'a SymbolTableType,
+    parent: Option<usize>,
+    span: Span,
+    symbols: Vec<SymbolJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct SymbolJson<'a> {
+    name: &'a str,
+    declarations: Vec<DeclarationJson>,
+}
+
+#[derive(Serialize)]
+struct DeclarationJson {
+    kind: SymbolKind,
+    span: Span,
+}
+
+/// Byte offsets of a node in the source
+#[derive(Serialize)]
+struct Span {
+    start: TextSize,
+    end: TextSize,
+}
+
+impl Span {
+    fn of(start: TextSize, end: TextSize) -> Self {
+        Span { start, end }
+    }
+}
+
 impl std::fmt::Display for SymbolTable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "-------------------")?;
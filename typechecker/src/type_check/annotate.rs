@@ -0,0 +1,275 @@
+use std::{cell::RefCell, sync::Arc};
+
+use enderpy_python_parser::{
+    TextSize,
+    ast::{FunctionDef, Statement},
+};
+
+use super::{type_evaluator::TypeEvaluator, types::PythonType};
+use crate::{
+    diagnostic::{CharacterSpan, TextEdit},
+    settings::Settings,
+    state::State,
+    symbol_table::{Declaration, Function, SymbolTable, SymbolTableNode, SymbolTableType},
+};
+
+/// Computes edits that insert inferred annotations into the source of `state`.
+///
+/// Return annotations are added to functions without one and variable
+/// annotations are added to the first assignment of unannotated variables.
+/// Types that cannot be written as an annotation (e.g. unknown types) are
+/// skipped. Classes that are not defined yet where the annotation is
+/// evaluated are written as forward references, e.g. `-> "A"` in class `A`.
+pub(crate) fn infer_annotations(
+    state: &State,
+    options: &Settings,
+    symbol_tables: Vec<Arc<SymbolTable>>,
+) -> Vec<TextEdit> {
+    let symbol_table = Arc::new(state.get_symbol_table());
+    let type_evaluator = TypeEvaluator {
+        symbol_table: Arc::clone(&symbol_table),
+        imported_symbol_tables: symbol_tables,
+        join_container_types: options.join_container_types,
+        carried_declarations: RefCell::default(),
+        expanding_aliases: RefCell::default(),
+        evaluating_method_calls: RefCell::default(),
+        type_parameter_bindings: RefCell::default(),
+    };
+    let source = state.file.source();
+    let mut edits = vec![];
+
+    for scope in symbol_table.iter_scopes() {
+        if scope.symbol_table_type == SymbolTableType::BUILTIN {
+            continue;
+        }
+        for symbol in scope.symbols().values() {
+            for declaration in &symbol.declarations {
+                let edit = match declaration {
+                    Declaration::Function(f) => {
+                        return_annotation_edit(&type_evaluator, &source, declaration, f)
+                    }
+                    Declaration::Variable(_) => {
+                        variable_annotation_edit(&type_evaluator, &source, symbol, declaration)
+                    }
+                    _ => None,
+                };
+                edits.extend(edit);
+            }
+        }
+    }
+
+    edits.sort_by_key(|e| e.span.0);
+    edits.dedup();
+    edits
+}
+
+fn return_annotation_edit(
+    type_evaluator: &TypeEvaluator,
+    source: &str,
+    declaration: &Declaration,
+    f: &Function,
+) -> Option<TextEdit> {
+    // Generator annotations would need an import from `typing`
+    if f.function_node.returns.is_some() || f.is_generator {
+        return None;
+    }
+    let return_type = if f.return_statements.is_empty() {
+        match type_evaluator.get_type_from_declaration(declaration) {
+            Ok(PythonType::Callable(callable)) => callable.return_type,
+            _ => return None,
+        }
+    } else {
+        returned_type(type_evaluator, f)?
+    };
+    let annotation = annotation_at(type_evaluator, &return_type, f.function_node.node.start)?;
+    let offset = signature_end(source, &f.function_node)?;
+    Some(TextEdit {
+        span: CharacterSpan(offset, offset),
+        new_text: format!(" -> {}", annotation),
+    })
+}
+
+fn variable_annotation_edit(
+    type_evaluator: &TypeEvaluator,
+    source: &str,
+    symbol: &SymbolTableNode,
+    declaration: &Declaration,
+) -> Option<TextEdit> {
+    let Declaration::Variable(v) = declaration else {
+        return None;
+    };
+    let already_annotated = symbol.declarations.iter().any(|d| match d {
+        Declaration::Variable(v) => v.type_annotation.is_some(),
+        _ => true,
+    });
+    if already_annotated {
+        return None;
+    }
+    // Only the first assignment of a variable is annotated
+    let first = symbol
+        .declarations
+        .iter()
+        .map(|d| d.declaration_path().node.start)
+        .min()?;
+    let start = v.declaration_path.node.start;
+    if start != first {
+        return None;
+    }
+    // The declaration node covers the whole statement, make sure it is a
+    // simple `name = value` assignment
+    let name_end = start + symbol.name.len() as TextSize;
+    let after_name = source
+        .get(name_end as usize..)?
+        .trim_start_matches([' ', '\t']);
+    if source.get(start as usize..name_end as usize)? != symbol.name
+        || !after_name.starts_with('=')
+        || after_name.starts_with("==")
+    {
+        return None;
+    }
+
+    let inferred_type = type_evaluator
+        .get_type(v.inferred_type_source.as_ref()?)
+        .ok()?;
+    let annotation = annotation_at(type_evaluator, &inferred_type, start)?;
+    Some(TextEdit {
+        span: CharacterSpan(name_end, name_end),
+        new_text: format!(": {}", annotation),
+    })
+}
+
+/// Union of the types of the values of the return statements. Returns None
+/// when the type of a value is not known or the end of the body can be
+/// reached, which returns `None` implicitly.
+fn returned_type(type_evaluator: &TypeEvaluator, f: &Function) -> Option<PythonType> {
+    if !matches!(
+        f.function_node.body.last(),
+        Some(Statement::Return(_) | Statement::Raise(_))
+    ) {
+        return None;
+    }
+    let mut members: Vec<PythonType> = vec![];
+    for return_statement in &f.return_statements {
+        let typ = match &return_statement.value {
+            Some(value) => type_evaluator.get_type(value).ok()?,
+            None => PythonType::None,
+        };
+        for member in typ.union_members() {
+            if !members.iter().any(|m| m.type_equal(&member)) {
+                members.push(member);
+            }
+        }
+    }
+    Some(PythonType::union(members))
+}
+
+/// Annotation text for a type that is evaluated at the position. Quoted when
+/// a class of the module is not defined yet at the position.
+fn annotation_at(
+    type_evaluator: &TypeEvaluator,
+    python_type: &PythonType,
+    position: TextSize,
+) -> Option<String> {
+    let annotation = annotation_for_type(python_type)?;
+    let module_name = type_evaluator.symbol_table.module_name();
+    if is_forward_reference(python_type, module_name, position) {
+        Some(format!("\"{}\"", annotation))
+    } else {
+        Some(annotation)
+    }
+}
+
+/// Returns true if the type refers to a class of the module whose definition
+/// does not end before the position
+fn is_forward_reference(python_type: &PythonType, module_name: &str, position: TextSize) -> bool {
+    match python_type {
+        PythonType::Class(class_type) => {
+            let declaration_path = &class_type.details.declaration_path;
+            (declaration_path.module_name == module_name && declaration_path.node.end > position)
+                || class_type
+                    .type_parameters
+                    .iter()
+                    .any(|t| is_forward_reference(t, module_name, position))
+        }
+        PythonType::MultiValue(members) => members
+            .iter()
+            .any(|t| is_forward_reference(t, module_name, position)),
+        _ => false,
+    }
+}
+
+/// Returns the annotation text for a type or None if the type cannot be
+/// expressed as an annotation
+fn annotation_for_type(python_type: &PythonType) -> Option<String> {
+    let annotation = match python_type {
+        PythonType::None => "None".to_string(),
+        PythonType::Bool => "bool".to_string(),
+        PythonType::Int => "int".to_string(),
+        PythonType::Float => "float".to_string(),
+        PythonType::Complex => "complex".to_string(),
+        PythonType::Str => "str".to_string(),
+        PythonType::Class(class_type) => {
+            if class_type.type_parameters.is_empty() {
+                class_type.details.name.clone()
+            } else {
+                let params = class_type
+                    .type_parameters
+                    .iter()
+                    .map(annotation_for_type)
+                    .collect::<Option<Vec<String>>>()?;
+                format!("{}[{}]", class_type.details.name, params.join(", "))
+            }
+        }
+        PythonType::MultiValue(members) => members
+            .iter()
+            .map(annotation_for_type)
+            .collect::<Option<Vec<String>>>()?
+            .join(" | "),
+        _ => return None,
+    };
+    Some(annotation)
+}
+
+/// Returns the offset right after the closing parenthesis of the parameters of
+/// a function definition
+fn signature_end(source: &str, f: &FunctionDef) -> Option<TextSize> {
+    let text = source.get(f.node.range())?;
+    // skip decorators by finding `def <name>`
+    let mut def_pos = None;
+    for (i, _) in text.match_indices("def") {
+        let before_ok = i == 0 || text[..i].ends_with(|c: char| c.is_whitespace());
+        let rest = &text[i + 3..];
+        let after = rest.trim_start();
+        if before_ok && rest.len() != after.len() && after.starts_with(f.name.as_str()) {
+            def_pos = Some(i + 3 + (rest.len() - after.len()) + f.name.len());
+            break;
+        }
+    }
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut chars = text[def_pos?..].char_indices().peekable();
+    let base = f.node.start + def_pos? as TextSize;
+    while let Some((i, c)) = chars.next() {
+        if let Some(q) = quote {
+            if c == '\\' {
+                chars.next();
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' => quote = Some(c),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 && c == ')' {
+                    return Some(base + i as TextSize + 1);
+                }
+            }
+            ':' if depth == 0 => return None,
+            _ => (),
+        }
+    }
+    None
+}
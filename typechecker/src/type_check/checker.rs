@@ -1,49 +1,256 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
 use ast::{Expression, Statement};
 use enderpy_python_parser as parser;
-use enderpy_python_parser::ast::{self, *};
+use enderpy_python_parser::{
+    TextSize,
+    ast::{self, *},
+};
 
-use super::{type_evaluator::TypeEvaluator, types::PythonType};
+use super::{
+    builtins,
+    docstring::documented_parameters,
+    format_string::{
+        FieldName, PrintfSpecifier, parse_format_spec, parse_printf, parse_str_format,
+    },
+    type_evaluator::{TypeEvaluator, bind_arguments},
+    types::PythonType,
+};
 use crate::{
-    ast_visitor::TraversalVisitor, diagnostic::CharacterSpan, settings::Settings, state::State,
-    symbol_table::SymbolTable,
+    ast_visitor::TraversalVisitor,
+    diagnostic::CharacterSpan,
+    error_code::ErrorCode,
+    plugin::{CheckerPlugin, PluginContext},
+    semanal_utils::{
+        is_type_qualifier, is_unbounded_tuple, static_condition, type_arguments, unpacked_type,
+        unpacked_value,
+    },
+    settings::{DocstringStyle, Settings},
+    state::State,
+    symbol_table::{
+        BindingState, Class, DataclassField, Declaration, Function, LookupSymbolRequest,
+        SuperMember, SymbolTable, TypeParameterKind,
+    },
 };
 
 pub struct TypeChecker<'a> {
     pub errors: Vec<TypeCheckError>,
+    #[allow(unused)]
     pub options: &'a Settings,
-    type_evaluator: TypeEvaluator,
+    pub(crate) module: &'a State,
+    pub(crate) type_evaluator: TypeEvaluator,
+    plugins: &'a [Box<dyn CheckerPlugin>],
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct TypeCheckError {
     pub msg: String,
     pub span: CharacterSpan,
+    pub code: ErrorCode,
+    /// Secondary spans with a message, e.g. where a name was declared
+    pub labels: Vec<(CharacterSpan, String)>,
+    /// How the error can be fixed
+    pub help: Option<String>,
 }
 
 #[allow(unused)]
 impl<'a> TypeChecker<'a> {
-    pub fn new(module: &'a State, options: &'a Settings, symbol_tables: Vec<SymbolTable>) -> Self {
-        let symbol_table = module.get_symbol_table();
+    pub fn new(
+        module: &'a State,
+        options: &'a Settings,
+        symbol_tables: Vec<Arc<SymbolTable>>,
+    ) -> Self {
         TypeChecker {
             errors: vec![],
             options,
+            module,
             type_evaluator: TypeEvaluator {
-                symbol_table: symbol_table.clone(),
-                imported_symbol_tables: symbol_tables.clone(),
+                symbol_table: Arc::new(module.get_symbol_table()),
+                imported_symbol_tables: symbol_tables,
+                join_container_types: options.join_container_types,
+                carried_declarations: RefCell::default(),
+                expanding_aliases: RefCell::default(),
+                evaluating_method_calls: RefCell::default(),
+                type_parameter_bindings: RefCell::default(),
             },
+            plugins: &[],
         }
     }
 
+    /// Sets the plugins that are called while checking the module
+    pub fn set_plugins(&mut self, plugins: &'a [Box<dyn CheckerPlugin>]) {
+        self.plugins = plugins;
+    }
+
     pub fn type_check(&mut self, statement: &Statement) {
         self.visit_stmt(statement);
     }
 
+    /// Lets the plugins check the module after all statements were checked
+    pub fn finish_module(&mut self) {
+        let plugins = self.plugins;
+        for plugin in plugins {
+            plugin.on_module_done(&mut PluginContext::new(self));
+        }
+    }
+
+    /// Checks that the module and its public classes and functions have
+    /// docstrings and that the documented parameters match the signatures.
+    /// Enabled with the `docstring_style` setting.
+    pub fn check_docstrings(&mut self, body: &[Statement]) {
+        let Some(style) = self.options.docstring_style else {
+            return;
+        };
+        let path = self.module.file.path();
+        if path.extension().is_some_and(|extension| extension == "pyi") {
+            return;
+        }
+        let is_public = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem == "__init__" || !stem.starts_with('_'));
+        if is_public && !body.is_empty() && docstring(body).is_none() {
+            self.make_error(
+                ErrorCode::MissingDocstring,
+                "Public module has no docstring",
+                0,
+                0,
+            );
+        }
+        self.check_definition_docstrings(body, style);
+    }
+
+    /// Functions nested in functions are not public and are not checked
+    fn check_definition_docstrings(&mut self, body: &[Statement], style: DocstringStyle) {
+        for stmt in body {
+            match stmt {
+                Statement::FunctionDef(f) => self.check_function_docstring(
+                    f.node,
+                    &f.name,
+                    &f.args,
+                    &f.body,
+                    &f.decorator_list,
+                    style,
+                ),
+                Statement::AsyncFunctionDef(f) => self.check_function_docstring(
+                    f.node,
+                    &f.name,
+                    &f.args,
+                    &f.body,
+                    &f.decorator_list,
+                    style,
+                ),
+                Statement::ClassDef(c) if !c.name.starts_with('_') => {
+                    if docstring(&c.body).is_none() {
+                        let (start, end) = self.definition_name_span(c.node, "class", &c.name);
+                        self.make_error(
+                            ErrorCode::MissingDocstring,
+                            &format!("Public class '{}' has no docstring", c.name),
+                            start,
+                            end,
+                        );
+                    }
+                    self.check_definition_docstrings(&c.body, style);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_function_docstring(
+        &mut self,
+        node: Node,
+        name: &str,
+        args: &Arguments,
+        body: &[Statement],
+        decorators: &[Expression],
+        style: DocstringStyle,
+    ) {
+        if name.starts_with('_')
+            || decorators
+                .iter()
+                .any(|decorator| is_type_qualifier(decorator, "overload"))
+        {
+            return;
+        }
+        let Some((docstring, docstring_node)) = docstring(body) else {
+            let (start, end) = self.definition_name_span(node, "def", name);
+            self.make_error(
+                ErrorCode::MissingDocstring,
+                &format!("Public function '{}' has no docstring", name),
+                start,
+                end,
+            );
+            return;
+        };
+        let Some(documented) = documented_parameters(docstring, style) else {
+            return;
+        };
+        // The receiver of methods is not documented
+        let parameters = args
+            .posonlyargs
+            .iter()
+            .chain(&args.args)
+            .chain(&args.vararg)
+            .chain(&args.kwonlyargs)
+            .chain(&args.kwarg)
+            .enumerate()
+            .filter(|(i, arg)| !(*i == 0 && matches!(arg.arg.as_str(), "self" | "cls")))
+            .map(|(_, arg)| arg)
+            .collect::<Vec<_>>();
+        for arg in &parameters {
+            if !documented.contains(&arg.arg) {
+                self.make_error(
+                    ErrorCode::DocstringParameters,
+                    &format!(
+                        "Parameter '{}' of '{}' is not documented in the docstring",
+                        arg.arg, name
+                    ),
+                    arg.node.start,
+                    arg.node.end,
+                );
+            }
+        }
+        for documented in &documented {
+            if !parameters.iter().any(|arg| &arg.arg == documented) {
+                self.make_error(
+                    ErrorCode::DocstringParameters,
+                    &format!(
+                        "Docstring of '{}' documents '{}' which is not a parameter",
+                        name, documented
+                    ),
+                    docstring_node.start,
+                    docstring_node.end,
+                );
+            }
+        }
+    }
+
+    /// Span of the name of a class or function definition. The node of the
+    /// definition starts at its decorators.
+    fn definition_name_span(&self, node: Node, keyword: &str, name: &str) -> (TextSize, TextSize) {
+        let source = &self.module.file.build_source.source;
+        let start = source
+            .get(node.range())
+            .and_then(|text| {
+                let keyword = text.find(&format!("{} ", keyword))?;
+                text[keyword..].find(name).map(|offset| keyword + offset)
+            })
+            .map_or(node.start, |offset| node.start + offset as TextSize);
+        (start, start + name.len() as TextSize)
+    }
+
     fn infer_expr_type(&mut self, expr: &Expression, emit_error: bool) -> PythonType {
         match self.type_evaluator.get_type(expr) {
             Ok(t) => t,
             Err(e) => {
                 if emit_error {
                     self.make_error(
+                        ErrorCode::from_report(&e),
                         e.to_string().as_str(),
                         expr.get_node().start,
                         expr.get_node().end,
@@ -54,11 +261,1741 @@ impl<'a> TypeChecker<'a> {
         }
     }
 
-    fn make_error(&mut self, msg: &str, start: usize, end: usize) {
-        let error = TypeCheckError {
+    fn check_comprehension_variable_leak(&mut self, name: &Name) {
+        let symbol_table = &self.type_evaluator.symbol_table;
+        let lookup_request = LookupSymbolRequest {
+            name: name.id.clone(),
+            position: Some(name.node.start),
+        };
+        if symbol_table.lookup_in_scope(lookup_request).is_none()
+            && symbol_table.is_comprehension_variable_before(&name.id, name.node.start)
+        {
+            self.make_error(
+                ErrorCode::ComprehensionVariableLeak,
+                &format!(
+                    "{} is only defined inside a comprehension and cannot be used after it",
+                    name.id
+                ),
+                name.node.start,
+                name.node.end,
+            );
+        }
+    }
+
+    fn check_name_used_after_del(&mut self, name: &Name) {
+        let state = self
+            .type_evaluator
+            .symbol_table
+            .binding_state(&name.id, name.node.start);
+        if state == BindingState::Unbound {
+            self.make_error(
+                ErrorCode::NameUsedAfterDel,
+                &format!("{} is used after it was deleted", name.id),
+                name.node.start,
+                name.node.end,
+            );
+        }
+    }
+
+    /// Reports base class expressions that evaluate to something other than
+    /// a class, e.g. `class A(1)` or a function
+    fn check_base_class(&mut self, base: &Expression) {
+        let found = match self.type_evaluator.get_type(base) {
+            Ok(PythonType::Callable(_)) => String::from("a function"),
+            Ok(
+                t @ (PythonType::None
+                | PythonType::Bool
+                | PythonType::Int
+                | PythonType::Float
+                | PythonType::Complex
+                | PythonType::Str
+                | PythonType::KnownValue(_)
+                | PythonType::Class(_)),
+            ) => format!("'{}'", t),
+            _ => return,
+        };
+        self.make_error(
+            ErrorCode::InvalidBaseClass,
+            &format!("Base class must be a class, found {}", found),
+            base.get_node().start,
+            base.get_node().end,
+        );
+    }
+
+    /// Reports attributes that a class does not define, inherit or get from
+    /// its metaclass, e.g. `A.missing`
+    fn check_class_attribute(&mut self, attribute: &Attribute) {
+        let Expression::Name(n) = attribute.value.as_ref() else {
+            return;
+        };
+        let symbol_table = &self.type_evaluator.symbol_table;
+        let Some(class) = symbol_table.lookup_class(&n.id, n.node.start) else {
+            return;
+        };
+        let in_metaclass = symbol_table
+            .builtin_class(builtins::TYPE_TYPE)
+            .is_some_and(|t| t.has_own_member(&attribute.attr));
+        if !in_metaclass
+            && symbol_table.class_has_member(class, &attribute.attr) == Some(false)
+            && !self
+                .type_evaluator
+                .is_guarded_by_hasattr(n, &attribute.attr)
+        {
+            self.make_error(
+                ErrorCode::UnknownAttribute,
+                &format!("'{}' has no attribute '{}'", n.id, attribute.attr),
+                attribute.node.start,
+                attribute.node.end,
+            );
+        }
+    }
+
+    /// Reports `super().attr` when no class after the class of the method in
+    /// the MRO defines the attribute
+    fn check_super_attribute(&mut self, attribute: &Attribute) {
+        if let Some(SuperMember::Missing) = self.type_evaluator.lookup_super_attribute(attribute) {
+            self.make_error(
+                ErrorCode::UnknownAttribute,
+                &format!("'super' object has no attribute '{}'", attribute.attr),
+                attribute.value.get_node().start,
+                attribute.node.end,
+            );
+        }
+    }
+
+    /// Reports values added to a list or a set that do not match its element
+    /// type, e.g. `names.append(1)` when `names` is a `list[str]`
+    fn check_container_insertion(&mut self, call: &Call) {
+        let Expression::Attribute(a) = call.func.as_ref() else {
+            return;
+        };
+        let (container, argument) = match (a.attr.as_str(), call.args.as_slice()) {
+            ("append", [argument]) | ("insert", [_, argument]) => (builtins::LIST_TYPE, argument),
+            ("add", [argument]) => (builtins::SET_TYPE, argument),
+            _ => return,
+        };
+        let container_type = self.infer_expr_type(&a.value, false);
+        let PythonType::Class(c) = &container_type else {
+            return;
+        };
+        let [element_type] = c.type_parameters.as_slice() else {
+            return;
+        };
+        if !c.details.is_builtin() || c.details.name != container {
+            return;
+        }
+        let argument_type = self.infer_expr_type(argument, false);
+        if self
+            .type_evaluator
+            .is_assignable(element_type, &argument_type)
+        {
+            return;
+        }
+        self.make_error(
+            ErrorCode::InvalidArguments,
+            &format!(
+                "Type '{}' is not assignable to the elements of '{}'",
+                argument_type, container_type
+            ),
+            argument.get_node().start,
+            argument.get_node().end,
+        );
+    }
+
+    /// Reports `"%s %d" % values` when the format string is not valid or the
+    /// values do not match its conversion specifiers. Only enabled with the
+    /// `check_format_strings` setting.
+    fn check_printf_format(&mut self, b: &BinOp) {
+        if !self.options.check_format_strings || !matches!(b.op, BinaryOperator::Mod) {
+            return;
+        }
+        let Expression::Constant(c) = b.left.as_ref() else {
+            return;
+        };
+        let ConstantValue::Str(format) = &c.value else {
+            return;
+        };
+        let specifiers = match parse_printf(format) {
+            Ok(specifiers) => specifiers,
+            Err(msg) => {
+                self.make_error(
+                    ErrorCode::InvalidFormatString,
+                    &msg,
+                    c.node.start,
+                    c.node.end,
+                );
+                return;
+            }
+        };
+        let (start, end) = (b.left.get_node().start, b.right.get_node().end);
+        if specifiers.iter().any(|specifier| specifier.key.is_some()) {
+            if specifiers.iter().any(|specifier| specifier.key.is_none()) {
+                self.make_error(
+                    ErrorCode::InvalidFormatString,
+                    "Format string mixes mapping keys and positional specifiers",
+                    c.node.start,
+                    c.node.end,
+                );
+                return;
+            }
+            self.check_printf_mapping(&specifiers, &b.right);
+            return;
+        }
+
+        let arguments = match b.right.as_ref() {
+            Expression::Tuple(t) => {
+                if t.elements
+                    .iter()
+                    .any(|e| matches!(e, Expression::Starred(_)))
+                {
+                    return;
+                }
+                t.elements
+                    .iter()
+                    .map(|element| (element, self.infer_expr_type(element, false)))
+                    .collect::<Vec<_>>()
+            }
+            value => match self.infer_expr_type(value, false) {
+                PythonType::Unknown | PythonType::Any => return,
+                // The values of a tuple are formatted, not the tuple
+                PythonType::Class(c)
+                    if c.details.is_builtin() && c.details.name == builtins::TUPLE_TYPE =>
+                {
+                    let Some(length) = self.type_evaluator.unpacked_length(value) else {
+                        return;
+                    };
+                    let mut types = c.type_parameters;
+                    types.resize(length, PythonType::Unknown);
+                    types.into_iter().map(|typ| (value, typ)).collect()
+                }
+                typ => vec![(value, typ)],
+            },
+        };
+        let expected: usize = specifiers
+            .iter()
+            .map(|specifier| 1 + specifier.star_arguments)
+            .sum();
+        if expected != arguments.len() {
+            self.make_error(
+                ErrorCode::InvalidFormatString,
+                &format!(
+                    "Format string expects {} but {}",
+                    plural(expected, "argument"),
+                    were_given(arguments.len())
+                ),
+                start,
+                end,
+            );
+            return;
+        }
+        let mut arguments = arguments.into_iter();
+        for specifier in &specifiers {
+            // `*` widths and precisions come before the value
+            let Some((argument, typ)) = arguments.nth(specifier.star_arguments) else {
+                return;
+            };
+            if !specifier.accepts(&typ) {
+                self.make_error(
+                    ErrorCode::InvalidFormatString,
+                    &format!(
+                        "Format '%{}' does not accept a value of type '{}'",
+                        specifier.conversion, typ
+                    ),
+                    argument.get_node().start,
+                    argument.get_node().end,
+                );
+            }
+        }
+    }
+
+    /// Reports the mapping keys of `"%(key)s" % {...}` that the dict display
+    /// does not have
+    fn check_printf_mapping(&mut self, specifiers: &[PrintfSpecifier], value: &Expression) {
+        // `{**values}` can have any key
+        let Expression::Dict(d) = value else {
+            return;
+        };
+        if d.keys.len() != d.values.len() {
+            return;
+        }
+        let mut keys = HashSet::new();
+        for key in &d.keys {
+            match key {
+                Expression::Constant(c) => match &c.value {
+                    ConstantValue::Str(key) => keys.insert(key.as_str()),
+                    _ => return,
+                },
+                _ => return,
+            };
+        }
+        let mut missing = specifiers
+            .iter()
+            .filter_map(|specifier| specifier.key.as_deref())
+            .filter(|key| !keys.contains(key))
+            .collect::<Vec<_>>();
+        missing.dedup();
+        for key in missing {
+            self.make_error(
+                ErrorCode::InvalidFormatString,
+                &format!("Format key '{}' is missing from the mapping", key),
+                d.node.start,
+                d.node.end,
+            );
+        }
+    }
+
+    /// Reports `"{} {name}".format(...)` calls when the format string is not
+    /// valid or the arguments do not match its replacement fields. Only
+    /// enabled with the `check_format_strings` setting.
+    fn check_str_format(&mut self, call: &Call) {
+        if !self.options.check_format_strings {
+            return;
+        }
+        let Expression::Attribute(a) = call.func.as_ref() else {
+            return;
+        };
+        let Expression::Constant(c) = a.value.as_ref() else {
+            return;
+        };
+        let (ConstantValue::Str(format), "format") = (&c.value, a.attr.as_str()) else {
+            return;
+        };
+        let fields = match parse_str_format(format) {
+            Ok(fields) => fields,
+            Err(msg) => {
+                self.make_error(
+                    ErrorCode::InvalidFormatString,
+                    &msg,
+                    c.node.start,
+                    c.node.end,
+                );
+                return;
+            }
+        };
+        // Unpacked arguments can provide any field
+        if call
+            .args
+            .iter()
+            .any(|arg| matches!(arg, Expression::Starred(_)))
+            || call.keywords.iter().any(|keyword| keyword.arg.is_none())
+        {
+            return;
+        }
+
+        let mut next_index = 0;
+        let mut expected = 0;
+        let mut arguments = vec![];
+        for field in &fields {
+            let argument = match &field.name {
+                FieldName::Auto | FieldName::Index(_) => {
+                    let index = match field.name {
+                        FieldName::Index(index) => index,
+                        _ => {
+                            next_index += 1;
+                            next_index - 1
+                        }
+                    };
+                    expected = expected.max(index + 1);
+                    call.args.get(index)
+                }
+                FieldName::Keyword(name) => {
+                    let keyword = call
+                        .keywords
+                        .iter()
+                        .find(|keyword| keyword.arg.as_ref() == Some(name));
+                    if keyword.is_none() {
+                        self.make_error(
+                            ErrorCode::InvalidFormatString,
+                            &format!("Format string expects keyword argument '{}'", name),
+                            c.node.start,
+                            c.node.end,
+                        );
+                    }
+                    keyword.map(|keyword| keyword.value.as_ref())
+                }
+            };
+            if let Some(argument) = argument {
+                arguments.push((field, argument));
+            }
+        }
+        if expected != call.args.len() {
+            self.make_error(
+                ErrorCode::InvalidFormatString,
+                &format!(
+                    "Format string expects {} but {}",
+                    plural(expected, "positional argument"),
+                    were_given(call.args.len())
+                ),
+                c.node.start,
+                call.node.end,
+            );
+        }
+        for keyword in &call.keywords {
+            let Some(arg) = &keyword.arg else {
+                continue;
+            };
+            if !fields
+                .iter()
+                .any(|field| matches!(&field.name, FieldName::Keyword(name) if name == arg))
+            {
+                self.make_error(
+                    ErrorCode::InvalidFormatString,
+                    &format!(
+                        "Keyword argument '{}' is not used by the format string",
+                        arg
+                    ),
+                    keyword.node.start,
+                    keyword.node.end,
+                );
+            }
+        }
+
+        for (field, argument) in arguments {
+            // Specs with nested fields are only known when formatting
+            if field.accesses_member || field.spec.contains('{') {
+                continue;
+            }
+            let typ = match field.conversion {
+                Some(_) => PythonType::Str,
+                None => self.infer_expr_type(argument, false),
+            };
+            let node = argument.get_node();
+            self.check_format_spec(&field.spec, &typ, node.start, node.end);
+        }
+    }
+
+    /// Reports the format spec of an f-string replacement field when it is
+    /// not valid for the formatted value, e.g. `f"{name:d}"` for a `str`
+    fn check_fstring_format_spec(&mut self, f: &FormattedValue) {
+        if !self.options.check_format_strings {
+            return;
+        }
+        let Some(Expression::JoinedStr(spec)) = f.format_spec.as_deref() else {
+            return;
+        };
+        // Specs with nested fields are only known when formatting
+        let text = match spec.values.as_slice() {
+            [] => "",
+            [Expression::Constant(c)] => match &c.value {
+                ConstantValue::Str(text) => text.as_str(),
+                _ => return,
+            },
+            _ => return,
+        };
+        let typ = match f.conversion {
+            Some(_) => PythonType::Str,
+            None => self.infer_expr_type(&f.value, false),
+        };
+        self.check_format_spec(text, &typ, spec.node.start, spec.node.end);
+    }
+
+    /// Reports a format spec that is not valid for the builtin type of the
+    /// formatted value. Other types can define their own specs in
+    /// `__format__`.
+    fn check_format_spec(&mut self, spec: &str, typ: &PythonType, start: TextSize, end: TextSize) {
+        if !matches!(
+            typ,
+            PythonType::Bool
+                | PythonType::Int
+                | PythonType::Float
+                | PythonType::Complex
+                | PythonType::Str
+        ) {
+            return;
+        }
+        let msg = match parse_format_spec(spec) {
+            Some(format_spec) if format_spec.accepts(typ) => return,
+            Some(_) => format!(
+                "Format specifier '{}' is not valid for type '{}'",
+                spec, typ
+            ),
+            None => format!("Invalid format specifier '{}'", spec),
+        };
+        self.make_error(ErrorCode::InvalidFormatString, &msg, start, end);
+    }
+
+    /// Reports `raise value` and `raise ... from cause` when the value or the
+    /// cause is known not to be an exception
+    fn check_raise(&mut self, r: &Raise) {
+        if let Some(exc) = &r.exc {
+            if let Some(found) = self.non_exception_operand(exc, true) {
+                self.make_error(
+                    ErrorCode::InvalidException,
+                    &format!("Exceptions must derive from BaseException, found {}", found),
+                    exc.get_node().start,
+                    exc.get_node().end,
+                );
+            }
+        }
+        let Some(cause) = &r.cause else {
+            return;
+        };
+        // `raise ... from None` suppresses the context of the exception
+        if matches!(cause, Expression::Constant(c) if c.value == ConstantValue::None) {
+            return;
+        }
+        if let Some(found) = self.non_exception_operand(cause, true) {
+            self.make_error(
+                ErrorCode::InvalidException,
+                &format!(
+                    "Exception cause must derive from BaseException or be None, found {}",
+                    found
+                ),
+                cause.get_node().start,
+                cause.get_node().end,
+            );
+        }
+    }
+
+    /// Reports the types of `except (A, B)` clauses that are known not to be
+    /// exception classes
+    fn check_except_handler(&mut self, handler: &ExceptHandler) {
+        let Some(typ) = &handler.typ else {
+            return;
+        };
+        let classes = match typ.as_ref() {
+            Expression::Tuple(t) => t.elements.iter().collect(),
+            typ => vec![typ],
+        };
+        for class in classes {
+            if let Some(found) = self.non_exception_operand(class, false) {
+                self.make_error(
+                    ErrorCode::InvalidException,
+                    &format!("Except clause must name exception classes, found {}", found),
+                    class.get_node().start,
+                    class.get_node().end,
+                );
+            }
+        }
+    }
+
+    /// Describes the value when it is known not to be an exception class or
+    /// an exception instance, e.g. `'Int'`. Exception instances are only
+    /// accepted when `allow_instance` is true.
+    fn non_exception_operand(
+        &mut self,
+        value: &Expression,
+        allow_instance: bool,
+    ) -> Option<String> {
+        let symbol_table = Arc::clone(&self.type_evaluator.symbol_table);
+        if let Expression::Name(n) = value {
+            if let Some(class) = symbol_table.lookup_class(&n.id, n.node.start) {
+                return (symbol_table.is_exception_class(class) == Some(false))
+                    .then(|| format!("'type[{}]'", class.name));
+            }
+            let lookup_request = LookupSymbolRequest {
+                name: n.id.clone(),
+                position: Some(n.node.start),
+            };
+            if builtins::EXCEPTION_TYPES.contains(&n.id.as_str())
+                && symbol_table.lookup_in_scope(lookup_request).is_none()
+            {
+                return None;
+            }
+        }
+        let typ = self.infer_expr_type(value, false);
+        let is_exception = match &typ {
+            PythonType::Callable(_) => return Some(String::from("a function")),
+            // `type[E]` values and tuples of exception classes in except
+            // clauses are not checked
+            PythonType::Class(c)
+                if c.details.is_builtin()
+                    && (c.details.name == builtins::TYPE_TYPE
+                        || (c.details.name == builtins::TUPLE_TYPE && !allow_instance)) =>
+            {
+                return None;
+            }
+            PythonType::Class(c) => symbol_table.is_exception_class(&c.details)?,
+            PythonType::None
+            | PythonType::Bool
+            | PythonType::Int
+            | PythonType::Float
+            | PythonType::Complex
+            | PythonType::Str
+            | PythonType::KnownValue(_) => false,
+            _ => return None,
+        };
+        (!is_exception || !allow_instance).then(|| format!("'{}'", typ))
+    }
+
+    /// Reports `a, *b, c = value` when the value cannot be iterated or when
+    /// it is known to have a different number of values than the targets
+    fn check_unpacking(&mut self, target: &Expression, value: &Expression) {
+        let targets = match target {
+            Expression::Tuple(t) => &t.elements,
+            Expression::List(l) => &l.elements,
+            _ => return,
+        };
+        let stars = targets
+            .iter()
+            .filter(|target| matches!(target, Expression::Starred(_)))
+            .count();
+        if stars > 1 {
+            return;
+        }
+        let star = targets
+            .iter()
+            .position(|target| matches!(target, Expression::Starred(_)));
+        let node = target.get_node();
+        let typ = self.infer_expr_type(value, false);
+        if matches!(
+            typ,
+            PythonType::None
+                | PythonType::Bool
+                | PythonType::Int
+                | PythonType::Float
+                | PythonType::Complex
+        ) {
+            self.make_error(
+                ErrorCode::InvalidUnpacking,
+                &format!("Type '{}' is not iterable and cannot be unpacked", typ),
+                node.start,
+                node.end,
+            );
+            return;
+        }
+        if let Some(length) = self.type_evaluator.unpacked_length(value) {
+            let expected = targets.len() - stars;
+            let msg = match star {
+                None if length > expected => Some(format!(
+                    "Too many values to unpack (expected {}, got {})",
+                    expected, length
+                )),
+                None if length < expected => Some(format!(
+                    "Not enough values to unpack (expected {}, got {})",
+                    expected, length
+                )),
+                Some(_) if length < expected => Some(format!(
+                    "Not enough values to unpack (expected at least {}, got {})",
+                    expected, length
+                )),
+                _ => None,
+            };
+            if let Some(msg) = msg {
+                self.make_error(ErrorCode::InvalidUnpacking, &msg, node.start, node.end);
+                return;
+            }
+        }
+        // Nested targets, e.g. `(a, b), c = value`
+        for (index, target) in targets.iter().enumerate() {
+            if let Some(element) = unpacked_value(value, targets.len(), star, index) {
+                self.check_unpacking(target, &element);
+            }
+        }
+    }
+
+    /// Reports `x.attr` and `x()` when the type of `x` includes None and no
+    /// check narrows it. Only enabled with the `strict_optional` setting.
+    fn check_optional_access(&mut self, value: &Expression, attribute: Option<&str>) {
+        if !self.options.strict_optional {
+            return;
+        }
+        // Attributes and subscripts are not narrowed by `is not None` checks
+        let Expression::Name(n) = value else {
+            return;
+        };
+        let Ok(typ) = self.type_evaluator.get_type(value) else {
+            return;
+        };
+        let members = typ.union_members();
+        if members.len() < 2 || !members.contains(&PythonType::None) {
+            return;
+        }
+        let msg = match attribute {
+            Some(attribute) => format!(
+                "Cannot access attribute '{}' because '{}' may be None",
+                attribute, n.id
+            ),
+            None => format!("Cannot call '{}' because it may be None", n.id),
+        };
+        self.make_error_with_help(
+            ErrorCode::OptionalMemberAccess,
+            &msg,
+            n.node.start,
+            n.node.end,
+            format!("add an `if {} is not None` guard", n.id),
+        );
+    }
+
+    /// Checks the arguments of `super().method(...)` against the signature of
+    /// the method in the parent class
+    fn check_super_call_arguments(&mut self, call: &Call) {
+        let Expression::Attribute(attribute) = call.func.as_ref() else {
+            return;
+        };
+        let Some(SuperMember::Found(class)) = self.type_evaluator.lookup_super_attribute(attribute)
+        else {
+            return;
+        };
+        let Some(Declaration::Function(f)) = self
+            .type_evaluator
+            .symbol_table
+            .class_member(class, &attribute.attr)
+            .and_then(|symbol| symbol.last_declaration())
+        else {
+            return;
+        };
+        let name = format!("{}.{}", class.name, attribute.attr);
+        let errors = function_call_errors(&self.type_evaluator, call, f, &name);
+        self.report_call_errors(errors, attribute.value.get_node().start, call.node.end);
+    }
+
+    /// Checks the arguments of calls of functions declared in the module and
+    /// of methods of instances of classes declared in the module against the
+    /// signature of the function
+    fn check_function_call_arguments(&mut self, call: &Call) {
+        let type_evaluator = &self.type_evaluator;
+        let symbol_table = &type_evaluator.symbol_table;
+        let errors = match call.func.as_ref() {
+            Expression::Name(n) => {
+                let lookup_request = LookupSymbolRequest {
+                    name: n.id.clone(),
+                    position: Some(n.node.start),
+                };
+                let position = symbol_table.evaluation_position(n.node.start);
+                let Some(Declaration::Function(f)) = symbol_table
+                    .lookup_in_scope(lookup_request)
+                    .and_then(|symbol| symbol.declaration_until_position(position))
+                else {
+                    return;
+                };
+                function_call_errors(type_evaluator, call, f, &n.id)
+            }
+            Expression::Attribute(attribute) => {
+                let Ok(PythonType::Class(c)) = type_evaluator.get_type(&attribute.value) else {
+                    return;
+                };
+                if c.details.declaration_path.module_name != symbol_table.module_name() {
+                    return;
+                }
+                let Some((class, symbol)) =
+                    symbol_table.lookup_class_member(&c.details, &attribute.attr)
+                else {
+                    return;
+                };
+                let Some(Declaration::Function(f)) = symbol.last_declaration() else {
+                    return;
+                };
+                let name = format!("{}.{}", class.name, attribute.attr);
+                function_call_errors(type_evaluator, call, f, &name)
+            }
+            _ => return,
+        };
+        self.report_call_errors(errors, call.func.get_node().start, call.node.end);
+    }
+
+    fn report_argument_errors(
+        &mut self,
+        errors: Vec<(String, Option<(Node, String)>)>,
+        start: TextSize,
+        end: TextSize,
+    ) {
+        for (msg, label) in errors {
+            self.make_error_with_labels(
+                ErrorCode::InvalidArguments,
+                &msg,
+                start,
+                end,
+                argument_labels(label),
+            );
+        }
+    }
+
+    /// Reports the argument errors at the call and the type errors at the
+    /// arguments
+    fn report_call_errors(&mut self, errors: CallErrors, start: TextSize, end: TextSize) {
+        self.report_argument_errors(errors.arguments, start, end);
+        for (msg, argument, label) in errors.types {
+            self.make_error_with_labels(
+                ErrorCode::InvalidArguments,
+                &msg,
+                argument.start,
+                argument.end,
+                argument_labels(Some(label)),
+            );
+        }
+    }
+
+    /// Reports calls of dataclasses that do not match the parameters of the
+    /// generated `__init__`
+    fn check_dataclass_call_arguments(&mut self, call: &Call) {
+        let Expression::Name(n) = call.func.as_ref() else {
+            return;
+        };
+        let symbol_table = &self.type_evaluator.symbol_table;
+        let Some(class) = symbol_table.lookup_class(&n.id, n.node.start) else {
+            return;
+        };
+        let Some(fields) = symbol_table.dataclass_fields(class) else {
+            return;
+        };
+        let Some(parameters) = dataclass_init_parameters(&fields) else {
+            return;
+        };
+        let errors = call_argument_errors(call, &parameters, false, &class.name);
+        self.report_argument_errors(errors, call.node.start, call.node.end);
+    }
+
+    /// Reports `assert_type(value, T)` calls where the inferred type of the
+    /// value is not `T`. Values whose type is not known are not reported.
+    fn check_assert_type(&mut self, call: &Call) {
+        if !is_type_qualifier(&call.func, "assert_type") {
+            return;
+        }
+        let [value, annotation] = call.args.as_slice() else {
+            return;
+        };
+        let actual = self.infer_expr_type(value, false);
+        if matches!(actual, PythonType::Unknown) {
+            return;
+        }
+        let expected = self.type_evaluator.get_type_from_annotation(annotation);
+        if !same_type(&actual, &expected) {
+            self.make_error(
+                ErrorCode::AssertTypeMismatch,
+                &format!(
+                    "\"assert_type\" mismatch: expected \"{}\" but received \"{}\"",
+                    expected, actual
+                ),
+                call.node.start,
+                call.node.end,
+            );
+        }
+    }
+
+    /// Reports `assert_never(value)` calls that can be reached with a value,
+    /// i.e. the type of the value is not narrowed to `Never`
+    fn check_assert_never(&mut self, call: &Call) {
+        if !is_type_qualifier(&call.func, "assert_never") {
+            return;
+        }
+        let [value] = call.args.as_slice() else {
+            return;
+        };
+        let actual = self.infer_expr_type(value, false);
+        if !matches!(actual, PythonType::Never | PythonType::Unknown) {
+            self.make_error(
+                ErrorCode::NonExhaustive,
+                &format!(
+                    "Argument of \"assert_never\" has type \"{}\", cases for it are not handled",
+                    actual
+                ),
+                call.node.start,
+                call.node.end,
+            );
+        }
+    }
+
+    /// Reports invalid uses of TypeVarTuples and wrong numbers of type
+    /// arguments of generic classes in the annotation
+    fn check_annotation(&mut self, annotation: &Expression) {
+        if let Some(value) = unpacked_type(annotation) {
+            self.check_unpacked_type(value);
+            return;
+        }
+        match annotation {
+            Expression::Name(n)
+                if self
+                    .type_evaluator
+                    .symbol_table
+                    .is_type_var_tuple(&n.id, n.node.start) =>
+            {
+                let msg = format!("TypeVarTuple '{}' must be unpacked, e.g. '*{}'", n.id, n.id);
+                self.make_error(
+                    ErrorCode::InvalidTypeArguments,
+                    &msg,
+                    n.node.start,
+                    n.node.end,
+                );
+            }
+            // The arguments of literals are values and the metadata of
+            // `Annotated` is not a type
+            Expression::Subscript(s) if is_type_qualifier(&s.value, "Literal") => {}
+            Expression::Subscript(s) if is_type_qualifier(&s.value, "Annotated") => {
+                if let Some(argument) = type_arguments(&s.slice).first() {
+                    self.check_annotation(argument);
+                }
+            }
+            Expression::Subscript(s) => {
+                let arguments = type_arguments(&s.slice);
+                self.check_type_arguments(s, arguments);
+                for argument in arguments {
+                    self.check_annotation(argument);
+                }
+            }
+            Expression::BinOp(b) => {
+                self.check_annotation(&b.left);
+                self.check_annotation(&b.right);
+            }
+            _ => {}
+        }
+    }
+
+    /// Reports unpacking of types other than TypeVarTuples and tuples, e.g.
+    /// `*int`. `Unpack` of classes is allowed for `**kwargs: Unpack[Movie]`.
+    fn check_unpacked_type(&mut self, value: &Expression) {
+        let Expression::Name(n) = value else {
+            self.check_annotation(value);
+            return;
+        };
+        let symbol_table = &self.type_evaluator.symbol_table;
+        let is_type_var = symbol_table.is_type_variable(&n.id, n.node.start, "TypeVar")
+            || symbol_table.is_type_variable(&n.id, n.node.start, "ParamSpec");
+        if is_type_var {
+            let msg = format!(
+                "Unpacked type must be a TypeVarTuple or a tuple, got '{}'",
+                n.id
+            );
+            self.make_error(
+                ErrorCode::InvalidTypeArguments,
+                &msg,
+                n.node.start,
+                n.node.end,
+            );
+        }
+    }
+
+    /// Reports type arguments with more than one unpacked TypeVarTuple or
+    /// unbounded tuple and wrong numbers of type arguments for generic
+    /// classes that declare their type parameters
+    fn check_type_arguments(&mut self, subscript: &Subscript, arguments: &[Expression]) {
+        let symbol_table = &self.type_evaluator.symbol_table;
+        let variadic_arguments = arguments
+            .iter()
+            .filter_map(unpacked_type)
+            .filter(|value| match value {
+                Expression::Name(n) => symbol_table.is_type_var_tuple(&n.id, n.node.start),
+                value => is_unbounded_tuple(value),
+            })
+            .count();
+        if variadic_arguments > 1 {
+            self.make_error(
+                ErrorCode::InvalidTypeArguments,
+                "Only one unpacked TypeVarTuple or unbounded tuple is allowed in type arguments",
+                subscript.node.start,
+                subscript.node.end,
+            );
+            return;
+        }
+
+        let Expression::Name(n) = subscript.value.as_ref() else {
+            return;
+        };
+        let Some(class) = symbol_table.lookup_class(&n.id, n.node.start) else {
+            return;
+        };
+        // A class with a ParamSpec accepts the parameters without brackets
+        let has_param_spec = class
+            .type_parameters
+            .iter()
+            .any(|p| p.kind == TypeParameterKind::ParamSpec);
+        let unpacks = arguments.iter().any(|a| unpacked_type(a).is_some());
+        if class.type_parameters.is_empty() || has_param_spec || unpacks {
+            return;
+        }
+        // Parameters with a default can be omitted and a TypeVarTuple takes
+        // any number of arguments
+        let (variadic, parameters): (Vec<_>, Vec<_>) = class
+            .type_parameters
+            .iter()
+            .partition(|p| p.kind == TypeParameterKind::TypeVarTuple);
+        let required = parameters.iter().filter(|p| !p.has_default).count();
+        let maximum = variadic.is_empty().then_some(parameters.len());
+        let got = arguments.len();
+        let expected = match maximum {
+            Some(maximum) if required == maximum && got != maximum => {
+                plural(maximum, "type argument")
+            }
+            Some(maximum) if got > maximum => {
+                format!("at most {}", plural(maximum, "type argument"))
+            }
+            _ if got < required => format!("at least {}", plural(required, "type argument")),
+            _ => return,
+        };
+        let msg = format!("Expected {} for '{}', got {}", expected, class.name, got);
+        self.make_error(
+            ErrorCode::InvalidTypeArguments,
+            &msg,
+            subscript.node.start,
+            subscript.node.end,
+        );
+    }
+
+    /// Reports invalid annotations of the parameters and the return type
+    fn check_function_annotations(&mut self, args: &Arguments, returns: Option<&Expression>) {
+        let parameters = args
+            .posonlyargs
+            .iter()
+            .chain(args.args.iter())
+            .chain(args.vararg.iter())
+            .chain(args.kwonlyargs.iter())
+            .chain(args.kwarg.iter());
+        for parameter in parameters {
+            if let Some(annotation) = &parameter.annotation {
+                self.check_annotation(annotation);
+            }
+        }
+        if let Some(returns) = returns {
+            self.check_annotation(returns);
+        }
+    }
+
+    /// Reports functions used as conditions, e.g. `if is_ready:` instead of
+    /// `if is_ready():`. Functions are always true.
+    fn check_condition(&mut self, test: &Expression) {
+        match test {
+            Expression::UnaryOp(u) if matches!(u.op, UnaryOperator::Not) => {
+                self.check_condition(&u.operand)
+            }
+            Expression::BoolOp(b) => {
+                for value in &b.values {
+                    self.check_condition(value);
+                }
+            }
+            Expression::Name(_) | Expression::Attribute(_) => {
+                if !matches!(self.infer_expr_type(test, false), PythonType::Callable(_)) {
+                    return;
+                }
+                let name = match test {
+                    Expression::Name(n) => n.id.clone(),
+                    Expression::Attribute(a) => a.attr.clone(),
+                    _ => return,
+                };
+                let node = test.get_node();
+                self.make_error_with_help(
+                    ErrorCode::AlwaysTrueCondition,
+                    &format!("Function '{}' is always true in a condition", name),
+                    node.start,
+                    node.end,
+                    format!("call the function with `{}()`", name),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Reports parameter defaults that are not assignable to the annotation of
+    /// the parameter. Without `strict_optional` a `None` default makes the
+    /// annotation implicitly optional, e.g. `def f(x: int = None)`.
+    fn check_parameter_defaults(&mut self, args: &Arguments) {
+        let parameters = args
+            .positional_with_defaults()
+            .chain(args.keyword_only_with_defaults())
+            .filter_map(|(arg, default)| Some((arg, default?)));
+        for (arg, default) in parameters {
+            self.visit_expr(default);
+            let Some(annotation) = &arg.annotation else {
+                continue;
+            };
+            let default_type = self.infer_expr_type(default, false);
+            if default_type == PythonType::None && !self.options.strict_optional {
+                continue;
+            }
+            let annotation_type = self.type_evaluator.get_type_from_annotation(annotation);
+            if self
+                .type_evaluator
+                .is_assignable(&annotation_type, &default_type)
+            {
+                continue;
+            }
+            let msg = format!(
+                "Default value of type '{}' is not assignable to parameter '{}' of type '{}'",
+                default_type, arg.arg, annotation_type
+            );
+            let node = default.get_node();
+            self.make_error(ErrorCode::InvalidDefault, &msg, node.start, node.end);
+        }
+    }
+
+    /// Reports generator functions annotated with a type that generators are
+    /// not instances of, e.g. `-> list[int]`. Annotations whose type is not
+    /// known are not reported.
+    fn check_generator_annotation(&mut self, f: &FunctionDef) {
+        let Some(returns) = f.returns.as_deref() else {
+            return;
+        };
+        let is_generator = f
+            .body
+            .first()
+            .and_then(|stmt| {
+                self.type_evaluator
+                    .symbol_table
+                    .enclosing_function(stmt.get_node().start)
+            })
+            .is_some_and(|function| function.is_generator);
+        if !is_generator {
+            return;
+        }
+        let is_generator_type = |name: &String| {
+            [
+                builtins::GENERATOR_TYPE,
+                builtins::ITER_TYPE,
+                "Iterable",
+                builtins::OBJECT_TYPE,
+            ]
+            .contains(&name.as_str())
+        };
+        let found = match self.type_evaluator.get_type_from_annotation(returns) {
+            PythonType::Class(c)
+                if is_generator_type(&c.details.name)
+                    || c.details.bases.iter().any(is_generator_type) =>
+            {
+                return;
+            }
+            t @ (PythonType::None
+            | PythonType::Bool
+            | PythonType::Int
+            | PythonType::Float
+            | PythonType::Complex
+            | PythonType::Str
+            | PythonType::KnownValue(_)
+            | PythonType::Class(_)) => t,
+            _ => return,
+        };
+        self.make_error(
+            ErrorCode::InvalidGeneratorAnnotation,
+            &format!(
+                "Return type of generator function '{}' must be Generator, Iterator or Iterable, found '{}'",
+                f.name, found
+            ),
+            returns.get_node().start,
+            returns.get_node().end,
+        );
+    }
+
+    /// Reports classes with more than one TypeVarTuple type parameter
+    fn check_class_type_parameters(&mut self, c: &ClassDef) {
+        let type_var_tuples = c
+            .type_params
+            .iter()
+            .filter(|type_param| matches!(type_param, TypeParam::TypeVarTuple(_)))
+            .count();
+        if type_var_tuples > 1 {
+            let msg = format!(
+                "Only one TypeVarTuple is allowed in the type parameters of '{}'",
+                c.name
+            );
+            self.make_error(
+                ErrorCode::InvalidTypeArguments,
+                &msg,
+                c.node.start,
+                c.node.end,
+            );
+        }
+    }
+
+    /// Reports assignments to attributes of `self` in methods of classes
+    /// whose `__slots__` do not declare the attribute or that declare the
+    /// attribute as `ClassVar`
+    fn check_slots_assignment(&mut self, target: &Expression) {
+        let attribute = match target {
+            Expression::Tuple(t) => {
+                for element in &t.elements {
+                    self.check_slots_assignment(element);
+                }
+                return;
+            }
+            Expression::List(l) => {
+                for element in &l.elements {
+                    self.check_slots_assignment(element);
+                }
+                return;
+            }
+            Expression::Starred(s) => {
+                self.check_slots_assignment(&s.value);
+                return;
+            }
+            Expression::Attribute(a) => a,
+            _ => return,
+        };
+        let Expression::Name(n) = attribute.value.as_ref() else {
+            return;
+        };
+        let symbol_table = &self.type_evaluator.symbol_table;
+        let Some((class, method)) = symbol_table.enclosing_method(n.node.start) else {
+            return;
+        };
+        if method.instance_parameter() != Some(n.id.as_str()) {
+            return;
+        }
+        let is_class_variable = symbol_table.mro(class).is_some_and(|mro| {
+            mro.iter()
+                .any(|c| c.class_variables.contains(&attribute.attr))
+        });
+        if symbol_table.slots_allow_attribute(class, &attribute.attr) == Some(false) {
+            let msg = format!(
+                "'{}' object has no attribute '{}', it is not declared in __slots__",
+                class.name, attribute.attr
+            );
+            self.make_error(
+                ErrorCode::SlotsViolation,
+                &msg,
+                n.node.start,
+                attribute.node.end,
+            );
+        }
+        if is_class_variable {
+            let msg = format!(
+                "Cannot assign to class variable '{}' via instance",
+                attribute.attr
+            );
+            self.make_error(
+                ErrorCode::ClassVariableAssignment,
+                &msg,
+                n.node.start,
+                attribute.node.end,
+            );
+        }
+    }
+
+    /// Reports assignments to names and class attributes that are declared
+    /// `Final` or are constants. `statement` is the assignment statement.
+    fn check_constant_reassignment(&mut self, target: &Expression, statement: Node) {
+        match target {
+            Expression::Name(n) => {
+                let symbol_table = &self.type_evaluator.symbol_table;
+                let constant = symbol_table
+                    .lookup_in_scope(LookupSymbolRequest {
+                        name: n.id.clone(),
+                        position: Some(n.node.start),
+                    })
+                    .and_then(|symbol| {
+                        symbol.declarations.iter().find(|decl| {
+                            decl.is_constant()
+                                && decl.declaration_path().node.start < statement.start
+                        })
+                    });
+                let msg = match constant {
+                    Some(Declaration::Variable(v))
+                        if v.type_annotation
+                            .as_ref()
+                            .is_some_and(|annotation| is_type_qualifier(annotation, "Final")) =>
+                    {
+                        format!("'{}' is declared Final and cannot be reassigned", n.id)
+                    }
+                    Some(_) => format!("'{}' is a constant and cannot be reassigned", n.id),
+                    None => return,
+                };
+                let labels = constant
+                    .map(|decl| declared_here(decl, &n.id))
+                    .into_iter()
+                    .collect();
+                self.make_error_with_labels(
+                    ErrorCode::FinalReassignment,
+                    &msg,
+                    n.node.start,
+                    n.node.end,
+                    labels,
+                );
+            }
+            Expression::Attribute(a) => self.check_final_attribute_assignment(a),
+            Expression::Tuple(t) => {
+                for element in &t.elements {
+                    self.check_constant_reassignment(element, statement);
+                }
+            }
+            Expression::List(l) => {
+                for element in &l.elements {
+                    self.check_constant_reassignment(element, statement);
+                }
+            }
+            Expression::Starred(s) => self.check_constant_reassignment(&s.value, statement),
+            _ => {}
+        }
+    }
+
+    /// Reports `self.attr = ...` and `Class.attr = ...` when a class in the
+    /// MRO declares the attribute `Final` in its body
+    fn check_final_attribute_assignment(&mut self, attribute: &Attribute) {
+        let Expression::Name(n) = attribute.value.as_ref() else {
+            return;
+        };
+        let symbol_table = &self.type_evaluator.symbol_table;
+        let class = match symbol_table.enclosing_method(n.node.start) {
+            Some((class, method)) if method.instance_parameter() == Some(n.id.as_str()) => {
+                Some(class)
+            }
+            _ => symbol_table.lookup_class(&n.id, n.node.start),
+        };
+        let owner = class
+            .and_then(|class| symbol_table.mro(class))
+            .and_then(|mro| {
+                mro.into_iter()
+                    .find(|c| symbol_table.is_final_member(c, &attribute.attr))
+            });
+        if let Some(owner) = owner {
+            let msg = format!(
+                "'{}' is declared Final in '{}' and cannot be reassigned",
+                attribute.attr, owner.name
+            );
+            let labels = self.final_member_label(owner, &attribute.attr);
+            self.make_error_with_labels(
+                ErrorCode::FinalReassignment,
+                &msg,
+                n.node.start,
+                attribute.node.end,
+                labels,
+            );
+        }
+    }
+
+    /// Reports members of a class that override a `Final` member of a base
+    /// class
+    fn check_final_overrides(&mut self, c: &parser::ast::ClassDef) {
+        let symbol_table = &self.type_evaluator.symbol_table;
+        let Some(class) = symbol_table.lookup_class(&c.name, c.node.end) else {
+            return;
+        };
+        let Some(mro) = symbol_table.mro(class) else {
+            return;
+        };
+        let mut errors = vec![];
+        for member in symbol_table.class_members(class) {
+            let Some(base) = mro
+                .iter()
+                .skip(1)
+                .find(|base| symbol_table.is_final_member(base, &member.name))
+            else {
+                continue;
+            };
+            let labels = self.final_member_label(base, &member.name);
+            for decl in &member.declarations {
+                let node = decl.declaration_path().node;
+                let msg = format!(
+                    "'{}' is declared Final in '{}' and cannot be overridden",
+                    member.name, base.name
+                );
+                errors.push((msg, node, labels.clone()));
+            }
+        }
+        for (msg, node, labels) in errors {
+            self.make_error_with_labels(
+                ErrorCode::FinalReassignment,
+                &msg,
+                node.start,
+                node.end,
+                labels,
+            );
+        }
+    }
+
+    /// Reports property setters that do not follow a getter in the class
+    /// body and setters whose value type does not accept the type that the
+    /// getter returns
+    fn check_properties(&mut self, c: &parser::ast::ClassDef) {
+        let mut getters: HashMap<&str, &FunctionDef> = HashMap::new();
+        let mut bound: HashSet<&str> = HashSet::new();
+        for stmt in &c.body {
+            let f = match stmt {
+                Statement::FunctionDef(f) => f,
+                Statement::AssignStatement(a) => {
+                    for target in &a.targets {
+                        if let Expression::Name(n) = target {
+                            bound.insert(&n.id);
+                        }
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let setter_of = f
+                .decorator_list
+                .iter()
+                .find_map(|decorator| match decorator {
+                    Expression::Attribute(a) if a.attr == "setter" => match a.value.as_ref() {
+                        Expression::Name(n) => Some((n, decorator)),
+                        _ => None,
+                    },
+                    _ => None,
+                });
+            let Some((name, decorator)) = setter_of else {
+                if is_property_getter(f) {
+                    getters.insert(&f.name, f);
+                } else {
+                    bound.insert(&f.name);
+                }
+                continue;
+            };
+            let Some(getter) = getters.get(name.id.as_str()) else {
+                // A property created without a decorator, e.g. `x = property(get)`
+                if !bound.contains(name.id.as_str()) {
+                    self.make_error(
+                        ErrorCode::InvalidProperty,
+                        &format!("Setter of property '{}' has no getter", name.id),
+                        decorator.get_node().start,
+                        decorator.get_node().end,
+                    );
+                }
+                continue;
+            };
+            self.check_setter_type(getter, f);
+        }
+    }
+
+    /// Reports a setter whose value parameter does not accept the return type
+    /// of the getter of the property
+    fn check_setter_type(&mut self, getter: &FunctionDef, setter: &FunctionDef) {
+        let Some(returns) = getter.returns.as_deref() else {
+            return;
+        };
+        let value = setter
+            .args
+            .posonlyargs
+            .iter()
+            .chain(setter.args.args.iter())
+            .nth(1);
+        let Some(annotation) = value.and_then(|value| value.annotation.as_ref()) else {
+            return;
+        };
+        let getter_type = self.type_evaluator.get_type_from_annotation(returns);
+        let setter_type = self.type_evaluator.get_type_from_annotation(annotation);
+        if self
+            .type_evaluator
+            .is_assignable(&setter_type, &getter_type)
+        {
+            return;
+        }
+        let label = (
+            CharacterSpan(returns.get_node().start, returns.get_node().end),
+            format!("the getter returns '{}'", getter_type),
+        );
+        self.make_error_with_labels(
+            ErrorCode::InvalidProperty,
+            &format!(
+                "Setter of property '{}' accepts '{}' which is not compatible with the getter type '{}'",
+                setter.name, setter_type, getter_type
+            ),
+            annotation.get_node().start,
+            annotation.get_node().end,
+            vec![label],
+        );
+    }
+
+    /// Reports assignments of properties that have no setter and values that
+    /// the setter of the property does not accept
+    fn check_property_assignment(&mut self, target: &Expression, value: Option<&Expression>) {
+        let Expression::Attribute(attribute) = target else {
+            return;
+        };
+        let Ok(PythonType::Class(c)) = self.type_evaluator.get_type(&attribute.value) else {
+            return;
+        };
+        let symbol_table = &self.type_evaluator.symbol_table;
+        if c.details.declaration_path.module_name != symbol_table.module_name() {
+            return;
+        }
+        let Some(property) = symbol_table.lookup_property(&c.details, &attribute.attr) else {
+            return;
+        };
+        let msg = match (property.setter, value) {
+            (None, _) => format!(
+                "Property '{}' of '{}' has no setter",
+                attribute.attr, c.details.name
+            ),
+            (Some(setter), Some(value)) => {
+                let setter_type = self.type_evaluator.property_setter_type(setter);
+                let value_type = self
+                    .type_evaluator
+                    .get_type(value)
+                    .unwrap_or(PythonType::Unknown);
+                if self.type_evaluator.is_assignable(&setter_type, &value_type) {
+                    return;
+                }
+                format!(
+                    "Type '{}' is not assignable to property '{}' of type '{}'",
+                    value_type, attribute.attr, setter_type
+                )
+            }
+            (Some(_), None) => return,
+        };
+        self.make_error(
+            ErrorCode::InvalidProperty,
+            &msg,
+            attribute.node.start,
+            attribute.node.end,
+        );
+    }
+
+    /// Reports an `__init__` method annotated with a return type other than
+    /// None
+    fn check_init_return(&mut self, c: &parser::ast::ClassDef) {
+        for stmt in &c.body {
+            let Statement::FunctionDef(f) = stmt else {
+                continue;
+            };
+            let Some(returns) = f.returns.as_deref().filter(|_| f.name == "__init__") else {
+                continue;
+            };
+            let return_type = self.type_evaluator.get_type_from_annotation(returns);
+            if matches!(
+                return_type,
+                PythonType::None | PythonType::Unknown | PythonType::Any
+            ) {
+                continue;
+            }
+            self.make_error(
+                ErrorCode::InvalidInitReturn,
+                &format!(
+                    "Return type of '__init__' must be None, found '{}'",
+                    return_type
+                ),
+                returns.get_node().start,
+                returns.get_node().end,
+            );
+        }
+    }
+
+    /// Reports methods that cannot replace the method of a base class that
+    /// they override and methods decorated with `@override` that do not
+    /// override a method
+    fn check_method_overrides(&mut self, c: &parser::ast::ClassDef) {
+        let symbol_table = Arc::clone(&self.type_evaluator.symbol_table);
+        let Some(class) = symbol_table.lookup_class(&c.name, c.node.end) else {
+            return;
+        };
+        let Some(mro) = symbol_table.mro(class) else {
+            return;
+        };
+        let mut errors = vec![];
+        for stmt in &c.body {
+            let Statement::FunctionDef(f) = stmt else {
+                continue;
+            };
+            let base = mro.iter().skip(1).find_map(|base| {
+                if base.is_builtin() {
+                    return base.has_own_member(&f.name).then_some((*base, None));
+                }
+                symbol_table
+                    .class_member(base, &f.name)
+                    .map(|symbol| (*base, symbol.last_declaration()))
+            });
+            match base {
+                None => {
+                    let decorator = f
+                        .decorator_list
+                        .iter()
+                        .find(|decorator| is_type_qualifier(decorator, "override"));
+                    if let Some(decorator) = decorator {
+                        let msg = format!(
+                            "Method '{}' is marked as override but no base class defines it",
+                            f.name
+                        );
+                        errors.push((msg, decorator.get_node(), vec![]));
+                    }
+                }
+                Some((base, Some(decl @ Declaration::Function(base_method)))) => {
+                    let base_name = format!("{}.{}", base.name, f.name);
+                    let labels = vec![declared_here(decl, &base_name)];
+                    for (detail, node) in self.override_errors(f, &base_method.function_node) {
+                        let msg = format!(
+                            "Method '{}' is not compatible with '{}': {}",
+                            f.name, base_name, detail
+                        );
+                        errors.push((msg, node, labels.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (msg, node, labels) in errors {
+            self.make_error_with_labels(
+                ErrorCode::InvalidOverride,
+                &msg,
+                node.start,
+                node.end,
+                labels,
+            );
+        }
+    }
+
+    /// Differences between the signature of a method and the signature of the
+    /// base method it overrides that break calls of the base method. The
+    /// override can add parameters with defaults, accept wider parameter
+    /// types and return a narrower type.
+    fn override_errors(&self, f: &FunctionDef, base: &FunctionDef) -> Vec<(String, Node)> {
+        // Constructors are not called through the base class and private
+        // names are mangled with the class name
+        let is_private = f.name.starts_with("__") && !f.name.ends_with("__");
+        if is_private
+            || ["__init__", "__new__", "__init_subclass__", "__post_init__"]
+                .contains(&f.name.as_str())
+        {
+            return vec![];
+        }
+        let (Some(binding), Some(base_binding)) = (method_binding(f), method_binding(base)) else {
+            return vec![];
+        };
+        if binding != base_binding {
+            return vec![];
+        }
+        let bound = binding != MethodBinding::Static;
+        let params = MethodParameters::new(&f.args, bound);
+        let base_params = MethodParameters::new(&base.args, bound);
+        let mut errors = vec![];
+
+        for (i, base_param) in base_params.positional.iter().enumerate() {
+            let Some(param) = params.positional.get(i) else {
+                if params.vararg.is_none() {
+                    let detail = format!("parameter '{}' is missing", base_param.arg.arg);
+                    errors.push((detail, f.args.node));
+                }
+                continue;
+            };
+            if !base_param.positional_only && param.arg.arg != base_param.arg.arg {
+                let detail = format!(
+                    "parameter '{}' is renamed to '{}'",
+                    base_param.arg.arg, param.arg.arg
+                );
+                errors.push((detail, param.arg.node));
+            }
+            errors.extend(self.parameter_errors(param, base_param));
+        }
+        for param in params.positional.iter().skip(base_params.positional.len()) {
+            if !param.has_default && base_params.vararg.is_none() {
+                let detail = format!("required parameter '{}' is added", param.arg.arg);
+                errors.push((detail, param.arg.node));
+            }
+        }
+        if let (Some(base_vararg), None) = (base_params.vararg, params.vararg) {
+            let detail = format!("parameter '*{}' is missing", base_vararg.arg);
+            errors.push((detail, f.args.node));
+        }
+
+        for base_param in &base_params.keyword {
+            let param = params
+                .keyword
+                .iter()
+                .find(|p| p.arg.arg == base_param.arg.arg)
+                .or_else(|| {
+                    params
+                        .positional
+                        .iter()
+                        .find(|p| !p.positional_only && p.arg.arg == base_param.arg.arg)
+                });
+            match param {
+                Some(param) => errors.extend(self.parameter_errors(param, base_param)),
+                None if params.kwarg.is_none() => {
+                    let detail = format!("parameter '{}' is missing", base_param.arg.arg);
+                    errors.push((detail, f.args.node));
+                }
+                None => {}
+            }
+        }
+        for param in &params.keyword {
+            let in_base = base_params
+                .positional
+                .iter()
+                .chain(base_params.keyword.iter())
+                .any(|p| p.arg.arg == param.arg.arg);
+            if !param.has_default && !in_base && base_params.kwarg.is_none() {
+                let detail = format!("required parameter '{}' is added", param.arg.arg);
+                errors.push((detail, param.arg.node));
+            }
+        }
+        if let (Some(base_kwarg), None) = (base_params.kwarg, params.kwarg) {
+            let detail = format!("parameter '**{}' is missing", base_kwarg.arg);
+            errors.push((detail, f.args.node));
+        }
+
+        if let (Some(returns), Some(base_returns)) = (f.returns.as_deref(), base.returns.as_deref())
+        {
+            let return_type = self.type_evaluator.get_type_from_annotation(returns);
+            let base_return_type = self.type_evaluator.get_type_from_annotation(base_returns);
+            if !self
+                .type_evaluator
+                .is_assignable(&base_return_type, &return_type)
+            {
+                let detail = format!(
+                    "return type '{}' is not compatible with '{}'",
+                    return_type, base_return_type
+                );
+                errors.push((detail, returns.get_node()));
+            }
+        }
+        errors
+    }
+
+    /// Reports a parameter of an override that drops the default of the base
+    /// parameter or does not accept its type
+    fn parameter_errors(
+        &self,
+        param: &MethodParameter,
+        base_param: &MethodParameter,
+    ) -> Vec<(String, Node)> {
+        let mut errors = vec![];
+        if base_param.has_default && !param.has_default {
+            let detail = format!("parameter '{}' has no default", param.arg.arg);
+            errors.push((detail, param.arg.node));
+        }
+        if let (Some(annotation), Some(base_annotation)) =
+            (&param.arg.annotation, &base_param.arg.annotation)
+        {
+            let param_type = self.type_evaluator.get_type_from_annotation(annotation);
+            let base_type = self
+                .type_evaluator
+                .get_type_from_annotation(base_annotation);
+            if !self.type_evaluator.is_assignable(&param_type, &base_type) {
+                let detail = format!(
+                    "parameter '{}' of type '{}' does not accept '{}'",
+                    param.arg.arg, param_type, base_type
+                );
+                errors.push((detail, annotation.get_node()));
+            }
+        }
+        errors
+    }
+
+    /// Label pointing to the `Final` declaration of a member in the body of
+    /// the class
+    fn final_member_label(&self, class: &Class, member: &str) -> Vec<(CharacterSpan, String)> {
+        self.type_evaluator
+            .symbol_table
+            .class_member(class, member)
+            .and_then(|symbol| symbol.declarations.iter().find(|d| d.is_constant()))
+            .map(|decl| declared_here(decl, member))
+            .into_iter()
+            .collect()
+    }
+
+    fn make_error(&mut self, code: ErrorCode, msg: &str, start: TextSize, end: TextSize) {
+        self.make_error_with_labels(code, msg, start, end, vec![]);
+    }
+
+    pub(crate) fn make_error_with_labels(
+        &mut self,
+        code: ErrorCode,
+        msg: &str,
+        start: TextSize,
+        end: TextSize,
+        labels: Vec<(CharacterSpan, String)>,
+    ) {
+        self.push_error(TypeCheckError {
             msg: msg.to_string(),
             span: CharacterSpan(start, end),
-        };
+            code,
+            labels,
+            help: None,
+        });
+    }
+
+    fn make_error_with_help(
+        &mut self,
+        code: ErrorCode,
+        msg: &str,
+        start: TextSize,
+        end: TextSize,
+        help: String,
+    ) {
+        self.push_error(TypeCheckError {
+            msg: msg.to_string(),
+            span: CharacterSpan(start, end),
+            code,
+            labels: vec![],
+            help: Some(help),
+        });
+    }
+
+    fn push_error(&mut self, error: TypeCheckError) {
         // check error doesn't already exist
         for e in &self.errors {
             if e == &error {
@@ -68,6 +2005,383 @@ impl<'a> TypeChecker<'a> {
         self.errors.push(error);
     }
 }
+
+/// Mismatches between the arguments of a call and the parameters of the
+/// called function
+#[derive(Default)]
+struct CallErrors {
+    /// Missing, extra and repeated arguments, with the declaration of the
+    /// parameter when the error is about one
+    arguments: Vec<(String, Option<(Node, String)>)>,
+    /// Arguments whose type is not assignable to the annotation of their
+    /// parameter, with the argument and the declaration of the parameter
+    types: Vec<(String, Node, (Node, String))>,
+}
+
+/// Matches the arguments of a call of the function with its parameters,
+/// like `call_argument_errors`, and checks the types of the arguments when
+/// they fit the parameters. Functions with decorators other than
+/// `staticmethod` and `classmethod` are not checked because decorators can
+/// change the signature.
+fn function_call_errors(
+    type_evaluator: &TypeEvaluator,
+    call: &Call,
+    f: &Function,
+    name: &str,
+) -> CallErrors {
+    let is_method_decorator = |decorator: &Expression| matches!(decorator, Expression::Name(n) if n.id == "staticmethod" || n.id == "classmethod");
+    if !f
+        .function_node
+        .decorator_list
+        .iter()
+        .all(is_method_decorator)
+    {
+        return CallErrors::default();
+    }
+    let bound = f.is_method && !f.has_decorator("staticmethod");
+    let arguments = call_argument_errors(call, &f.function_node.args, bound, name);
+    let types = if arguments.is_empty() {
+        argument_type_errors(type_evaluator, call, f, bound)
+    } else {
+        vec![]
+    };
+    CallErrors { arguments, types }
+}
+
+/// Checks the types of the arguments against the annotations of the
+/// parameters they are passed to. The annotations are evaluated in the
+/// module, functions of other modules are not checked. Unpacked arguments
+/// are not checked.
+fn argument_type_errors(
+    type_evaluator: &TypeEvaluator,
+    call: &Call,
+    f: &Function,
+    bound: bool,
+) -> Vec<(String, Node, (Node, String))> {
+    if f.declaration_path.module_name != type_evaluator.symbol_table.module_name() {
+        return vec![];
+    }
+    let unpacks = call
+        .args
+        .iter()
+        .any(|arg| matches!(arg, Expression::Starred(_)));
+    if unpacks {
+        return vec![];
+    }
+    let Some(bound_arguments) = bind_arguments(call, &f.function_node.args, bound) else {
+        return vec![];
+    };
+    let mut errors = vec![];
+    for (parameter, argument) in bound_arguments {
+        let Some(annotation) = &parameter.annotation else {
+            continue;
+        };
+        let argument_type = type_evaluator
+            .get_type(argument)
+            .unwrap_or(PythonType::Unknown);
+        let parameter_type = type_evaluator.get_type_from_annotation(annotation);
+        if type_evaluator.is_assignable(&parameter_type, &argument_type) {
+            continue;
+        }
+        let msg = format!(
+            "Argument of type '{}' is not assignable to parameter '{}' of type '{}'",
+            argument_type, parameter.arg, parameter_type
+        );
+        let label = (
+            parameter.node,
+            format!("'{}' is declared here", parameter.arg),
+        );
+        errors.push((msg, argument.get_node(), label));
+    }
+    errors
+}
+
+fn argument_labels(label: Option<(Node, String)>) -> Vec<(CharacterSpan, String)> {
+    label
+        .map(|(node, message)| (CharacterSpan(node.start, node.end), message))
+        .into_iter()
+        .collect()
+}
+
+fn declared_here(decl: &Declaration, name: &str) -> (CharacterSpan, String) {
+    let node = decl.declaration_path().node;
+    (
+        CharacterSpan(node.start, node.end),
+        format!("'{}' is declared here", name),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MethodBinding {
+    Instance,
+    Class,
+    Static,
+}
+
+/// How the method is bound when it is called. Returns None when a decorator
+/// can change the signature of the method, e.g. `@overload` or `@property`.
+fn method_binding(f: &FunctionDef) -> Option<MethodBinding> {
+    let mut binding = MethodBinding::Instance;
+    for decorator in &f.decorator_list {
+        let name = match decorator {
+            Expression::Name(n) => &n.id,
+            Expression::Attribute(a) => &a.attr,
+            _ => return None,
+        };
+        match name.as_str() {
+            "staticmethod" => binding = MethodBinding::Static,
+            "classmethod" => binding = MethodBinding::Class,
+            "override" | "abstractmethod" | "final" => {}
+            _ => return None,
+        }
+    }
+    Some(binding)
+}
+
+struct MethodParameter<'a> {
+    arg: &'a Arg,
+    has_default: bool,
+    positional_only: bool,
+}
+
+/// Parameters of a method without the parameter that receives the instance
+/// or the class
+struct MethodParameters<'a> {
+    positional: Vec<MethodParameter<'a>>,
+    vararg: Option<&'a Arg>,
+    keyword: Vec<MethodParameter<'a>>,
+    kwarg: Option<&'a Arg>,
+}
+
+impl<'a> MethodParameters<'a> {
+    fn new(args: &'a Arguments, bound: bool) -> Self {
+        let positional = args
+            .positional_with_defaults()
+            .enumerate()
+            .map(|(i, (arg, default))| MethodParameter {
+                arg,
+                has_default: default.is_some(),
+                positional_only: i < args.posonlyargs.len(),
+            })
+            .skip(usize::from(bound))
+            .collect();
+        let keyword = args
+            .keyword_only_with_defaults()
+            .map(|(arg, default)| MethodParameter {
+                arg,
+                has_default: default.is_some(),
+                positional_only: false,
+            })
+            .collect();
+        MethodParameters {
+            positional,
+            vararg: args.vararg.as_ref(),
+            keyword,
+            kwarg: args.kwarg.as_ref(),
+        }
+    }
+}
+
+fn is_property_getter(f: &FunctionDef) -> bool {
+    f.decorator_list
+        .iter()
+        .any(|decorator| matches!(decorator, Expression::Name(n) if n.id == "property"))
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, word)
+    } else {
+        format!("{} {}s", count, word)
+    }
+}
+
+/// "1 was given" or "2 were given"
+fn were_given(count: usize) -> String {
+    if count == 1 {
+        format!("{} was given", count)
+    } else {
+        format!("{} were given", count)
+    }
+}
+
+/// Returns true if the types are the same, members of unions can be in any
+/// order
+fn same_type(left: &PythonType, right: &PythonType) -> bool {
+    let left_members = left.clone().union_members();
+    let right_members = right.clone().union_members();
+    let contains = |members: &[PythonType], typ: &PythonType| {
+        members.iter().any(|member| member.type_equal(typ))
+    };
+    left_members
+        .iter()
+        .all(|member| contains(&right_members, member))
+        && right_members
+            .iter()
+            .all(|member| contains(&left_members, member))
+}
+
+/// Parameters of the `__init__` that `@dataclass` generates for the fields.
+/// Returns None when a field without a default follows one with a default,
+/// the class definition itself fails in that case.
+fn dataclass_init_parameters(fields: &[DataclassField]) -> Option<Arguments> {
+    let parameter = |field: &DataclassField| Arg {
+        node: field.node,
+        arg: field.name.clone(),
+        annotation: None,
+    };
+    // The values of the defaults are not used when checking calls
+    let default = || {
+        Expression::Constant(Box::new(Constant {
+            node: Node::default(),
+            value: ConstantValue::None,
+            kind: None,
+        }))
+    };
+    let mut parameters = Arguments {
+        node: Node::default(),
+        posonlyargs: vec![],
+        args: vec![],
+        vararg: None,
+        kwonlyargs: vec![],
+        kw_defaults: vec![],
+        kwarg: None,
+        defaults: vec![],
+    };
+    for field in fields {
+        if field.kw_only {
+            parameters.kwonlyargs.push(parameter(field));
+            parameters.kw_defaults.push(field.has_default.then(default));
+        } else {
+            if !field.has_default && !parameters.defaults.is_empty() {
+                return None;
+            }
+            parameters.args.push(parameter(field));
+            if field.has_default {
+                parameters.defaults.push(default());
+            }
+        }
+    }
+    Some(parameters)
+}
+
+/// Matches the arguments of the call with the parameters of the function and
+/// returns a message for each mismatch, with a label pointing to the
+/// declaration of the parameter when the error is about one. `bound` skips
+/// the first parameter, which receives the instance or class for methods.
+/// Calls that unpack arguments with `*` or `**` are not checked.
+pub(crate) fn call_argument_errors(
+    call: &Call,
+    parameters: &Arguments,
+    bound: bool,
+    name: &str,
+) -> Vec<(String, Option<(Node, String)>)> {
+    let unpacks = call
+        .args
+        .iter()
+        .any(|arg| matches!(arg, Expression::Starred(_)))
+        || call.keywords.iter().any(|keyword| keyword.arg.is_none());
+    if unpacks {
+        return vec![];
+    }
+
+    let positional = parameters
+        .posonlyargs
+        .iter()
+        .chain(parameters.args.iter())
+        .collect::<Vec<&Arg>>();
+    let skipped = usize::from(bound && !positional.is_empty());
+    let positional_only = parameters.posonlyargs.len();
+    // Parameters generated by plugins and dataclasses have no declaration
+    let declared_here = |parameter: &Arg| {
+        (parameter.node.end > parameter.node.start).then(|| {
+            (
+                parameter.node,
+                format!("'{}' is declared here", parameter.arg),
+            )
+        })
+    };
+
+    let mut errors = vec![];
+    let mut assigned = vec![false; positional.len()];
+    let mut assigned_keywords = vec![];
+    let expected = positional.len() - skipped;
+    let extra = call.args.len().saturating_sub(expected);
+    if extra > 0 && parameters.vararg.is_none() {
+        // The extra arguments would fill the keyword-only parameters if they
+        // were not keyword-only
+        if extra <= parameters.kwonlyargs.len() {
+            for parameter in &parameters.kwonlyargs[..extra] {
+                let msg = format!(
+                    "Keyword-only parameter '{}' of '{}' cannot be passed positionally",
+                    parameter.arg, name
+                );
+                errors.push((msg, declared_here(parameter)));
+                assigned_keywords.push(parameter.arg.as_str());
+            }
+        } else {
+            let msg = format!(
+                "Too many positional arguments for '{}': expected {}, got {}",
+                name,
+                expected,
+                call.args.len()
+            );
+            errors.push((msg, None));
+        }
+    }
+    for i in 0..call.args.len().min(expected) {
+        assigned[i + skipped] = true;
+    }
+
+    for keyword in &call.keywords {
+        let Some(arg) = &keyword.arg else {
+            continue;
+        };
+        let index = positional.iter().position(|p| &p.arg == arg);
+        match index {
+            Some(i) if i >= skipped && i >= positional_only => {
+                if assigned[i] {
+                    let msg = format!("Multiple values for argument '{}' of '{}'", arg, name);
+                    errors.push((msg, None));
+                }
+                assigned[i] = true;
+            }
+            _ if parameters.kwonlyargs.iter().any(|p| &p.arg == arg) => {
+                assigned_keywords.push(arg.as_str());
+            }
+            // A keyword named like a positional-only parameter goes to `**kwargs`
+            _ if parameters.kwarg.is_some() => {}
+            Some(i) if i >= skipped => {
+                let msg = format!(
+                    "Positional-only parameter '{}' of '{}' cannot be passed by keyword",
+                    arg, name
+                );
+                errors.push((msg, declared_here(positional[i])));
+                assigned[i] = true;
+            }
+            _ => {
+                let msg = format!("Unexpected keyword argument '{}' for '{}'", arg, name);
+                errors.push((msg, None));
+            }
+        }
+    }
+
+    let with_defaults = parameters.positional_with_defaults().enumerate();
+    for (i, (parameter, default)) in with_defaults.skip(skipped) {
+        if !assigned[i] && default.is_none() {
+            let msg = format!("Missing argument '{}' for '{}'", parameter.arg, name);
+            errors.push((msg, None));
+        }
+    }
+    for (parameter, default) in parameters.keyword_only_with_defaults() {
+        if default.is_none() && !assigned_keywords.contains(&parameter.arg.as_str()) {
+            let msg = format!("Missing argument '{}' for '{}'", parameter.arg, name);
+            errors.push((msg, None));
+        }
+    }
+    errors
+}
+
 #[allow(unused)]
 impl<'a> TraversalVisitor for TypeChecker<'a> {
     fn visit_stmt(&mut self, s: &Statement) {
@@ -113,10 +2427,15 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
             Expression::Set(s) => self.visit_set(s),
             Expression::Name(n) => {
                 self.infer_expr_type(e, true);
+                self.check_comprehension_variable_leak(n);
+                self.check_name_used_after_del(n);
                 self.visit_name(n)
             }
             Expression::BoolOp(b) => self.visit_bool_op(b),
-            Expression::UnaryOp(u) => self.visit_unary_op(u),
+            Expression::UnaryOp(u) => {
+                self.infer_expr_type(e, true);
+                self.visit_unary_op(u)
+            }
             Expression::BinOp(b) => self.visit_bin_op(b),
             Expression::NamedExpr(n) => self.visit_named_expr(n),
             Expression::Yield(y) => self.visit_yield(y),
@@ -148,16 +2467,29 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
 
     fn visit_if(&mut self, i: &parser::ast::If) {
         self.visit_expr(&i.test);
-        for stmt in &i.body {
-            self.visit_stmt(stmt);
+        self.check_condition(&i.test);
+        // Branches that do not run on the target version and platform are not
+        // in the symbol table and are not checked
+        let condition = static_condition(
+            &i.test,
+            self.options.python_version,
+            self.options.python_platform,
+        );
+        if condition != Some(false) {
+            for stmt in &i.body {
+                self.visit_stmt(stmt);
+            }
         }
-        for stmt in &i.orelse {
-            self.visit_stmt(stmt);
+        if condition != Some(true) {
+            for stmt in &i.orelse {
+                self.visit_stmt(stmt);
+            }
         }
     }
 
     fn visit_while(&mut self, w: &parser::ast::While) {
         self.visit_expr(&w.test);
+        self.check_condition(&w.test);
         for stmt in &w.body {
             self.visit_stmt(stmt)
         }
@@ -179,9 +2511,8 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
         }
         for with_items in &w.items {
             self.visit_expr(&with_items.context_expr);
-            match &with_items.optional_vars {
-                Some(items) => self.visit_expr(items),
-                None => (),
+            if let Some(items) = &with_items.optional_vars {
+                self.visit_expr(items)
             }
         }
     }
@@ -203,6 +2534,7 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
             if let Some(typ) = &handler.typ {
                 self.visit_expr(typ);
             }
+            self.check_except_handler(handler);
         }
     }
 
@@ -223,18 +2555,33 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
             if let Some(typ) = &handler.typ {
                 self.visit_expr(typ);
             }
+            self.check_except_handler(handler);
         }
     }
 
     fn visit_function_def(&mut self, f: &parser::ast::FunctionDef) {
+        self.check_function_annotations(&f.args, f.returns.as_deref());
+        self.check_parameter_defaults(&f.args);
+        self.check_generator_annotation(f);
+        for stmt in &f.body {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_async_function_def(&mut self, f: &parser::ast::AsyncFunctionDef) {
+        self.check_function_annotations(&f.args, f.returns.as_deref());
+        self.check_parameter_defaults(&f.args);
         for stmt in &f.body {
             self.visit_stmt(stmt);
         }
     }
 
     fn visit_class_def(&mut self, c: &parser::ast::ClassDef) {
+        self.check_class_type_parameters(c);
         for base in &c.bases {
             self.visit_expr(base);
+            self.check_base_class(base);
+            self.check_annotation(base);
         }
         for dec in &c.decorator_list {
             self.visit_expr(dec);
@@ -245,6 +2592,14 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
         for keyword in &c.keywords {
             self.visit_expr(&keyword.value);
         }
+        self.check_final_overrides(c);
+        self.check_properties(c);
+        self.check_method_overrides(c);
+        self.check_init_return(c);
+        let plugins = self.plugins;
+        for plugin in plugins {
+            plugin.on_class(c, &mut PluginContext::new(self));
+        }
     }
 
     fn visit_match(&mut self, m: &parser::ast::Match) {
@@ -263,13 +2618,13 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
     fn visit_match_pattern(&mut self, _m: &parser::ast::MatchPattern) {
         match _m {
             MatchPattern::MatchValue(m) => self.visit_expr(&m.value),
-            MatchPattern::MatchSingleton(m) => self.visit_expr(m),
+            MatchPattern::MatchSingleton(m) => self.visit_expr(&m.value),
             MatchPattern::MatchSequence(m) => {
-                for item in m.iter() {
+                for item in m.patterns.iter() {
                     self.visit_match_pattern(item);
                 }
             }
-            MatchPattern::MatchStar(m) => self.visit_expr(m),
+            MatchPattern::MatchStar(_) => {}
             MatchPattern::MatchMapping(m) => {
                 for key in &m.keys {
                     self.visit_expr(key);
@@ -290,7 +2645,7 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
                 }
             }
             MatchPattern::MatchOr(m) => {
-                for pattern in m.iter() {
+                for pattern in m.patterns.iter() {
                     self.visit_match_pattern(pattern);
                 }
             }
@@ -340,6 +2695,9 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
     }
 
     fn visit_bin_op(&mut self, b: &BinOp) {
+        self.visit_expr(&b.left);
+        self.visit_expr(&b.right);
+        self.check_printf_format(b);
         let l_type = self.infer_expr_type(&b.left, true);
         let r_type = self.infer_expr_type(&b.right, true);
 
@@ -351,10 +2709,12 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
                 "Operator '{}' not supported for types '{}' and '{}'",
                 b.op, l_type, r_type
             );
-            self.errors.push(TypeCheckError {
-                msg,
-                span: CharacterSpan(b.left.get_node().start, b.right.get_node().end),
-            });
+            self.make_error(
+                ErrorCode::UnsupportedOperator,
+                &msg,
+                b.left.get_node().start,
+                b.right.get_node().end,
+            );
         }
     }
 
@@ -420,6 +2780,9 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
 
     fn visit_attribute(&mut self, _a: &Attribute) {
         self.visit_expr(&_a.value);
+        self.check_class_attribute(_a);
+        self.check_super_attribute(_a);
+        self.check_optional_access(&_a.value, Some(&_a.attr));
     }
 
     fn visit_subscript(&mut self, _s: &Subscript) {
@@ -440,12 +2803,25 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
     }
 
     fn visit_call(&mut self, _c: &Call) {
+        self.visit_expr(&_c.func);
+        self.check_optional_access(&_c.func, None);
+        self.check_super_call_arguments(_c);
+        self.check_dataclass_call_arguments(_c);
+        self.check_function_call_arguments(_c);
+        self.check_assert_type(_c);
+        self.check_assert_never(_c);
+        self.check_container_insertion(_c);
+        self.check_str_format(_c);
         for arg in &_c.args {
             self.visit_expr(arg);
         }
         for keyword in &_c.keywords {
             self.visit_expr(&keyword.value);
         }
+        let plugins = self.plugins;
+        for plugin in plugins {
+            plugin.on_call(_c, &mut PluginContext::new(self));
+        }
     }
 
     fn visit_await(&mut self, _a: &Await) {
@@ -459,14 +2835,16 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
         }
     }
 
-    fn visit_lambda(&mut self, _l: &Lambda) {
-        self.visit_expr(&_l.body);
-        // todo: this can share a visit args with functions
+    fn visit_lambda(&mut self, l: &Lambda) {
+        // Lambda parameters cannot be annotated, only the defaults are visited
+        self.check_parameter_defaults(&l.args);
+        self.visit_expr(&l.body);
     }
 
     fn visit_if_exp(&mut self, _i: &IfExp) {
         self.visit_expr(&_i.body);
         self.visit_expr(&_i.test);
+        self.check_condition(&_i.test);
         self.visit_expr(&_i.orelse);
     }
 
@@ -478,6 +2856,10 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
 
     fn visit_formatted_value(&mut self, _f: &FormattedValue) {
         self.visit_expr(&_f.value);
+        if let Some(spec) = &_f.format_spec {
+            self.visit_expr(spec);
+        }
+        self.check_fstring_format_spec(_f);
     }
 
     fn visit_alias(&mut self, _a: &Alias) {}
@@ -485,6 +2867,10 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
     fn visit_assign(&mut self, _a: &Assign) {
         self.visit_expr(&_a.value);
         for target in &_a.targets {
+            self.check_slots_assignment(target);
+            self.check_constant_reassignment(target, _a.node);
+            self.check_property_assignment(target, Some(&_a.value));
+            self.check_unpacking(target, &_a.value);
             #[allow(clippy::single_match)]
             match target {
                 ast::Expression::Name(n) => {
@@ -498,15 +2884,22 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
         if let Some(value) = &_a.value {
             self.visit_expr(value);
         }
+        self.check_annotation(&_a.annotation);
+        self.check_slots_assignment(&_a.target);
+        self.check_constant_reassignment(&_a.target, _a.node);
     }
 
     fn visit_aug_assign(&mut self, _a: &AugAssign) {
         self.visit_expr(&_a.value);
         self.visit_expr(&_a.target);
+        self.check_slots_assignment(&_a.target);
+        self.check_constant_reassignment(&_a.target, _a.node);
+        self.check_property_assignment(&_a.target, None);
     }
 
     fn visit_assert(&mut self, _a: &Assert) {
         self.visit_expr(&_a.test);
+        self.check_condition(&_a.test);
         if let Some(msg) = &_a.msg {
             self.visit_expr(msg);
         }
@@ -527,6 +2920,7 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
     }
 
     fn visit_raise(&mut self, _r: &Raise) {
+        self.check_raise(_r);
         if let Some(exc) = &_r.exc {
             self.visit_expr(exc);
         }
@@ -542,4 +2936,18 @@ impl<'a> TraversalVisitor for TypeChecker<'a> {
     fn visit_global(&mut self, _g: &Global) {}
 
     fn visit_nonlocal(&mut self, _n: &Nonlocal) {}
+
+    // Values of `type` statements are evaluated lazily where they are used
+    fn visit_type_alias(&mut self, _t: &TypeAlias) {}
+}
+
+/// The docstring of a module, class or function body and its node
+fn docstring(body: &[Statement]) -> Option<(&str, Node)> {
+    match body.first()? {
+        Statement::ExpressionStatement(Expression::Constant(constant)) => match &constant.value {
+            ConstantValue::Str(value) => Some((value.as_str(), constant.node)),
+            _ => None,
+        },
+        _ => None,
+    }
 }
@@ -0,0 +1,155 @@
+// Parsing of the parameter sections of Google and NumPy style docstrings.
+// The checker compares the documented parameters with the signature.
+
+use crate::settings::DocstringStyle;
+
+const GOOGLE_HEADERS: &[&str] = &["Args:", "Arguments:", "Parameters:"];
+const NUMPY_HEADERS: &[&str] = &["Parameters", "Other Parameters"];
+
+/// Names of the parameters that the docstring documents, `None` if the
+/// docstring has no parameter section. Leading `*` of `*args` and `**kwargs`
+/// are removed.
+pub(crate) fn documented_parameters(docstring: &str, style: DocstringStyle) -> Option<Vec<String>> {
+    let lines = docstring.lines().collect::<Vec<_>>();
+    let names = match style {
+        DocstringStyle::Google => google_parameters(&lines),
+        DocstringStyle::Numpy => numpy_parameters(&lines),
+    }?;
+    Some(
+        names
+            .into_iter()
+            .map(|name| name.trim_start_matches('*').to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+    )
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Entries of an `Args:` section are indented below the header, e.g.
+/// `name (int): description`. Lines that are indented more continue the
+/// description of the entry above.
+fn google_parameters(lines: &[&str]) -> Option<Vec<String>> {
+    let mut names = vec![];
+    let mut found = false;
+    let mut i = 0;
+    while i < lines.len() {
+        if !GOOGLE_HEADERS.contains(&lines[i].trim()) {
+            i += 1;
+            continue;
+        }
+        found = true;
+        let header_indentation = indentation(lines[i]);
+        let mut entry_indentation = None;
+        i += 1;
+        while i < lines.len() {
+            let line = lines[i];
+            if line.trim().is_empty() {
+                i += 1;
+                continue;
+            }
+            let indentation = indentation(line);
+            if indentation <= header_indentation {
+                break;
+            }
+            let entry_indentation = *entry_indentation.get_or_insert(indentation);
+            if indentation == entry_indentation {
+                let entry = line.trim();
+                let end = entry.find([' ', '(', ':']).unwrap_or(entry.len());
+                names.push(entry[..end].to_string());
+            }
+            i += 1;
+        }
+    }
+    found.then_some(names)
+}
+
+/// A `Parameters` header is underlined with dashes. Entries are at the
+/// indentation of the header, e.g. `x, y : int`, and their descriptions are
+/// indented below them. The section ends at the next underlined header.
+fn numpy_parameters(lines: &[&str]) -> Option<Vec<String>> {
+    let is_header = |i: usize| {
+        lines.get(i + 1).is_some_and(|underline| {
+            let underline = underline.trim();
+            !underline.is_empty() && underline.chars().all(|c| c == '-')
+        })
+    };
+    let mut names = vec![];
+    let mut found = false;
+    let mut i = 0;
+    while i < lines.len() {
+        if !(NUMPY_HEADERS.contains(&lines[i].trim()) && is_header(i)) {
+            i += 1;
+            continue;
+        }
+        found = true;
+        let header_indentation = indentation(lines[i]);
+        i += 2;
+        while i < lines.len() && !is_header(i) {
+            let line = lines[i];
+            i += 1;
+            if line.trim().is_empty() || indentation(line) != header_indentation {
+                continue;
+            }
+            let entry = line.split(':').next().unwrap_or_default();
+            names.extend(entry.split(',').map(|name| name.trim().to_string()));
+        }
+    }
+    found.then_some(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_google_parameters() {
+        let docstring = "Sends a request.
+
+    Args:
+        url (str): Address of the
+            server.
+        timeout: Seconds to wait.
+        *args: Passed on.
+
+    Returns:
+        The response.
+    ";
+        assert_eq!(
+            documented_parameters(docstring, DocstringStyle::Google),
+            Some(vec!["url".into(), "timeout".into(), "args".into()])
+        );
+        assert_eq!(
+            documented_parameters("Sends a request.", DocstringStyle::Google),
+            None
+        );
+    }
+
+    #[test]
+    fn test_numpy_parameters() {
+        let docstring = "Sends a request.
+
+    Parameters
+    ----------
+    url : str
+        Address of the server.
+    x, y : int
+    **kwargs
+        Passed on.
+
+    Returns
+    -------
+    Response
+    ";
+        assert_eq!(
+            documented_parameters(docstring, DocstringStyle::Numpy),
+            Some(vec!["url".into(), "x".into(), "y".into(), "kwargs".into()])
+        );
+        assert_eq!(
+            documented_parameters(docstring, DocstringStyle::Google),
+            None
+        );
+    }
+}
@@ -0,0 +1,344 @@
+// Parsing of printf-style format strings (`"%s" % value`), of the format
+// strings of `str.format` and of format specs (`"{:>10}"` and `f"{x:>10}"`).
+// The checker compares what the format strings expect with the formatted
+// values.
+
+use super::types::PythonType;
+
+/// A conversion specifier of a printf-style format string, e.g. `%(name)5d`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PrintfSpecifier {
+    /// The mapping key, e.g. `name` in `%(name)s`
+    pub key: Option<String>,
+    /// Number of arguments that a `*` width and precision take
+    pub star_arguments: usize,
+    pub conversion: char,
+}
+
+impl PrintfSpecifier {
+    /// Whether a value of the type can be formatted with the conversion.
+    /// Values of other types than the builtin scalars are not checked.
+    pub fn accepts(&self, typ: &PythonType) -> bool {
+        let typ = match typ {
+            PythonType::Bool => &PythonType::Int,
+            typ => typ,
+        };
+        match (self.conversion, typ) {
+            ('s' | 'r' | 'a', _) => true,
+            (_, PythonType::Int) => true,
+            ('d' | 'i' | 'u' | 'e' | 'E' | 'f' | 'F' | 'g' | 'G', PythonType::Float) => true,
+            ('c', PythonType::Str) => true,
+            (_, PythonType::Float | PythonType::Complex | PythonType::Str | PythonType::None) => {
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Returns the conversion specifiers of a printf-style format string, `%%`
+/// is not a specifier
+pub(crate) fn parse_printf(format: &str) -> Result<Vec<PrintfSpecifier>, String> {
+    let mut specifiers = vec![];
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        let mut key = None;
+        if chars.next_if_eq(&'(').is_some() {
+            let mut name = String::new();
+            let mut depth = 1;
+            loop {
+                match chars.next() {
+                    Some(')') if depth == 1 => break,
+                    Some(c) => {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        name.push(c);
+                    }
+                    None => return Err("Incomplete format key".into()),
+                }
+            }
+            key = Some(name);
+        }
+        while chars.next_if(|c| "#0- +".contains(*c)).is_some() {}
+        let mut star_arguments = 0;
+        if chars.next_if_eq(&'*').is_some() {
+            star_arguments += 1;
+        }
+        while chars.next_if(char::is_ascii_digit).is_some() {}
+        if chars.next_if_eq(&'.').is_some() {
+            if chars.next_if_eq(&'*').is_some() {
+                star_arguments += 1;
+            }
+            while chars.next_if(char::is_ascii_digit).is_some() {}
+        }
+        while chars.next_if(|c| "hlL".contains(*c)).is_some() {}
+        match chars.next() {
+            Some('%') => {}
+            Some(conversion) if "diouxXeEfFgGcrsa".contains(conversion) => {
+                specifiers.push(PrintfSpecifier {
+                    key,
+                    star_arguments,
+                    conversion,
+                })
+            }
+            Some(c) => return Err(format!("Unsupported format character '{}'", c)),
+            None => return Err("Incomplete format".into()),
+        }
+    }
+    Ok(specifiers)
+}
+
+/// The argument that a replacement field of `str.format` formats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FieldName {
+    /// `{}`, the next positional argument
+    Auto,
+    /// `{0}`
+    Index(usize),
+    /// `{name}`
+    Keyword(String),
+}
+
+/// A replacement field of a `str.format` format string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FormatField {
+    pub name: FieldName,
+    /// The field formats an attribute or an item of the argument, e.g.
+    /// `{0.real}` or `{0[key]}`
+    pub accesses_member: bool,
+    pub conversion: Option<char>,
+    pub spec: String,
+}
+
+/// Returns the replacement fields of a `str.format` format string in the
+/// order of the arguments that they take. The fields nested in a format
+/// spec come after the field of the spec.
+pub(crate) fn parse_str_format(format: &str) -> Result<Vec<FormatField>, String> {
+    let mut fields = vec![];
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => {}
+            '}' if chars.next_if_eq(&'}').is_some() => {}
+            '}' => return Err("Single '}' encountered in format string".into()),
+            '{' => {
+                let mut field = String::new();
+                let mut depth = 1;
+                // The field ends at its `}`, the spec can contain nested
+                // fields
+                loop {
+                    match chars.next() {
+                        Some('}') if depth == 1 => break,
+                        Some(c) => {
+                            match c {
+                                '{' => depth += 1,
+                                '}' => depth -= 1,
+                                _ => {}
+                            }
+                            field.push(c);
+                        }
+                        None => return Err("Expected '}' before end of string".into()),
+                    }
+                }
+                parse_field(&field, &mut fields)?;
+            }
+            _ => {}
+        }
+    }
+    let auto = fields.iter().any(|f| f.name == FieldName::Auto);
+    let manual = fields.iter().any(|f| matches!(f.name, FieldName::Index(_)));
+    if auto && manual {
+        return Err(
+            "Cannot switch between automatic field numbering and manual field specification".into(),
+        );
+    }
+    Ok(fields)
+}
+
+fn parse_field(field: &str, fields: &mut Vec<FormatField>) -> Result<(), String> {
+    let (name, rest) = field
+        .find(['!', ':'])
+        .map_or((field, ""), |index| field.split_at(index));
+    let (conversion, spec) = match rest.strip_prefix('!') {
+        Some(rest) => {
+            let mut rest = rest.chars();
+            let conversion = match rest.next() {
+                Some(c @ ('r' | 's' | 'a')) => c,
+                Some(c) => return Err(format!("Unknown conversion specifier {}", c)),
+                None => return Err("End of string while looking for conversion specifier".into()),
+            };
+            let rest = rest.as_str();
+            if !rest.is_empty() && !rest.starts_with(':') {
+                return Err("Expected ':' after conversion specifier".into());
+            }
+            (Some(conversion), rest.strip_prefix(':').unwrap_or(rest))
+        }
+        None => (None, rest.strip_prefix(':').unwrap_or(rest)),
+    };
+    let argument = name.split(['.', '[']).next().unwrap_or_default();
+    let field_name = if argument.is_empty() {
+        FieldName::Auto
+    } else if let Ok(index) = argument.parse() {
+        FieldName::Index(index)
+    } else {
+        FieldName::Keyword(argument.to_string())
+    };
+    fields.push(FormatField {
+        name: field_name,
+        accesses_member: argument.len() < name.len(),
+        conversion,
+        spec: spec.to_string(),
+    });
+    // Fields nested in the spec, e.g. `{width}` in `{:{width}}`
+    let mut nested = spec;
+    while let Some(start) = nested.find('{') {
+        let end = nested[start..]
+            .find('}')
+            .map(|end| start + end)
+            .ok_or("Expected '}' before end of string")?;
+        parse_field(&nested[start + 1..end], fields)?;
+        nested = &nested[end + 1..];
+    }
+    Ok(())
+}
+
+/// A standard format spec,
+/// `[[fill]align][sign]["z"]["#"]["0"][width][grouping]["." precision][type]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FormatSpec {
+    pub align: Option<char>,
+    pub sign: bool,
+    pub alternate: bool,
+    pub grouping: bool,
+    pub precision: bool,
+    pub presentation: Option<char>,
+}
+
+impl FormatSpec {
+    /// Whether values of the type can be formatted with the spec. Values of
+    /// other types than the builtin scalars are not checked because their
+    /// `__format__` method can accept other specs, e.g. `datetime`.
+    pub fn accepts(&self, typ: &PythonType) -> bool {
+        let float_presentation = matches!(
+            self.presentation,
+            None | Some('e' | 'E' | 'f' | 'F' | 'g' | 'G' | 'n' | '%')
+        );
+        match typ {
+            PythonType::Str => {
+                matches!(self.presentation, None | Some('s'))
+                    && !self.sign
+                    && !self.alternate
+                    && !self.grouping
+                    && self.align != Some('=')
+            }
+            // Integers are converted to floats by the float presentations
+            PythonType::Int | PythonType::Bool => match self.presentation {
+                None | Some('b' | 'c' | 'd' | 'n' | 'o' | 'x' | 'X') => !self.precision,
+                Some('s') => false,
+                _ => true,
+            },
+            PythonType::Float => float_presentation,
+            PythonType::Complex => float_presentation && self.presentation != Some('%'),
+            _ => true,
+        }
+    }
+}
+
+/// Parses a standard format spec, returns None if it is not valid
+pub(crate) fn parse_format_spec(spec: &str) -> Option<FormatSpec> {
+    let chars = spec.chars().collect::<Vec<_>>();
+    let is_align = |c: &char| "<>=^".contains(*c);
+    let mut i = 0;
+    let align = match chars.as_slice() {
+        [_, align, ..] if is_align(align) => {
+            i = 2;
+            Some(*align)
+        }
+        [align, ..] if is_align(align) => {
+            i = 1;
+            Some(*align)
+        }
+        _ => None,
+    };
+    let mut eat = |accepted: &str| {
+        let found = chars.get(i).is_some_and(|c| accepted.contains(*c));
+        if found {
+            i += 1;
+        }
+        found
+    };
+    let sign = eat("+- ");
+    eat("z");
+    let alternate = eat("#");
+    eat("0");
+    while eat("0123456789") {}
+    let grouping = eat(",_");
+    let precision = eat(".");
+    if precision && !eat("0123456789") {
+        return None;
+    }
+    while eat("0123456789") {}
+    let presentation = match chars.get(i) {
+        Some(c) if "bcdeEfFgGnosxX%".contains(*c) => {
+            i += 1;
+            Some(*c)
+        }
+        _ => None,
+    };
+    if i != chars.len() {
+        return None;
+    }
+    Some(FormatSpec {
+        align,
+        sign,
+        alternate,
+        grouping,
+        precision,
+        presentation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_strings() {
+        let specifiers = parse_printf("%(a(b))s %-*.*f %% %5d").unwrap();
+        assert_eq!(
+            specifiers
+                .iter()
+                .map(|s| (s.key.as_deref(), s.star_arguments, s.conversion))
+                .collect::<Vec<_>>(),
+            vec![(Some("a(b)"), 0, 's'), (None, 2, 'f'), (None, 0, 'd')]
+        );
+
+        let fields = parse_str_format("{{}} {0.real!r:>{1}} {name[0]}").unwrap();
+        assert_eq!(
+            fields
+                .iter()
+                .map(|f| (&f.name, f.accesses_member, f.conversion, f.spec.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (&FieldName::Index(0), true, Some('r'), ">{1}"),
+                (&FieldName::Index(1), false, None, ""),
+                (&FieldName::Keyword("name".into()), true, None, ""),
+            ]
+        );
+
+        let spec = parse_format_spec("*^+#012,.3f").unwrap();
+        assert_eq!(
+            (spec.align, spec.sign, spec.grouping, spec.presentation),
+            (Some('^'), true, true, Some('f'))
+        );
+        assert!(parse_format_spec(">>").is_some());
+        assert!(parse_format_spec(".f").is_none());
+        assert!(parse_format_spec("10q").is_none());
+    }
+}
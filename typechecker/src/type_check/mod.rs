@@ -1,4 +1,7 @@
+pub(crate) mod annotate;
 pub mod checker;
+mod docstring;
+mod format_string;
 mod rules;
 pub mod type_evaluator;
 mod types;
@@ -8,8 +11,149 @@ pub(crate) mod builtins {
     pub const TUPLE_TYPE: &str = "tuple";
     pub const DICT_TYPE: &str = "dict";
     pub const SET_TYPE: &str = "set";
+    pub const BYTES_TYPE: &str = "bytes";
+    pub const OBJECT_TYPE: &str = "object";
+    pub const TYPE_TYPE: &str = "type";
+
+    // Methods and attributes every object has
+    pub const OBJECT_METHODS: &[&str] = &[
+        "__delattr__",
+        "__dir__",
+        "__eq__",
+        "__format__",
+        "__getattribute__",
+        "__getstate__",
+        "__hash__",
+        "__init__",
+        "__init_subclass__",
+        "__ne__",
+        "__new__",
+        "__reduce__",
+        "__reduce_ex__",
+        "__repr__",
+        "__setattr__",
+        "__sizeof__",
+        "__str__",
+        "__subclasshook__",
+    ];
+    pub const OBJECT_ATTRIBUTES: &[&str] = &["__class__", "__dict__", "__doc__", "__module__"];
+
+    // Methods and attributes that classes get from their metaclass `type`
+    pub const TYPE_METHODS: &[&str] = &[
+        "__call__",
+        "__instancecheck__",
+        "__or__",
+        "__prepare__",
+        "__ror__",
+        "__subclasscheck__",
+        "__subclasses__",
+        "mro",
+    ];
+    pub const TYPE_ATTRIBUTES: &[&str] = &[
+        "__base__",
+        "__bases__",
+        "__basicsize__",
+        "__dictoffset__",
+        "__flags__",
+        "__itemsize__",
+        "__mro__",
+        "__name__",
+        "__qualname__",
+        "__text_signature__",
+        "__type_params__",
+        "__weakrefoffset__",
+    ];
+
+    // Members that `@dataclass` adds to a class
+    pub const DATACLASS_MEMBERS: &[&str] = &[
+        "__dataclass_fields__",
+        "__dataclass_params__",
+        "__ge__",
+        "__gt__",
+        "__le__",
+        "__lt__",
+        "__match_args__",
+        "__replace__",
+    ];
 
     pub const ITER_TYPE: &str = "Iterator";
+    pub const GENERATOR_TYPE: &str = "Generator";
+
+    // Builtin exception classes, they derive from `BaseException`
+    pub const EXCEPTION_TYPES: &[&str] = &[
+        "ArithmeticError",
+        "AssertionError",
+        "AttributeError",
+        "BaseException",
+        "BaseExceptionGroup",
+        "BlockingIOError",
+        "BrokenPipeError",
+        "BufferError",
+        "BytesWarning",
+        "ChildProcessError",
+        "ConnectionAbortedError",
+        "ConnectionError",
+        "ConnectionRefusedError",
+        "ConnectionResetError",
+        "DeprecationWarning",
+        "EOFError",
+        "EncodingWarning",
+        "EnvironmentError",
+        "Exception",
+        "ExceptionGroup",
+        "FileExistsError",
+        "FileNotFoundError",
+        "FloatingPointError",
+        "FutureWarning",
+        "GeneratorExit",
+        "IOError",
+        "ImportError",
+        "ImportWarning",
+        "IndentationError",
+        "IndexError",
+        "InterruptedError",
+        "IsADirectoryError",
+        "KeyError",
+        "KeyboardInterrupt",
+        "LookupError",
+        "MemoryError",
+        "ModuleNotFoundError",
+        "NameError",
+        "NotADirectoryError",
+        "NotImplementedError",
+        "OSError",
+        "OverflowError",
+        "PendingDeprecationWarning",
+        "PermissionError",
+        "ProcessLookupError",
+        "RecursionError",
+        "ReferenceError",
+        "ResourceWarning",
+        "RuntimeError",
+        "RuntimeWarning",
+        "StopAsyncIteration",
+        "StopIteration",
+        "SyntaxError",
+        "SyntaxWarning",
+        "SystemError",
+        "SystemExit",
+        "TabError",
+        "TimeoutError",
+        "TypeError",
+        "UnboundLocalError",
+        "UnicodeDecodeError",
+        "UnicodeEncodeError",
+        "UnicodeError",
+        "UnicodeTranslateError",
+        "UnicodeWarning",
+        "UserWarning",
+        "ValueError",
+        "Warning",
+        "ZeroDivisionError",
+    ];
+
+    // Module name of the typeshed stub that declares the builtin functions
+    pub const STUB_MODULE: &str = "stdlib.builtins.pyi";
 
     // list of all builtin function names
     pub const BUILTINS: &[&str] = &[
@@ -0,0 +1,27 @@
+from typing import Literal
+import typing
+
+
+def dispatch(mode: Literal["r", "w", "a"]):
+    if mode == "r":
+        return mode
+    elif mode != "w":
+        return mode
+    else:
+        return mode
+
+
+def negated(flag: typing.Literal[1, 2]):
+    if not flag == 1:
+        return flag
+
+
+def combined(mode: Literal["r", "w", "a"]):
+    if mode != "r" and mode != "w":
+        return mode
+
+
+def reassigned(mode: Literal["r", "w"]):
+    if mode == "r":
+        mode = "w"
+        return mode
@@ -0,0 +1,34 @@
+def read() -> int | None:
+    pass
+
+
+count = None
+while read():
+    before = count
+    count = 1
+after = count
+
+total = 0
+for item in [1, 2]:
+    total = total + 1.5
+summed = total
+
+value = read()
+while value is not None:
+    doubled = value * 2
+    value = read()
+remaining = value
+
+text = ""
+while read():
+    while text:
+        text = 1
+    inner = text
+outer = text
+
+first = 1
+second = "a"
+while read():
+    first = second
+    second = first
+swapped = first
@@ -0,0 +1,18 @@
+i = 1
+f = 1.5
+c = 2j
+b = True
+
+int_sum = i + b
+float_sum = i + f
+complex_sum = f + c
+true_division = i / i
+complex_division = c / i
+floor_division = f // i
+remainder = i % b
+power = i ** i
+bool_and = b & b
+bool_or = b | i
+shifted = b << i
+quotient = divmod(i, b)
+float_quotient = divmod(i, f)
@@ -0,0 +1,50 @@
+from typing import Literal, TypeAlias, overload
+
+TextMode: TypeAlias = Literal["r", "w"]
+BinaryMode = Literal["rb", "wb"]
+
+
+class TextFile:
+    pass
+
+
+class BinaryFile:
+    pass
+
+
+@overload
+def open_file(path: str, mode: TextMode = "r") -> TextFile:
+    pass
+
+
+@overload
+def open_file(path: str, mode: BinaryMode, buffering: int = -1) -> BinaryFile:
+    pass
+
+
+@overload
+def open_file(path: str, mode: str) -> str:
+    pass
+
+
+def open_file(path, mode="r", buffering=-1):
+    return path
+
+
+text = open_file("a.txt")
+written = open_file("a.txt", "w")
+binary = open_file("a.txt", "rb")
+keyword = open_file("a.txt", mode="wb", buffering=0)
+unbuffered = open_file(path="a.txt", mode="rb", buffering=0)
+other = open_file("a.txt", "x")
+invalid = open_file("a.txt", "rb", "buffer")
+
+
+def read_binary(mode: Literal["rb"]):
+    file = open_file("a.txt", mode)
+    return file
+
+
+def open_with(mode):
+    file = open_file("a.txt", mode)
+    return file
@@ -0,0 +1,31 @@
+from typing import Self
+
+
+class Builder:
+    def set_name(self, name: str) -> Self:
+        self.name = name
+        return self
+
+    def set_size(self, size: int):
+        self.size = size
+        return self
+
+    def build(self, output: str) -> str:
+        return output
+
+
+class HtmlBuilder(Builder):
+    def set_tag(self, tag: str) -> Self:
+        self.tag = tag
+        return self
+
+
+builder = HtmlBuilder().set_name("div")
+chained = HtmlBuilder().set_name("div").set_size(2).set_tag("p")
+base = Builder().set_size(1)
+result = HtmlBuilder().set_tag("p").build("html")
+
+# The receiver is declared by the call, its type is not known
+builder = builder.set_size(1)
+line = " a "
+line = line.strip()
@@ -0,0 +1,19 @@
+line = "name=enderpy, version=1"
+fields = line.split(", ")
+pairs = line.partition("=")
+key = fields[0].strip().lower()
+joined = "; ".join(fields)
+found = line.find("=")
+is_name = key.startswith("name")
+message = "{}: {}".format(key, found)
+formatted = f"{key}={found}"
+percent = "%s=%d" % (key, found)
+repeated = "-" * 10
+concatenated = key + "!"
+
+data = b"a,b"
+encoded = line.encode()
+chunks = data.split(b",")
+decoded = data.decode()
+hexed = data.hex()
+doubled = data * 2
@@ -0,0 +1,43 @@
+class Node:
+    pass
+
+
+class Bag:
+    def __len__(self) -> int:
+        pass
+
+
+def find() -> Node | None:
+    pass
+
+
+def collect() -> Bag | None:
+    pass
+
+
+def count() -> int | None:
+    pass
+
+
+node = find()
+if node:
+    found = node
+else:
+    missing = node
+if not node:
+    negated = node
+
+bag = collect()
+if bag:
+    filled = bag
+else:
+    empty = bag
+
+number = count()
+if number:
+    nonzero = number
+else:
+    zero = number
+
+while node:
+    looping = node
@@ -0,0 +1,49 @@
+from typing import TypeGuard, TypeIs
+
+
+class Animal:
+    pass
+
+
+class Cat(Animal):
+    pass
+
+
+class Dog(Animal):
+    pass
+
+
+def is_str(value: int | str) -> TypeIs[str]:
+    pass
+
+
+def is_int(value: object) -> TypeGuard[int]:
+    pass
+
+
+def is_cat(animal: Cat | Dog) -> TypeIs[Cat]:
+    pass
+
+
+def check_type_is(value: int | str):
+    if is_str(value):
+        a = value
+    else:
+        b = value
+    if not is_str(value):
+        c = value
+
+
+def check_type_guard(value: int | str):
+    if is_int(value):
+        d = value
+    else:
+        e = value
+    result = is_int(value)
+
+
+def check_classes(animal: Cat | Dog):
+    if is_cat(animal):
+        f = animal
+    else:
+        g = animal
@@ -0,0 +1,22 @@
+from typing import TypeVarTuple, Unpack
+
+Ts = TypeVarTuple("Ts")
+
+
+def to_tuple(*args: *Ts) -> tuple[*Ts]:
+    return args
+
+
+def prefixed(first: int, *rest: Unpack[Ts]) -> tuple[int, *Ts]:
+    return rest
+
+
+def flattened(*args: *Ts) -> tuple[*tuple[str, float], *Ts]:
+    return args
+
+
+pair = to_tuple(1, "a")
+empty = to_tuple()
+triple = prefixed(1, "a", 2.5)
+flat = flattened(1)
+pairs: dict[str, int] = {}
@@ -2,24 +2,36 @@
 #![allow(unused_variables)]
 
 use core::panic;
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
 use enderpy_python_parser as parser;
 use enderpy_python_parser::ast;
 use log::debug;
-use miette::{bail, miette, Result};
-use parser::ast::{Expression, GetNode, Statement};
+use miette::{Result, bail, miette};
+use parser::{
+    TextSize,
+    ast::{Expression, GetNode, Statement},
+};
 
 use super::{
     builtins,
-    types::{CallableType, LiteralValue, PythonType},
+    types::{CallableType, KnownValue, LiteralValue, PythonType},
 };
 use crate::{
     ast_visitor::TraversalVisitor,
     ast_visitor_generic::TraversalVisitorImmutGeneric,
+    build_source::get_module_name,
     nodes::EnderpyFile,
+    semanal_utils::{
+        is_tuple_type, is_type_qualifier, is_unbounded_tuple, literal_elements, substitute_names,
+        type_arguments, unpacked_type,
+    },
+    settings::Settings,
     state::State,
-    symbol_table::{self, Declaration, LookupSymbolRequest, SymbolTable, SymbolTableNode},
+    symbol_table::{
+        self, Declaration, Function, LookupSymbolRequest, SuperMember, SymbolTable,
+        SymbolTableNode, TypeParameterKind,
+    },
     type_check::types::ClassType,
 };
 
@@ -29,14 +41,34 @@ const LITERAL_TYPE_PARAMETER_MSG: &str = "Type arguments for 'Literal' must be N
 const UNION_TYPE_PARAMETER_MSG: &str = "Type arguments for 'Union' must be names or literal values";
 
 pub struct TypeEvaluator {
-    // TODO: make this a reference to the symbol table in the checker
-    pub symbol_table: SymbolTable,
-    pub imported_symbol_tables: Vec<SymbolTable>,
+    pub symbol_table: Arc<SymbolTable>,
+    /// Symbol tables of all the modules of the build, the annotations of
+    /// imported names are evaluated in the module they are declared in
+    pub imported_symbol_tables: Vec<Arc<SymbolTable>>,
+    /// Infer the element type of a container display as the common base of
+    /// its elements instead of their union
+    pub join_container_types: bool,
+    /// Starts of the loop-carried declarations whose types are being
+    /// evaluated. A declaration is not carried into its own value, which
+    /// stops the evaluation of `x = x + 1` in a loop from recursing.
+    pub carried_declarations: RefCell<Vec<TextSize>>,
+    /// Starts of the values of the type aliases that are being expanded. An
+    /// alias that is reached again while it is expanded is recursive and its
+    /// type is unknown.
+    pub expanding_aliases: RefCell<Vec<TextSize>>,
+    /// Starts of the method calls whose receivers are being evaluated. The
+    /// receiver of `x = x.method()` is declared by the call itself, when the
+    /// call is reached again its type is unknown.
+    pub evaluating_method_calls: RefCell<Vec<TextSize>>,
+    /// Types of the type parameters of the generic class whose member
+    /// annotation is being evaluated, e.g. `T` is `int` in the return
+    /// annotation of `get` for `Box[int]().get()`
+    pub type_parameter_bindings: RefCell<Vec<HashMap<String, PythonType>>>,
 }
 
 pub struct TypeEvalError {
     pub message: String,
-    pub position: usize,
+    pub position: TextSize,
 }
 
 /// Struct for evaluating the type of an expression
@@ -45,7 +77,7 @@ impl TypeEvaluator {
     pub fn get_symbol_node_type(
         &self,
         symbol: &SymbolTableNode,
-        position: Option<usize>,
+        position: Option<TextSize>,
     ) -> Result<PythonType> {
         let decl = match position {
             Some(position) => symbol.declaration_until_position(position),
@@ -53,9 +85,76 @@ impl TypeEvaluator {
         };
 
         log::debug!("fetch symbol declaration: {:?}", decl);
-        match decl {
-            Some(decl) => self.get_type_from_declaration(decl),
-            None => Ok(PythonType::Any),
+        match (decl, position) {
+            (Some(decl), Some(position)) => {
+                let typ = self.get_type_from_declaration(decl)?;
+                Ok(self.loop_carried_type(symbol, decl, position, typ))
+            }
+            (Some(decl), None) => self.get_type_from_declaration(decl),
+            (None, _) => Ok(PythonType::Any),
+        }
+    }
+
+    /// Widens the type of a declaration for the loops around the position.
+    /// In a loop the declarations of the previous iteration are visible, and
+    /// after a loop the declaration before it is visible when the body did
+    /// not run.
+    fn loop_carried_type(
+        &self,
+        symbol: &SymbolTableNode,
+        decl: &Declaration,
+        position: TextSize,
+        typ: PythonType,
+    ) -> PythonType {
+        let decl_start = decl.declaration_path().node.start;
+        let exited_loop = self
+            .symbol_table
+            .loops_at(decl_start)
+            .into_iter()
+            .find(|l| l.node.end <= position);
+        if let Some(exited_loop) = exited_loop {
+            if symbol
+                .declaration_until_position(exited_loop.node.start)
+                .is_none()
+            {
+                return typ;
+            }
+            return match self.get_symbol_node_type(symbol, Some(exited_loop.node.start)) {
+                Ok(before) => union_of(before, typ),
+                Err(_) => PythonType::Unknown,
+            };
+        }
+
+        let Some(entered_loop) = self
+            .symbol_table
+            .loops_at(position)
+            .into_iter()
+            .find(|l| decl_start < l.node.start)
+        else {
+            return typ;
+        };
+        // The last declaration of the body is visible in the next iteration
+        let Some(carried) = symbol
+            .declarations
+            .iter()
+            .filter(|d| {
+                let start = d.declaration_path().node.start;
+                entered_loop.node.start <= start && start < entered_loop.node.end
+            })
+            .max_by_key(|d| d.declaration_path().node.start)
+        else {
+            return typ;
+        };
+        let carried_start = carried.declaration_path().node.start;
+        if self.carried_declarations.borrow().contains(&carried_start) {
+            return typ;
+        }
+        self.carried_declarations.borrow_mut().push(carried_start);
+        let carried_type = self.get_type_from_declaration(carried);
+        self.carried_declarations.borrow_mut().pop();
+        match carried_type {
+            Ok(carried_type) => union_of(typ, carried_type),
+            Err(_) => PythonType::Unknown,
         }
     }
 
@@ -73,33 +172,100 @@ impl TypeEvaluator {
                     // declared as such https://peps.python.org/pep-0586/#type-inference
                     ast::ConstantValue::Int(_) => PythonType::Int,
                     ast::ConstantValue::Float(_) => PythonType::Float,
+                    ast::ConstantValue::Complex { .. } => PythonType::Complex,
                     ast::ConstantValue::Str(_) => PythonType::Str,
                     ast::ConstantValue::Bool(_) => PythonType::Bool,
                     ast::ConstantValue::None => PythonType::None,
+                    ast::ConstantValue::Bytes(_) => self.bytes_type(),
                     _ => PythonType::Unknown,
                 };
                 Ok(typ)
             }
             ast::Expression::Name(n) => {
-                self.infer_type_from_symbol_table(&n.id, Some(n.node.start))
+                let typ = self.infer_type_from_symbol_table(&n.id, Some(n.node.start))?;
+                Ok(self.narrow_name_type(n, typ))
             }
             ast::Expression::Call(call) => {
+                // cast(T, value) evaluates to T whatever the type of the value
+                if is_type_qualifier(&call.func, "cast") {
+                    if let [typ, _] = call.args.as_slice() {
+                        return Ok(self.get_type_from_annotation(typ));
+                    }
+                }
                 let func = *call.func.clone();
                 match func {
                     ast::Expression::Name(n) => {
+                        // assert_type(value, T) returns the value unchanged
+                        if n.id == "assert_type" {
+                            if let Some(value) = call.args.first() {
+                                return self.get_type(value);
+                            }
+                        }
+                        if n.id == "getattr" && self.is_builtin_name(&n) {
+                            return Ok(self.getattr_type(call));
+                        }
+                        if n.id == "divmod" && self.is_builtin_name(&n) {
+                            return self.divmod_type(call);
+                        }
+                        if let Some(return_type) = self.overloaded_call_type(&n, call) {
+                            return Ok(return_type);
+                        }
                         // check if name is one of the builtins
                         if builtins::BUILTINS.contains(&n.id.as_str()) {
                             return Ok(PythonType::Unknown);
                         }
+                        // Calling a class creates an instance of it
+                        if let Some(class) = self.symbol_table.lookup_class(&n.id, n.node.start) {
+                            return Ok(self.constructor_call_type(class, call));
+                        }
                         let f_type = self.infer_type_from_symbol_table(n.id.as_str(), None)?;
                         log::debug!("f_type: {:?}", f_type);
                         match f_type {
-                            PythonType::Callable(callable_type) => Ok(callable_type.return_type),
+                            PythonType::Callable(callable_type) => {
+                                Ok(self.solve_type_var_tuple(&callable_type, call))
+                            }
                             PythonType::Never => Ok(PythonType::Never),
-                            _ => Err(miette!("{} is not callable", n.id)),
+                            // Imported names, their types are not resolved yet
+                            PythonType::Any => Ok(PythonType::Unknown),
+                            // Defined names of unknown type, e.g. unannotated
+                            // parameters, may be callable
+                            PythonType::Unknown
+                                if self
+                                    .symbol_table
+                                    .lookup_in_scope(LookupSymbolRequest {
+                                        name: n.id.clone(),
+                                        position: Some(n.node.start),
+                                    })
+                                    .is_some() =>
+                            {
+                                Ok(PythonType::Unknown)
+                            }
+                            _ => Err(miette!(code = "E201", "{} is not callable", n.id)),
                         }
                     }
-                    ast::Expression::Attribute(_a) => Ok(PythonType::Unknown),
+                    // Calling a specialized generic class, e.g. `Box[int](1)`
+                    ast::Expression::Subscript(s)
+                        if matches!(s.value.as_ref(), Expression::Name(n)
+                            if self.symbol_table.lookup_class(&n.id, n.node.start).is_some()) =>
+                    {
+                        Ok(self.get_type_from_annotation(&call.func))
+                    }
+                    ast::Expression::Attribute(a) => match self.lookup_super_attribute(&a) {
+                        Some(SuperMember::Found(class)) => {
+                            let method_type = match self.symbol_table.class_member(class, &a.attr) {
+                                Some(symbol) => self.get_symbol_node_type(symbol, None)?,
+                                None => PythonType::Unknown,
+                            };
+                            match method_type {
+                                PythonType::Callable(callable_type) => {
+                                    Ok(callable_type.return_type)
+                                }
+                                _ => Ok(PythonType::Unknown),
+                            }
+                        }
+                        Some(SuperMember::Missing) => Ok(PythonType::Unknown),
+                        None => self.method_call_type(&a),
+                    },
                     _ => {
                         debug!("infer type from call not implemented");
                         Ok(PythonType::Unknown)
@@ -147,31 +313,30 @@ impl TypeEvaluator {
             ast::Expression::BoolOp(_) => Ok(PythonType::Bool),
             ast::Expression::UnaryOp(u) => match u.op {
                 ast::UnaryOperator::Not => Ok(PythonType::Bool),
-                ast::UnaryOperator::Invert => match self.get_type(&u.operand)? {
-                    PythonType::Int => Ok(PythonType::Int),
-                    _ => bail!(
-                        "cannot invert type {}",
-                        self.get_type(&u.operand)?.to_string()
-                    ),
-                },
+                // Errors in the operand are reported at the operand
+                ast::UnaryOperator::Invert => {
+                    match self.get_type(&u.operand).unwrap_or(PythonType::Unknown) {
+                        PythonType::Int => Ok(PythonType::Int),
+                        PythonType::Unknown | PythonType::Any => Ok(PythonType::Unknown),
+                        operand_type => {
+                            bail!(code = "E202", "cannot invert type {}", operand_type)
+                        }
+                    }
+                }
                 _ => self.get_type(&u.operand),
             },
             ast::Expression::NamedExpr(e) => self.get_type(&e.value),
-            ast::Expression::Yield(a) => {
-                let yield_type = match a.value {
-                    Some(ref v) => self.get_type(v)?,
-                    None => PythonType::None,
-                };
-                let builtin_type = self.get_builtin_type(builtins::ITER_TYPE);
-                todo!()
-            }
-            ast::Expression::YieldFrom(yf) => {
-                let yield_type = match *yf.value.clone() {
-                    ast::Expression::List(l) => self.get_sequence_type_from_elements(&l.elements),
-                    _ => panic!("TODO: infer type from yield from"),
-                };
-                todo!()
-            }
+            // A yield expression evaluates to the value sent to the generator
+            ast::Expression::Yield(y) => Ok(self.generator_send_type(y.node.start)),
+            // `yield from g` evaluates to the value that g returns
+            ast::Expression::YieldFrom(yf) => match self.get_type(&yf.value)? {
+                PythonType::Class(c)
+                    if c.details.name == "Generator" && c.type_parameters.len() == 3 =>
+                {
+                    Ok(c.type_parameters[2].clone())
+                }
+                _ => Ok(PythonType::Unknown),
+            },
             ast::Expression::Starred(s) => Ok(PythonType::Unknown),
             ast::Expression::Generator(g) => {
                 // This is not correct
@@ -190,16 +355,51 @@ impl TypeEvaluator {
             ast::Expression::ListComp(_) => Ok(PythonType::Unknown),
             ast::Expression::SetComp(_) => Ok(PythonType::Unknown),
             ast::Expression::DictComp(_) => Ok(PythonType::Unknown),
-            ast::Expression::Attribute(a) => Ok(PythonType::Unknown),
+            ast::Expression::Attribute(a) => {
+                if let Some(typ) = self.module_attribute_type(a) {
+                    return Ok(typ);
+                }
+                match self.get_type(&a.value)? {
+                    // Reading a property calls its getter
+                    PythonType::Class(c)
+                        if c.details.declaration_path.module_name
+                            == self.symbol_table.module_name() =>
+                    {
+                        if let Some(property) =
+                            self.symbol_table.lookup_property(&c.details, &a.attr)
+                        {
+                            return Ok(self.function_return_type(property.getter));
+                        }
+                        // Attributes declared with an annotation in a class of
+                        // this module, e.g. `x: int`
+                        Ok(c.details
+                            .attribute_annotations
+                            .get(&a.attr)
+                            .map_or(PythonType::Unknown, |annotation| {
+                                self.specialized_member_type(&c, annotation)
+                            }))
+                    }
+                    _ => Ok(PythonType::Unknown),
+                }
+            }
             ast::Expression::Subscript(s) => {
                 let value_type = &self.get_type(&s.value)?;
+                if let Some(element_type) = tuple_element_type(value_type, &s.slice)
+                    .or_else(|| container_element_type(value_type, &s.slice))
+                {
+                    return Ok(element_type);
+                }
                 // This only handles container types and TODO
                 Ok(value_type.clone())
             }
             ast::Expression::Slice(_) => Ok(PythonType::Unknown),
             ast::Expression::Await(_) => Ok(PythonType::Unknown),
             ast::Expression::Compare(_) => Ok(PythonType::Bool),
-            ast::Expression::Lambda(_) => Ok(PythonType::Unknown),
+            ast::Expression::Lambda(l) => Ok(PythonType::Callable(Box::new(CallableType {
+                name: String::from("<lambda>"),
+                arguments: l.args.clone(),
+                return_type: self.get_type(&l.body)?,
+            }))),
             ast::Expression::IfExp(_) => Ok(PythonType::Unknown),
             ast::Expression::JoinedStr(_) => Ok(PythonType::Str),
             ast::Expression::FormattedValue(f) => self.get_type(&f.value),
@@ -208,16 +408,195 @@ impl TypeEvaluator {
 
     // This function tries to find the python type from an annotation expression
     // If the annotation is invalid it returns uknown type
+    /// Looks up `super().attr` in the classes that follow the class of the
+    /// enclosing method in its MRO. Only the zero argument form of the
+    /// builtin `super` is supported.
+    pub fn lookup_super_attribute(&self, attribute: &ast::Attribute) -> Option<SuperMember<'_>> {
+        let Expression::Call(call) = attribute.value.as_ref() else {
+            return None;
+        };
+        let Expression::Name(n) = call.func.as_ref() else {
+            return None;
+        };
+        let is_builtin_super = n.id == "super" && self.is_builtin_name(n);
+        if !is_builtin_super || !call.args.is_empty() || !call.keywords.is_empty() {
+            return None;
+        }
+        self.symbol_table
+            .lookup_super_member(call.node.start, &attribute.attr)
+    }
+
+    /// Returns true if the name is not declared in the module, so it refers
+    /// to the builtin with that name
+    fn is_builtin_name(&self, name: &ast::Name) -> bool {
+        self.symbol_table
+            .lookup_in_scope(LookupSymbolRequest {
+                name: name.id.clone(),
+                position: Some(name.node.start),
+            })
+            .is_none()
+    }
+
+    /// Number of values that the value unpacks to when it is known, i.e. for
+    /// tuple and list displays and for names and calls that are declared as
+    /// a tuple of fixed length, e.g. `tuple[int, str]`
+    pub fn unpacked_length(&self, value: &Expression) -> Option<usize> {
+        if let Some(elements) = literal_elements(value) {
+            return Some(elements.len());
+        }
+        let (name, position) = match value {
+            Expression::Name(n) => (&n.id, n.node.start),
+            Expression::Call(c) => match c.func.as_ref() {
+                Expression::Name(n) => (&n.id, n.node.start),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let declaration = self
+            .symbol_table
+            .lookup_in_scope(LookupSymbolRequest {
+                name: name.clone(),
+                position: Some(position),
+            })?
+            .declaration_until_position(position)?;
+        let annotation = match (value, declaration) {
+            (Expression::Call(_), Declaration::Function(f)) => f.function_node.returns.as_deref(),
+            (Expression::Name(_), Declaration::Parameter(p)) => p.type_annotation.as_ref(),
+            (Expression::Name(_), Declaration::Variable(v)) => match &v.type_annotation {
+                Some(annotation) => Some(annotation),
+                // Names are not followed to avoid cycles, e.g. `x = x`
+                None => match &v.inferred_type_source {
+                    Some(
+                        source @ (Expression::Call(_) | Expression::Tuple(_) | Expression::List(_)),
+                    ) => {
+                        return self.unpacked_length(source);
+                    }
+                    _ => None,
+                },
+            },
+            _ => None,
+        }?;
+        let Expression::Subscript(s) = annotation else {
+            return None;
+        };
+        let arguments = type_arguments(&s.slice);
+        if !is_tuple_type(&s.value)
+            || is_unbounded_tuple(annotation)
+            || arguments.iter().any(|a| unpacked_type(a).is_some())
+        {
+            return None;
+        }
+        Some(arguments.len())
+    }
+
+    /// `getattr(obj, "name")` has the type of `obj.name`. The type of the
+    /// default is added when the call passes one.
+    fn getattr_type(&self, call: &ast::Call) -> PythonType {
+        let (object, name, default) = match call.args.as_slice() {
+            [object, name] => (object, name, None),
+            [object, name, default] => (object, name, Some(default)),
+            _ => return PythonType::Unknown,
+        };
+        let Expression::Constant(c) = name else {
+            return PythonType::Unknown;
+        };
+        let ast::ConstantValue::Str(attr) = &c.value else {
+            return PythonType::Unknown;
+        };
+        let attribute = Expression::Attribute(Box::new(ast::Attribute {
+            node: call.node,
+            value: Box::new(object.clone()),
+            attr: attr.clone(),
+        }));
+        let attribute_type = self.get_type(&attribute).unwrap_or(PythonType::Unknown);
+        let default_type = match default {
+            Some(default) => self.get_type(default).unwrap_or(PythonType::Unknown),
+            None => return attribute_type,
+        };
+        match (attribute_type, default_type) {
+            (PythonType::Unknown, _) | (_, PythonType::Unknown) => PythonType::Unknown,
+            (attribute_type, default_type) if attribute_type.type_equal(&default_type) => {
+                attribute_type
+            }
+            (attribute_type, default_type) => PythonType::union(vec![attribute_type, default_type]),
+        }
+    }
+
+    /// Returns true if `hasattr(name, attr)` holds where the name is used,
+    /// e.g. in the body of `if hasattr(obj, "x"):`
+    pub fn is_guarded_by_hasattr(&self, name: &ast::Name, attr: &str) -> bool {
+        self.symbol_table
+            .narrowings_at(name.node.start)
+            .into_iter()
+            .any(|n| is_hasattr_guard(&n.test, n.positive, &name.id, attr))
+    }
+
     pub fn get_type_from_annotation(&self, type_annotation: &ast::Expression) -> PythonType {
         log::debug!("Getting type from annotation: {:?}", type_annotation);
+        // `*args: *Ts` declares `args` as `tuple[*Ts]`
+        if let Some(value) = unpacked_type(type_annotation) {
+            let types = self.get_unpacked_types(value);
+            if types == [PythonType::Unknown] {
+                return PythonType::Unknown;
+            }
+            let builtin_type = self.get_builtin_type(builtins::TUPLE_TYPE);
+            return PythonType::Class(ClassType::new(builtin_type, types));
+        }
         let expr_type = match type_annotation {
+            // Final[T] declares a variable of type T that cannot be reassigned
+            // and ClassVar[T] a class attribute of type T
+            Expression::Subscript(s)
+                if is_type_qualifier(&s.value, "Final")
+                    || is_type_qualifier(&s.value, "ClassVar") =>
+            {
+                self.get_type_from_annotation(&s.slice)
+            }
+            // A type guard function returns a bool
+            Expression::Subscript(s)
+                if is_type_qualifier(&s.value, "TypeGuard")
+                    || is_type_qualifier(&s.value, "TypeIs") =>
+            {
+                PythonType::Bool
+            }
+            // Self is the class of the method that the annotation belongs to
+            Expression::Name(_) | Expression::Attribute(_)
+                if is_type_qualifier(type_annotation, "Self") =>
+            {
+                match self
+                    .symbol_table
+                    .enclosing_class(type_annotation.get_node().start)
+                {
+                    Some(class) => PythonType::Class(ClassType::new(class.clone(), vec![])),
+                    None => PythonType::Unknown,
+                }
+            }
             Expression::Name(name) => match name.id.as_str() {
                 "int" => PythonType::Int,
                 "float" => PythonType::Float,
+                "complex" => PythonType::Complex,
                 "str" => PythonType::Str,
                 "bool" => PythonType::Bool,
                 "None" => PythonType::None,
-                _ => PythonType::Unknown,
+                _ => {
+                    if let Some(typ) = self.bound_type_parameter(&name.id) {
+                        typ
+                    } else if let Some(class) =
+                        self.symbol_table.lookup_class(&name.id, name.node.start)
+                    {
+                        PythonType::Class(ClassType::new(class.clone(), vec![]))
+                    } else if let Some(alias) = self
+                        .symbol_table
+                        .lookup_type_alias(&name.id, name.node.start)
+                    {
+                        // Type parameters that are not given are unknown
+                        self.expand_type_alias(&alias.type_alias_node.value)
+                    } else if let Some(target) = self.type_alias_target(name) {
+                        self.expand_type_alias(target)
+                    } else {
+                        self.imported_annotation_type(name)
+                            .unwrap_or(PythonType::Unknown)
+                    }
+                }
             },
             // Illegal type annotation
             Expression::Constant(c) => {
@@ -254,7 +633,19 @@ impl TypeEvaluator {
                                 _ => panic!("Union type must have a tuple as parameter"),
                             }
                         }
-                        self.get_builtin_type(n.id.as_str())
+                        if let Some(alias) =
+                            self.symbol_table.lookup_type_alias(&n.id, n.node.start)
+                        {
+                            return self.specialize_type_alias(alias, &s.slice);
+                        }
+                        // User defined generic classes, e.g. `Array[int, str]`
+                        match self.symbol_table.lookup_class(&n.id, n.node.start) {
+                            Some(class) if !class.is_builtin() => class.clone(),
+                            _ => match self.symbol_table.builtin_class(&n.id) {
+                                Some(class) => class.clone(),
+                                None => return PythonType::Unknown,
+                            },
+                        }
                     }
                     Expression::BoolOp(_) => todo!(),
                     Expression::UnaryOp(_) => todo!(),
@@ -267,6 +658,10 @@ impl TypeEvaluator {
                     Expression::ListComp(_) => todo!(),
                     Expression::SetComp(_) => todo!(),
                     Expression::DictComp(_) => todo!(),
+                    // typing.Literal
+                    Expression::Attribute(a) if self.is_literal(a.attr.clone()) => {
+                        return self.handle_literal_type(s);
+                    }
                     Expression::Attribute(_) => todo!(),
                     Expression::Subscript(_) => todo!(),
                     Expression::Slice(_) => todo!(),
@@ -280,7 +675,7 @@ impl TypeEvaluator {
                 };
                 PythonType::Class(ClassType {
                     details: typ,
-                    type_parameters: vec![self.get_type_from_annotation(&s.slice)],
+                    type_parameters: self.get_type_arguments(&s.slice),
                 })
             }
             Expression::BinOp(b) => {
@@ -302,27 +697,540 @@ impl TypeEvaluator {
         expr_type
     }
 
-    fn get_type_from_declaration(&self, declaration: &Declaration) -> Result<PythonType> {
+    fn expand_type_alias(&self, target: &Expression) -> PythonType {
+        let start = target.get_node().start;
+        if self.expanding_aliases.borrow().contains(&start) {
+            return PythonType::Unknown;
+        }
+        self.expanding_aliases.borrow_mut().push(start);
+        let typ = self.get_type_from_annotation(target);
+        self.expanding_aliases.borrow_mut().pop();
+        typ
+    }
+
+    /// Type of a generic `type` statement with its type parameters bound to
+    /// the type arguments, e.g. `Pair[int]` for `type Pair[T] = tuple[T, T]`
+    /// is `tuple[int, int]`
+    fn specialize_type_alias(
+        &self,
+        alias: &symbol_table::TypeAlias,
+        slice: &Expression,
+    ) -> PythonType {
+        let alias_node = &alias.type_alias_node;
+        let arguments = type_arguments(slice);
+        if arguments.len() != alias_node.type_params.len() {
+            return PythonType::Unknown;
+        }
+        let names = alias_node
+            .type_params
+            .iter()
+            .map(|type_param| type_param.get_name())
+            .collect::<Vec<_>>();
+        let bindings = names
+            .iter()
+            .map(String::as_str)
+            .zip(arguments.iter())
+            .collect::<HashMap<_, _>>();
+        self.expand_type_alias(&substitute_names(&alias_node.value, &bindings))
+    }
+
+    /// The annotation that a type alias stands for, e.g. `Literal["r", "w"]`
+    /// for `Mode: TypeAlias = Literal["r", "w"]` or `Mode = Literal["r", "w"]`
+    fn type_alias_target(&self, name: &ast::Name) -> Option<&Expression> {
+        let position = name.node.start;
+        let declaration = self
+            .symbol_table
+            .lookup_in_scope(LookupSymbolRequest {
+                name: name.id.clone(),
+                position: Some(position),
+            })?
+            .declaration_until_position(position)?;
+        let Declaration::Variable(v) = declaration else {
+            return None;
+        };
+        let target = v.inferred_type_source.as_ref()?;
+        // Recursive aliases refer to themselves in their value
+        let target_node = target.get_node();
+        if target_node.start <= position && position < target_node.end {
+            return None;
+        }
+        let is_type_alias = match &v.type_annotation {
+            Some(annotation) => is_type_qualifier(annotation, "TypeAlias"),
+            None => {
+                matches!(target, Expression::Subscript(s) if is_type_qualifier(&s.value, "Literal"))
+            }
+        };
+        is_type_alias.then_some(target)
+    }
+
+    /// Types of the type arguments of a subscript annotation. Unpacked
+    /// TypeVarTuples stay unpacked and the types of unpacked tuples are
+    /// added, e.g. the arguments of `tuple[int, *tuple[str, bytes]]` are
+    /// `int, str, bytes`. The argument of `tuple[int, ...]` is `int`.
+    fn get_type_arguments(&self, slice: &Expression) -> Vec<PythonType> {
+        match type_arguments(slice) {
+            [argument, Expression::Constant(c)]
+                if matches!(c.value, ast::ConstantValue::Ellipsis) =>
+            {
+                vec![self.get_type_from_annotation(argument)]
+            }
+            arguments => arguments
+                .iter()
+                .flat_map(|argument| match unpacked_type(argument) {
+                    Some(value) => self.get_unpacked_types(value),
+                    None => vec![self.get_type_from_annotation(argument)],
+                })
+                .collect(),
+        }
+    }
+
+    /// Types that `*value` adds to type arguments
+    fn get_unpacked_types(&self, value: &Expression) -> Vec<PythonType> {
+        match value {
+            Expression::Name(n) if self.symbol_table.is_type_var_tuple(&n.id, n.node.start) => {
+                vec![PythonType::TypeVarTuple(n.id.clone())]
+            }
+            Expression::Subscript(s) if is_tuple_type(&s.value) && !is_unbounded_tuple(value) => {
+                self.get_type_arguments(&s.slice)
+            }
+            _ => vec![PythonType::Unknown],
+        }
+    }
+
+    /// Binds the TypeVarTuple of a `*args: *Ts` parameter to the types of the
+    /// extra positional arguments of the call and replaces it in the return
+    /// type, e.g. `def f(*args: *Ts) -> tuple[*Ts]` returns `tuple[int, str]`
+    /// for `f(1, "a")`
+    fn solve_type_var_tuple(&self, callable: &CallableType, call: &ast::Call) -> PythonType {
+        let parameters = &callable.arguments;
+        let type_var_tuple = parameters
+            .vararg
+            .as_ref()
+            .and_then(|vararg| unpacked_type(vararg.annotation.as_ref()?))
+            .and_then(|value| match value {
+                Expression::Name(n) if self.symbol_table.is_type_var_tuple(&n.id, n.node.start) => {
+                    Some(n.id.as_str())
+                }
+                _ => None,
+            });
+        let unpacks = call
+            .args
+            .iter()
+            .any(|arg| matches!(arg, Expression::Starred(_)));
+        let Some(type_var_tuple) = type_var_tuple.filter(|_| !unpacks) else {
+            return callable.return_type.clone();
+        };
+        let positional = parameters.posonlyargs.len() + parameters.args.len();
+        let types = call
+            .args
+            .iter()
+            .skip(positional)
+            .map(|arg| self.get_type(arg).unwrap_or(PythonType::Unknown))
+            .collect::<Vec<_>>();
+        replace_type_var_tuple(callable.return_type.clone(), type_var_tuple, &types)
+    }
+
+    /// Type of a call that constructs an instance of the class. The type
+    /// parameters of a generic class are solved from the arguments passed to
+    /// the parameters of `__init__` and `__new__` that they annotate. When
+    /// `__new__` is annotated to return another type, the call returns it.
+    fn constructor_call_type(&self, class: &symbol_table::Class, call: &ast::Call) -> PythonType {
+        let method = |name: &str| match self
+            .symbol_table
+            .lookup_class_member(class, name)
+            .and_then(|(_, symbol)| symbol.last_declaration())
+        {
+            Some(Declaration::Function(f)) => Some(f),
+            _ => None,
+        };
+        let new = method("__new__");
+        let new_returns = new
+            .and_then(|f| f.function_node.returns.as_deref())
+            .filter(|returns| !is_type_qualifier(returns, "Self"));
+        if let Some(returns) = new_returns {
+            match self.get_type_from_annotation(returns) {
+                PythonType::Unknown => {}
+                PythonType::Class(c) if c.details.name == class.name => {}
+                return_type => return return_type,
+            }
+        }
+        if class.type_parameters.is_empty() {
+            return PythonType::Class(ClassType::new(class.clone(), vec![]));
+        }
+        let mut solved = HashMap::new();
+        for f in [method("__init__"), new].into_iter().flatten() {
+            self.solve_type_parameters(class, &f.function_node.args, call, &mut solved);
+        }
+        let type_parameters = class
+            .type_parameters
+            .iter()
+            .map(|parameter| {
+                solved
+                    .remove(&parameter.name)
+                    .unwrap_or(PythonType::Unknown)
+            })
+            .collect();
+        PythonType::Class(ClassType::new(class.clone(), type_parameters))
+    }
+
+    /// Evaluates the annotation of a member of a generic class with the type
+    /// parameters of the class bound to the type arguments of the instance,
+    /// e.g. `list[T]` is `list[int]` for a member of `Box[int]`
+    fn specialized_member_type(
+        &self,
+        class_type: &ClassType,
+        annotation: &Expression,
+    ) -> PythonType {
+        if class_type.type_parameters.is_empty() {
+            return self.get_type_from_annotation(annotation);
+        }
+        let bindings = class_type
+            .details
+            .type_parameters
+            .iter()
+            .zip(class_type.type_parameters.iter())
+            .filter(|(parameter, _)| parameter.kind == TypeParameterKind::TypeVar)
+            .map(|(parameter, typ)| (parameter.name.clone(), typ.clone()))
+            .collect();
+        self.type_parameter_bindings.borrow_mut().push(bindings);
+        let typ = self.get_type_from_annotation(annotation);
+        self.type_parameter_bindings.borrow_mut().pop();
+        typ
+    }
+
+    /// Type bound to a type parameter of the class whose member annotation
+    /// is being evaluated
+    fn bound_type_parameter(&self, name: &str) -> Option<PythonType> {
+        self.type_parameter_bindings
+            .borrow()
+            .last()
+            .and_then(|bindings| bindings.get(name).cloned())
+    }
+
+    /// Binds the type parameters of the class that annotate parameters of the
+    /// constructor method to the types of the arguments passed to them. The
+    /// first argument that binds a type parameter wins.
+    fn solve_type_parameters(
+        &self,
+        class: &symbol_table::Class,
+        parameters: &ast::Arguments,
+        call: &ast::Call,
+        solved: &mut HashMap<String, PythonType>,
+    ) {
+        // The first parameter receives the instance or the class
+        let positional = parameters
+            .posonlyargs
+            .iter()
+            .chain(parameters.args.iter())
+            .skip(1)
+            .collect::<Vec<_>>();
+        let mut bind = |parameter: &ast::Arg, argument: &Expression| {
+            if let Some(annotation) = &parameter.annotation {
+                let argument_type = self.get_type(argument).unwrap_or(PythonType::Unknown);
+                self.bind_type_parameters(class, annotation, argument_type, solved);
+            }
+        };
+        for (parameter, argument) in positional.iter().zip(call.args.iter()) {
+            if matches!(argument, Expression::Starred(_)) {
+                break;
+            }
+            bind(parameter, argument);
+        }
+        for keyword in &call.keywords {
+            let parameter = positional
+                .iter()
+                .copied()
+                .chain(parameters.kwonlyargs.iter())
+                .find(|parameter| keyword.arg.as_deref() == Some(parameter.arg.as_str()));
+            if let Some(parameter) = parameter {
+                bind(parameter, &keyword.value);
+            }
+        }
+    }
+
+    /// Matches the annotation of a parameter with the type of its argument,
+    /// e.g. `list[T]` with `list[int]` binds `T` to `int`
+    fn bind_type_parameters(
+        &self,
+        class: &symbol_table::Class,
+        annotation: &Expression,
+        argument_type: PythonType,
+        solved: &mut HashMap<String, PythonType>,
+    ) {
+        match (annotation, argument_type) {
+            (_, PythonType::Unknown | PythonType::Any) => {}
+            (Expression::Name(n), argument_type) => {
+                let is_type_var = class.type_parameters.iter().any(|parameter| {
+                    parameter.name == n.id && parameter.kind == TypeParameterKind::TypeVar
+                });
+                if is_type_var {
+                    solved.entry(n.id.clone()).or_insert(argument_type);
+                }
+            }
+            (Expression::Subscript(s), PythonType::Class(c)) => {
+                for (annotation, argument_type) in
+                    type_arguments(&s.slice).iter().zip(c.type_parameters)
+                {
+                    self.bind_type_parameters(class, annotation, argument_type, solved);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Return type of a call of an overloaded function, the first overload
+    /// that accepts the arguments of the call is selected. Functions imported
+    /// with `from x import f` and builtins are looked up in the symbol table
+    /// of their module when the module is loaded. Returns None when the
+    /// function is not overloaded.
+    fn overloaded_call_type(&self, name: &ast::Name, call: &ast::Call) -> Option<PythonType> {
+        let position = name.node.start;
+        let symbol = self.symbol_table.lookup_in_scope(LookupSymbolRequest {
+            name: name.id.clone(),
+            position: Some(position),
+        });
+        let (symbol_table, symbol_name) =
+            match symbol.and_then(|symbol| symbol.declaration_until_position(position)) {
+                Some(Declaration::Function(_)) => {
+                    return self.select_overload(&symbol?.overloads(), call, self);
+                }
+                Some(Declaration::Alias(alias)) => self.imported_symbol(alias)?,
+                None if builtins::BUILTINS.contains(&name.id.as_str()) => (
+                    self.imported_symbol_tables.iter().find(|symbol_table| {
+                        symbol_table.module_name().ends_with(builtins::STUB_MODULE)
+                    })?,
+                    name.id.as_str(),
+                ),
+                _ => return None,
+            };
+        let overloads = symbol_table
+            .global_scope()
+            .symbols()
+            .get(symbol_name)?
+            .overloads();
+        if overloads.is_empty() {
+            return None;
+        }
+        self.select_overload(&overloads, call, &self.module_evaluator(symbol_table))
+    }
+
+    /// Symbol table of the module that `from x import name` imports from and
+    /// the name of the imported symbol. None when the module is not loaded.
+    fn imported_symbol<'a>(
+        &'a self,
+        alias: &'a symbol_table::Alias,
+    ) -> Option<(&'a Arc<SymbolTable>, &'a str)> {
+        let module_name = get_module_name(alias.import_result.resolved_paths.last()?);
+        let symbol_table = self
+            .imported_symbol_tables
+            .iter()
+            .find(|symbol_table| symbol_table.module_name() == module_name)?;
+        Some((symbol_table, alias.symbol_name.as_deref()?))
+    }
+
+    /// Type of a module level symbol reached through an imported module,
+    /// e.g. `a.b.c.x` after `import a.b.c` or `c.x` after `import a.b.c as c`.
+    /// `import a.b.c` binds `a` and resolves a path for every package on the
+    /// way to `a.b.c`, the attribute chain selects one of them. None when the
+    /// value is not an imported module or the module is not loaded.
+    fn module_attribute_type(&self, attribute: &ast::Attribute) -> Option<PythonType> {
+        let mut path = vec![];
+        let mut value = attribute.value.as_ref();
+        while let Expression::Attribute(a) = value {
+            path.push(a.attr.as_str());
+            value = &a.value;
+        }
+        let Expression::Name(root) = value else {
+            return None;
+        };
+        path.push(root.id.as_str());
+        path.reverse();
+
+        let position = root.node.start;
+        let symbol = self.symbol_table.lookup_in_scope(LookupSymbolRequest {
+            name: root.id.clone(),
+            position: Some(position),
+        })?;
+        let module_path = symbol
+            .declarations
+            .iter()
+            .filter(|decl| decl.declaration_path().node.start < position)
+            .filter_map(|decl| match decl {
+                Declaration::Alias(alias) => Some(alias),
+                _ => None,
+            })
+            .find_map(|alias| {
+                let import_alias = alias
+                    .import_node
+                    .as_ref()?
+                    .names
+                    .iter()
+                    .find(|name| name.node == alias.declaration_path.node)?;
+                let resolved_paths = &alias.import_result.resolved_paths;
+                if import_alias.asname.is_some() {
+                    return (path.len() == 1).then(|| resolved_paths.last())?;
+                }
+                let segments = import_alias.name.split('.').collect::<Vec<_>>();
+                if segments.starts_with(&path) {
+                    resolved_paths.get(path.len() - 1)
+                } else {
+                    None
+                }
+            })?;
+
+        let module_name = get_module_name(module_path);
+        let symbol_table = self
+            .imported_symbol_tables
+            .iter()
+            .find(|symbol_table| symbol_table.module_name() == module_name)?;
+        let symbol = symbol_table.global_scope().symbols().get(&attribute.attr)?;
+        Some(
+            self.module_evaluator(symbol_table)
+                .get_symbol_node_type(symbol, None)
+                .unwrap_or(PythonType::Unknown),
+        )
+    }
+
+    /// Evaluator for the annotations of an imported module. The current
+    /// module is left out of its imported modules so that import cycles
+    /// cannot recurse forever.
+    fn module_evaluator(&self, symbol_table: &Arc<SymbolTable>) -> TypeEvaluator {
+        TypeEvaluator {
+            symbol_table: Arc::clone(symbol_table),
+            imported_symbol_tables: self
+                .imported_symbol_tables
+                .iter()
+                .filter(|imported| imported.module_name() != self.symbol_table.module_name())
+                .cloned()
+                .collect(),
+            join_container_types: self.join_container_types,
+            carried_declarations: RefCell::default(),
+            expanding_aliases: RefCell::default(),
+            evaluating_method_calls: RefCell::default(),
+            type_parameter_bindings: RefCell::default(),
+        }
+    }
+
+    /// Type of an annotation that names a symbol imported with
+    /// `from x import name`, evaluated in the module that declares the symbol
+    fn imported_annotation_type(&self, name: &ast::Name) -> Option<PythonType> {
+        let position = name.node.start;
+        let declaration = self
+            .symbol_table
+            .lookup_in_scope(LookupSymbolRequest {
+                name: name.id.clone(),
+                position: Some(position),
+            })?
+            .declaration_until_position(position)?;
+        let Declaration::Alias(alias) = declaration else {
+            return None;
+        };
+        let (symbol_table, symbol_name) = self.imported_symbol(alias)?;
+        // A name at the end of the module sees every module level declaration
+        let annotation = Expression::Name(Box::new(ast::Name {
+            node: ast::Node {
+                start: TextSize::MAX,
+                end: TextSize::MAX,
+            },
+            id: symbol_name.to_string(),
+        }));
+        Some(
+            self.module_evaluator(symbol_table)
+                .get_type_from_annotation(&annotation),
+        )
+    }
+
+    /// Return type of the first overload that accepts the arguments of the
+    /// call. `module_evaluator` evaluates the annotations of the overloads.
+    fn select_overload(
+        &self,
+        overloads: &[&Function],
+        call: &ast::Call,
+        module_evaluator: &TypeEvaluator,
+    ) -> Option<PythonType> {
+        if overloads.is_empty() {
+            return None;
+        }
+        // Arguments that are unpacked cannot be matched with the parameters
+        let unpacks = call
+            .args
+            .iter()
+            .any(|arg| matches!(arg, Expression::Starred(_)))
+            || call.keywords.iter().any(|keyword| keyword.arg.is_none());
+        if unpacks {
+            return Some(PythonType::Unknown);
+        }
+        let mut matching = overloads.iter().filter(|f| {
+            bind_arguments(call, &f.function_node.args, false).is_some_and(|bound| {
+                bound.iter().all(|(parameter, argument)| {
+                    parameter.annotation.as_ref().is_none_or(|annotation| {
+                        let parameter_type = module_evaluator.get_type_from_annotation(annotation);
+                        self.argument_matches(argument, &parameter_type)
+                    })
+                })
+            })
+        });
+        let return_type = |f: &Function| match &f.function_node.returns {
+            Some(returns) => module_evaluator.get_type_from_annotation(returns),
+            None => PythonType::Unknown,
+        };
+        let Some(selected) = matching.next() else {
+            return Some(PythonType::Unknown);
+        };
+        let selected_type = return_type(selected);
+        // Arguments of unknown types match every overload, the call is
+        // ambiguous when the other overloads that match return other types
+        let has_unknown_argument = call
+            .args
+            .iter()
+            .chain(call.keywords.iter().map(|keyword| keyword.value.as_ref()))
+            .any(|argument| {
+                matches!(
+                    self.get_type(argument),
+                    Ok(PythonType::Unknown | PythonType::Any) | Err(_)
+                )
+            });
+        if has_unknown_argument && matching.any(|f| return_type(f) != selected_type) {
+            return Some(PythonType::Unknown);
+        }
+        Some(selected_type)
+    }
+
+    /// Returns true if the argument can be passed to a parameter of the type.
+    /// Literal arguments are matched by their value, e.g. `"rb"` can be
+    /// passed to `Literal["rb", "wb"]` and `str` but not `Literal["r"]`.
+    fn argument_matches(&self, argument: &Expression, parameter_type: &PythonType) -> bool {
+        let argument_type = match argument {
+            Expression::Constant(c) => match constant_literal_value(c) {
+                Some(literal_value) => PythonType::KnownValue(KnownValue { literal_value }),
+                None => self.get_type(argument).unwrap_or(PythonType::Unknown),
+            },
+            _ => self.get_type(argument).unwrap_or(PythonType::Unknown),
+        };
+        self.is_assignable(parameter_type, &argument_type)
+    }
+
+    pub fn get_type_from_declaration(&self, declaration: &Declaration) -> Result<PythonType> {
         match declaration {
             Declaration::Variable(v) => {
-                if let Some(type_annotation) = &v.type_annotation {
+                // A bare `Final` or `ClassVar` takes the type of the assigned value
+                let type_annotation = v.type_annotation.as_ref().filter(|annotation| {
+                    !matches!(annotation, Expression::Name(_) | Expression::Attribute(_))
+                        || !(is_type_qualifier(annotation, "Final")
+                            || is_type_qualifier(annotation, "ClassVar"))
+                });
+                if let Some(type_annotation) = type_annotation {
                     Ok(self.get_type_from_annotation(type_annotation))
                 } else if let Some(source) = &v.inferred_type_source {
-                    self.get_type(source)
+                    // Errors in the value are reported at the declaration, the
+                    // uses of the name are Unknown so they do not repeat them
+                    Ok(self.get_type(source).unwrap_or(PythonType::Unknown))
                 } else {
                     Ok(PythonType::Unknown)
                 }
             }
             Declaration::Function(f) => {
-                let annotated_return_type =
-                    if let Some(type_annotation) = f.function_node.returns.clone() {
-                        self.get_type_from_annotation(&type_annotation)
-                    } else {
-                        let inferred_return_type = self.infer_function_return_type(f);
-                        log::debug!("infered_return_type: {:?}", inferred_return_type);
-                        inferred_return_type
-                    };
-
+                let annotated_return_type = self.function_return_type(f);
                 let arguments = f.function_node.args.clone();
                 let name = f.function_node.name.clone();
 
@@ -339,19 +1247,436 @@ impl TypeEvaluator {
                 } else if let Some(default) = &p.default_value {
                     self.get_type(default)
                 } else {
-                    Ok(PythonType::Any)
+                    // The instance parameter of a method, e.g. `self`
+                    let method = self
+                        .symbol_table
+                        .enclosing_method(p.declaration_path.node.start);
+                    match method {
+                        Some((class, f))
+                            if f.instance_parameter() == Some(&p.parameter_node.arg) =>
+                        {
+                            Ok(PythonType::Class(ClassType::new(class.clone(), vec![])))
+                        }
+                        _ => Ok(PythonType::Any),
+                    }
                 }
             }
-            Declaration::Alias(_) => Ok(PythonType::Unknown),
+            Declaration::Alias(_) => Ok(PythonType::Any),
             Declaration::TypeParameter(_) => Ok(PythonType::Unknown),
             Declaration::TypeAlias(_) => Ok(PythonType::Unknown),
         }
     }
 
+    /// The return type of the function from its annotation, or inferred from
+    /// its body when it is not annotated
+    fn function_return_type(&self, f: &symbol_table::Function) -> PythonType {
+        if let Some(type_annotation) = &f.function_node.returns {
+            self.get_type_from_annotation(type_annotation)
+        } else {
+            let inferred_return_type = self.infer_function_return_type(f);
+            log::debug!("infered_return_type: {:?}", inferred_return_type);
+            inferred_return_type
+        }
+    }
+
+    /// The type of the values that can be assigned to a property, from the
+    /// annotation of the value parameter of its setter
+    pub fn property_setter_type(&self, setter: &symbol_table::Function) -> PythonType {
+        let args = &setter.function_node.args;
+        match args.posonlyargs.iter().chain(args.args.iter()).nth(1) {
+            Some(parameter) => parameter
+                .annotation
+                .as_ref()
+                .map_or(PythonType::Unknown, |annotation| {
+                    self.get_type_from_annotation(annotation)
+                }),
+            None => PythonType::Unknown,
+        }
+    }
+
+    /// Returns true if a value of the source type can be assigned to a target
+    /// of the target type, every member of a union must be assignable
+    pub fn is_assignable(&self, target: &PythonType, source: &PythonType) -> bool {
+        let target_types = target.clone().union_members();
+        source.clone().union_members().iter().all(|source| {
+            target_types
+                .iter()
+                .any(|target| accepts_type(target, source))
+        })
+    }
+
+    /// Type of calling a method of an instance or a class, e.g. `obj.method()`.
+    /// Methods that return `Self` or `self` return the type of the object
+    /// they are called on, a call on a subclass returns the subclass.
+    fn method_call_type(&self, attribute: &ast::Attribute) -> Result<PythonType> {
+        let start = attribute.node.start;
+        if self.evaluating_method_calls.borrow().contains(&start) {
+            return Ok(PythonType::Unknown);
+        }
+        self.evaluating_method_calls.borrow_mut().push(start);
+        let receiver = match attribute.value.as_ref() {
+            Expression::Name(n) => match self.symbol_table.lookup_class(&n.id, n.node.start) {
+                Some(class) => Ok(PythonType::Class(ClassType::new(class.clone(), vec![]))),
+                None => self.get_type(&attribute.value),
+            },
+            value => self.get_type(value),
+        };
+        self.evaluating_method_calls.borrow_mut().pop();
+        let receiver = receiver?;
+        if let Some(return_type) = self.str_method_type(&receiver, &attribute.attr) {
+            return Ok(return_type);
+        }
+        let PythonType::Class(class_type) = &receiver else {
+            return Ok(PythonType::Unknown);
+        };
+        let method = self
+            .symbol_table
+            .lookup_class_member(&class_type.details, &attribute.attr)
+            .and_then(|(_, symbol)| symbol.last_declaration());
+        let Some(declaration @ Declaration::Function(f)) = method else {
+            return Ok(PythonType::Unknown);
+        };
+        if f.returns_self() {
+            return Ok(receiver);
+        }
+        if let Some(returns) = &f.function_node.returns {
+            return Ok(self.specialized_member_type(class_type, returns));
+        }
+        match self.get_type_from_declaration(declaration)? {
+            PythonType::Callable(callable_type) => Ok(callable_type.return_type),
+            _ => Ok(PythonType::Unknown),
+        }
+    }
+
+    /// Return type of a method of `str` or `bytes`, e.g. `list[str]` for
+    /// `"a,b".split(",")`. Returns None for other receivers and for methods
+    /// that are not modeled.
+    fn str_method_type(&self, receiver: &PythonType, method: &str) -> Option<PythonType> {
+        let is_bytes = match receiver {
+            PythonType::Str => false,
+            PythonType::KnownValue(v) => match v.literal_value {
+                LiteralValue::Str(_) => false,
+                LiteralValue::Bytes(_) => true,
+                _ => return None,
+            },
+            typ if is_bytes(typ) => true,
+            _ => return None,
+        };
+        // Methods that return a new string of the same kind as the receiver
+        let same = || {
+            if is_bytes {
+                self.bytes_type()
+            } else {
+                PythonType::Str
+            }
+        };
+        let builtin = |name: &str, type_parameters: Vec<PythonType>| {
+            PythonType::Class(ClassType::new(self.get_builtin_type(name), type_parameters))
+        };
+        let typ = match method {
+            "capitalize" | "center" | "expandtabs" | "join" | "ljust" | "lower" | "lstrip"
+            | "removeprefix" | "removesuffix" | "replace" | "rjust" | "rstrip" | "strip"
+            | "swapcase" | "title" | "translate" | "upper" | "zfill" => same(),
+            "casefold" | "format" | "format_map" if !is_bytes => PythonType::Str,
+            "split" | "rsplit" | "splitlines" => builtin(builtins::LIST_TYPE, vec![same()]),
+            "partition" | "rpartition" => {
+                builtin(builtins::TUPLE_TYPE, vec![same(), same(), same()])
+            }
+            "count" | "find" | "index" | "rfind" | "rindex" => PythonType::Int,
+            "endswith" | "isalnum" | "isalpha" | "isascii" | "isdigit" | "islower" | "isspace"
+            | "istitle" | "isupper" | "startswith" => PythonType::Bool,
+            "isdecimal" | "isidentifier" | "isnumeric" | "isprintable" if !is_bytes => {
+                PythonType::Bool
+            }
+            "encode" if !is_bytes => self.bytes_type(),
+            "decode" | "hex" if is_bytes => PythonType::Str,
+            _ => return None,
+        };
+        Some(typ)
+    }
+
+    /// `divmod(a, b)` is the tuple `(a // b, a % b)` of two numbers of the
+    /// same type
+    fn divmod_type(&self, call: &ast::Call) -> Result<PythonType> {
+        let [a, b] = call.args.as_slice() else {
+            return Ok(PythonType::Unknown);
+        };
+        let quotient = self.bin_op_result_type(
+            &self.get_type(a)?,
+            &self.get_type(b)?,
+            &ast::BinaryOperator::FloorDiv,
+        );
+        if matches!(quotient, PythonType::Unknown) {
+            return Ok(PythonType::Unknown);
+        }
+        Ok(PythonType::Class(ClassType::new(
+            self.get_builtin_type(builtins::TUPLE_TYPE),
+            vec![quotient.clone(), quotient],
+        )))
+    }
+
+    fn bytes_type(&self) -> PythonType {
+        PythonType::Class(ClassType::new(
+            self.get_builtin_type(builtins::BYTES_TYPE),
+            vec![],
+        ))
+    }
+
+    /// Narrows the type of a name with the conditions that hold where the
+    /// name is used. Conditions of blocks that the name is assigned in
+    /// before the use are ignored.
+    fn narrow_name_type(&self, name: &ast::Name, typ: PythonType) -> PythonType {
+        let position = name.node.start;
+        let declaration_start = self
+            .symbol_table
+            .lookup_in_scope(LookupSymbolRequest {
+                name: name.id.clone(),
+                position: Some(position),
+            })
+            .and_then(|symbol| symbol.declaration_until_position(position))
+            .map(|decl| decl.declaration_path().node.start);
+        self.symbol_table
+            .narrowings_at(position)
+            .into_iter()
+            .filter(|n| declaration_start.is_none_or(|start| start < n.node.start))
+            .fold(typ, |typ, n| {
+                self.narrow_type(&name.id, &n.test, n.positive, typ)
+            })
+    }
+
+    /// Narrows the type of the name assuming the test is true, or false when
+    /// `positive` is false
+    fn narrow_type(
+        &self,
+        name: &str,
+        test: &Expression,
+        positive: bool,
+        typ: PythonType,
+    ) -> PythonType {
+        match test {
+            Expression::UnaryOp(u) if matches!(u.op, ast::UnaryOperator::Not) => {
+                self.narrow_type(name, &u.operand, !positive, typ)
+            }
+            // Every operand of `a and b` holds in the body and the negation
+            // of every operand of `a or b` holds in the else branch
+            Expression::BoolOp(b)
+                if matches!(
+                    (&b.op, positive),
+                    (ast::BooleanOperator::And, true) | (ast::BooleanOperator::Or, false)
+                ) =>
+            {
+                b.values.iter().fold(typ, |typ, value| {
+                    self.narrow_type(name, value, positive, typ)
+                })
+            }
+            Expression::Compare(c) => self.narrow_type_by_comparison(name, c, positive, typ),
+            Expression::Call(c) if is_type_qualifier(&c.func, "isinstance") => {
+                self.narrow_type_by_isinstance(name, c, positive, typ)
+            }
+            Expression::Call(c) => self.narrow_type_by_type_guard(name, c, positive, typ),
+            // `if x:` removes the members that are always false, e.g. None
+            test if narrowed_name(test).is_some_and(|n| n.id == name) => {
+                let members = typ
+                    .union_members()
+                    .into_iter()
+                    .filter(|member| self.truthiness(member) != Some(!positive))
+                    .collect();
+                PythonType::union(members)
+            }
+            _ => typ,
+        }
+    }
+
+    /// Whether values of the type are always true or always false in a
+    /// boolean context. Returns None when it depends on the value, e.g. for
+    /// numbers, strings and containers that can be empty.
+    pub fn truthiness(&self, typ: &PythonType) -> Option<bool> {
+        match typ {
+            PythonType::None => Some(false),
+            PythonType::KnownValue(v) => match &v.literal_value {
+                LiteralValue::Bool(b) => Some(*b),
+                LiteralValue::Int(i) => i.parse::<i64>().ok().map(|i| i != 0),
+                LiteralValue::Float(_) => None,
+                LiteralValue::Str(s) => Some(!s.is_empty()),
+                LiteralValue::Bytes(b) => Some(!b.is_empty()),
+                LiteralValue::None => Some(false),
+            },
+            PythonType::Callable(_) => Some(true),
+            // Instances are true unless a class of the MRO defines
+            // `__bool__` or `__len__`. The MRO of builtin classes other than
+            // `object` is not known.
+            PythonType::Class(c) => {
+                // Bases of classes of other modules are looked up in their module
+                if c.details.declaration_path.module_name != self.symbol_table.module_name() {
+                    return None;
+                }
+                let mro = self.symbol_table.mro(&c.details)?;
+                let defines_truth = mro.iter().any(|class| {
+                    ["__bool__", "__len__"]
+                        .iter()
+                        .any(|member| self.symbol_table.class_member(class, member).is_some())
+                });
+                (!defines_truth).then_some(true)
+            }
+            _ => None,
+        }
+    }
+
+    /// Narrows the type by `isinstance(name, C)` to the members of the union
+    /// that are instances of `C`, or to the members that are not when the
+    /// test is false
+    fn narrow_type_by_isinstance(
+        &self,
+        name: &str,
+        call: &ast::Call,
+        positive: bool,
+        typ: PythonType,
+    ) -> PythonType {
+        let [argument, class] = call.args.as_slice() else {
+            return typ;
+        };
+        if narrowed_name(argument).is_none_or(|argument| argument.id != name) {
+            return typ;
+        }
+        // `isinstance(x, (A, B))` is true for instances of any of the classes
+        let class_type = match class {
+            Expression::Tuple(t) => {
+                let types = t
+                    .elements
+                    .iter()
+                    .map(|element| self.get_type_from_annotation(element))
+                    .collect::<Vec<_>>();
+                if types.iter().any(|t| matches!(t, PythonType::Unknown)) {
+                    return typ;
+                }
+                PythonType::union(types)
+            }
+            class => self.get_type_from_annotation(class),
+        };
+        if matches!(class_type, PythonType::Unknown) {
+            return typ;
+        }
+        narrow_to_type(typ, class_type, positive)
+    }
+
+    /// Narrows the first argument of a call of a user-defined type guard.
+    /// `TypeGuard[T]` narrows the argument to `T` when the call returns true,
+    /// `TypeIs[T]` also removes `T` from the argument type when it returns
+    /// false.
+    fn narrow_type_by_type_guard(
+        &self,
+        name: &str,
+        call: &ast::Call,
+        positive: bool,
+        typ: PythonType,
+    ) -> PythonType {
+        match call.args.first().and_then(narrowed_name) {
+            Some(argument) if argument.id == name => {}
+            _ => return typ,
+        }
+        let Expression::Name(function) = call.func.as_ref() else {
+            return typ;
+        };
+        let Some((guard, guarded_type)) = self.type_guard(function) else {
+            return typ;
+        };
+        if is_type_qualifier(&guard.value, "TypeIs") {
+            narrow_to_type(typ, guarded_type, positive)
+        } else if positive {
+            guarded_type
+        } else {
+            typ
+        }
+    }
+
+    /// The return annotation of the type guard function that the name refers
+    /// to and the narrowed type. Type guards imported with `from x import f`
+    /// are evaluated in their module when the module is loaded.
+    fn type_guard(&self, name: &ast::Name) -> Option<(ast::Subscript, PythonType)> {
+        let position = name.node.start;
+        let declaration = self
+            .symbol_table
+            .lookup_in_scope(LookupSymbolRequest {
+                name: name.id.clone(),
+                position: Some(position),
+            })?
+            .declaration_until_position(position)?;
+        match declaration {
+            Declaration::Function(f) => {
+                let guard = f.type_guard()?;
+                Some((guard.clone(), self.get_type_from_annotation(&guard.slice)))
+            }
+            Declaration::Alias(alias) => {
+                let (symbol_table, symbol_name) = self.imported_symbol(alias)?;
+                let Some(Declaration::Function(f)) = symbol_table
+                    .global_scope()
+                    .symbols()
+                    .get(symbol_name)?
+                    .last_declaration()
+                else {
+                    return None;
+                };
+                let guard = f.type_guard()?;
+                let guarded_type = self
+                    .module_evaluator(symbol_table)
+                    .get_type_from_annotation(&guard.slice);
+                Some((guard.clone(), guarded_type))
+            }
+            _ => None,
+        }
+    }
+
+    /// Narrows literal types by `==` and `!=` comparisons with a literal, e.g.
+    /// `mode == "r"` narrows `Literal["r", "w"]` to `Literal["r"]`, and
+    /// optional types by `is None` and `is not None`
+    fn narrow_type_by_comparison(
+        &self,
+        name: &str,
+        compare: &ast::Compare,
+        positive: bool,
+        typ: PythonType,
+    ) -> PythonType {
+        let ([op], [comparator]) = (compare.ops.as_slice(), compare.comparators.as_slice()) else {
+            return typ;
+        };
+        let constant = match (compare.left.as_ref(), comparator) {
+            (operand, Expression::Constant(c)) | (Expression::Constant(c), operand)
+                if narrowed_name(operand).is_some_and(|n| n.id == name) =>
+            {
+                c
+            }
+            _ => return typ,
+        };
+        let Some(literal) = constant_literal_value(constant) else {
+            return typ;
+        };
+        let (is_equal, is_identity) = match op {
+            ast::ComparisonOperator::Eq => (positive, false),
+            ast::ComparisonOperator::NotEq => (!positive, false),
+            ast::ComparisonOperator::Is => (positive, true),
+            ast::ComparisonOperator::IsNot => (!positive, true),
+            _ => return typ,
+        };
+        let is_none = literal == LiteralValue::None;
+        let members = typ
+            .union_members()
+            .into_iter()
+            .filter(|member| match member {
+                PythonType::KnownValue(v) => (v.literal_value == literal) == is_equal,
+                PythonType::None => is_none == is_equal,
+                PythonType::Unknown | PythonType::Any => true,
+                // Only None is identical to None
+                _ if is_identity && is_none => !is_equal,
+                _ => true,
+            })
+            .collect();
+        PythonType::union(members)
+    }
+
     fn infer_type_from_symbol_table(
         &self,
         name: &str,
-        position: Option<usize>,
+        position: Option<TextSize>,
     ) -> Result<PythonType> {
         let lookup_request = LookupSymbolRequest {
             name: name.to_string(),
@@ -365,42 +1690,72 @@ impl TypeEvaluator {
         result
     }
 
+    /// Element type of a container display. Elements of different types are
+    /// unified into their union, or joined into their common base class with
+    /// the `join_container_types` setting.
     fn get_sequence_type_from_elements(&self, elements: &Vec<ast::Expression>) -> PythonType {
-        let mut prev_elm_type = PythonType::Unknown;
+        let mut types: Vec<PythonType> = vec![];
         for elm in elements {
             let elm_type = self.get_type(elm).unwrap_or(PythonType::Unknown);
-            if prev_elm_type == PythonType::Unknown {
-                prev_elm_type = elm_type;
-            } else if prev_elm_type != elm_type {
-                prev_elm_type = PythonType::Unknown;
-                break;
+            for member in elm_type.union_members() {
+                if matches!(member, PythonType::Unknown | PythonType::Any) {
+                    return PythonType::Unknown;
+                }
+                if !types.iter().any(|typ| typ.type_equal(&member)) {
+                    types.push(member);
+                }
+            }
+        }
+        if types.is_empty() {
+            PythonType::Unknown
+        } else if self.join_container_types {
+            self.join_types(types)
+        } else {
+            PythonType::union(types)
+        }
+    }
+
+    /// The narrowest type that all the types are assignable to. Numbers join
+    /// along the numeric tower, classes into their first common base in the MRO and any
+    /// other types into `object`.
+    fn join_types(&self, mut types: Vec<PythonType>) -> PythonType {
+        if types.len() == 1 {
+            return types.remove(0);
+        }
+        if let Some(ranks) = types.iter().map(numeric_rank).collect::<Option<Vec<_>>>() {
+            if let Some(rank) = ranks.into_iter().max() {
+                return rank.python_type();
             }
         }
-        prev_elm_type
+        let classes = types
+            .iter()
+            .map(|typ| match typ {
+                PythonType::Class(c) => Some(&c.details),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        let base = classes
+            .and_then(|classes| self.symbol_table.common_base(&classes))
+            .or_else(|| self.symbol_table.builtin_class(builtins::OBJECT_TYPE));
+        match base {
+            Some(base) => PythonType::Class(ClassType::new(base.clone(), vec![])),
+            None => PythonType::Unknown,
+        }
     }
 
     fn infer_function_return_type(&self, f: &crate::symbol_table::Function) -> PythonType {
+        // Calling a generator function creates a generator whatever the body
+        // of the function does
+        if f.is_generator {
+            return self.infer_generator_type(f);
+        }
         if !f.is_abstract() && !f.raise_statements.is_empty() {
             return PythonType::Never;
         }
-        if !f.yeild_statements.is_empty() {
-            let mut yield_types = vec![];
-            for yield_statement in &f.yeild_statements {
-                if let Some(value) = &yield_statement.value {
-                    yield_types.push(self.get_type(value).unwrap_or(PythonType::Unknown));
-                }
-            }
-            if yield_types.len() == 1 {
-                todo!()
-                // return PythonType::Class(super::types::ClassType {
-                //     name: builtins::ITER_TYPE.to_string(),
-                //     args: vec![yield_types[0].clone()],
-                // });
-            } else {
-                // TODO: Union type
-                return PythonType::Unknown;
-            }
-        }
+        self.infer_return_statements_type(f)
+    }
+
+    fn infer_return_statements_type(&self, f: &crate::symbol_table::Function) -> PythonType {
         if f.return_statements.is_empty() {
             PythonType::None
         } else {
@@ -419,6 +1774,94 @@ impl TypeEvaluator {
         }
     }
 
+    /// Infers `Generator[Y, S, R]` for a generator function without a return
+    /// annotation. `Y` is the union of the yielded types, the send type is
+    /// not known and `R` is inferred from the return statements.
+    fn infer_generator_type(&self, f: &crate::symbol_table::Function) -> PythonType {
+        let yielded = f.yield_statements.iter().map(|y| {
+            let typ = match &y.value {
+                Some(value) => self.get_type(value).unwrap_or(PythonType::Unknown),
+                None => PythonType::None,
+            };
+            (y.node.start, typ)
+        });
+        // `yield from g` yields the values that g yields
+        let delegated = f.yield_from_statements.iter().map(|y| {
+            let typ = match self.get_type(&y.value).unwrap_or(PythonType::Unknown) {
+                PythonType::Class(c)
+                    if c.details.name == builtins::GENERATOR_TYPE
+                        && c.type_parameters.len() == 3 =>
+                {
+                    c.type_parameters[0].clone()
+                }
+                _ => PythonType::Unknown,
+            };
+            (y.node.start, typ)
+        });
+        let mut yields = yielded.chain(delegated).collect::<Vec<_>>();
+        yields.sort_by_key(|(start, _)| *start);
+        let mut yield_types: Vec<PythonType> = vec![];
+        for typ in yields.into_iter().flat_map(|(_, typ)| typ.union_members()) {
+            if !yield_types.iter().any(|t| t.type_equal(&typ)) {
+                yield_types.push(typ);
+            }
+        }
+        let yield_type = if yield_types.iter().any(|t| matches!(t, PythonType::Unknown)) {
+            PythonType::Unknown
+        } else {
+            PythonType::union(yield_types)
+        };
+        let generator = symbol_table::Class::builtin(builtins::GENERATOR_TYPE, &[], &[]);
+        PythonType::Class(ClassType::new(
+            generator,
+            vec![
+                yield_type,
+                PythonType::Any,
+                self.infer_return_statements_type(f),
+            ],
+        ))
+    }
+
+    /// The type of the values sent to the generator function that contains
+    /// the position, from its return annotation, e.g. `S` for
+    /// `Generator[Y, S, R]`. Iterators cannot be sent values other than None.
+    fn generator_send_type(&self, position: TextSize) -> PythonType {
+        let Some(returns) = self
+            .symbol_table
+            .enclosing_function(position)
+            .and_then(|function| function.function_node.returns.as_deref())
+        else {
+            return PythonType::Unknown;
+        };
+        let arguments = match returns {
+            Expression::Subscript(s) => type_arguments(&s.slice),
+            _ => &[],
+        };
+        if is_type_qualifier(returns, "Generator") {
+            match arguments {
+                [_, send, _] => self.get_type_from_annotation(send),
+                _ => PythonType::Unknown,
+            }
+        } else if is_type_qualifier(returns, "AsyncGenerator") {
+            match arguments {
+                [_, send] => self.get_type_from_annotation(send),
+                _ => PythonType::Unknown,
+            }
+        } else if [
+            builtins::ITER_TYPE,
+            "Iterable",
+            "AsyncIterator",
+            "AsyncIterable",
+        ]
+        .iter()
+        .any(|iterator| is_type_qualifier(returns, iterator))
+        {
+            PythonType::None
+        } else {
+            PythonType::Unknown
+        }
+    }
+
     /// Retrieves a pythoh type that is present in the builtin scope
     fn get_builtin_type(&self, name: &str) -> symbol_table::Class {
         let builtin_symbol = self.symbol_table.lookup_in_builtin_scope(name);
@@ -509,14 +1952,15 @@ impl TypeEvaluator {
     fn handle_literal_type(&self, s: &ast::Subscript) -> PythonType {
         // Only simple parameters are allowed for literal type:
         // https://peps.python.org/pep-0586/#legal-and-illegal-parameterizations
-        let value = self.get_literal_value_from_param(&s.slice.clone());
-        if value.len() > 1 {
-            todo!("MultiValue literal type is not supported yet")
-        }
-
-        PythonType::KnownValue(super::types::KnownValue {
-            literal_value: value.last().unwrap().clone(),
-        })
+        let values = self.get_literal_value_from_param(&s.slice.clone());
+        PythonType::union(
+            values
+                .into_iter()
+                .map(|literal_value| {
+                    PythonType::KnownValue(super::types::KnownValue { literal_value })
+                })
+                .collect(),
+        )
     }
 
     /// Write a function that takes in an expression which is a parameter to a
@@ -573,6 +2017,14 @@ impl TypeEvaluator {
                     }
                 }
             }
+            // Literal["a", "b"] is the union of the values
+            Expression::Tuple(t) => {
+                return t
+                    .elements
+                    .iter()
+                    .flat_map(|elm| self.get_literal_value_from_param(elm))
+                    .collect();
+            }
             // Only can be enum values
             Expression::Attribute(a) => {
                 let value = match *a.value.clone() {
@@ -612,58 +2064,7 @@ impl TypeEvaluator {
         t2: &PythonType,
         op: &ast::BinaryOperator,
     ) -> bool {
-        let check_table = match op {
-            ast::BinaryOperator::Add => vec![
-                (PythonType::Int, PythonType::Int),
-                (PythonType::Float, PythonType::Float),
-            ],
-            ast::BinaryOperator::Sub => vec![
-                (PythonType::Int, PythonType::Int),
-                (PythonType::Float, PythonType::Float),
-            ],
-            ast::BinaryOperator::Mult => vec![
-                (PythonType::Int, PythonType::Int),
-                (PythonType::Float, PythonType::Float),
-                (PythonType::Str, PythonType::Int),
-                (PythonType::Int, PythonType::Str),
-            ],
-            ast::BinaryOperator::Div => vec![
-                (PythonType::Int, PythonType::Int),
-                (PythonType::Float, PythonType::Float),
-            ],
-            ast::BinaryOperator::Mod => vec![
-                (PythonType::Int, PythonType::Int),
-                (PythonType::Float, PythonType::Float),
-            ],
-            ast::BinaryOperator::Pow => vec![
-                (PythonType::Int, PythonType::Int),
-                (PythonType::Float, PythonType::Float),
-            ],
-            ast::BinaryOperator::LShift => vec![(PythonType::Int, PythonType::Int)],
-            ast::BinaryOperator::RShift => vec![(PythonType::Int, PythonType::Int)],
-            ast::BinaryOperator::BitOr => vec![(PythonType::Int, PythonType::Int)],
-            ast::BinaryOperator::BitAnd => vec![(PythonType::Int, PythonType::Int)],
-            ast::BinaryOperator::BitXor => vec![(PythonType::Int, PythonType::Int)],
-            ast::BinaryOperator::FloorDiv => vec![
-                (PythonType::Int, PythonType::Int),
-                (PythonType::Float, PythonType::Float),
-            ],
-            ast::BinaryOperator::MatMult => vec![
-                (PythonType::Int, PythonType::Int),
-                (PythonType::Float, PythonType::Float),
-            ],
-        };
-
-        for (t1_, t2_) in check_table {
-            if matches!(t1, PythonType::Unknown) || matches!(t2, PythonType::Unknown) {
-                return true;
-            }
-            if self.type_equal(t1, &t1_) && self.type_equal(t2, &t2_) {
-                return true;
-            }
-        }
-
-        false
+        self.binary_operation_type(t1, t2, op).is_some()
     }
 
     pub fn bin_op_result_type(
@@ -672,43 +2073,81 @@ impl TypeEvaluator {
         t2: &PythonType,
         op: &ast::BinaryOperator,
     ) -> PythonType {
-        if !self.type_check_bin_op(t1, t2, op) {
-            return PythonType::Unknown;
+        self.binary_operation_type(t1, t2, op)
+            .unwrap_or(PythonType::Unknown)
+    }
+
+    /// The type of the result of the operator or None if the operator is not
+    /// supported for the operand types. Numbers follow the numeric tower,
+    /// `bool` is promoted to `int`, `int` to `float` and `float` to
+    /// `complex`.
+    fn binary_operation_type(
+        &self,
+        t1: &PythonType,
+        t2: &PythonType,
+        op: &ast::BinaryOperator,
+    ) -> Option<PythonType> {
+        use ast::BinaryOperator;
+
+        if matches!(t1, PythonType::Unknown | PythonType::Any)
+            || matches!(t2, PythonType::Unknown | PythonType::Any)
+        {
+            return Some(PythonType::Unknown);
+        }
+        if let PythonType::KnownValue(value) = t1 {
+            return self.binary_operation_type(&literal_base_type(&value.literal_value), t2, op);
+        }
+        if let PythonType::KnownValue(value) = t2 {
+            return self.binary_operation_type(t1, &literal_base_type(&value.literal_value), op);
         }
 
-        match op {
-            ast::BinaryOperator::Add
-            | ast::BinaryOperator::Sub
-            | ast::BinaryOperator::Mult
-            | ast::BinaryOperator::MatMult
-            | ast::BinaryOperator::Div
-            | ast::BinaryOperator::Mod
-            | ast::BinaryOperator::Pow
-            | ast::BinaryOperator::LShift
-            | ast::BinaryOperator::RShift
-            | ast::BinaryOperator::BitOr
-            | ast::BinaryOperator::BitXor
-            | ast::BinaryOperator::BitAnd
-            | ast::BinaryOperator::FloorDiv => {
-                if self.type_equal(t1, &PythonType::Float)
-                    || self.type_equal(t2, &PythonType::Float)
-                {
-                    return PythonType::Float;
-                }
-                if self.type_equal(t1, &PythonType::Int) || self.type_equal(t2, &PythonType::Int) {
-                    return PythonType::Int;
-                }
-                match t1 {
-                    PythonType::Str => PythonType::Str,
-                    PythonType::None => PythonType::None,
-                    PythonType::Unknown => PythonType::Unknown,
-                    PythonType::Bool => PythonType::Bool,
-                    PythonType::Int => PythonType::Int,
-                    PythonType::Float => PythonType::Float,
-                    _ => PythonType::Unknown,
+        // `"%s" % value` formats any value
+        if matches!((t1, op), (PythonType::Str, BinaryOperator::Mod)) {
+            return Some(PythonType::Str);
+        }
+        if is_bytes(t1) || is_bytes(t2) {
+            let supported = match op {
+                BinaryOperator::Add => is_bytes(t1) && is_bytes(t2),
+                BinaryOperator::Mult => {
+                    matches!(t1, PythonType::Int) || matches!(t2, PythonType::Int)
                 }
+                BinaryOperator::Mod => is_bytes(t1),
+                _ => false,
+            };
+            return supported.then(|| self.bytes_type());
+        }
+        match (t1, t2, op) {
+            (PythonType::Str, PythonType::Str, BinaryOperator::Add)
+            | (PythonType::Str, PythonType::Int, BinaryOperator::Mult)
+            | (PythonType::Int, PythonType::Str, BinaryOperator::Mult) => {
+                return Some(PythonType::Str);
             }
+            _ => {}
         }
+
+        let rank = numeric_rank(t1)?.max(numeric_rank(t2)?);
+        let result = match op {
+            BinaryOperator::Add
+            | BinaryOperator::Sub
+            | BinaryOperator::Mult
+            | BinaryOperator::Pow => rank.max(NumericRank::Int),
+            // True division of integers is a float
+            BinaryOperator::Div => rank.max(NumericRank::Float),
+            BinaryOperator::FloorDiv | BinaryOperator::Mod if rank < NumericRank::Complex => {
+                rank.max(NumericRank::Int)
+            }
+            // Bitwise operators on two bools are logical operators
+            BinaryOperator::BitAnd | BinaryOperator::BitOr | BinaryOperator::BitXor
+                if rank <= NumericRank::Int =>
+            {
+                rank
+            }
+            BinaryOperator::LShift | BinaryOperator::RShift if rank <= NumericRank::Int => {
+                NumericRank::Int
+            }
+            _ => return None,
+        };
+        Some(result.python_type())
     }
 
     pub fn is_literal(&self, name: String) -> bool {
@@ -1004,6 +2443,339 @@ impl TraversalVisitorImmutGeneric<PythonType> for TypeEvaluator {
     }
 }
 
+/// Literal value of a constant that can be used in `Literal[...]`
+/// Replaces the unpacked TypeVarTuple in the type arguments of the type with
+/// the types that it binds to
+fn replace_type_var_tuple(typ: PythonType, name: &str, types: &[PythonType]) -> PythonType {
+    match typ {
+        PythonType::Class(class_type) => {
+            let type_parameters = class_type
+                .type_parameters
+                .into_iter()
+                .flat_map(|parameter| match parameter {
+                    PythonType::TypeVarTuple(n) if n == name => types.to_vec(),
+                    parameter => vec![replace_type_var_tuple(parameter, name, types)],
+                })
+                .collect();
+            PythonType::Class(ClassType::new(class_type.details, type_parameters))
+        }
+        PythonType::MultiValue(members) => PythonType::MultiValue(
+            members
+                .into_iter()
+                .map(|member| replace_type_var_tuple(member, name, types))
+                .collect(),
+        ),
+        typ => typ,
+    }
+}
+
+/// Pairs the parameters of a function with the arguments of a call that are
+/// passed to them. Returns None when the arguments do not fit the parameters,
+/// e.g. a required parameter is missing or a keyword is unknown. `bound`
+/// skips the first parameter, which receives the instance or class for
+/// methods.
+pub(crate) fn bind_arguments<'a>(
+    call: &'a ast::Call,
+    parameters: &'a ast::Arguments,
+    bound: bool,
+) -> Option<Vec<(&'a ast::Arg, &'a Expression)>> {
+    let skipped =
+        usize::from(bound && !(parameters.posonlyargs.is_empty() && parameters.args.is_empty()));
+    let positional = parameters
+        .posonlyargs
+        .iter()
+        .chain(parameters.args.iter())
+        .skip(skipped)
+        .collect::<Vec<_>>();
+    if call.args.len() > positional.len() && parameters.vararg.is_none() {
+        return None;
+    }
+    let mut bound = positional
+        .iter()
+        .zip(call.args.iter())
+        .map(|(parameter, argument)| (*parameter, argument))
+        .collect::<Vec<_>>();
+    for keyword in &call.keywords {
+        let name = keyword.arg.as_deref()?;
+        let parameter = positional
+            .iter()
+            .skip(parameters.posonlyargs.len().saturating_sub(skipped))
+            .copied()
+            .chain(parameters.kwonlyargs.iter())
+            .find(|parameter| parameter.arg == name);
+        match parameter {
+            Some(parameter) if bound.iter().any(|(p, _)| p.arg == name) => return None,
+            Some(parameter) => bound.push((parameter, keyword.value.as_ref())),
+            None if parameters.kwarg.is_some() => {}
+            None => return None,
+        }
+    }
+    let required = parameters
+        .positional_with_defaults()
+        .skip(skipped)
+        .chain(parameters.keyword_only_with_defaults())
+        .filter(|(_, default)| default.is_none())
+        .map(|(parameter, _)| parameter);
+    for parameter in required {
+        if !bound.iter().any(|(p, _)| p.arg == parameter.arg) {
+            return None;
+        }
+    }
+    Some(bound)
+}
+
+/// Returns true if a value of the argument type can be passed to a parameter
+/// of the parameter type. Types that are not known accept and are accepted by
+/// every type. Classes are not checked yet.
+fn accepts_type(parameter_type: &PythonType, argument_type: &PythonType) -> bool {
+    match (parameter_type, argument_type) {
+        (PythonType::Unknown | PythonType::Any, _) | (_, PythonType::Unknown | PythonType::Any) => {
+            true
+        }
+        (PythonType::KnownValue(p), PythonType::KnownValue(a)) => p == a,
+        (PythonType::KnownValue(_), _) => false,
+        (_, PythonType::KnownValue(a)) => match a.literal_value {
+            LiteralValue::Bool(_) => accepts_type(parameter_type, &PythonType::Bool),
+            LiteralValue::Int(_) => accepts_type(parameter_type, &PythonType::Int),
+            LiteralValue::Float(_) => accepts_type(parameter_type, &PythonType::Float),
+            LiteralValue::Str(_) => accepts_type(parameter_type, &PythonType::Str),
+            LiteralValue::None => accepts_type(parameter_type, &PythonType::None),
+            LiteralValue::Bytes(_) => true,
+        },
+        // `int` is accepted for `float` and `float` for `complex`
+        (
+            PythonType::Int | PythonType::Float | PythonType::Complex,
+            PythonType::Bool | PythonType::Int | PythonType::Float | PythonType::Complex,
+        ) => numeric_rank(parameter_type) >= numeric_rank(argument_type),
+        (PythonType::Class(_), PythonType::Class(_)) => true,
+        (parameter_type, argument_type) => parameter_type.type_equal(argument_type),
+    }
+}
+
+/// Union of the types without duplicate members. The union is not known
+/// when one of the types is not known.
+fn union_of(first: PythonType, second: PythonType) -> PythonType {
+    let mut members: Vec<PythonType> = vec![];
+    let candidates = first
+        .union_members()
+        .into_iter()
+        .chain(second.union_members());
+    for member in candidates {
+        if matches!(member, PythonType::Unknown) {
+            return PythonType::Unknown;
+        }
+        if !members.iter().any(|m| m.type_equal(&member)) {
+            members.push(member);
+        }
+    }
+    PythonType::union(members)
+}
+
+/// The name that a test narrows, the target of an assignment expression
+/// is narrowed like the name itself, e.g. `y` in `(y := f()) is not None`
+fn narrowed_name(expr: &Expression) -> Option<&ast::Name> {
+    match expr {
+        Expression::Name(n) => Some(n),
+        Expression::NamedExpr(n) => match n.target.as_ref() {
+            Expression::Name(target) => Some(target),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Narrows the type to the members of the union that are the guarded type,
+/// or to the other members when `positive` is false. Used by `isinstance`
+/// and `TypeIs` guards.
+fn narrow_to_type(typ: PythonType, guarded_type: PythonType, positive: bool) -> PythonType {
+    let guarded_members = guarded_type.clone().union_members();
+    let is_guarded = |member: &PythonType| {
+        guarded_members
+            .iter()
+            .any(|guarded| is_narrowed_to(guarded, member))
+    };
+    let members = typ.union_members();
+    if positive {
+        let narrowed = members
+            .into_iter()
+            .filter(|member| is_guarded(member))
+            .collect::<Vec<_>>();
+        if narrowed.is_empty() {
+            guarded_type
+        } else {
+            PythonType::union(narrowed)
+        }
+    } else {
+        PythonType::union(
+            members
+                .into_iter()
+                .filter(|member| !is_guarded(member))
+                .collect(),
+        )
+    }
+}
+
+/// Returns true if the test, or its negation when `positive` is false,
+/// implies `hasattr(name, attr)`
+fn is_hasattr_guard(test: &Expression, positive: bool, name: &str, attr: &str) -> bool {
+    match test {
+        Expression::UnaryOp(u) if matches!(u.op, ast::UnaryOperator::Not) => {
+            is_hasattr_guard(&u.operand, !positive, name, attr)
+        }
+        Expression::BoolOp(b)
+            if matches!(
+                (&b.op, positive),
+                (ast::BooleanOperator::And, true) | (ast::BooleanOperator::Or, false)
+            ) =>
+        {
+            b.values
+                .iter()
+                .any(|value| is_hasattr_guard(value, positive, name, attr))
+        }
+        Expression::Call(c) if positive && is_type_qualifier(&c.func, "hasattr") => {
+            match c.args.as_slice() {
+                [Expression::Name(n), Expression::Constant(constant)] => {
+                    n.id == name
+                        && matches!(&constant.value, ast::ConstantValue::Str(s) if s == attr)
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if a value of the member type of a union is narrowed to the
+/// guarded type. Classes match by name or by their direct bases.
+fn is_narrowed_to(guarded_type: &PythonType, member_type: &PythonType) -> bool {
+    match (guarded_type, member_type) {
+        (_, PythonType::Unknown | PythonType::Any) => false,
+        (PythonType::Class(g), PythonType::Class(m)) => {
+            g.details.name == m.details.name || m.details.bases.contains(&g.details.name)
+        }
+        _ => accepts_type(guarded_type, member_type),
+    }
+}
+
+fn is_bytes(typ: &PythonType) -> bool {
+    match typ {
+        PythonType::Class(c) => c.details.is_builtin() && c.details.name == builtins::BYTES_TYPE,
+        _ => false,
+    }
+}
+
+/// Position of a number type in the numeric tower. The result of an
+/// arithmetic operation has the highest rank of its operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum NumericRank {
+    Bool,
+    Int,
+    Float,
+    Complex,
+}
+
+impl NumericRank {
+    fn python_type(self) -> PythonType {
+        match self {
+            NumericRank::Bool => PythonType::Bool,
+            NumericRank::Int => PythonType::Int,
+            NumericRank::Float => PythonType::Float,
+            NumericRank::Complex => PythonType::Complex,
+        }
+    }
+}
+
+fn numeric_rank(typ: &PythonType) -> Option<NumericRank> {
+    match typ {
+        PythonType::Bool => Some(NumericRank::Bool),
+        PythonType::Int => Some(NumericRank::Int),
+        PythonType::Float => Some(NumericRank::Float),
+        PythonType::Complex => Some(NumericRank::Complex),
+        _ => None,
+    }
+}
+
+/// The type of the values that a literal belongs to, e.g. `int` for
+/// `Literal[1]`
+fn literal_base_type(value: &LiteralValue) -> PythonType {
+    match value {
+        LiteralValue::Bool(_) => PythonType::Bool,
+        LiteralValue::Int(_) => PythonType::Int,
+        LiteralValue::Float(_) => PythonType::Float,
+        LiteralValue::Str(_) => PythonType::Str,
+        LiteralValue::None => PythonType::None,
+        LiteralValue::Bytes(_) => PythonType::Unknown,
+    }
+}
+
+/// Type of `value[index]` for a tuple and a constant index, e.g. `str` for
+/// `t[1]` or `t[-1]` when `t` is a `tuple[int, str]`. A tuple type with a
+/// single argument is also used for tuples of any length, so all of its
+/// elements have that type. Returns None for other subscripts.
+fn tuple_element_type(value_type: &PythonType, index: &Expression) -> Option<PythonType> {
+    let PythonType::Class(c) = value_type else {
+        return None;
+    };
+    if c.details.name != builtins::TUPLE_TYPE || !c.details.is_builtin() {
+        return None;
+    }
+    let Expression::Constant(constant) = index else {
+        return None;
+    };
+    let ast::ConstantValue::Int(index) = &constant.value else {
+        return None;
+    };
+    let arguments = &c.type_parameters;
+    if arguments
+        .iter()
+        .any(|argument| matches!(argument, PythonType::TypeVarTuple(_)))
+    {
+        return Some(PythonType::Unknown);
+    }
+    if let [argument] = arguments.as_slice() {
+        return Some(argument.clone());
+    }
+    let position = match index.parse::<isize>().ok()? {
+        index if index < 0 => arguments.len().checked_sub(index.unsigned_abs()),
+        index => Some(index as usize),
+    };
+    Some(
+        position
+            .and_then(|position| arguments.get(position))
+            .cloned()
+            .unwrap_or(PythonType::Unknown),
+    )
+}
+
+/// Type of `value[key]` for a list or a dict, e.g. `int` for `l[0]` when
+/// `l` is a `list[int]` and `str` for `d["a"]` when `d` is a `dict[str, str]`.
+/// Returns None for slices and other subscripts.
+fn container_element_type(value_type: &PythonType, key: &Expression) -> Option<PythonType> {
+    let PythonType::Class(c) = value_type else {
+        return None;
+    };
+    if !c.details.is_builtin() || matches!(key, Expression::Slice(_)) {
+        return None;
+    }
+    match (c.details.name.as_str(), c.type_parameters.as_slice()) {
+        (builtins::LIST_TYPE, [element]) | (builtins::DICT_TYPE, [_, element]) => {
+            Some(element.clone())
+        }
+        _ => None,
+    }
+}
+
+fn constant_literal_value(constant: &ast::Constant) -> Option<LiteralValue> {
+    match &constant.value {
+        ast::ConstantValue::Bool(b) => Some(LiteralValue::Bool(*b)),
+        ast::ConstantValue::Int(i) => Some(LiteralValue::Int(i.clone())),
+        ast::ConstantValue::Str(s) => Some(LiteralValue::Str(s.clone())),
+        ast::ConstantValue::Bytes(b) => Some(LiteralValue::Bytes(b.clone())),
+        ast::ConstantValue::None => Some(LiteralValue::None),
+        _ => None,
+    }
+}
+
 /// visits the ast and calls get_type on each expression and saves that type in
 /// the types hashmap the key is the position of the expression in the source:
 /// (line, start, end)
@@ -1016,13 +2788,18 @@ struct TypeEvalVisitor {
 impl TypeEvalVisitor {
     pub fn new(enderpy_file: EnderpyFile) -> Self {
         let mut state = State::new(enderpy_file);
-        state.populate_symbol_table();
+        state.populate_symbol_table(&Settings::default());
         let symbol_table = state.get_symbol_table();
         Self {
             types: HashMap::new(),
             type_eval: TypeEvaluator {
-                symbol_table,
+                symbol_table: Arc::new(symbol_table),
                 imported_symbol_tables: vec![],
+                join_container_types: false,
+                carried_declarations: RefCell::default(),
+                expanding_aliases: RefCell::default(),
+                evaluating_method_calls: RefCell::default(),
+                type_parameter_bindings: RefCell::default(),
             },
             state,
         }
@@ -1078,7 +2855,7 @@ impl TraversalVisitor for TypeEvalVisitor {
             }
             ast::Statement::AugAssignStatement(a) => self.visit_aug_assign(a),
             ast::Statement::Assert(a) => self.visit_assert(a),
-            ast::Statement::Pass(p) => self.visit_pass(p),
+            ast::Statement::Pass(p) => {}
             ast::Statement::Delete(d) => self.visit_delete(d),
             ast::Statement::Return(r) => {
                 if let Some(r) = r.value.as_ref() {
@@ -1181,12 +2958,17 @@ mod tests {
         );
 
         let mut module = State::new(enderpy_file);
-        module.populate_symbol_table();
+        module.populate_symbol_table(&Settings::test_settings());
         let symbol_table = module.get_symbol_table();
 
         let type_eval = TypeEvaluator {
-            symbol_table,
+            symbol_table: Arc::new(symbol_table),
             imported_symbol_tables: vec![],
+            join_container_types: false,
+            carried_declarations: RefCell::default(),
+            expanding_aliases: RefCell::default(),
+            evaluating_method_calls: RefCell::default(),
+            type_parameter_bindings: RefCell::default(),
         };
 
         let mut type_eval_visitor = TypeEvalVisitor::new(module.file);
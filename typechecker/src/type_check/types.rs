@@ -5,6 +5,7 @@ use enderpy_python_parser::ast;
 use crate::symbol_table;
 
 #[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
 pub enum PythonType {
     None,
     /// Unknown and Any type are similar but we are using Uknown when we cannot
@@ -27,41 +28,81 @@ pub enum PythonType {
     Bool,
     Int,
     Float,
+    Complex,
     Str,
     Class(ClassType),
+    /// An unpacked TypeVarTuple in type arguments, e.g. `*Ts` in
+    /// `tuple[int, *Ts]`. Calls replace it with the types that it binds to.
+    TypeVarTuple(String),
     Never,
 }
 
 impl PythonType {
+    /// Creates the union of the types. The union of a single type is the type
+    /// itself and an empty union is `Never`.
+    pub fn union(mut types: Vec<PythonType>) -> Self {
+        match types.len() {
+            0 => PythonType::Never,
+            1 => types.remove(0),
+            _ => PythonType::MultiValue(types),
+        }
+    }
+
+    /// Members of the type if it is a union, nested unions are flattened
+    pub fn union_members(self) -> Vec<PythonType> {
+        match self {
+            PythonType::MultiValue(members) => members
+                .into_iter()
+                .flat_map(PythonType::union_members)
+                .collect(),
+            t => vec![t],
+        }
+    }
+
     pub fn type_equal(&self, other: &Self) -> bool {
-        match (self, other) {
+        types_equal(vec![(self, other)])
+    }
+}
+
+/// Whether the types of every pair are equal. The pairs of the members and
+/// type parameters are pushed to the pairs left to compare instead of
+/// recursing, so deeply nested types do not overflow the stack.
+fn types_equal<'a>(mut pairs: Vec<(&'a PythonType, &'a PythonType)>) -> bool {
+    while let Some(pair) = pairs.pop() {
+        let equal = match pair {
             (PythonType::None, PythonType::None) => true,
             (PythonType::Unknown, PythonType::Unknown) => true,
             (PythonType::Any, PythonType::Any) => true,
             (PythonType::Bool, PythonType::Bool) => true,
             (PythonType::Int, PythonType::Int) => true,
             (PythonType::Float, PythonType::Float) => true,
+            (PythonType::Complex, PythonType::Complex) => true,
             (PythonType::Str, PythonType::Str) => true,
             (PythonType::Never, PythonType::Never) => true,
             (PythonType::KnownValue(v1), PythonType::KnownValue(v2)) => v1 == v2,
             (PythonType::MultiValue(m1), PythonType::MultiValue(m2)) => {
-                if m1.len() != m2.len() {
-                    return false;
-                }
-
-                for (t1, t2) in m1.iter().zip(m2.iter()) {
-                    if !t1.type_equal(t2) {
-                        return false;
-                    }
-                }
-
+                pairs.extend(m1.iter().zip(m2.iter()));
+                m1.len() == m2.len()
+            }
+            // TODO: add check for args too. We need to check what should be
+            // the rule for two args to be equal
+            (PythonType::Callable(c1), PythonType::Callable(c2)) => {
+                pairs.push((&c1.return_type, &c2.return_type));
                 true
             }
-            (PythonType::Callable(c1), PythonType::Callable(c2)) => c1.type_equal(c2),
-            (PythonType::Class(c1), PythonType::Class(c2)) => c1.type_equal(c2),
+            (PythonType::Class(c1), PythonType::Class(c2)) => {
+                pairs.extend(c1.type_parameters.iter().zip(c2.type_parameters.iter()));
+                c1.details.name == c2.details.name
+                    && c1.type_parameters.len() == c2.type_parameters.len()
+            }
+            (PythonType::TypeVarTuple(t1), PythonType::TypeVarTuple(t2)) => t1 == t2,
             _ => false,
+        };
+        if !equal {
+            return false;
         }
     }
+    true
 }
 
 #[allow(unused)]
@@ -73,6 +114,7 @@ pub enum TypeFlags {
     Instantiable,
 }
 
+#[allow(unused)]
 #[derive(Debug, Clone, PartialEq)]
 pub struct Any {
     pub source: AnySource,
@@ -94,13 +136,6 @@ pub struct CallableType {
     pub return_type: PythonType,
 }
 
-impl CallableType {
-    pub fn type_equal(&self, other: &Self) -> bool {
-        // TODO: add check for args too. We need to check what should be the rule for
-        self.return_type.type_equal(&other.return_type)
-    }
-}
-
 impl PartialEq for CallableType {
     fn eq(&self, other: &Self) -> bool {
         // TODO: add check for args too. We need to check what should be the rule for
@@ -125,16 +160,6 @@ impl ClassType {
             type_parameters,
         }
     }
-
-    pub fn type_equal(&self, other: &Self) -> bool {
-        self.details.name == other.details.name
-            && self.type_parameters.len() == other.type_parameters.len()
-            && self
-                .type_parameters
-                .iter()
-                .zip(other.type_parameters.iter())
-                .all(|(t1, t2)| t1.type_equal(t2))
-    }
 }
 
 impl PartialEq for ClassType {
@@ -187,6 +212,7 @@ impl Display for PythonType {
             PythonType::Bool => "Bool",
             PythonType::Int => "Int",
             PythonType::Float => "Float",
+            PythonType::Complex => "Complex",
             PythonType::Str => "Str",
             PythonType::Unknown => "Unknown",
             PythonType::Callable(callable_type) => callable_type.name.as_str(),
@@ -202,6 +228,7 @@ impl Display for PythonType {
                 return write!(f, "{}", fmt);
             }
             PythonType::Never => "Never",
+            PythonType::TypeVarTuple(name) => return write!(f, "*{}", name),
             PythonType::KnownValue(value) => {
                 let value = format!("{}", value.literal_value);
                 return write!(f, "Literal[{}]", value);
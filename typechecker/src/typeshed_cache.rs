@@ -0,0 +1,136 @@
+// Analyzed typeshed modules shared by every build manager of the process.
+// Typeshed stubs are the same for every project, so parsing them and building
+// their symbol tables is done once instead of for every build, e.g. for every
+// edit in the language server.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::SystemTime,
+};
+
+use crate::{
+    file_system::FileSystem,
+    ruff_python_import_resolver::{python_platform::PythonPlatform, python_version::PythonVersion},
+    settings::Settings,
+    state::State,
+};
+
+/// A stub is analyzed again when it is modified on disk or when it is
+/// analyzed with options that change its symbol table
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    path: PathBuf,
+    modified: SystemTime,
+    python_version: PythonVersion,
+    python_platform: PythonPlatform,
+    uppercase_constants: bool,
+}
+
+impl CacheKey {
+    /// None when the file is not a typeshed stub or its modification time is
+    /// not available
+    fn new(file_system: &dyn FileSystem, path: &Path, options: &Settings) -> Option<Self> {
+        let typeshed_path = options.import_discovery.typeshed_path.as_ref()?;
+        if !path.starts_with(typeshed_path) {
+            return None;
+        }
+        let modified = file_system.modified(path).ok()?;
+        Some(CacheKey {
+            path: path.to_path_buf(),
+            modified,
+            python_version: options.python_version,
+            python_platform: options.python_platform,
+            uppercase_constants: options.uppercase_constants,
+        })
+    }
+}
+
+fn cache() -> &'static Mutex<HashMap<CacheKey, State>> {
+    static CACHE: OnceLock<Mutex<HashMap<CacheKey, State>>> = OnceLock::new();
+    CACHE.get_or_init(Mutex::default)
+}
+
+/// The analyzed module of the typeshed stub at `path`, with its imports
+/// resolved and its symbol table built
+pub(crate) fn get(file_system: &dyn FileSystem, path: &Path, options: &Settings) -> Option<State> {
+    let key = CacheKey::new(file_system, path, options)?;
+    cache().lock().ok()?.get(&key).cloned()
+}
+
+/// Saves a typeshed module after its symbol table is built, other modules
+/// are not cached. The AST of the cached module is dropped.
+pub(crate) fn insert(file_system: &dyn FileSystem, state: &State, options: &Settings) {
+    let Some(key) = CacheKey::new(file_system, &state.file.path(), options) else {
+        return;
+    };
+    let mut state = state.clone();
+    state.file.drop_ast();
+    if let Ok(mut cache) = cache().lock() {
+        cache.insert(key, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc, time::Duration};
+
+    use super::*;
+    use crate::{
+        file_system::{OsFileSystem, OverlayFileSystem},
+        nodes::EnderpyFile,
+    };
+
+    fn analyzed_state(path: &Path, options: &Settings) -> State {
+        let source = fs::read_to_string(path).unwrap();
+        let mut state = State::new(EnderpyFile::from_source(path.to_path_buf(), source));
+        state.populate_symbol_table(options);
+        state
+    }
+
+    #[test]
+    fn test_typeshed_cache() {
+        let typeshed = tempfile::tempdir().unwrap();
+        let stub = typeshed.path().join("stdlib").join("mod.pyi");
+        fs::create_dir_all(stub.parent().unwrap()).unwrap();
+        fs::write(&stub, "x: int\n").unwrap();
+        let mut options = Settings::test_settings();
+        options.import_discovery.typeshed_path = Some(typeshed.path().to_path_buf());
+
+        assert!(get(&OsFileSystem, &stub, &options).is_none());
+        insert(&OsFileSystem, &analyzed_state(&stub, &options), &options);
+        let cached = get(&OsFileSystem, &stub, &options).unwrap();
+        assert!(
+            cached
+                .symbol_table()
+                .global_scope()
+                .symbols()
+                .contains_key("x")
+        );
+
+        // Other options build a different symbol table
+        options.python_version = PythonVersion::Py38;
+        assert!(get(&OsFileSystem, &stub, &options).is_none());
+        options.python_version = PythonVersion::default();
+
+        // A modified stub is analyzed again
+        let file = fs::File::options().write(true).open(&stub).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert!(get(&OsFileSystem, &stub, &options).is_none());
+
+        // Unsaved stubs of an editor are not cached
+        let mut overlay = OverlayFileSystem::new(Arc::new(OsFileSystem));
+        overlay.set_file(stub.clone(), "z: int\n".to_string());
+        insert(&overlay, &analyzed_state(&stub, &options), &options);
+        assert!(get(&overlay, &stub, &options).is_none());
+
+        // Files outside of typeshed are not cached
+        let project = tempfile::tempdir().unwrap();
+        let source = project.path().join("main.py");
+        fs::write(&source, "y = 1\n").unwrap();
+        insert(&OsFileSystem, &analyzed_state(&source, &options), &options);
+        assert!(get(&OsFileSystem, &source, &options).is_none());
+    }
+}
@@ -0,0 +1,533 @@
+// Finds module level symbols that are never referenced in the project.
+// References are matched by name only. A symbol is considered used if a name,
+// an attribute or an imported name with the same name appears anywhere in the
+// built modules. This is conservative, it may miss unused symbols but it does
+// not report symbols that are used.
+
+use std::{collections::HashSet, fmt::Display, path::PathBuf};
+
+use enderpy_python_parser as parser;
+use enderpy_python_parser::ast::*;
+
+use crate::{
+    ast_visitor::TraversalVisitor,
+    diagnostic::Position,
+    settings::Settings,
+    state::State,
+    symbol_table::{Declaration, SymbolTableType},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnusedSymbolKind {
+    Function,
+    Class,
+    Variable,
+}
+
+impl Display for UnusedSymbolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnusedSymbolKind::Function => write!(f, "function"),
+            UnusedSymbolKind::Class => write!(f, "class"),
+            UnusedSymbolKind::Variable => write!(f, "variable"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedSymbol {
+    pub name: String,
+    pub kind: UnusedSymbolKind,
+    pub module_name: String,
+    pub path: PathBuf,
+    pub position: Position,
+}
+
+pub(crate) fn find_unused_symbols<'a>(
+    modules: impl Iterator<Item = &'a State> + Clone,
+    options: &Settings,
+) -> Vec<UnusedSymbol> {
+    let mut collector = ReferenceCollector::default();
+    for state in modules.clone() {
+        for stmt in state.file.ast(options).iter() {
+            collector.visit_stmt(stmt);
+        }
+    }
+
+    let mut unused = vec![];
+    for state in modules {
+        // Only report symbols of the files that are part of the project
+        if state.file.build_source.followed {
+            continue;
+        }
+        let symbol_table = state.get_symbol_table();
+        let Some(global_scope) = symbol_table
+            .iter_scopes()
+            .find(|scope| scope.symbol_table_type == SymbolTableType::Module)
+        else {
+            continue;
+        };
+        for (name, symbol) in global_scope.symbols() {
+            if is_dunder(name)
+                || collector.references.contains(name)
+                || collector.exported.contains(name)
+            {
+                continue;
+            }
+            let Some(declaration) = symbol
+                .declarations
+                .iter()
+                .min_by_key(|d| d.declaration_path().node.start)
+            else {
+                continue;
+            };
+            let kind = match declaration {
+                Declaration::Function(_) => UnusedSymbolKind::Function,
+                Declaration::Class(_) => UnusedSymbolKind::Class,
+                Declaration::Variable(_) => UnusedSymbolKind::Variable,
+                _ => continue,
+            };
+            unused.push(UnusedSymbol {
+                name: name.clone(),
+                kind,
+                module_name: state.file.module_name(),
+                path: state.file.path(),
+                position: state
+                    .file
+                    .get_position(declaration.declaration_path().node.start),
+            });
+        }
+    }
+
+    unused.sort_by(|a, b| {
+        (&a.path, a.position.line, a.position.character, &a.name).cmp(&(
+            &b.path,
+            b.position.line,
+            b.position.character,
+            &b.name,
+        ))
+    });
+    unused
+}
+
+fn is_dunder(name: &str) -> bool {
+    name.len() > 4 && name.starts_with("__") && name.ends_with("__")
+}
+
+/// Collects every name that is read in a module and the names listed in
+/// `__all__`.
+#[derive(Default)]
+struct ReferenceCollector {
+    references: HashSet<String>,
+    exported: HashSet<String>,
+}
+
+impl ReferenceCollector {
+    /// Visits the sub expressions of an assignment target that are read
+    fn visit_target(&mut self, target: &Expression) {
+        match target {
+            Expression::Name(_) => {}
+            Expression::Tuple(t) => t.elements.iter().for_each(|e| self.visit_target(e)),
+            Expression::List(l) => l.elements.iter().for_each(|e| self.visit_target(e)),
+            Expression::Starred(s) => self.visit_target(&s.value),
+            _ => self.visit_expr(target),
+        }
+    }
+
+    fn visit_arguments(&mut self, args: &Arguments) {
+        let all_args = args
+            .posonlyargs
+            .iter()
+            .chain(args.args.iter())
+            .chain(args.vararg.iter())
+            .chain(args.kwonlyargs.iter())
+            .chain(args.kwarg.iter());
+        for arg in all_args {
+            if let Some(annotation) = &arg.annotation {
+                self.visit_expr(annotation);
+            }
+        }
+        for default in &args.defaults {
+            self.visit_expr(default);
+        }
+        for default in args.kw_defaults.iter().flatten() {
+            self.visit_expr(default);
+        }
+    }
+
+    fn visit_comprehensions(&mut self, generators: &[Comprehension]) {
+        for generator in generators {
+            self.visit_target(&generator.target);
+            self.visit_expr(&generator.iter);
+            for if_expr in &generator.ifs {
+                self.visit_expr(if_expr);
+            }
+        }
+    }
+
+    fn visit_type_params(&mut self, type_params: &[TypeParam]) {
+        for type_param in type_params {
+            if let TypeParam::TypeVar(t) = type_param {
+                if let Some(bound) = &t.bound {
+                    self.visit_expr(bound);
+                }
+            }
+        }
+    }
+
+    fn visit_body(&mut self, body: &[Statement]) {
+        for stmt in body {
+            self.visit_stmt(stmt);
+        }
+    }
+}
+
+impl TraversalVisitor for ReferenceCollector {
+    fn visit_import(&mut self, _i: &Import) {}
+
+    fn visit_import_from(&mut self, i: &ImportFrom) {
+        for alias in &i.names {
+            self.references.insert(alias.name.clone());
+        }
+    }
+
+    fn visit_if(&mut self, i: &parser::ast::If) {
+        self.visit_expr(&i.test);
+        self.visit_body(&i.body);
+        self.visit_body(&i.orelse);
+    }
+
+    fn visit_while(&mut self, w: &parser::ast::While) {
+        self.visit_expr(&w.test);
+        self.visit_body(&w.body);
+        self.visit_body(&w.orelse);
+    }
+
+    fn visit_for(&mut self, f: &parser::ast::For) {
+        self.visit_target(&f.target);
+        self.visit_expr(&f.iter);
+        self.visit_body(&f.body);
+        self.visit_body(&f.orelse);
+    }
+
+    fn visit_async_for(&mut self, f: &parser::ast::AsyncFor) {
+        self.visit_target(&f.target);
+        self.visit_expr(&f.iter);
+        self.visit_body(&f.body);
+        self.visit_body(&f.orelse);
+    }
+
+    fn visit_with(&mut self, w: &parser::ast::With) {
+        for item in &w.items {
+            self.visit_expr(&item.context_expr);
+            if let Some(vars) = &item.optional_vars {
+                self.visit_target(vars);
+            }
+        }
+        self.visit_body(&w.body);
+    }
+
+    fn visit_async_with(&mut self, w: &parser::ast::AsyncWith) {
+        for item in &w.items {
+            self.visit_expr(&item.context_expr);
+            if let Some(vars) = &item.optional_vars {
+                self.visit_target(vars);
+            }
+        }
+        self.visit_body(&w.body);
+    }
+
+    fn visit_try(&mut self, t: &parser::ast::Try) {
+        self.visit_body(&t.body);
+        for handler in &t.handlers {
+            if let Some(typ) = &handler.typ {
+                self.visit_expr(typ);
+            }
+            self.visit_body(&handler.body);
+        }
+        self.visit_body(&t.orelse);
+        self.visit_body(&t.finalbody);
+    }
+
+    fn visit_try_star(&mut self, t: &parser::ast::TryStar) {
+        self.visit_body(&t.body);
+        for handler in &t.handlers {
+            if let Some(typ) = &handler.typ {
+                self.visit_expr(typ);
+            }
+            self.visit_body(&handler.body);
+        }
+        self.visit_body(&t.orelse);
+        self.visit_body(&t.finalbody);
+    }
+
+    fn visit_function_def(&mut self, f: &parser::ast::FunctionDef) {
+        for decorator in &f.decorator_list {
+            self.visit_expr(decorator);
+        }
+        self.visit_type_params(&f.type_params);
+        self.visit_arguments(&f.args);
+        if let Some(returns) = &f.returns {
+            self.visit_expr(returns);
+        }
+        self.visit_body(&f.body);
+    }
+
+    fn visit_async_function_def(&mut self, f: &parser::ast::AsyncFunctionDef) {
+        for decorator in &f.decorator_list {
+            self.visit_expr(decorator);
+        }
+        self.visit_type_params(&f.type_params);
+        self.visit_arguments(&f.args);
+        if let Some(returns) = &f.returns {
+            self.visit_expr(returns);
+        }
+        self.visit_body(&f.body);
+    }
+
+    fn visit_class_def(&mut self, c: &parser::ast::ClassDef) {
+        for decorator in &c.decorator_list {
+            self.visit_expr(decorator);
+        }
+        self.visit_type_params(&c.type_params);
+        for base in &c.bases {
+            self.visit_expr(base);
+        }
+        for keyword in &c.keywords {
+            self.visit_expr(&keyword.value);
+        }
+        self.visit_body(&c.body);
+    }
+
+    fn visit_match(&mut self, m: &parser::ast::Match) {
+        self.visit_expr(&m.subject);
+        for case in &m.cases {
+            self.visit_match_pattern(&case.pattern);
+            if let Some(guard) = &case.guard {
+                self.visit_expr(guard);
+            }
+            self.visit_body(&case.body);
+        }
+    }
+
+    fn visit_constant(&mut self, _c: &Constant) {}
+
+    fn visit_list(&mut self, l: &List) {
+        l.elements.iter().for_each(|e| self.visit_expr(e));
+    }
+
+    fn visit_tuple(&mut self, t: &Tuple) {
+        t.elements.iter().for_each(|e| self.visit_expr(e));
+    }
+
+    fn visit_dict(&mut self, d: &Dict) {
+        d.keys.iter().for_each(|e| self.visit_expr(e));
+        d.values.iter().for_each(|e| self.visit_expr(e));
+    }
+
+    fn visit_set(&mut self, s: &Set) {
+        s.elements.iter().for_each(|e| self.visit_expr(e));
+    }
+
+    fn visit_name(&mut self, n: &Name) {
+        self.references.insert(n.id.clone());
+    }
+
+    fn visit_bool_op(&mut self, b: &BoolOperation) {
+        b.values.iter().for_each(|e| self.visit_expr(e));
+    }
+
+    fn visit_unary_op(&mut self, u: &UnaryOperation) {
+        self.visit_expr(&u.operand);
+    }
+
+    fn visit_bin_op(&mut self, b: &BinOp) {
+        self.visit_expr(&b.left);
+        self.visit_expr(&b.right);
+    }
+
+    fn visit_named_expr(&mut self, n: &NamedExpression) {
+        self.visit_target(&n.target);
+        self.visit_expr(&n.value);
+    }
+
+    fn visit_yield(&mut self, y: &Yield) {
+        if let Some(value) = &y.value {
+            self.visit_expr(value);
+        }
+    }
+
+    fn visit_yield_from(&mut self, y: &YieldFrom) {
+        self.visit_expr(&y.value);
+    }
+
+    fn visit_starred(&mut self, s: &Starred) {
+        self.visit_expr(&s.value);
+    }
+
+    fn visit_generator(&mut self, g: &Generator) {
+        self.visit_comprehensions(&g.generators);
+        self.visit_expr(&g.element);
+    }
+
+    fn visit_list_comp(&mut self, l: &ListComp) {
+        self.visit_comprehensions(&l.generators);
+        self.visit_expr(&l.element);
+    }
+
+    fn visit_set_comp(&mut self, s: &SetComp) {
+        self.visit_comprehensions(&s.generators);
+        self.visit_expr(&s.element);
+    }
+
+    fn visit_dict_comp(&mut self, d: &DictComp) {
+        self.visit_comprehensions(&d.generators);
+        self.visit_expr(&d.key);
+        self.visit_expr(&d.value);
+    }
+
+    fn visit_attribute(&mut self, a: &Attribute) {
+        self.references.insert(a.attr.clone());
+        self.visit_expr(&a.value);
+    }
+
+    fn visit_subscript(&mut self, s: &Subscript) {
+        self.visit_expr(&s.value);
+        self.visit_expr(&s.slice);
+    }
+
+    fn visit_slice(&mut self, s: &Slice) {
+        for part in [&s.lower, &s.upper, &s.step].into_iter().flatten() {
+            self.visit_expr(part);
+        }
+    }
+
+    fn visit_call(&mut self, c: &Call) {
+        self.visit_expr(&c.func);
+        c.args.iter().for_each(|e| self.visit_expr(e));
+        for keyword in &c.keywords {
+            self.visit_expr(&keyword.value);
+        }
+        for arg in [&c.starargs, &c.kwargs].into_iter().flatten() {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_await(&mut self, a: &Await) {
+        self.visit_expr(&a.value);
+    }
+
+    fn visit_compare(&mut self, c: &Compare) {
+        self.visit_expr(&c.left);
+        c.comparators.iter().for_each(|e| self.visit_expr(e));
+    }
+
+    fn visit_lambda(&mut self, l: &Lambda) {
+        self.visit_arguments(&l.args);
+        self.visit_expr(&l.body);
+    }
+
+    fn visit_if_exp(&mut self, i: &IfExp) {
+        self.visit_expr(&i.test);
+        self.visit_expr(&i.body);
+        self.visit_expr(&i.orelse);
+    }
+
+    fn visit_joined_str(&mut self, j: &JoinedStr) {
+        j.values.iter().for_each(|e| self.visit_expr(e));
+    }
+
+    fn visit_formatted_value(&mut self, f: &FormattedValue) {
+        self.visit_expr(&f.value);
+        if let Some(spec) = &f.format_spec {
+            self.visit_expr(spec);
+        }
+    }
+
+    fn visit_assign(&mut self, a: &Assign) {
+        for target in &a.targets {
+            if let Expression::Name(n) = target {
+                if n.id == "__all__" {
+                    self.exported.extend(exported_names(&a.value));
+                }
+            }
+            self.visit_target(target);
+        }
+        self.visit_expr(&a.value);
+    }
+
+    fn visit_ann_assign(&mut self, a: &AnnAssign) {
+        self.visit_target(&a.target);
+        self.visit_expr(&a.annotation);
+        if let Some(value) = &a.value {
+            self.visit_expr(value);
+        }
+    }
+
+    fn visit_aug_assign(&mut self, a: &AugAssign) {
+        if let Expression::Name(n) = &a.target {
+            if n.id == "__all__" {
+                self.exported.extend(exported_names(&a.value));
+            }
+        }
+        self.visit_expr(&a.target);
+        self.visit_expr(&a.value);
+    }
+
+    fn visit_assert(&mut self, a: &Assert) {
+        self.visit_expr(&a.test);
+        if let Some(msg) = &a.msg {
+            self.visit_expr(msg);
+        }
+    }
+
+    fn visit_pass(&mut self, _p: &Pass) {}
+
+    fn visit_delete(&mut self, d: &Delete) {
+        d.targets.iter().for_each(|e| self.visit_target(e));
+    }
+
+    fn visit_return(&mut self, r: &Return) {
+        if let Some(value) = &r.value {
+            self.visit_expr(value);
+        }
+    }
+
+    fn visit_raise(&mut self, r: &Raise) {
+        for expr in [&r.exc, &r.cause].into_iter().flatten() {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_break(&mut self, _b: &Break) {}
+
+    fn visit_continue(&mut self, _c: &Continue) {}
+
+    fn visit_global(&mut self, _g: &Global) {}
+
+    fn visit_nonlocal(&mut self, _n: &Nonlocal) {}
+
+    fn visit_type_alias(&mut self, t: &TypeAlias) {
+        self.visit_type_params(&t.type_params);
+        self.visit_expr(&t.value);
+    }
+}
+
+/// Returns the string elements of a `__all__` list or tuple
+fn exported_names(value: &Expression) -> Vec<String> {
+    let elements = match value {
+        Expression::List(l) => &l.elements,
+        Expression::Tuple(t) => &t.elements,
+        _ => return vec![],
+    };
+    elements
+        .iter()
+        .filter_map(|e| match e {
+            Expression::Constant(c) => match &c.value {
+                ConstantValue::Str(s) => Some(s.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
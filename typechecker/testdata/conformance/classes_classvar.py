@@ -0,0 +1,20 @@
+"""
+Tests assignments to class variables declared with typing.ClassVar.
+"""
+
+from typing import ClassVar
+
+
+class ClassA:
+    x: ClassVar[int] = 0
+    y: str = ""
+
+    def method1(self):
+        self.x = 1  # E: Cannot assign a ClassVar through an instance
+        self.y = "a"
+        ClassA.x = 1
+
+
+class ClassB(ClassA):
+    def method2(self):
+        self.x = 2  # E: Cannot assign a ClassVar through an instance
@@ -0,0 +1,15 @@
+"""
+Tests the typing.assert_type function.
+"""
+
+from typing import Literal, assert_type
+
+
+def func1(a: int, b: str, c: list[int], d: Literal[4]):
+    assert_type(a, int)
+    assert_type(b, str)
+    assert_type(d, Literal[4])
+
+    assert_type(a, str)  # E: Type mismatch
+    assert_type(b, int)  # E: Type mismatch
+    assert_type(a, int | str)  # E: Type mismatch
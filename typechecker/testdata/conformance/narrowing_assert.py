@@ -0,0 +1,32 @@
+"""
+Tests narrowing after assert statements and exhaustiveness checks with
+assert_never.
+"""
+
+from typing import Literal, Optional, assert_never, assert_type
+
+
+def func1(val: Optional[int]):
+    assert val is not None
+    assert_type(val, int)
+
+
+def func2(val: int | str):
+    assert isinstance(val, int)
+    assert_type(val, int)
+
+
+def func3(val: Literal["a", "b"]):
+    if val == "a":
+        pass
+    elif val == "b":
+        pass
+    else:
+        assert_never(val)
+
+
+def func4(val: Literal["a", "b"]):
+    if val == "a":
+        pass
+    else:
+        assert_never(val)  # E: Not exhaustive
@@ -0,0 +1,30 @@
+"""
+Tests TypeGuard and TypeIs functions used to narrow their arguments.
+"""
+
+from typing import TypeGuard, TypeIs, assert_type
+
+
+def is_str_list(val: list[object]) -> TypeGuard[list[str]]:
+    return True
+
+
+def is_int(val: int | str) -> TypeIs[int]:
+    return True
+
+
+def func1(val: list[object]):
+    if is_str_list(val):
+        assert_type(val, list[str])
+
+
+def func2(val: int | str):
+    if is_int(val):
+        assert_type(val, int)
+    else:
+        assert_type(val, str)
+
+
+def func3(val: int | str):
+    if is_int(val):
+        assert_type(val, str)  # E
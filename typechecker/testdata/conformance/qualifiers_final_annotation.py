@@ -0,0 +1,33 @@
+"""
+Tests reassignment of names and attributes declared with typing.Final.
+"""
+
+import typing
+from typing import Final
+
+ID1: Final = 1
+ID2: Final[int] = 1
+ID3: typing.Final = 1
+
+ID1 = 2  # E: Cannot reassign Final
+ID2 += 1  # E: Cannot reassign Final
+ID3, other = 1, 2  # E: Cannot reassign Final
+
+
+def func1():
+    ID1 = 3
+    return ID1
+
+
+class ClassA:
+    ID4: Final = 1
+
+    def __init__(self):
+        self.ID4 = 2  # E: Cannot reassign Final
+
+
+class ClassB(ClassA):
+    ID4 = 3  # E: Cannot override Final
+
+
+ClassA.ID4 = 4  # E: Cannot reassign Final
@@ -0,0 +1,29 @@
+import os
+
+x = 1
+name = "a"
+x = 2
+
+@decorator
+def add(a: int, b: int = (1)):
+    return a + b
+
+def nothing(s="):"):
+    pass
+
+class A:
+    def method(self):
+        return [1, 2]
+
+def b(x):
+    if x:
+        return "s"
+    return 2
+
+def incomplete(x):
+    if x:
+        return 1
+
+class K:
+    def m(self):
+        return K()
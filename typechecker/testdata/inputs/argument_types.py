@@ -0,0 +1,43 @@
+from typing import TypeVar, Generic
+
+T = TypeVar("T")
+
+
+def first(x: T) -> T:
+    return x
+
+
+def total(a: int, b: float = 0.0) -> float:
+    return a + b
+
+
+class Box(Generic[T]):
+    def put(self, item: T) -> None:
+        pass
+
+    def resize(self, width: int, height: int = 0) -> None:
+        pass
+
+
+class A:
+    pass
+
+
+def take(a: A, items: list[int], maybe: int | None, t: tuple[int, str]) -> None:
+    pass
+
+
+first(1)
+first("a")
+total(1, 2)
+total(True)
+# `float` is not accepted for `int`
+total(1.5)
+total("1", b="2")
+box = Box[int]()
+box.put(1)
+box.put("a")
+box.resize(1, height="2")
+take(A(), [1], None, (1, "a"))
+# The elements of containers are not checked
+take(1, ["a"], "x", (1, 2))
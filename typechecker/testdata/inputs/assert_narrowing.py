@@ -0,0 +1,46 @@
+from typing import Literal, Optional, assert_never, assert_type
+
+
+class Cat:
+    pass
+
+
+class Dog:
+    pass
+
+
+def optional(value: Optional[int]):
+    assert value is not None
+    assert_type(value, int)
+
+
+def animals(animal: Cat | Dog):
+    assert isinstance(animal, Cat)
+    assert_type(animal, Cat)
+
+
+def wrong(value: int | str):
+    assert_type(value, int)
+
+
+def exhaustive(mode: Literal["r", "w"]):
+    if mode == "r":
+        pass
+    elif mode == "w":
+        pass
+    else:
+        assert_never(mode)
+
+
+def not_exhaustive(value: int | str):
+    if isinstance(value, int):
+        pass
+    else:
+        assert_never(value)
+
+
+def block_scoped(value: Optional[int]):
+    if value:
+        assert value is None
+        assert_type(value, None)
+    assert_type(value, Optional[int])
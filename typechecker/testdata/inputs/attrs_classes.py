@@ -0,0 +1,65 @@
+import attr
+import attrs
+from attrs import define, field, frozen
+
+
+@attr.s
+class Point:
+    x = attr.ib()
+    y = attr.ib(default=0)
+
+
+@define
+class User:
+    name: str
+    age: int = 0
+    tags: list = field(factory=list)
+    _secret: str = field(default="", alias="secret")
+    internal: int = field(init=False, default=0)
+
+
+@attrs.define(kw_only=True)
+class Options:
+    verbose: bool
+
+
+@frozen
+class Frozen:
+    value: int
+
+
+p = Point(1, 2)
+p.x
+p.z
+u = User("a", 1, secret="s")
+u.name
+u.missing
+User()
+User("a", internal=1)
+Options(verbose=True)
+Options(True)
+Frozen(1).value
+Point()
+
+
+@attr.s(auto_attribs=True)
+class Annotated:
+    a: int
+    b: str = "b"
+
+
+@define
+class Admin(User):
+    level: int = 1
+
+
+@define(init=False)
+class Manual:
+    value: int
+
+
+Annotated(1)
+Annotated()
+Admin("a", 1, secret="s", level=2)
+Admin("a", 1, secret="s", level=2, other=3)
+Manual()
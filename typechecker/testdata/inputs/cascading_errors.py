@@ -0,0 +1,18 @@
+# Errors are reported where they happen, not at the uses of the values
+x = 1
+y = x()
+z = y + 1
+print(y)
+
+w = ~"a"
+v = w
+u = ~x()
+t = ~y + 1
+
+# The same error is reported once
+a = 1 + "a"
+b = (1 + "a") + 1
+
+# Values of unknown type may be callable
+def call(callback):
+    return callback()
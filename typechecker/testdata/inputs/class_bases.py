@@ -0,0 +1,41 @@
+class Base:
+    x = 1
+
+    def method(self):
+        pass
+
+
+class Child(Base):
+    pass
+
+
+print(Base.__eq__, Base.__class__, Base.__name__, Base.mro)
+print(Child.x, Child.method, Child.__repr__)
+print(Base.missing)
+print(Child.missing)
+
+
+def make_base():
+    pass
+
+
+number = 1
+
+
+class FromFunction(make_base):
+    pass
+
+
+class FromNumber(number):
+    pass
+
+
+class FromLiteral(1):
+    pass
+
+
+class FromList(list):
+    pass
+
+
+print(FromList.anything)
@@ -0,0 +1,37 @@
+from typing import Generic, TypeVar, assert_type
+
+T = TypeVar("T")
+
+
+class Box[T]:
+    label: T
+
+    def __init__(self, item: T) -> None:
+        self.item = item
+
+    def get(self) -> T:
+        return self.item
+
+    def pair(self, other: T) -> tuple[T, T]:
+        return (self.item, other)
+
+
+class Mapping[K, V]:
+    def items(self) -> list[tuple[K, V]]:
+        ...
+
+
+class Legacy(Generic[T]):
+    def get(self) -> T:
+        ...
+
+
+assert_type(Box[int](1).get(), int)
+assert_type(Box("a").get(), str)
+assert_type(Box[str]("a").pair("b"), tuple[str, str])
+assert_type(Box[int](1).label, int)
+assert_type(Mapping[str, int]().items(), list[tuple[str, int]])
+assert_type(Legacy[float]().get(), float)
+box: Box[bool] = Box(True)
+assert_type(box.get(), bool)
+assert_type(Box[int](1).get(), str)
@@ -0,0 +1,64 @@
+from dataclasses import dataclass, field
+from typing import ClassVar
+
+
+class Counter:
+    count: ClassVar[int] = 0
+    name: str = "counter"
+
+    def increment(self):
+        self.count += 1
+        self.name = "incremented"
+        Counter.count += 1
+
+
+class SubCounter(Counter):
+    def reset(self):
+        self.count = 0
+
+
+@dataclass
+class Point:
+    x: int
+    y: int = 0
+    dimensions: ClassVar[int] = 2
+    tags: list = field(default_factory=list)
+    cache: dict = field(init=False)
+
+
+Point(1)
+Point(1, 2, [])
+Point(x=1, y=2)
+Point()
+Point(1, 2, [], 3)
+Point(1, dimensions=3)
+Point(1, cache={})
+
+
+@dataclass
+class Point3D(Point):
+    z: int = 0
+
+
+Point3D(1, 2, [], 3)
+Point3D(1, 2, [], 3, 4)
+
+
+@dataclass(kw_only=True)
+class Options:
+    verbose: bool
+    level: int = 1
+
+
+Options(verbose=True)
+Options(True)
+Options(level=2)
+
+
+@dataclass(init=False)
+class Manual:
+    value: int
+
+
+Manual()
+Point.dimensions
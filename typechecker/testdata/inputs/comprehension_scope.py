@@ -0,0 +1,22 @@
+xs = [1, 2, 3]
+ys = [n + 1 for n in xs]
+zs = {k: v for k, v in [(1, 2)]}
+gen = (m for m in xs if m)
+
+print(n)
+print(xs)
+
+
+def other():
+    return [i for i in range(3)]
+
+
+def week():
+    for i in range(7):
+        print(i)
+    print(i)
+
+
+def leak():
+    squares = [j * j for j in range(3)]
+    return j
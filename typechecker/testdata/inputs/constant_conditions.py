@@ -0,0 +1,27 @@
+from typing import TYPE_CHECKING, assert_type
+import typing
+
+if False:
+    DEBUG_LEVEL = "verbose"
+    undefined_function()
+
+if TYPE_CHECKING:
+    X = 1
+else:
+    X = ""
+
+if not typing.TYPE_CHECKING:
+    Y = ""
+elif 1 < 2 and "a" != "b":
+    Y = 1
+else:
+    Y = None
+
+if 0:
+    Z = ""
+else:
+    Z = 1
+
+assert_type(X, int)
+assert_type(Y, int)
+assert_type(Z, int)
@@ -0,0 +1,50 @@
+from typing import Generic, TypeVar, assert_type
+
+T = TypeVar("T")
+K = TypeVar("K")
+V = TypeVar("V")
+
+
+class Plain:
+    def __init__(self, value: int) -> None:
+        self.value = value
+
+
+class Box(Generic[T]):
+    def __init__(self, item: T) -> None:
+        self.item = item
+
+
+class Pair(Generic[K, V]):
+    def __init__(self, key: K, *, value: V):
+        pass
+
+
+class Wrapper(Generic[T]):
+    def __init__(self, items: list[T]):
+        pass
+
+
+class Token:
+    def __new__(cls, text: str) -> str:
+        return text
+
+
+class Bad:
+    def __init__(self) -> int:
+        return 1
+
+
+class AlsoBad:
+    def __init__(self) -> Plain:
+        pass
+
+
+assert_type(Plain(1), Plain)
+assert_type(Box(1), Box[int])
+assert_type(Box("a"), Box[str])
+assert_type(Pair("a", value=1.0), Pair[str, float])
+assert_type(Pair(key=1, value=True), Pair[int, bool])
+assert_type(Wrapper([1, 2]), Wrapper[int])
+assert_type(Token("a"), str)
+assert_type(Box(1), Box[str])
@@ -0,0 +1,17 @@
+numbers = [1, 2]
+numbers.append(3)
+numbers.append("a")
+numbers.insert(0, 3.0)
+
+mixed = [1, "a"]
+mixed.append("b")
+mixed.append(None)
+
+tags = {"a", "b"}
+tags.add(1)
+
+empty = []
+empty.append(1)
+
+counts = {"a": 1, "b": 2}
+count: int = counts["a"]
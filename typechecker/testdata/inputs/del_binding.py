@@ -0,0 +1,30 @@
+a = 1
+del a
+print(a)
+
+b = 1
+if b:
+    del b
+print(b)
+
+c = 1
+try:
+    del c
+    print(c)
+except Exception:
+    pass
+
+d = 1
+del d
+d = 2
+print(d)
+
+e, f = 1, 2
+del e, f
+print(e, f)
+
+
+def func():
+    g = 1
+    del g
+    return g
@@ -0,0 +1,65 @@
+from typing import overload
+
+
+def documented(url: str, timeout: float = 1.0, *args: int, **kwargs: int) -> None:
+    """Sends a request.
+
+    Args:
+        url (str): Address of the
+            server.
+        timeout: Seconds to wait.
+        *args: Passed on.
+        **kwargs: Passed on.
+    """
+
+
+def mismatched(url: str, retries: int) -> None:
+    """Sends a request.
+
+    Args:
+        address: Address of the server.
+        url: Address of the server.
+    """
+
+
+def without_parameter_section(url: str) -> None:
+    """Sends a request to the url."""
+
+
+async def undocumented() -> None:
+    pass
+
+
+def _private() -> None:
+    def nested() -> None:
+        pass
+
+
+@overload
+def parse(value: int) -> int: ...
+@overload
+def parse(value: str) -> str: ...
+def parse(value):
+    """Parses the value."""
+    return value
+
+
+class Client:
+    def send(self, body: bytes) -> None:
+        """Sends the body.
+
+        Args:
+            body: Content of the request.
+        """
+
+    @classmethod
+    def create(cls, name: str) -> None:
+        pass
+
+    def _reset(self) -> None:
+        pass
+
+
+class _Private:
+    def method(self) -> None:
+        pass
@@ -0,0 +1,14 @@
+try:
+    pass
+except ValueError as err:
+    print(err)
+
+print(err)
+
+try:
+    pass
+except (TypeError, KeyError) as exc:
+    message = exc
+
+exc = None
+print(exc)
@@ -0,0 +1,33 @@
+class MyError(Exception):
+    pass
+
+
+class SpecificError(MyError):
+    pass
+
+
+class NotError:
+    pass
+
+
+def make() -> int:
+    return 1
+
+
+def f(x: int, e: MyError, n: NotError):
+    try:
+        raise SpecificError()
+    except (ValueError, MyError, NotError):
+        pass
+    except x:
+        pass
+    except e:
+        pass
+    raise MyError() from e
+    raise MyError() from None
+    raise "error"
+    raise NotError
+    raise n from x
+    raise make
+    raise KeyError
+    raise MyError from n
@@ -0,0 +1,41 @@
+import typing
+from typing import Final
+
+MAX_SIZE: Final = 10
+TIMEOUT: Final[int] = 30
+RETRIES: typing.Final = 3
+
+MAX_SIZE = 20
+TIMEOUT += 1
+RETRIES, other = 4, 5
+MIN_SIZE = 1
+MIN_SIZE = 2
+
+
+def local_names():
+    MAX_SIZE = 0
+    return MAX_SIZE
+
+
+def rebind_global():
+    global TIMEOUT
+    TIMEOUT = 60
+
+
+class Base:
+    LIMIT: Final = 100
+
+    def __init__(self):
+        self.LIMIT = 5
+        self.size: Final = 1
+
+
+class Child(Base):
+    LIMIT = 200
+
+    def reset(self):
+        self.LIMIT = 0
+
+
+Base.LIMIT = 1
+total = MAX_SIZE + TIMEOUT
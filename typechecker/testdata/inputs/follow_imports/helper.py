@@ -0,0 +1,3 @@
+def helper():
+    print(value)
+    global value
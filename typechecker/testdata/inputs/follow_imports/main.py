@@ -0,0 +1,3 @@
+from helper import helper
+
+helper()
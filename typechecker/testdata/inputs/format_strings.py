@@ -0,0 +1,37 @@
+name = "enderpy"
+count = 3
+ratio = 0.5
+pair = (name, count)
+
+"%s has %d checks" % (name, count)
+"%s has %d checks" % (name,)
+"%s" % name
+"%s %s" % pair
+"%d" % name
+"%x" % ratio
+"%5.*f" % (2, ratio)
+"%(name)s %(count)d" % {"name": name}
+"%(name)s %s" % {"name": name}
+"%y" % count
+"100%" % ()
+"%s" % ()
+
+"{} has {} checks".format(name, count)
+"{} has {} checks".format(name)
+"{0} {1} {0}".format(name, count)
+"{} {0}".format(name, count)
+"{name} {extra}".format(name=name, unused=1)
+"{:d}".format(ratio)
+"{:>10}".format(name)
+"{:,}".format(name)
+"{:.2f}".format(count)
+"{:{width}}".format(name, width=count)
+"{!r:>10}".format(count)
+"{".format()
+
+f"{name:>10} {ratio:.2f} {count:x}"
+f"{name:d}"
+f"{count:.2}"
+f"{ratio:>>}"
+f"{ratio:{count}}"
+f"{count!r:d}"
@@ -0,0 +1,54 @@
+from typing import Generator, Iterable, Iterator
+
+
+def countdown(n: int) -> Iterator[int]:
+    while n > 0:
+        yield n
+        n -= 1
+
+
+def pairs() -> Iterable[str]:
+    for c in "ab":
+        yield c
+
+
+def echo() -> Generator[int, str, None]:
+    received = yield 1
+
+
+def numbers() -> list[int]:
+    if True:
+        yield 1
+
+
+def total() -> int:
+    yield 1
+    return 1
+
+
+def nested() -> int:
+    def inner():
+        yield 1
+
+    return 1
+
+
+def callback() -> int:
+    f = lambda: (yield)
+    return 1
+
+
+class Stream:
+    pass
+
+
+class Lines(Iterator):
+    pass
+
+
+def stream() -> Stream:
+    yield 1
+
+
+def lines() -> Lines:
+    yield 1
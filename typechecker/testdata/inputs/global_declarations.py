@@ -0,0 +1,33 @@
+counter = 0
+
+
+def increment():
+    global counter
+    counter = counter + 1
+
+
+def assigned_first():
+    counter = 1
+    global counter
+
+
+def used_first():
+    print(counter)
+    global counter
+
+
+def annotated():
+    global counter
+    counter: int = 1
+
+
+def parameter(counter):
+    global counter
+
+
+def outer():
+    total = 0
+
+    def inner():
+        print(total)
+        nonlocal total
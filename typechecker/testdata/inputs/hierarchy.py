@@ -0,0 +1,18 @@
+class Base(Exception):
+    pass
+
+
+class Mid(Base):
+    pass
+
+
+class Other:
+    pass
+
+
+class Leaf(Mid, Other):
+    pass
+
+
+class Sibling(Base):
+    pass
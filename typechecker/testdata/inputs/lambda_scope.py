@@ -0,0 +1,4 @@
+xs = [1, 2, 3]
+ys = map(lambda x: x + 1, xs)
+add = lambda a, b=1: a + b
+print(add(2))
@@ -0,0 +1,19 @@
+x = "global"
+
+class A:
+    x = "class"
+    y = x
+
+    def method(self):
+        return x
+
+def set_global():
+    global counter
+    counter = 1
+
+def outer():
+    n = 1
+    def inner():
+        nonlocal n
+        n = 2
+    return n
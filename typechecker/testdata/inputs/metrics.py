@@ -0,0 +1,16 @@
+def f(x):
+    if x and x > 1:
+        for i in range(x):
+            if i:
+                pass
+    elif x:
+        return [a for a in x if a]
+    else:
+        return 1 if x else 2
+
+class A:
+    def m(self):
+        try:
+            pass
+        except ValueError:
+            pass
@@ -0,0 +1,12 @@
+def outer():
+    a = 1
+    def inner():
+        b = 2
+    c = 3
+    return c
+
+class A:
+    def method(self):
+        d = 4
+
+e = 5
@@ -0,0 +1,32 @@
+i = 1
+f = 1.5
+c = 2j
+b = True
+
+# Mixed operands are promoted along the numeric tower
+i + b
+i + f
+f * c
+i / i
+c / f
+f // i
+b % i
+i ** b
+b & b
+b ^ i
+b << i
+
+
+def mean(total: float, count: int) -> float:
+    return total / count
+
+
+mean(i, i)
+mean(b, 2)
+
+# complex numbers are not ordered and floats are not bits
+c // i
+c % f
+f << i
+f & i
+i @ i
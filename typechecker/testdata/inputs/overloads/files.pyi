@@ -0,0 +1,11 @@
+from typing import overload
+
+from modes import BinaryMode, TextMode
+
+class TextFile: ...
+class BinaryFile: ...
+
+@overload
+def open_file(file: str, mode: TextMode = "r") -> TextFile: ...
+@overload
+def open_file(file: str, mode: BinaryMode) -> BinaryFile: ...
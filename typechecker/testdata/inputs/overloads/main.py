@@ -0,0 +1,6 @@
+from files import open_file
+
+text = open_file("notes.txt")
+binary = open_file("notes.txt", "rb")
+text_io = open("notes.txt", "w")
+binary_io = open("notes.txt", mode="rb")
@@ -0,0 +1,4 @@
+from typing import Literal, TypeAlias
+
+TextMode: TypeAlias = Literal["r", "w"]
+BinaryMode: TypeAlias = Literal["rb", "wb"]
@@ -0,0 +1,12 @@
+from typing import Literal, TypeAlias, overload
+
+OpenTextMode: TypeAlias = Literal["r", "w"]
+OpenBinaryMode: TypeAlias = Literal["rb", "wb"]
+
+class TextIOWrapper: ...
+class BufferedReader: ...
+
+@overload
+def open(file: str, mode: OpenTextMode = "r", buffering: int = -1) -> TextIOWrapper: ...
+@overload
+def open(file: str, mode: OpenBinaryMode, buffering: int = -1) -> BufferedReader: ...
@@ -0,0 +1,84 @@
+from typing import override
+
+
+class Shape:
+    def scale(self, factor: float) -> "Shape":
+        return self
+
+    def area(self) -> float:
+        return 0.0
+
+    def move(self, x: int, y: int = 0, *, relative: bool = False) -> None:
+        pass
+
+    def describe(self, *args, **kwargs) -> str:
+        return ""
+
+    @classmethod
+    def create(cls, size: int) -> None:
+        pass
+
+    @staticmethod
+    def unit() -> int:
+        return 1
+
+    def __private(self, a):
+        pass
+
+    def __init__(self, name: str):
+        pass
+
+
+class Square(Shape):
+    def scale(self, factor: float, center: bool = False) -> "Square":
+        return self
+
+    def area(self) -> str:
+        return ""
+
+    def move(self, x: float, dy: int, *, relative: bool, absolute: bool) -> None:
+        pass
+
+    def describe(self) -> str:
+        return ""
+
+    @classmethod
+    def create(cls, size: bool) -> None:
+        pass
+
+    @staticmethod
+    def unit(extra) -> int:
+        return 1
+
+    def __private(self):
+        pass
+
+    def __init__(self):
+        pass
+
+    @override
+    def scale_up(self):
+        pass
+
+    @override
+    def area_of(self):
+        pass
+
+    def __eq__(self, other: object) -> bool:
+        return True
+
+    @override
+    def __hash__(self) -> int:
+        return 0
+
+
+class Circle(Shape):
+    @override
+    def area(self) -> float:
+        return 3.14
+
+    def move(self, x: int, y: int = 0, *args, relative: bool = False, **kwargs) -> None:
+        pass
+
+    def describe(self, *args, verbose=False, **kwargs) -> str:
+        return ""
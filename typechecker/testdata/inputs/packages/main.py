@@ -0,0 +1,8 @@
+import pkg.sub.mod
+import pkg.sub.mod as mod
+
+version = pkg.version
+name = pkg.sub.name
+value = pkg.sub.mod.value
+aliased = mod.value
+missing = pkg.sub.mod.missing
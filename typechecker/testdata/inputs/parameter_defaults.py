@@ -0,0 +1,25 @@
+def greet(name: str = "world", times: int = "2") -> str:
+    return name * times
+
+
+def scale(value: float = 1, factor: int = 1.5) -> float:
+    return value * factor
+
+
+def lookup(key: str, default: int = None) -> int:
+    return default
+
+
+def configure(*, verbose: bool = 0, level: int | None = None) -> None:
+    pass
+
+
+def untyped(a=1, b: str = ..., c: str | int = 2) -> None:
+    pass
+
+
+async def fetch(url: str = b"/", retries: int = 3) -> None:
+    pass
+
+
+adder = lambda x, y=1 + "a": x + y
@@ -0,0 +1,54 @@
+from dataclasses import dataclass
+
+
+class Base:
+    def move(self, x: int, /, y: int, *, speed: int = 1) -> None:
+        pass
+
+    def log(self, message: str, /, **fields) -> None:
+        pass
+
+
+class Child(Base):
+    def move(self, x: int, /, y: int, *, speed: int = 1) -> None:
+        super().move(x=x, y=y)
+        super().move(x, y, speed)
+        super().move(x, y=y, speed=speed)
+
+    def log(self, message: str, /, **fields) -> None:
+        super().log("moved", message=message)
+
+
+@dataclass(kw_only=True)
+class Point:
+    x: int
+    y: int = 0
+
+
+Point(1, 2)
+Point(x=1, y=2)
+
+
+def po(a, /, *, k):
+    pass
+
+
+po(a=1, k=2)
+po(1, 2)
+po(1, k=2)
+
+
+class Mover:
+    def move(self, x: int, /, *, speed: int) -> None:
+        pass
+
+    @staticmethod
+    def create(name: str, /) -> "Mover":
+        return Mover()
+
+
+mover = Mover()
+mover.move(x=1, speed=2)
+mover.move(1, 2)
+mover.move(1, speed=2)
+mover.create(name="a")
@@ -0,0 +1,61 @@
+from typing import assert_type
+
+
+class Circle:
+    def __init__(self, radius: float):
+        self._radius = radius
+        self.radius = "large"
+
+    @property
+    def radius(self) -> float:
+        return self._radius
+
+    @radius.setter
+    def radius(self, value: float):
+        self._radius = value
+
+    @property
+    def area(self) -> float:
+        return 3.14 * self._radius * self._radius
+
+    @property
+    def name(self) -> str:
+        return "circle"
+
+    @name.setter
+    def name(self, value: int):
+        pass
+
+    @diameter.setter
+    def diameter(self, value: float):
+        pass
+
+    def get_label(self) -> str:
+        return ""
+
+    def set_label(self, value: str):
+        pass
+
+    label = property(get_label)
+
+    @label.setter
+    def label(self, value: str):
+        pass
+
+
+circle = Circle(1.0)
+assert_type(circle.radius, float)
+assert_type(circle.area, float)
+circle.radius = 2.0
+circle.radius = 2
+circle.radius = "big"
+circle.area = 1.0
+circle.area += 1.0
+circle.name = 1
+
+
+class Square(Circle):
+    pass
+
+
+Square(1.0).area = 2.0
@@ -0,0 +1,48 @@
+from typing import ClassVar, Optional, Union
+
+import pydantic
+from pydantic import BaseModel, ConfigDict, Field
+
+
+class User(BaseModel):
+    name: str
+    age: int = 0
+    nickname: Optional[str]
+    email: str | None
+    tags: list[str] = Field(default_factory=list)
+    user_id: int = Field(..., alias="id")
+    registry: ClassVar[dict] = {}
+    _secret: str
+
+
+class Admin(User):
+    level: int
+
+
+class Strict(pydantic.BaseModel):
+    model_config = ConfigDict(extra="forbid")
+
+    value: Union[int, None]
+    code: str = Field()
+
+
+class StrictChild(Strict):
+    pass
+
+
+class Plain:
+    name: str
+
+
+User(name="a", id=1)
+User(name="a", id=1, nickname="b", extra=True)
+User("a", id=1)
+User(id=1)
+User(name="a", user_id=1)
+Admin(name="a", id=1, level=2)
+Admin(name="a", id=1)
+Strict(code="x")
+Strict(code="x", other=1)
+Strict()
+StrictChild(code="x", other=1)
+Plain()
@@ -0,0 +1,58 @@
+class Point:
+    __slots__ = ("x", "y")
+
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+        self.z = 0
+
+    def move(self, dx):
+        self.x += dx
+        self.label: str = "moved"
+        self.x, self.color = 1, "red"
+
+    @property
+    def norm(self):
+        return self.x
+
+    @norm.setter
+    def norm(self, value):
+        self.x = value
+
+    def reset(self):
+        self.norm = 0
+
+    @classmethod
+    def create(cls):
+        cls.origin = 0
+
+
+class Point3D(Point):
+    __slots__ = ["z"]
+
+    def __init__(self, x, y, z):
+        super().__init__(x, y)
+        self.z = z
+        self.w = 0
+
+
+class Named(Point):
+    def __init__(self, x, y):
+        super().__init__(x, y)
+        # Subclasses without __slots__ have a __dict__
+        self.name = "point"
+
+
+class WithDict:
+    __slots__ = ("x", "__dict__")
+
+    def __init__(self):
+        self.y = 0
+
+
+class Single:
+    __slots__ = "value"
+
+    def __init__(self):
+        self.value = 1
+        self.other = 2
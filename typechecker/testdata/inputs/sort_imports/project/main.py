@@ -0,0 +1,13 @@
+"""Imports in the wrong order."""
+import requests
+from . import sibling  # relative
+import sys
+# Paths
+from os import path
+from helpers import helper
+from __future__ import annotations
+
+print(sys, path, requests, helper, sibling)
+
+import json
+import collections
@@ -0,0 +1,11 @@
+from __future__ import annotations
+
+import os
+import sys
+from typing import Any
+
+import requests
+
+from helpers import helper
+
+from . import sibling
@@ -0,0 +1,45 @@
+from typing import assert_type, cast
+import typing
+
+
+class A:
+    x: int = 0
+
+
+class B:
+    y: str = ""
+
+
+def tuple_isinstance(value: A | B | None, number: int | str | None):
+    if isinstance(value, (A, B)):
+        assert_type(value, A | B)
+    else:
+        assert_type(value, None)
+    if isinstance(number, (int, str)):
+        assert_type(number, int | str)
+    if not isinstance(number, (int,)):
+        assert_type(number, str | None)
+
+
+def casts(value: object):
+    assert_type(cast(int, value), int)
+    assert_type(typing.cast(A, value), A)
+
+
+def getattrs():
+    assert_type(getattr(A(), "x"), int)
+    assert_type(getattr(A(), "x", 1), int)
+    assert_type(getattr(A(), "x", None), int | None)
+    assert_type(getattr(B(), "y", ""), str)
+
+
+def hasattrs():
+    if hasattr(A, "z"):
+        A.z
+    if not hasattr(B, "z"):
+        pass
+    else:
+        B.z
+    A.z
+    if hasattr(A, "y"):
+        A.z
@@ -0,0 +1,4 @@
+__all__ = ["exported"]
+
+exported = 1
+not_exported = 2
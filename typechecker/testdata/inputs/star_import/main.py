@@ -0,0 +1,4 @@
+from exports import *
+from plain import *
+
+print(exported, public, nested)
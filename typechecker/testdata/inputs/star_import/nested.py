@@ -0,0 +1 @@
+nested = 1
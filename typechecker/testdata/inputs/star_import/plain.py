@@ -0,0 +1,4 @@
+from nested import *
+
+public = 1
+_private = 2
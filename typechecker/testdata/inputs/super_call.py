@@ -0,0 +1,65 @@
+class Base:
+    def __init__(self, name: str, size=0):
+        self.name = name
+        self.size = size
+
+    def describe(self) -> str:
+        return self.name
+
+    def scale(self, factor, *, round_up):
+        return factor
+
+
+class Child(Base):
+    def __init__(self):
+        super().__init__("child")
+        super().__init__()
+        super().__init__("child", 1, 2)
+        super().__init__("child", name="other")
+        super().__init__("child", color="red")
+
+    def describe(self) -> str:
+        text = super().describe()
+        super().missing()
+        super().__repr__()
+        return text
+
+    def scale(self, factor, *, round_up):
+        super().scale(factor)
+        return super().scale(factor, round_up=round_up)
+
+
+class Left(Base):
+    def describe(self) -> str:
+        return "left"
+
+
+class Right(Base):
+    def describe(self):
+        return 1
+
+
+class Diamond(Left, Right):
+    def describe(self) -> str:
+        # Left is next in the MRO, describe returns str
+        return super().describe() + 1
+
+
+class ReversedDiamond(Right, Left):
+    def describe(self):
+        return super().describe() + 1
+
+
+class Mixin:
+    def describe(self):
+        return super().describe()
+
+
+class Sized(Base):
+    def __init__(self, size: int):
+        super().__init__(size)
+        super().__init__("sized", size=size)
+        super().__init__(name="sized")
+
+    def scale(self, factor, *, round_up):
+        return super().scale(1.5, round_up=True)
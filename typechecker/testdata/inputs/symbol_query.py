@@ -0,0 +1,8 @@
+import os
+x = 1
+class A:
+    y = 2
+    def m(self, a):
+        z = a
+def f():
+    pass
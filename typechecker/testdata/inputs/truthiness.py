@@ -0,0 +1,40 @@
+from typing import Optional
+
+
+class Node:
+    def __init__(self, value: int) -> None:
+        self.value = value
+
+
+class Bag:
+    def __len__(self) -> int:
+        return 0
+
+
+def is_ready() -> bool:
+    return True
+
+
+def describe(node: Optional[Node], bag: Bag | None, count: int | None) -> None:
+    if node:
+        node.value
+    else:
+        node
+    if not node:
+        node
+    else:
+        node.value
+    if bag:
+        bag
+    else:
+        bag
+    if count:
+        count
+    else:
+        count
+    while node:
+        node.value
+    label = "ready" if is_ready else "waiting"
+    if is_ready or node:
+        pass
+    assert not is_ready
@@ -0,0 +1,28 @@
+from typing import assert_type
+
+type Items = list[Item]
+type Pair[T] = tuple[T, T]
+type Mapping[K, V] = dict[K, list[V]]
+type Nested = list[Nested]
+
+
+class Item:
+    name: str
+
+
+def use(items: Items, pair: Pair[int], mapping: Mapping[str, Item], nested: Nested) -> None:
+    assert_type(items, list[Item])
+    assert_type(pair, tuple[int, int])
+    assert_type(mapping, dict[str, list[Item]])
+    nested
+    assert_type(pair, tuple[str, str])
+
+
+def local() -> None:
+    type Local = Later
+
+    class Later:
+        pass
+
+    def inner(value: Local) -> None:
+        assert_type(value, Later)
@@ -0,0 +1,28 @@
+def pair() -> tuple[int, str]:
+    return 1, "a"
+
+
+def many() -> tuple[int, ...]:
+    return 1, 2
+
+
+a, b = 1, "x"
+first, second = pair()
+head, *middle, tail = 1, 2.0, 3.0, "x"
+start, *others, end = pair()
+values, *more = many()
+
+c, d, e = pair()
+[f, g] = [1, 2, 3]
+h, *rest = 1
+i, *j, k = []
+(l, m), n = pair()
+
+
+def auto(x):
+    while True:
+        if x:
+            return (1, 2)
+
+
+n, _ = auto(1)
@@ -0,0 +1,9 @@
+from b import used_func
+__all__ = ["exported"]
+CONST = 1
+other = 2
+def exported(): pass
+def unused_fn(): pass
+class Unused: pass
+def __dunder__(): pass
+print(CONST)
@@ -0,0 +1,68 @@
+from typing import Generic, ParamSpec, TypeVar, TypeVarTuple, Unpack
+
+T = TypeVar("T")
+P = ParamSpec("P")
+Ts = TypeVarTuple("Ts")
+Us = TypeVarTuple("Us")
+D = TypeVar("D", default=int)
+
+
+class Array(Generic[T, *Ts]):
+    pass
+
+
+class Box(Generic[T]):
+    pass
+
+
+class Task(Generic[P]):
+    pass
+
+
+class Defaulted(Generic[T, D]):
+    pass
+
+
+class Pair[*Vs, *Ws]:
+    pass
+
+
+class Grid(Generic[*Ts, *Us]):
+    pass
+
+
+def to_tuple(*args: *Ts) -> tuple[*Ts]:
+    return args
+
+
+def prefixed(first: int, *rest: Unpack[Ts]) -> tuple[int, *Ts]:
+    return (first, *rest)
+
+
+def bare(values: tuple[Ts]) -> None:
+    pass
+
+
+def unpacked_type_var(*args: *T) -> None:
+    pass
+
+
+def two_unbounded(values: tuple[*tuple[int, ...], *Ts]) -> None:
+    pass
+
+
+def bounded(values: tuple[*tuple[int, str], *Ts]) -> None:
+    pass
+
+
+a: Array[int]
+b: Array[int, str, bytes]
+c: Array[()]
+d: Box[int, str]
+e: Box[int]
+f: Task[int, str]
+g: Array[*Ts]
+h: Defaulted[int]
+i: Defaulted[int, str]
+j: Defaulted[int, str, bytes]
+k: Defaulted[()]
@@ -0,0 +1,14 @@
+def last_positive(values):
+    [last := v for v in values if v > 0]
+    return last
+
+
+total = [running := 0 for _ in range(3)]
+print(running)
+
+invalid_rebind = [[x := 1 for x in range(3)]]
+invalid_iterable = [y for y in (z := [1, 2])]
+
+
+class Config:
+    flags = [last_flag := f for f in range(3)]
@@ -0,0 +1,82 @@
+// WebAssembly entry points used by the in-browser playground.
+// Build with `wasm-pack build wasm --target web`.
+// The playground has no file system, so only the given source is checked and
+// imports are not followed.
+
+use std::path::PathBuf;
+
+use enderpy_python_parser::Parser;
+use enderpy_python_type_checker::{
+    build::BuildManager, build_source::BuildSource, diagnostic::Diagnostic, settings::Settings,
+};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+const FILE_NAME: &str = "main.py";
+
+/// Parses the source and returns the syntax tree followed by the syntax errors
+#[wasm_bindgen]
+pub fn parse(source: &str) -> String {
+    let mut parser = Parser::new(source.to_string(), FILE_NAME.to_string());
+    let ast = parser.parse();
+    let mut output = format!("{:#?}", ast);
+    for err in parser.errors {
+        output.push_str(&format!("\n{:#?}", err));
+    }
+    output
+}
+
+/// Type checks the source and returns the diagnostics as a JSON array
+#[wasm_bindgen]
+pub fn check(source: &str) -> String {
+    let build_source = BuildSource {
+        path: PathBuf::from(FILE_NAME),
+        module: String::from("main"),
+        source: source.to_string(),
+        followed: false,
+    };
+    let mut manager = BuildManager::new(vec![build_source], Settings::default());
+    manager.type_check();
+    let diagnostics = manager
+        .get_result()
+        .iter()
+        .flat_map(|state| state.diagnostics.iter().map(Diagnostic::to_json))
+        .collect::<Vec<_>>();
+    Value::Array(diagnostics).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let diagnostics: Value = serde_json::from_str(&check("x = 1\nx()\n")).unwrap();
+        assert_eq!(
+            diagnostics,
+            json!([{
+                "code": "E201",
+                "message": "x is not callable",
+                "start": { "line": 1, "character": 0 },
+                "end": { "line": 1, "character": 3 },
+                "relatedInformation": [],
+            }])
+        );
+    }
+
+    #[test]
+    fn test_check_related_information() {
+        let source = "from typing import Final\nX: Final = 1\nX = 2\n";
+        let diagnostics: Value = serde_json::from_str(&check(source)).unwrap();
+        assert_eq!(
+            diagnostics[0]["relatedInformation"],
+            json!([{
+                "message": "'X' is declared here",
+                "start": { "line": 1, "character": 0 },
+                "end": { "line": 1, "character": 12 },
+            }])
+        );
+    }
+}